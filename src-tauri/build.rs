@@ -1,3 +1,4 @@
 fn main() {
+  println!("cargo:rerun-if-changed=permissions");
   tauri_build::build()
 }
@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::secret::SecretString;
+use super::{
+    audit, bearer_headers, current_api_config, ensure_valid_token, history_log, load_credentials, network,
+    remove_user_data, save_credentials, uploads_in_flight, SavedCredentials,
+};
+
+// =============================================================================================================
+// ============================================== ACCOUNT PROFILE ==============================================
+// =============================================================================================================
+//
+// Email, display name and notification preferences, proxied to the account endpoints. The last profile is kept
+// in memory per user so the header and reports can ask for it freely; updates replace it and emit
+// `account_updated`.
+
+const PROFILE_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AccountProfile {
+    #[serde(default)]
+    pub email: Option<String>,
+    /// `None` when the backend doesn't bind emails
+    #[serde(default)]
+    pub email_verified: Option<bool>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Passed through as the server defines them
+    #[serde(default)]
+    pub notification_prefs: serde_json::Map<String, serde_json::Value>,
+}
+
+pub struct CachedProfile {
+    user_id: String,
+    profile: AccountProfile,
+    fetched_at: Instant,
+}
+
+pub type AccountProfileState = Mutex<Option<CachedProfile>>;
+pub fn new_account_profile_state() -> AccountProfileState { Mutex::new(None) }
+
+fn cached_profile(user_id: &str, app_handle: &AppHandle) -> Option<AccountProfile> {
+    let state = app_handle.state::<AccountProfileState>();
+    let cache = state.lock().ok()?;
+    cache
+        .as_ref()
+        .filter(|c| c.user_id == user_id && c.fetched_at.elapsed() < PROFILE_TTL)
+        .map(|c| c.profile.clone())
+}
+
+fn store_profile(user_id: &str, profile: &AccountProfile, app_handle: &AppHandle) {
+    if let Ok(mut cache) = app_handle.state::<AccountProfileState>().lock() {
+        *cache = Some(CachedProfile { user_id: user_id.to_string(), profile: profile.clone(), fetched_at: Instant::now() });
+    }
+}
+
+/// Apply `edit` to the last known profile of `user_id` (even a stale one), cache and announce the result
+fn edit_cached_profile<F>(user_id: &str, app_handle: &AppHandle, edit: F) -> AccountProfile
+where
+    F: FnOnce(&mut AccountProfile),
+{
+    let mut profile = app_handle
+        .state::<AccountProfileState>()
+        .lock()
+        .ok()
+        .and_then(|cache| cache.as_ref().filter(|c| c.user_id == user_id).map(|c| c.profile.clone()))
+        .unwrap_or_default();
+    edit(&mut profile);
+    store_profile(user_id, &profile, app_handle);
+    app_handle.emit("account_updated", &profile).ok();
+    profile
+}
+
+/// Drop the cached profile after the account itself changed
+fn forget_profile(app_handle: &AppHandle) {
+    if let Ok(mut cache) = app_handle.state::<AccountProfileState>().lock() {
+        *cache = None;
+    }
+}
+
+/// Some backends wrap the profile in a `profile` object. A body without any profile field isn't a profile.
+fn parse_profile(text: &str) -> Result<AccountProfile, String> {
+    let json: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let json = json.get("profile").cloned().unwrap_or(json);
+    if !["email", "email_verified", "display_name", "notification_prefs"].iter().any(|k| json.get(*k).is_some()) {
+        return Err("Response has no profile".to_string());
+    }
+    serde_json::from_value(json).map_err(|e| format!("Invalid profile: {}", e))
+}
+
+/// Cached profile of the signed-in user; `refresh` forces a fetch
+#[tauri::command]
+pub async fn get_account_profile(refresh: Option<bool>, app_handle: AppHandle) -> Result<AccountProfile, String> {
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.account_profile, "Account profile")?;
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    if !refresh.unwrap_or(false) {
+        if let Some(profile) = cached_profile(&credentials.user_id, &app_handle) {
+            return Ok(profile);
+        }
+    }
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let resp = client.get(&url).headers(bearer_headers(&credentials)?).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+
+    let profile = parse_profile(&text)?;
+    store_profile(&credentials.user_id, &profile, &app_handle);
+    Ok(profile)
+}
+
+/// Update the given fields; `None` leaves a field unchanged
+#[tauri::command]
+pub async fn update_account_profile(
+    email: Option<String>,
+    display_name: Option<String>,
+    notification_prefs: Option<serde_json::Map<String, serde_json::Value>>,
+    app_handle: AppHandle,
+) -> Result<AccountProfile, String> {
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.update_account_profile, "Update account profile")?;
+    let email = email.map(|e| e.trim().to_string());
+    if let Some(email) = email.as_deref().filter(|e| !e.is_empty()) {
+        if !email.contains('@') || email.contains(char::is_whitespace) {
+            return Err(format!("'{}' is not a valid email address", email));
+        }
+    }
+
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+    let mut body = serde_json::json!({});
+    if let Some(email) = &email { body["email"] = serde_json::Value::String(email.clone()); }
+    if let Some(name) = &display_name { body["display_name"] = serde_json::Value::String(name.trim().to_string()); }
+    if let Some(prefs) = &notification_prefs { body["notification_prefs"] = serde_json::Value::Object(prefs.clone()); }
+
+    let resp = client.post(&url).headers(bearer_headers(&credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+
+    // prefer the server's copy; otherwise apply the change to what we had
+    let server_copy = parse_profile(&text).ok();
+    let profile = edit_cached_profile(&credentials.user_id, &app_handle, |profile| match server_copy {
+        Some(server) => *profile = server,
+        None => {
+            if let Some(email) = email { profile.email = Some(email).filter(|e| !e.is_empty()); }
+            if let Some(name) = display_name { profile.display_name = Some(name.trim().to_string()).filter(|n| !n.is_empty()); }
+            if let Some(prefs) = notification_prefs { profile.notification_prefs.extend(prefs); }
+        }
+    });
+    println!("👤 Updated account profile for {}", credentials.user_id);
+    Ok(profile)
+}
+
+// =============================================================================================================
+// ========================================= USERNAME / ACCOUNT DELETION =======================================
+// =============================================================================================================
+//
+// Both need the current password. Deleting also needs the typed phrase `delete <username>` and no running uploads;
+// local data for the user (credentials, history, links, vault) is only wiped once the server confirmed.
+
+fn account_name(credentials: &SavedCredentials) -> &str {
+    credentials.username.as_deref().unwrap_or(&credentials.user_id)
+}
+
+/// What the user has to type to delete the account
+pub(super) fn deletion_phrase(credentials: &SavedCredentials) -> String {
+    format!("delete {}", account_name(credentials))
+}
+
+/// POST `body` to an account endpoint with the signed-in user's tokens
+pub(super) async fn account_request(
+    endpoint: &Option<String>,
+    name: &str,
+    body: serde_json::Value,
+    app_handle: &AppHandle,
+) -> Result<(SavedCredentials, String), String> {
+    let api_config = current_api_config(app_handle);
+    let url = api_config.optional_url(endpoint, name)?;
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let client = network::client(app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, app_handle).await?;
+
+    let resp = client.post(&url).headers(bearer_headers(&credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    Ok((credentials, text))
+}
+
+#[tauri::command]
+pub async fn change_username(new_username: String, password: SecretString, app_handle: AppHandle) -> Result<SavedCredentials, String> {
+    let new_username = new_username.trim().to_string();
+    if new_username.is_empty() {
+        return Err("Username can't be empty".to_string());
+    }
+    if password.is_empty() {
+        return Err("Password is required".to_string());
+    }
+    let endpoint = current_api_config(&app_handle).change_username;
+    let body = serde_json::json!({ "new_username": new_username, "password": password });
+    let (mut credentials, _) = account_request(&endpoint, "Change username", body, &app_handle).await?;
+
+    let old = account_name(&credentials).to_string();
+    credentials.username = Some(new_username);
+    save_credentials(credentials.clone(), app_handle.clone()).await?;
+    forget_profile(&app_handle);
+    println!("👤 Renamed account {} to {}", old, account_name(&credentials));
+    Ok(credentials)
+}
+
+/// Permanently delete the signed-in account, then everything stored locally for it.
+/// Only reachable through a confirmation token (`confirmations`).
+pub(super) async fn delete_account(password: SecretString, confirmation_phrase: String, app_handle: AppHandle) -> Result<String, String> {
+    let credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let expected = deletion_phrase(&credentials);
+    if confirmation_phrase.trim() != expected {
+        return Err(format!("Type '{}' to confirm", expected));
+    }
+    if password.is_empty() {
+        return Err("Password is required".to_string());
+    }
+    let running = uploads_in_flight(&app_handle);
+    if running > 0 {
+        return Err(format!("{} upload(s) still running; wait for them or cancel them first", running));
+    }
+
+    let endpoint = current_api_config(&app_handle).delete_account;
+    let body = serde_json::json!({ "password": password });
+    let deleted = account_request(&endpoint, "Delete account", body, &app_handle).await;
+    audit::record(&app_handle, "account_delete", account_name(&credentials), Some(&credentials.user_id), None, &deleted);
+    let (credentials, _) = deleted?;
+
+    // queued history lines would otherwise recreate the user folder after it's removed
+    history_log::flush(&app_handle).await;
+    remove_user_data(credentials.user_id.clone(), app_handle.clone()).await?;
+    forget_profile(&app_handle);
+    println!("🗑️ Deleted account {} and its local data", credentials.user_id);
+    Ok(format!("Account {} deleted", account_name(&credentials)))
+}
+
+// =============================================================================================================
+// ============================================ EMAIL VERIFICATION =============================================
+// =============================================================================================================
+//
+// Only on backends that bind an email to the account: a code is sent to the address and confirmed here.
+
+#[tauri::command]
+pub async fn request_email_verification(email: String, app_handle: AppHandle) -> Result<AccountProfile, String> {
+    let email = email.trim().to_string();
+    if !email.contains('@') || email.contains(char::is_whitespace) {
+        return Err(format!("'{}' is not a valid email address", email));
+    }
+    let endpoint = current_api_config(&app_handle).email_verification_request;
+    let body = serde_json::json!({ "email": email });
+    let (credentials, _) = account_request(&endpoint, "Email verification", body, &app_handle).await?;
+
+    println!("📧 Verification code sent to {}", email);
+    Ok(edit_cached_profile(&credentials.user_id, &app_handle, |profile| {
+        profile.email = Some(email);
+        profile.email_verified = Some(false);
+    }))
+}
+
+#[tauri::command]
+pub async fn confirm_email(code: String, app_handle: AppHandle) -> Result<AccountProfile, String> {
+    let code = code.trim().to_string();
+    if code.is_empty() {
+        return Err("Verification code is required".to_string());
+    }
+    let endpoint = current_api_config(&app_handle).email_verification_confirm;
+    let body = serde_json::json!({ "code": code });
+    let (credentials, text) = account_request(&endpoint, "Email confirmation", body, &app_handle).await?;
+
+    let server_copy = parse_profile(&text).ok();
+    let profile = edit_cached_profile(&credentials.user_id, &app_handle, |profile| {
+        if let Some(server) = server_copy {
+            *profile = server;
+        }
+        profile.email_verified = Some(true);
+    });
+    println!("📧 Email confirmed for {}", credentials.user_id);
+    Ok(profile)
+}
@@ -0,0 +1,67 @@
+use tauri::AppHandle;
+
+use super::{credential_file, get_user_data_dir, load_credentials, SavedCredentials};
+
+// =============================================================================================================
+// =============================================== ACCOUNT SCOPE ===============================================
+// =============================================================================================================
+//
+// Commands that read or write a user's local files (history, links, vault, scoped keys) take the `user_id`
+// from the frontend. It names a folder under the app data dir, so its shape is checked wherever that folder is
+// built, and those commands only act for the signed-in account: the one `load_credentials` returns, which
+// must also have a credentials file on this device.
+
+const MAX_USER_ID_LEN: usize = 128;
+
+/// Reject user ids that could point outside their own folder
+pub fn check_user_id(user_id: &str) -> Result<(), String> {
+    let invalid = user_id.is_empty()
+        || user_id.len() > MAX_USER_ID_LEN
+        || user_id.starts_with('.')
+        || user_id.chars().any(|c| matches!(c, '/' | '\\' | ':') || c.is_control());
+    if invalid {
+        return Err(format!("Invalid user id {:?}", user_id));
+    }
+    Ok(())
+}
+
+/// Whether `user_id` has saved credentials on this device
+pub fn is_known(user_id: &str, app_handle: &AppHandle) -> Result<bool, String> {
+    let dir = get_user_data_dir(user_id, app_handle)?;
+    Ok(credential_file::credentials_path(&dir, user_id).is_file())
+}
+
+/// Saved credentials of the local account `user_id`, signed in or not
+pub fn saved_credentials(user_id: &str, app_handle: &AppHandle) -> Result<SavedCredentials, String> {
+    if !is_known(user_id, app_handle)? {
+        return Err(format!("No saved account {}", user_id));
+    }
+    credential_file::read(&credential_file::credentials_path(&get_user_data_dir(user_id, app_handle)?, user_id))
+}
+
+/// Credentials of `user_id`, when it is a local account and the one currently signed in
+pub async fn authorize(user_id: &str, app_handle: &AppHandle) -> Result<SavedCredentials, String> {
+    if !is_known(user_id, app_handle)? {
+        return Err(format!("No saved account {}", user_id));
+    }
+    let active = load_credentials(app_handle.clone()).await?.ok_or("No account is signed in")?;
+    if active.user_id != user_id {
+        println!("[ACCOUNT] Rejected access to {}'s data while {} is signed in", user_id, active.user_id);
+        return Err(format!("Account {} is not signed in", user_id));
+    }
+    Ok(active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_ids_cannot_leave_their_folder() {
+        assert!(check_user_id("c0ffee-42_ab").is_ok());
+        for bad in ["", ".", "..", "../other", "a/b", "a\\b", "C:evil", ".hidden", "a\0b"] {
+            assert!(check_user_id(bad).is_err(), "{:?} accepted", bad);
+        }
+        assert!(check_user_id(&"x".repeat(MAX_USER_ID_LEN + 1)).is_err());
+    }
+}
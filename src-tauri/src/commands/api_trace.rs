@@ -0,0 +1,117 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::redaction;
+
+// =============================================================================================================
+// ================================================= API TRACE =================================================
+// =============================================================================================================
+//
+// The last API exchanges (frontend proxy calls, uploads, downloads, logins and token refreshes), each with the
+// request id the server attached, so a support ticket can quote the id the server logs under. Entries are kept
+// in memory, emitted as `api_trace` as they happen, and HTTP errors returned to the frontend end with the id.
+
+const MAX_TRACE_ENTRIES: usize = 200;
+/// Header names servers use for a request's correlation id, most common first
+const REQUEST_ID_HEADERS: &[&str] = &["x-request-id", "x-amzn-requestid", "x-correlation-id", "request-id", "cf-ray"];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ApiTraceEntry {
+    pub at: String,
+    pub method: String,
+    /// URL path, without the query (which can carry user ids and names)
+    pub endpoint: String,
+    pub status: Option<u16>,
+    pub duration_ms: u64,
+    pub request_id: Option<String>,
+    /// Transport error when no response came back
+    pub error: Option<String>,
+}
+
+pub type ApiTraceState = Mutex<VecDeque<ApiTraceEntry>>;
+pub fn new_api_trace_state() -> ApiTraceState { Mutex::new(VecDeque::new()) }
+
+/// Correlation id the server attached to a response
+pub fn request_id(headers: &HeaderMap) -> Option<String> {
+    REQUEST_ID_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// `message`, redacted, with the request id appended when there is one
+pub fn tag_error(message: String, request_id: Option<&str>) -> String {
+    let message = redaction::redact(&message);
+    match request_id {
+        Some(id) => format!("{} (request id: {})", message, id),
+        None => message,
+    }
+}
+
+fn push(app_handle: &AppHandle, entry: ApiTraceEntry) {
+    if let Some(state) = app_handle.try_state::<ApiTraceState>() {
+        let mut trace = state.lock().unwrap();
+        if trace.len() == MAX_TRACE_ENTRIES {
+            trace.pop_front();
+        }
+        trace.push_back(entry.clone());
+    }
+    app_handle.emit("api_trace", entry).ok();
+}
+
+fn endpoint_of(url: &str) -> String {
+    reqwest::Url::parse(url).map(|u| u.path().to_string()).unwrap_or_else(|_| url.split('?').next().unwrap_or(url).to_string())
+}
+
+/// Trace a response; returns its request id
+pub fn record(app_handle: &AppHandle, method: &str, url: &str, status: StatusCode, headers: &HeaderMap, started: Instant) -> Option<String> {
+    let id = request_id(headers);
+    push(
+        app_handle,
+        ApiTraceEntry {
+            at: Utc::now().to_rfc3339(),
+            method: method.to_string(),
+            endpoint: endpoint_of(url),
+            status: Some(status.as_u16()),
+            duration_ms: started.elapsed().as_millis() as u64,
+            request_id: id.clone(),
+            error: None,
+        },
+    );
+    id
+}
+
+/// Trace a request that got no response
+pub fn record_failure(app_handle: &AppHandle, method: &str, url: &str, error: &str, started: Instant) {
+    push(
+        app_handle,
+        ApiTraceEntry {
+            at: Utc::now().to_rfc3339(),
+            method: method.to_string(),
+            endpoint: endpoint_of(url),
+            status: None,
+            duration_ms: started.elapsed().as_millis() as u64,
+            request_id: None,
+            error: Some(redaction::redact(error)),
+        },
+    );
+}
+
+/// Traced exchanges, oldest first
+#[tauri::command]
+pub async fn get_api_trace(app_handle: AppHandle) -> Result<Vec<ApiTraceEntry>, String> {
+    Ok(app_handle.state::<ApiTraceState>().lock().unwrap().iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn clear_api_trace(app_handle: AppHandle) -> Result<(), String> {
+    app_handle.state::<ApiTraceState>().lock().unwrap().clear();
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use percent_encoding::{percent_decode_str, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::AppHandle;
+
+use super::{current_api_config, data_dir, ensure_valid_token, load_credentials, network, workspaces, QUERY_ENCODE_SET};
+
+// =============================================================================================================
+// ============================================ ASSET URI PROTOCOL =============================================
+// =============================================================================================================
+//
+// `firestarter-asset://localhost/<percent-encoded remote name>` (or `http://firestarter-asset.localhost/...`
+// on Windows/Android, see `convertFileSrc(name, 'firestarter-asset')`) lets `<img src>` show stored files.
+// Credentials are injected here and never reach the webview; responses are cached on disk per user.
+
+pub const ASSET_PROTOCOL: &str = "firestarter-asset";
+
+/// Larger files should go through the stream bridge or a real download
+const ASSET_MAX_BYTES: u64 = 25 * 1024 * 1024;
+const ASSET_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct CachedAssetMeta {
+    content_type: String,
+    file_name: String,
+}
+
+fn cache_key(user_id: &str, file_name: &str) -> String {
+    blake3::hash(format!("{}\n{}", user_id, file_name).as_bytes()).to_hex().to_string()
+}
+
+fn asset_cache_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    data_dir::app_cache_dir(app_handle).map(|d| d.join("assets"))
+}
+
+fn respond(status: StatusCode, content_type: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+fn respond_error(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    respond(status, "text/plain", message.as_bytes().to_vec())
+}
+
+fn read_cached(path: &PathBuf) -> Option<(CachedAssetMeta, Vec<u8>)> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    if SystemTime::now().duration_since(modified).unwrap_or_default() > ASSET_CACHE_TTL {
+        return None;
+    }
+    let meta: CachedAssetMeta = serde_json::from_str(&std::fs::read_to_string(path.with_extension("meta")).ok()?).ok()?;
+    let bytes = std::fs::read(path).ok()?;
+    Some((meta, bytes))
+}
+
+async fn fetch_asset(app_handle: &AppHandle, file_name: &str) -> Result<Response<Vec<u8>>, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+
+    let cache_dir = asset_cache_dir(app_handle)?;
+    let cache_path = cache_dir.join(cache_key(&credentials.user_id, file_name));
+    if let Some((meta, bytes)) = read_cached(&cache_path) {
+        return Ok(respond(StatusCode::OK, &meta.content_type, bytes));
+    }
+
+    let api_config = current_api_config(app_handle);
+    let client = network::client(app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, app_handle).await?;
+
+    let url = format!(
+        "{}{}?file_name={}",
+        api_config.api_base_url,
+        api_config.download,
+        utf8_percent_encode(file_name, QUERY_ENCODE_SET)
+    );
+    let resp = workspaces::scope(client.get(&url), &credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .send()
+        .await
+        .map_err(|e| format!("Asset request failed: {}", e))?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        return Ok(respond_error(StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::BAD_GATEWAY), "Asset fetch failed"));
+    }
+    if resp.content_length().map(|l| l > ASSET_MAX_BYTES).unwrap_or(false) {
+        return Ok(respond_error(StatusCode::PAYLOAD_TOO_LARGE, "Asset too large for inline preview"));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let bytes = resp.bytes().await.map_err(|e| format!("Failed to read asset: {}", e))?.to_vec();
+    if bytes.len() as u64 > ASSET_MAX_BYTES {
+        return Ok(respond_error(StatusCode::PAYLOAD_TOO_LARGE, "Asset too large for inline preview"));
+    }
+
+    // cache failures only cost a refetch
+    let _ = write_cached(&cache_path, &content_type, file_name, &bytes);
+
+    Ok(respond(StatusCode::OK, &content_type, bytes))
+}
+
+fn write_cached(cache_path: &Path, content_type: &str, file_name: &str, bytes: &[u8]) -> Result<(), String> {
+    if let Some(dir) = cache_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create asset cache: {}", e))?;
+    }
+    let meta = CachedAssetMeta { content_type: content_type.to_string(), file_name: file_name.to_string() };
+    let json = serde_json::to_string(&meta).map_err(|e| format!("Failed to serialize asset meta: {}", e))?;
+    std::fs::write(cache_path.with_extension("meta"), json).map_err(|e| format!("Failed to write asset cache: {}", e))?;
+    std::fs::write(cache_path, bytes).map_err(|e| format!("Failed to write asset cache: {}", e))
+}
+
+/// Seed the cache with a file we just uploaded, so it shows without a round trip
+pub(super) fn prime_asset_cache(app_handle: &AppHandle, user_id: &str, file_name: &str, content_type: &str, bytes: &[u8]) -> Result<(), String> {
+    let cache_path = asset_cache_dir(app_handle)?.join(cache_key(user_id, file_name));
+    write_cached(&cache_path, content_type, file_name, bytes)
+}
+
+/// Resolve one `firestarter-asset` request. Registered in `lib.rs`.
+pub async fn serve_asset(app_handle: AppHandle, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let file_name = percent_decode_str(request.uri().path().trim_start_matches('/'))
+        .decode_utf8_lossy()
+        .to_string();
+    if file_name.is_empty() {
+        return respond_error(StatusCode::BAD_REQUEST, "Missing file name");
+    }
+
+    match fetch_asset(&app_handle, &file_name).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            println!("[ASSET] {}: {}", file_name, e);
+            respond_error(StatusCode::INTERNAL_SERVER_ERROR, "Asset unavailable")
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn clear_asset_cache(app_handle: AppHandle) -> Result<String, String> {
+    let dir = asset_cache_dir(&app_handle)?;
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to clear asset cache: {}", e))?;
+    }
+    Ok("Asset cache cleared".to_string())
+}
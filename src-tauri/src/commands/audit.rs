@@ -0,0 +1,150 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tokio::io::AsyncWriteExt;
+
+use super::{app_data_root, create_local_file, ipc_guard, output_paths, redaction};
+
+// =============================================================================================================
+// ================================================= AUDIT LOG =================================================
+// =============================================================================================================
+//
+// Append-only record of destructive actions taken through the app: remote deletes, link deletions, withdrawals,
+// credential and account removal, key revocation. It lives next to the user folders rather than inside one, so
+// wiping a user's data doesn't wipe the record of having done so. Nothing here ever rewrites the file.
+
+const AUDIT_FILE: &str = "audit-log.jsonl";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    /// e.g. "remote_delete", "link_delete", "withdrawal", "credentials_clear"
+    pub action: String,
+    pub target: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub success: bool,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+fn audit_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join(AUDIT_FILE))
+}
+
+/// Append one entry for the outcome of a destructive action. Failures to write are logged, never returned,
+/// so auditing can't change the result of the action itself.
+pub fn record<T>(
+    app_handle: &AppHandle,
+    action: &str,
+    target: &str,
+    user_id: Option<&str>,
+    detail: Option<String>,
+    result: &Result<T, String>,
+) {
+    let entry = AuditEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        action: action.to_string(),
+        target: target.to_string(),
+        user_id: user_id.map(str::to_string),
+        success: result.is_ok(),
+        detail: match result {
+            Err(e) => Some(detail.map(|d| format!("{}: {}", d, e)).unwrap_or_else(|| e.clone())),
+            Ok(_) => detail,
+        }
+        .map(|d| redaction::redact(&d)),
+    };
+    let appended: Result<(), String> = (|| {
+        let path = audit_path(app_handle)?;
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {}", e))?; }
+        let mut line = serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        line.push('\n');
+        // one write per line on an append-mode file keeps concurrent entries whole
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write audit log: {}", e))
+    })();
+    if let Err(e) = appended {
+        println!("[AUDIT] {}", e);
+    }
+}
+
+fn read_audit_log(app_handle: &AppHandle) -> Result<Vec<AuditEntry>, String> {
+    let path = audit_path(app_handle)?;
+    if !path.exists() { return Ok(vec![]); }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read audit log: {}", e))?;
+    // a torn last line (crash mid-write) is skipped rather than hiding the rest
+    Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+/// Newest first, optionally filtered by action and user
+#[tauri::command]
+pub async fn get_audit_log(
+    action: Option<String>,
+    user_id: Option<String>,
+    limit: Option<usize>,
+    app_handle: AppHandle,
+) -> Result<Vec<AuditEntry>, String> {
+    let mut entries = read_audit_log(&app_handle)?;
+    entries.retain(|e| {
+        action.as_ref().map(|a| &e.action == a).unwrap_or(true)
+            && user_id.as_ref().map(|u| e.user_id.as_ref() == Some(u)).unwrap_or(true)
+    });
+    entries.reverse();
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+    Ok(entries)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write the whole log to `output_path` as "json" (default) or "csv"
+#[tauri::command]
+pub async fn export_audit_log(
+    output_path: String,
+    format: Option<String>,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    ipc_guard::check_sensitive("export_audit_log", &webview, &ipc_nonce, &app_handle)?;
+    let entries = read_audit_log(&app_handle)?;
+    let content = match format.as_deref().unwrap_or("json") {
+        "json" => serde_json::to_string_pretty(&entries).map_err(|e| format!("Failed to serialize audit log: {}", e))?,
+        "csv" => {
+            let mut csv = String::from("timestamp,action,target,user_id,success,detail\n");
+            for e in &entries {
+                let row = [
+                    csv_field(&e.timestamp),
+                    csv_field(&e.action),
+                    csv_field(&e.target),
+                    csv_field(e.user_id.as_deref().unwrap_or("")),
+                    e.success.to_string(),
+                    csv_field(e.detail.as_deref().unwrap_or("")),
+                ];
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+        other => return Err(format!("Unknown export format '{}' (expected json or csv)", other)),
+    };
+
+    let output_path = output_paths::check_output_path(&output_path, &app_handle)?;
+    let mut file = create_local_file(&output_path, &app_handle).await?;
+    file.write_all(content.as_bytes()).await.map_err(|e| format!("Failed to write export: {}", e))?;
+    file.flush().await.map_err(|e| format!("Failed to write export: {}", e))?;
+    Ok(format!("Exported {} audit entries to '{}'", entries.len(), output_path))
+}
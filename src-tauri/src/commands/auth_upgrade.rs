@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
+
+use super::secret::SecretString;
+use super::{
+    get_user_data_dir, history_log, load_credentials, login_with_password, save_credentials, set_user_password, ApiConfigState,
+    PublicLinkEntry, SavedCredentials,
+};
+
+// =============================================================================================================
+// =========================================== LEGACY ACCOUNT UPGRADE ==========================================
+// =============================================================================================================
+//
+// Accounts created before password login only have a user id and app key, which is enough for uploads and
+// downloads but not for anything behind a Bearer token (public links, sessions, account settings). Setting a
+// password and logging in once gives them tokens; they're merged into the saved credentials so nothing else
+// about the account changes. If the server reports the account under a different user id, the local folder
+// (history, links, keys) is moved over to it.
+
+fn response_field(json: &serde_json::Value, key: &str) -> Option<String> {
+    json.get(key)
+        .or_else(|| json.get("user").and_then(|u| u.get(key)))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .filter(|v| !v.is_empty())
+}
+
+/// Replace `to` with `contents` through a temp file, so a crash leaves either the old or the merged file
+fn replace_file(to: &Path, contents: String) -> Result<(), String> {
+    let tmp = to.with_extension("merge.tmp");
+    std::fs::write(&tmp, contents).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, to).map_err(|e| format!("Failed to replace {}: {}", to.display(), e))
+}
+
+/// History files are JSON lines: append the old entries to the new file
+fn merge_history(from: &Path, to: &Path) -> Result<(), String> {
+    let old = std::fs::read_to_string(from).map_err(|e| format!("Failed to read history: {}", e))?;
+    let mut merged = std::fs::read_to_string(to).map_err(|e| format!("Failed to read history: {}", e))?;
+    if !merged.is_empty() && !merged.ends_with('\n') {
+        merged.push('\n');
+    }
+    merged.push_str(&old);
+    replace_file(to, merged)
+}
+
+/// Link files are JSON arrays: keep the new file's entries, add old ones it doesn't have
+fn merge_links(from: &Path, to: &Path) -> Result<(), String> {
+    let read = |path: &Path| -> Result<Vec<PublicLinkEntry>, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read links: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse links: {}", e))
+    };
+    let mut links = read(to)?;
+    let known: HashSet<String> = links.iter().map(|l| l.link_hash.clone()).collect();
+    links.extend(read(from)?.into_iter().filter(|l| !known.contains(&l.link_hash)));
+    let json = serde_json::to_string_pretty(&links).map_err(|e| format!("Failed to serialize links: {}", e))?;
+    replace_file(to, json)
+}
+
+/// Move a user's local files to another user id, renaming files that carry the id. Returns what moved. The
+/// upgraded credentials must already be saved under `new_id`: the old credentials file goes last, so an
+/// interrupted move still leaves an account that signs in.
+fn migrate_user_files(old_id: &str, new_id: &str, app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let old_dir = get_user_data_dir(old_id, app_handle)?;
+    let new_dir = get_user_data_dir(new_id, app_handle)?;
+    if !old_dir.exists() {
+        return Ok(vec![]);
+    }
+    std::fs::create_dir_all(&new_dir).map_err(|e| format!("Failed to create user dir: {}", e))?;
+
+    let old_credentials = format!("{}.json", old_id);
+    let mut moved = Vec::new();
+    let entries = std::fs::read_dir(&old_dir).map_err(|e| format!("Failed to read user dir: {}", e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let from = entry.path();
+        // replaced by the upgraded credentials, removed once everything else has moved
+        if name == old_credentials {
+            continue;
+        }
+        let to = new_dir.join(name.replace(old_id, new_id));
+        let result = if !to.exists() {
+            std::fs::rename(&from, &to).map_err(|e| format!("Failed to move {}: {}", name, e))
+        } else if name.starts_with("list-upload-") {
+            merge_history(&from, &to).and_then(|_| std::fs::remove_file(&from).map_err(|e| e.to_string()))
+        } else if name.starts_with("link-") {
+            merge_links(&from, &to).and_then(|_| std::fs::remove_file(&from).map_err(|e| e.to_string()))
+        } else {
+            println!("[UPGRADE] Leaving {} in place; {} already exists", name, to.display());
+            continue;
+        };
+        match result {
+            Ok(()) => moved.push(name),
+            Err(e) => println!("[UPGRADE] {}", e),
+        }
+    }
+    if let Err(e) = std::fs::remove_file(old_dir.join(&old_credentials)) {
+        println!("[UPGRADE] Failed to remove {}: {}", old_credentials, e);
+    }
+    // only goes if everything was moved
+    let _ = std::fs::remove_dir(&old_dir);
+    Ok(moved)
+}
+
+/// Give a legacy (user id + app key only) account a password and JWT tokens.
+/// `username` is needed only when the saved credentials don't have one.
+#[tauri::command]
+pub async fn upgrade_to_password_auth(
+    password: SecretString,
+    username: Option<String>,
+    state: State<'_, ApiConfigState>,
+    app_handle: AppHandle,
+) -> Result<SavedCredentials, String> {
+    if password.expose().chars().count() < 8 {
+        return Err("Password must be at least 8 characters".to_string());
+    }
+    let credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    if credentials.auth_tokens.is_some() {
+        return Err("This account already signs in with a password".to_string());
+    }
+    let username = username
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .or_else(|| credentials.username.clone())
+        .ok_or("A username is required to sign in")?;
+
+    let set_response = set_user_password(state, credentials.user_id.clone(), credentials.user_app_key.clone(), password.clone(), app_handle.clone()).await?;
+    let set_json: serde_json::Value = serde_json::from_str(&set_response).unwrap_or_default();
+    let (auth_tokens, login_json) = login_with_password(&username, password, &app_handle).await?;
+
+    let user_id = response_field(&login_json, "user_id")
+        .or_else(|| response_field(&set_json, "user_id"))
+        .unwrap_or_else(|| credentials.user_id.clone());
+    let upgraded = SavedCredentials {
+        user_id: user_id.clone(),
+        user_app_key: credentials.user_app_key.clone(),
+        auth_tokens: Some(auth_tokens),
+        username: response_field(&login_json, "username").or(Some(username)),
+        workspace_id: credentials.workspace_id.clone(),
+        gateway_id: credentials.gateway_id.clone(),
+    };
+
+    save_credentials(upgraded.clone(), app_handle.clone()).await?;
+    if user_id != credentials.user_id {
+        history_log::flush(&app_handle).await;
+        let moved = migrate_user_files(&credentials.user_id, &user_id, &app_handle)?;
+        println!("🔁 Moved {} file(s) from {} to {}", moved.len(), credentials.user_id, user_id);
+    }
+
+    println!("✅ Upgraded {} to password sign-in", upgraded.user_id);
+    app_handle
+        .emit("account_upgraded", serde_json::json!({ "old_user_id": credentials.user_id, "user_id": upgraded.user_id }))
+        .ok();
+    Ok(upgraded)
+}
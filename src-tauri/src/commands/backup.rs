@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use zeroize::Zeroizing;
+
+use super::secret::SecretString;
+use super::settings::{load_app_settings, AppSettingsState};
+use super::{account_scope, app_data_root, credential_file, history_log, ipc_guard, network, vault};
+
+// =============================================================================================================
+// ============================================== APP STATE BACKUP =============================================
+// =============================================================================================================
+//
+// One file holding everything needed to pick up on another machine or after a reinstall: settings, lifecycle
+// rules, the transfer queue, extra endpoints, and for every saved account its credentials, history, links,
+// scoped keys and key vault. The whole bundle is encrypted with ChaCha20-Poly1305 under a key derived from the
+// passphrase with argon2id, like vault keys, so the file is safe to keep on a USB stick or in cloud storage.
+// Caches, logs and the audit trail stay behind. Importing overwrites files with the same name and leaves the
+// rest alone; settings apply at once, everything else after a restart.
+
+const BACKUP_FORMAT: &str = "firestarter-backup";
+const BACKUP_VERSION: u32 = 1;
+/// Files in the data root that belong in a backup
+const TOP_LEVEL_FILES: &[&str] = &["settings.json", "lifecycle-rules.json", "transfer-queue.json", "extra_endpoints.json"];
+
+#[derive(Serialize, Deserialize)]
+struct BackupFile {
+    format: String,
+    version: u32,
+    created_at: String,
+    kdf: vault::KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundleEntry {
+    content: String,
+    /// `content` is hex, for files that aren't UTF-8 (compressed history archives)
+    #[serde(default)]
+    hex: bool,
+}
+
+/// What the encrypted part holds: file contents by path relative to the data root
+#[derive(Serialize, Deserialize, Default)]
+struct Bundle {
+    files: BTreeMap<String, BundleEntry>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AppStateImport {
+    pub files: usize,
+    pub accounts: Vec<String>,
+    /// Accounts, history and jobs are read at startup
+    pub restart_required: bool,
+}
+
+fn check_passphrase(passphrase: &SecretString) -> Result<(), String> {
+    if passphrase.expose().chars().count() < 8 {
+        return Err("Passphrase must be at least 8 characters".to_string());
+    }
+    Ok(())
+}
+
+/// Add `path` and everything below it, keyed by its path relative to `root`. Symlinks are skipped.
+fn collect(root: &Path, path: &Path, bundle: &mut Bundle) -> Result<(), String> {
+    let meta = std::fs::symlink_metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if meta.is_dir() {
+        let entries = std::fs::read_dir(path).map_err(|e| format!("Failed to list {}: {}", path.display(), e))?;
+        for entry in entries.flatten() {
+            collect(root, &entry.path(), bundle)?;
+        }
+    } else if meta.is_file() {
+        let Some(relative) = path.strip_prefix(root).ok().and_then(|p| p.to_str()) else { return Ok(()) };
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let entry = match String::from_utf8(bytes) {
+            Ok(content) => BundleEntry { content, hex: false },
+            Err(e) => BundleEntry { content: hex::encode(e.into_bytes()), hex: true },
+        };
+        bundle.files.insert(relative.replace('\\', "/"), entry);
+    }
+    Ok(())
+}
+
+/// Account folders in the data root: named by a valid user id and holding that user's credentials
+fn account_dirs(root: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(root) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| Some((e.file_name().to_str()?.to_string(), e.path())))
+        .filter(|(user_id, dir)| account_scope::check_user_id(user_id).is_ok() && credential_file::credentials_path(dir, user_id).is_file())
+        .collect()
+}
+
+fn gather(root: &Path) -> Result<(Bundle, usize), String> {
+    let mut bundle = Bundle::default();
+    for name in TOP_LEVEL_FILES {
+        let path = root.join(name);
+        if path.is_file() {
+            collect(root, &path, &mut bundle)?;
+        }
+    }
+    let accounts = account_dirs(root);
+    for (_, dir) in &accounts {
+        collect(root, dir, &mut bundle)?;
+    }
+    Ok((bundle, accounts.len()))
+}
+
+/// Where a bundle path goes under `root`, refusing anything outside the files a backup can contain
+fn restore_path(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let path = Path::new(relative);
+    let components: Vec<&str> = path
+        .components()
+        .map(|c| match c {
+            Component::Normal(part) => part.to_str().ok_or(()),
+            _ => Err(()),
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid path in backup: {}", relative))?;
+    let allowed = match components.as_slice() {
+        [] => false,
+        [file] => TOP_LEVEL_FILES.contains(file),
+        [user_id, ..] => account_scope::check_user_id(user_id).is_ok(),
+    };
+    if !allowed {
+        return Err(format!("Invalid path in backup: {}", relative));
+    }
+    Ok(root.join(path))
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &SecretString) -> Result<BackupFile, String> {
+    let kdf = vault::new_kdf_params();
+    let key = Zeroizing::new(vault::derive_wrapping_key(passphrase.expose(), &kdf)?);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, plaintext).map_err(|e| format!("Failed to encrypt backup: {}", e))?;
+    Ok(BackupFile {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: Utc::now().to_rfc3339(),
+        kdf,
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn decrypt(backup: &BackupFile, passphrase: &SecretString) -> Result<Zeroizing<Vec<u8>>, String> {
+    if backup.format != BACKUP_FORMAT {
+        return Err("Not a Firestarter backup".to_string());
+    }
+    if backup.version != BACKUP_VERSION {
+        return Err(format!("Unsupported backup version: {}", backup.version));
+    }
+    let key = Zeroizing::new(vault::derive_wrapping_key(passphrase.expose(), &backup.kdf)?);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+    let nonce = hex::decode(&backup.nonce).map_err(|e| format!("Invalid nonce: {}", e))?;
+    if nonce.len() != 12 { return Err("Invalid nonce length".to_string()); }
+    let ciphertext = hex::decode(&backup.ciphertext).map_err(|e| format!("Invalid backup data: {}", e))?;
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map(Zeroizing::new)
+        .map_err(|_| "Wrong passphrase or corrupted backup".to_string())
+}
+
+/// Write settings, accounts, history, links and job definitions to `output_path`, encrypted with `passphrase`.
+/// The file carries every account's credentials, so only the app's own windows may ask for it.
+#[tauri::command]
+pub async fn export_app_state(
+    output_path: String,
+    passphrase: SecretString,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    ipc_guard::check_sensitive("export_app_state", &webview, &ipc_nonce, &app_handle)?;
+    check_passphrase(&passphrase)?;
+    history_log::flush(&app_handle).await;
+    let root = app_data_root(&app_handle)?;
+    let (bundle, accounts) = gather(&root)?;
+    let plaintext = Zeroizing::new(serde_json::to_vec(&bundle).map_err(|e| format!("Failed to serialize backup: {}", e))?);
+    let backup = encrypt(&plaintext, &passphrase)?;
+    let json = serde_json::to_string_pretty(&backup).map_err(|e| format!("Failed to serialize backup: {}", e))?;
+    std::fs::write(&output_path, json).map_err(|e| format!("Failed to write backup: {}", e))?;
+    println!("💾 Exported {} file(s) and {} account(s) to {}", bundle.files.len(), accounts, output_path);
+    Ok(format!("Backup with {} account(s) written to '{}'", accounts, output_path))
+}
+
+/// Restore a backup written by `export_app_state`. Nothing is written unless the passphrase opens it.
+#[tauri::command]
+pub async fn import_app_state(
+    path: String,
+    passphrase: SecretString,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<AppStateImport, String> {
+    ipc_guard::check_sensitive("import_app_state", &webview, &ipc_nonce, &app_handle)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let backup: BackupFile = serde_json::from_str(&content).map_err(|e| format!("Invalid backup: {}", e))?;
+    let plaintext = decrypt(&backup, &passphrase)?;
+    let bundle: Bundle = serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid backup contents: {}", e))?;
+
+    let root = app_data_root(&app_handle)?;
+    let mut targets = Vec::with_capacity(bundle.files.len());
+    for (relative, entry) in &bundle.files {
+        let bytes = match entry.hex {
+            true => Zeroizing::new(hex::decode(&entry.content).map_err(|e| format!("Invalid backup data for {}: {}", relative, e))?),
+            false => Zeroizing::new(entry.content.as_bytes().to_vec()),
+        };
+        targets.push((restore_path(&root, relative)?, bytes));
+    }
+
+    // queued history lines would otherwise land on top of the restored logs
+    history_log::flush(&app_handle).await;
+    for (target, bytes) in &targets {
+        if let Some(dir) = target.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        std::fs::write(target, bytes.as_slice()).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+    }
+
+    let restored = load_app_settings(&app_handle).into_inner().unwrap_or_default();
+    *app_handle.state::<AppSettingsState>().lock().unwrap() = restored;
+    network::reset_client(&app_handle);
+
+    let accounts: BTreeSet<String> = bundle.files.keys().filter_map(|p| p.split_once('/')).map(|(user_id, _)| user_id.to_string()).collect();
+    let report = AppStateImport { files: targets.len(), accounts: accounts.into_iter().collect(), restart_required: true };
+    println!("💾 Imported {} file(s) for {} account(s) from backup", report.files, report.accounts.len());
+    let _ = app_handle.emit("app_state_imported", &report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_stays_inside_backed_up_files() {
+        let root = Path::new("/data");
+        assert_eq!(restore_path(root, "settings.json").unwrap(), root.join("settings.json"));
+        assert_eq!(restore_path(root, "user-1/link-user-1.json").unwrap(), root.join("user-1/link-user-1.json"));
+        for bad in ["", "audit-log.jsonl", "../settings.json", "/etc/passwd", "user-1/../../x", ".hidden/x", "./settings.json"] {
+            assert!(restore_path(root, bad).is_err(), "{:?} accepted", bad);
+        }
+    }
+}
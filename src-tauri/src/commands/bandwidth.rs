@@ -0,0 +1,160 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::settings::{current_settings, update_settings};
+
+// =============================================================================================================
+// ============================================= BANDWIDTH SCHEDULE ============================================
+// =============================================================================================================
+//
+// Time windows in local time with their own speed limit, e.g. full speed 00:00–07:00 and 1 MB/s otherwise.
+// Uploads and downloads pace every chunk through one shared budget, so the limit covers all running transfers
+// together. The schedule is looked up per chunk: a transfer that runs into a new window changes speed there.
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BandwidthWindow {
+    /// Local time as "HH:MM"; a window that ends before it starts runs over midnight
+    pub start: String,
+    pub end: String,
+    /// Bytes per second inside the window; full speed when unset
+    pub limit_bytes_per_sec: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BandwidthSchedule {
+    /// The first window containing the current time applies
+    pub windows: Vec<BandwidthWindow>,
+    /// Limit outside every window; full speed when unset
+    #[serde(default)]
+    pub default_limit_bytes_per_sec: Option<u64>,
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Invalid time '{}', expected HH:MM", time))
+}
+
+impl BandwidthWindow {
+    fn contains(&self, now: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else { return false };
+        if start <= end {
+            start <= now && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+impl BandwidthSchedule {
+    /// Limit in effect at `now`; `None` is full speed
+    pub fn limit_at(&self, now: NaiveTime) -> Option<u64> {
+        match self.windows.iter().find(|w| w.contains(now)) {
+            Some(window) => window.limit_bytes_per_sec,
+            None => self.default_limit_bytes_per_sec,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for window in &self.windows {
+            if parse_time(&window.start)? == parse_time(&window.end)? {
+                return Err(format!("Window {}–{} is empty", window.start, window.end));
+            }
+        }
+        let limits = self.windows.iter().map(|w| w.limit_bytes_per_sec).chain([self.default_limit_bytes_per_sec]);
+        if limits.flatten().any(|limit| limit == 0) {
+            return Err("A speed limit must be at least 1 byte per second".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct Pacer {
+    /// When the shared budget has room for the next chunk
+    next_free: Option<Instant>,
+    /// Limit seen by the last chunk, to report window changes
+    limit: Option<u64>,
+}
+
+pub type BandwidthState = Mutex<Pacer>;
+pub fn new_bandwidth_state() -> BandwidthState { Mutex::new(Pacer::default()) }
+
+/// Speed limit from the schedule right now
+pub fn current_limit(app_handle: &AppHandle) -> Option<u64> {
+    current_settings(app_handle).bandwidth_schedule.and_then(|s| s.limit_at(Local::now().time()))
+}
+
+/// Wait until `bytes` more fit in the current limit. Returns at once at full speed.
+pub async fn pace(app_handle: &AppHandle, bytes: usize) {
+    let limit = current_limit(app_handle);
+    let send_at = {
+        let state = app_handle.state::<BandwidthState>();
+        let mut pacer = state.lock().unwrap();
+        if pacer.limit != limit {
+            pacer.limit = limit;
+            pacer.next_free = None;
+            match limit {
+                Some(limit) => println!("🐢 Bandwidth limited to {} B/s", limit),
+                None => println!("🐇 Bandwidth limit lifted"),
+            }
+            app_handle.emit("bandwidth_limit_changed", serde_json::json!({ "limit_bytes_per_sec": limit })).ok();
+        }
+        let Some(limit) = limit else { return };
+        let now = Instant::now();
+        let send_at = pacer.next_free.filter(|t| *t > now).unwrap_or(now);
+        pacer.next_free = Some(send_at + Duration::from_secs_f64(bytes as f64 / limit as f64));
+        send_at
+    };
+    tokio::time::sleep_until(send_at.into()).await;
+}
+
+/// Replace the bandwidth schedule; `None` removes it. Returns the limit in effect now.
+#[tauri::command]
+pub async fn set_bandwidth_schedule(schedule: Option<BandwidthSchedule>, app_handle: AppHandle) -> Result<Option<u64>, String> {
+    if let Some(schedule) = &schedule {
+        schedule.validate()?;
+    }
+    update_settings(&app_handle, |s| s.bandwidth_schedule = schedule)?;
+    Ok(current_limit(&app_handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveTime {
+        parse_time(time).unwrap()
+    }
+
+    fn window(start: &str, end: &str, limit: Option<u64>) -> BandwidthWindow {
+        BandwidthWindow { start: start.into(), end: end.into(), limit_bytes_per_sec: limit }
+    }
+
+    #[test]
+    fn windows_pick_the_limit() {
+        let schedule = BandwidthSchedule { windows: vec![window("00:00", "07:00", None)], default_limit_bytes_per_sec: Some(1_000_000) };
+        assert_eq!(schedule.limit_at(at("03:30")), None);
+        assert_eq!(schedule.limit_at(at("07:00")), Some(1_000_000));
+        assert_eq!(schedule.limit_at(at("23:59")), Some(1_000_000));
+    }
+
+    #[test]
+    fn windows_can_run_over_midnight() {
+        let schedule = BandwidthSchedule { windows: vec![window("22:00", "06:00", Some(500))], default_limit_bytes_per_sec: None };
+        assert_eq!(schedule.limit_at(at("23:00")), Some(500));
+        assert_eq!(schedule.limit_at(at("05:59")), Some(500));
+        assert_eq!(schedule.limit_at(at("12:00")), None);
+    }
+
+    #[test]
+    fn bad_schedules_are_rejected() {
+        let empty = BandwidthSchedule { windows: vec![window("08:00", "08:00", Some(1))], default_limit_bytes_per_sec: None };
+        assert!(empty.validate().is_err());
+        let zero = BandwidthSchedule { windows: vec![], default_limit_bytes_per_sec: Some(0) };
+        assert!(zero.validate().is_err());
+        let bad_time = BandwidthSchedule { windows: vec![window("8am", "09:00", None)], default_limit_bytes_per_sec: None };
+        assert!(bad_time.validate().is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+use futures_util::StreamExt;
+use percent_encoding::utf8_percent_encode;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::network::{self, ClientTuning};
+use super::redaction::println_redacted;
+use super::{
+    current_api_config, ensure_valid_token, gateways, lifecycle, load_credentials, upload_route, workspaces, QUERY_ENCODE_SET,
+};
+
+// =============================================================================================================
+// ============================================= TRANSFER BENCHMARK ============================================
+// =============================================================================================================
+//
+// Uploads a synthetic object of the requested size, downloads it again and deletes it, timing both directions
+// with the shared client as currently tuned. Running it before and after `set_client_tuning` shows which
+// settings suit the connection. The payload is random so compression along the way can't flatter the numbers.
+
+const MIN_BENCHMARK_BYTES: u64 = 64 * 1024;
+const MAX_BENCHMARK_BYTES: u64 = 1024 * 1024 * 1024;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BenchmarkReport {
+    pub size_bytes: u64,
+    pub upload_ms: u64,
+    pub upload_mb_per_sec: f64,
+    pub download_ms: u64,
+    pub download_mb_per_sec: f64,
+    /// Tuning the client was built with
+    pub tuning: ClientTuning,
+}
+
+fn mb_per_sec(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 / 1_048_576.0 / elapsed.as_secs_f64().max(0.001)
+}
+
+/// Incompressible bytes from a random seed
+fn synthetic_payload(size: usize) -> Vec<u8> {
+    let mut payload = vec![0u8; size];
+    blake3::Hasher::new().update(uuid::Uuid::new_v4().as_bytes()).finalize_xof().fill(&mut payload);
+    payload
+}
+
+/// Upload and download a `size`-byte test object and report the throughput of each direction
+#[tauri::command]
+pub async fn benchmark_transfer(size: u64, app_handle: AppHandle) -> Result<BenchmarkReport, String> {
+    if !(MIN_BENCHMARK_BYTES..=MAX_BENCHMARK_BYTES).contains(&size) {
+        return Err(format!("Benchmark size must be between {} and {} bytes", MIN_BENCHMARK_BYTES, MAX_BENCHMARK_BYTES));
+    }
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let name = format!(".firestarter-benchmark-{}.bin", uuid::Uuid::new_v4().simple());
+    let query = format!("?file_name={}", utf8_percent_encode(&name, QUERY_ENCODE_SET));
+    let payload = synthetic_payload(size as usize);
+    println!("⏱️ Benchmarking a {} byte transfer", size);
+
+    let transfer_config = gateways::transfer_config(&api_config, &credentials, &app_handle).await;
+    let (_, upload_url) = upload_route(&transfer_config, false);
+    let started = Instant::now();
+    let response = workspaces::scope(client.post(format!("{}{}", upload_url, query)), &credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .body(payload)
+        .send()
+        .await
+        .map_err(|e| format!("Upload request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Upload failed - Status: {}", response.status()));
+    }
+    let upload_time = started.elapsed();
+
+    let download: Result<Duration, String> = async {
+        let started = Instant::now();
+        let url = format!("{}{}{}", transfer_config.api_base_url, transfer_config.download, query);
+        let response = workspaces::scope(client.get(url), &credentials)
+            .header("X-User-Id", &credentials.user_id)
+            .header("X-User-App-Key", credentials.user_app_key.expose())
+            .send()
+            .await
+            .map_err(|e| format!("Download request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Download failed - Status: {}", response.status()));
+        }
+        let mut received = 0u64;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            received += chunk.map_err(|e| format!("Download chunk error: {}", e))?.len() as u64;
+        }
+        if received != size {
+            return Err(format!("Downloaded {} of {} bytes", received, size));
+        }
+        Ok(started.elapsed())
+    }
+    .await;
+    if let Err(e) = lifecycle::delete_remote(&client, &api_config, &credentials, &name, &app_handle).await {
+        println_redacted!("[BENCHMARK] Benchmark object {} left behind: {}", name, e);
+    }
+    let download_time = download?;
+
+    let report = BenchmarkReport {
+        size_bytes: size,
+        upload_ms: upload_time.as_millis() as u64,
+        upload_mb_per_sec: mb_per_sec(size, upload_time),
+        download_ms: download_time.as_millis() as u64,
+        download_mb_per_sec: mb_per_sec(size, download_time),
+        tuning: network::client_tuning(&app_handle),
+    };
+    println!("⏱️ Benchmark: up {:.1} MB/s, down {:.1} MB/s", report.upload_mb_per_sec, report.download_mb_per_sec);
+    Ok(report)
+}
@@ -0,0 +1,150 @@
+use std::ffi::OsString;
+use std::sync::{Arc, Mutex};
+use futures_util::StreamExt;
+use tauri::AppHandle;
+use tokio_util::io::ReaderStream;
+
+use super::shell_integration::UPLOAD_FLAG;
+use super::{
+    append_upload_log, current_api_config, encoding, ensure_valid_token, load_credentials, network, upload_bytes,
+    verification, UploadLogEntry,
+};
+
+// =============================================================================================================
+// ================================================ HEADLESS CLI ===============================================
+// =============================================================================================================
+//
+// `firestarter --upload - --remote-name <name>` uploads whatever is piped to stdin with the saved credentials,
+// then exits without opening a window: `pg_dump mydb | firestarter --upload - --remote-name backups/db.sql`.
+// The data streams straight into the request body and is hashed on the way, so nothing is buffered or written
+// to disk. The result goes to stdout, errors to stderr; the exit code is 0 on success, 1 if the upload failed
+// and 2 for bad arguments. `--upload <paths>` without `-` is the file manager entry and still opens the app.
+
+const STDIN_ARG: &str = "-";
+const REMOTE_NAME_FLAG: &str = "--remote-name";
+
+#[derive(Debug, PartialEq)]
+pub struct StdinUpload {
+    pub remote_name: String,
+}
+
+/// The stdin upload asked for on the command line, `None` for a normal (windowed) start
+pub fn stdin_upload(args: &[OsString]) -> Option<Result<StdinUpload, String>> {
+    let start = args.iter().position(|a| a == UPLOAD_FLAG)?;
+    if args.get(start + 1)? != STDIN_ARG {
+        return None;
+    }
+    let remote_name = args
+        .iter()
+        .position(|a| a == REMOTE_NAME_FLAG)
+        .and_then(|i| args.get(i + 1))
+        .map(|name| name.to_string_lossy().to_string());
+    Some(match remote_name {
+        Some(remote_name) => encoding::check_remote_name(&remote_name).map(|()| StdinUpload { remote_name }),
+        None => Err(format!("{} {} needs {} <name>", UPLOAD_FLAG, STDIN_ARG, REMOTE_NAME_FLAG)),
+    })
+}
+
+async fn upload_stdin(upload: &StdinUpload, app_handle: &AppHandle) -> Result<String, String> {
+    let mut credentials = load_credentials(app_handle.clone())
+        .await?
+        .ok_or("No saved credentials found; log in with the app first")?;
+    let api_config = current_api_config(app_handle);
+    let client = network::client(app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, app_handle).await?;
+
+    let hashed = Arc::new(Mutex::new((blake3::Hasher::new(), 0u64)));
+    let tally = hashed.clone();
+    let body = ReaderStream::new(tokio::io::stdin()).inspect(move |chunk| {
+        if let Ok(bytes) = chunk {
+            let mut tally = tally.lock().unwrap();
+            tally.0.update(bytes);
+            tally.1 += bytes.len() as u64;
+        }
+    });
+    let started = std::time::Instant::now();
+    let sent = upload_bytes(&client, &api_config, &credentials, &upload.remote_name, reqwest::Body::wrap_stream(body)).await;
+    let (hash, size) = {
+        let hashed = hashed.lock().unwrap();
+        (hashed.0.finalize().to_hex().to_string(), hashed.1)
+    };
+
+    let server_hash = sent.as_ref().ok().and_then(|response| {
+        verification::server_reported_hash(&reqwest::header::HeaderMap::new(), response)
+    });
+    let result = sent.and_then(|_| match &server_hash {
+        Some(server) if *server != hash => Err(format!("Hash mismatch: server {} / client {}", server, hash)),
+        _ => Ok(format!(
+            "Uploaded {} bytes from stdin as '{}' in {:.1} s (blake3 {})",
+            size,
+            upload.remote_name,
+            started.elapsed().as_secs_f64(),
+            hash
+        )),
+    });
+    let (status, message) = match &result {
+        Ok(message) => ("success", message.clone()),
+        Err(e) => ("failed", e.clone()),
+    };
+    let entry = UploadLogEntry {
+        verified: server_hash.map(|server| server == hash),
+        blake3_hash: hash,
+        file_size: size,
+        workspace_id: credentials.workspace_id.clone(),
+        ..UploadLogEntry::new(STDIN_ARG, &upload.remote_name, status, message)
+    };
+    if let Err(e) = append_upload_log(&credentials.user_id, &entry, app_handle) {
+        eprintln!("{}", e);
+    }
+    result
+}
+
+/// Run the stdin upload, then exit the app with its result. Called from setup instead of opening the window.
+pub fn run_stdin_upload(app_handle: &AppHandle, upload: StdinUpload) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let code = match upload_stdin(&upload, &app_handle).await {
+            Ok(message) => {
+                println!("{}", message);
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        };
+        app_handle.exit(code);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn stdin_uploads_need_a_valid_remote_name() {
+        assert_eq!(
+            stdin_upload(&args(&["firestarter", "--upload", "-", "--remote-name", "backups/db.sql"])),
+            Some(Ok(StdinUpload { remote_name: "backups/db.sql".to_string() }))
+        );
+        assert_eq!(
+            stdin_upload(&args(&["firestarter", "--remote-name", "db.sql", "--upload", "-"])),
+            Some(Ok(StdinUpload { remote_name: "db.sql".to_string() }))
+        );
+        assert!(stdin_upload(&args(&["firestarter", "--upload", "-"])).unwrap().is_err());
+        assert!(stdin_upload(&args(&["firestarter", "--upload", "-", "--remote-name"])).unwrap().is_err());
+        assert!(stdin_upload(&args(&["firestarter", "--upload", "-", "--remote-name", "/db.sql"])).unwrap().is_err());
+    }
+
+    #[test]
+    fn other_starts_open_the_app() {
+        assert_eq!(stdin_upload(&args(&["firestarter"])), None);
+        assert_eq!(stdin_upload(&args(&["firestarter", "--upload", "/home/me/a.txt", "-"])), None);
+        assert_eq!(stdin_upload(&args(&["firestarter", "--upload"])), None);
+        assert_eq!(stdin_upload(&args(&["firestarter", "-", "--remote-name", "x"])), None);
+    }
+}
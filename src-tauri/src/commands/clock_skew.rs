@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+// =============================================================================================================
+// ================================================ CLOCK SKEW =================================================
+// =============================================================================================================
+//
+// Token expiry is the server's clock, not ours. Responses from the API carry a `Date` header; comparing it with
+// the middle of the request's round trip gives the offset between the two clocks, which is kept in memory and
+// applied wherever an expiry is computed or checked. Until the first measurement the offset is zero.
+
+/// Round trips slower than this make the comparison too vague to use
+const MAX_ROUND_TRIP_SECS: i64 = 5;
+/// `Date` has one-second resolution; changes smaller than this are rounding, not a clock change
+const MIN_CHANGE_SECS: i64 = 2;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ClockSkew {
+    /// Server time minus local time
+    pub offset_secs: i64,
+    pub measured_at: String,
+}
+
+pub type ClockSkewState = Mutex<Option<ClockSkew>>;
+pub fn new_clock_skew_state() -> ClockSkewState { Mutex::new(None) }
+
+/// Server minus local seconds from a response's `Date` header, given when the request was sent and answered
+pub fn measure(headers: &reqwest::header::HeaderMap, sent: DateTime<Utc>, received: DateTime<Utc>) -> Option<i64> {
+    if (received - sent).num_seconds() > MAX_ROUND_TRIP_SECS {
+        return None;
+    }
+    let server_time = headers
+        .get(reqwest::header::DATE)?
+        .to_str()
+        .ok()
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())?;
+    let midpoint = sent + (received - sent) / 2;
+    Some((server_time.with_timezone(&Utc) - midpoint).num_seconds())
+}
+
+/// Record the offset seen on an API response
+pub fn observe(app_handle: &AppHandle, headers: &reqwest::header::HeaderMap, sent: DateTime<Utc>, received: DateTime<Utc>) {
+    let Some(offset_secs) = measure(headers, sent, received) else { return };
+    let Some(state) = app_handle.try_state::<ClockSkewState>() else { return };
+    let mut skew = state.lock().unwrap();
+    if skew.as_ref().is_some_and(|s| (s.offset_secs - offset_secs).abs() < MIN_CHANGE_SECS) {
+        return;
+    }
+    if offset_secs.abs() >= MIN_CHANGE_SECS {
+        println!("🕰️ Local clock is {}s {} the server", offset_secs.abs(), if offset_secs < 0 { "ahead of" } else { "behind" });
+    }
+    *skew = Some(ClockSkew { offset_secs, measured_at: Utc::now().to_rfc3339() });
+}
+
+/// Current time on the server's clock, as far as we know it
+pub fn server_now(app_handle: &AppHandle) -> DateTime<Utc> {
+    let offset = app_handle
+        .try_state::<ClockSkewState>()
+        .and_then(|state| state.lock().unwrap().as_ref().map(|s| s.offset_secs))
+        .unwrap_or(0);
+    Utc::now() + chrono::Duration::seconds(offset)
+}
+
+/// Last measured offset, `None` before any API response has been seen
+#[tauri::command]
+pub async fn get_clock_skew(app_handle: AppHandle) -> Result<Option<ClockSkew>, String> {
+    Ok(app_handle.state::<ClockSkewState>().lock().unwrap().clone())
+}
@@ -0,0 +1,320 @@
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{account_scope, get_link_file_path, get_user_data_dir, update_public_links, UploadLogEntry};
+
+// =============================================================================================================
+// ============================================ LOCAL DATA COMPACTION ==========================================
+// =============================================================================================================
+//
+// The upload log is rotated into `history-archive/` once it passes `HISTORY_ROTATE_BYTES`, so normal history
+// reads stay small. `history-index-<user>.json` describes every archive (entry count, time range) so the UI
+// can list them without opening each file. Retention limits (`prune_history`) cut the oldest entries from the
+// archives and the current log; archives left empty are deleted.
+
+const HISTORY_ROTATE_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryArchive {
+    pub file: String,
+    pub entries: usize,
+    pub bytes: u64,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+    pub archived_at: String,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CompactionReport {
+    pub history_entries_kept: usize,
+    pub history_lines_dropped: usize,
+    pub history_rotated: Option<HistoryArchive>,
+    pub duplicate_links_removed: usize,
+    pub link_file_bytes_before: u64,
+    pub link_file_bytes_after: u64,
+}
+
+/// What a retention pass removed from one account's history
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct HistoryPrune {
+    pub entries_removed: usize,
+    pub archives_removed: usize,
+    pub bytes_freed: u64,
+}
+
+fn history_log_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join(format!("list-upload-{}.json", user_id)))
+}
+
+fn archive_dir(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join("history-archive"))
+}
+
+fn index_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join(format!("history-index-{}.json", user_id)))
+}
+
+fn read_index(user_id: &str, app_handle: &AppHandle) -> Vec<HistoryArchive> {
+    index_path(user_id, app_handle)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(user_id: &str, index: &[HistoryArchive], app_handle: &AppHandle) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(index).map_err(|e| format!("Failed to serialize history index: {}", e))?;
+    std::fs::write(index_path(user_id, app_handle)?, json).map_err(|e| format!("Failed to write history index: {}", e))
+}
+
+fn parse_entries(content: &str) -> (Vec<UploadLogEntry>, usize) {
+    let mut dropped = 0;
+    let entries = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| match serde_json::from_str::<UploadLogEntry>(l) {
+            Ok(entry) => Some(entry),
+            Err(_) => {
+                dropped += 1;
+                None
+            }
+        })
+        .collect();
+    (entries, dropped)
+}
+
+/// Move the current log into the archive and start a fresh one
+fn rotate_history(user_id: &str, app_handle: &AppHandle) -> Result<HistoryArchive, String> {
+    let log_path = history_log_path(user_id, app_handle)?;
+    let content = std::fs::read_to_string(&log_path).map_err(|e| format!("Failed to read log file: {}", e))?;
+    let (entries, _) = parse_entries(&content);
+
+    let dir = archive_dir(user_id, app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create history archive dir: {}", e))?;
+    let file = format!("list-upload-{}-{}.jsonl", user_id, Utc::now().format("%Y%m%dT%H%M%S"));
+    let target = dir.join(&file);
+    std::fs::rename(&log_path, &target).map_err(|e| format!("Failed to archive log file: {}", e))?;
+
+    let archive = HistoryArchive {
+        file,
+        entries: entries.len(),
+        bytes: std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0),
+        first_timestamp: entries.first().map(|e| e.timestamp.clone()),
+        last_timestamp: entries.last().map(|e| e.timestamp.clone()),
+        archived_at: Utc::now().to_rfc3339(),
+    };
+    let mut index = read_index(user_id, app_handle);
+    index.push(archive.clone());
+    write_index(user_id, &index, app_handle)?;
+    println!("🗄️ Archived upload history for {} ({} entries)", user_id, archive.entries);
+    Ok(archive)
+}
+
+/// Called by the history writer after each batch
+pub fn rotate_history_if_needed(user_id: &str, app_handle: &AppHandle) -> Result<Option<HistoryArchive>, String> {
+    let log_path = history_log_path(user_id, app_handle)?;
+    let size = std::fs::metadata(&log_path).map(|m| m.len()).unwrap_or(0);
+    if size < HISTORY_ROTATE_BYTES {
+        return Ok(None);
+    }
+    rotate_history(user_id, app_handle).map(Some)
+}
+
+fn is_expired(timestamp: &str, cutoff: Option<DateTime<Utc>>) -> bool {
+    cutoff.is_some_and(|cutoff| DateTime::parse_from_rfc3339(timestamp).is_ok_and(|t| t < cutoff))
+}
+
+/// History lines (oldest first) without entries older than `cutoff` or beyond the newest `keep_last`. Lines
+/// that don't parse are kept and don't count. Returns the remaining content and the entries kept and removed.
+fn prune_lines(content: &str, cutoff: Option<DateTime<Utc>>, keep_last: Option<usize>) -> (String, usize, usize) {
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut keep = vec![true; lines.len()];
+    let mut budget = keep_last;
+    let (mut kept, mut removed) = (0, 0);
+    for (i, line) in lines.iter().enumerate().rev() {
+        let Ok(entry) = serde_json::from_str::<UploadLogEntry>(line) else { continue };
+        if budget == Some(0) || is_expired(&entry.timestamp, cutoff) {
+            keep[i] = false;
+            removed += 1;
+        } else {
+            budget = budget.map(|b| b - 1);
+            kept += 1;
+        }
+    }
+    let mut pruned = String::with_capacity(content.len());
+    for (line, _) in lines.iter().zip(&keep).filter(|(_, keep)| **keep) {
+        pruned.push_str(line);
+        pruned.push('\n');
+    }
+    (pruned, kept, removed)
+}
+
+/// Rewrite a history file through a temporary file so a crash can't leave it half written
+fn replace_file(path: &Path, content: &str) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Drop history entries older than `cutoff` and all but the newest `keep_last`, across the current log and the
+/// archives. Runs on the history writer task (`history_log::prune`) so no append lands in between.
+pub fn prune_history(
+    user_id: &str,
+    cutoff: Option<DateTime<Utc>>,
+    keep_last: Option<usize>,
+    app_handle: &AppHandle,
+) -> Result<HistoryPrune, String> {
+    let mut pruned = HistoryPrune::default();
+    let mut budget = keep_last;
+
+    let log_path = history_log_path(user_id, app_handle)?;
+    if log_path.exists() {
+        let content = std::fs::read_to_string(&log_path).map_err(|e| format!("Failed to read log file: {}", e))?;
+        let (remaining, kept, removed) = prune_lines(&content, cutoff, budget);
+        if removed > 0 {
+            replace_file(&log_path, &remaining)?;
+            pruned.entries_removed += removed;
+            pruned.bytes_freed += (content.len() - remaining.len()) as u64;
+        }
+        budget = budget.map(|b| b.saturating_sub(kept));
+    }
+
+    let dir = archive_dir(user_id, app_handle)?;
+    let index = read_index(user_id, app_handle);
+    let mut kept_archives = Vec::with_capacity(index.len());
+    // newest archive first, so the entry budget goes to recent history
+    for mut archive in index.into_iter().rev() {
+        let path = dir.join(&archive.file);
+        let expired = archive.last_timestamp.as_deref().is_some_and(|t| is_expired(t, cutoff)) || budget == Some(0);
+        let within = archive.entries <= budget.unwrap_or(usize::MAX) && !archive.first_timestamp.as_deref().is_some_and(|t| is_expired(t, cutoff));
+        if !expired && within {
+            budget = budget.map(|b| b - archive.entries);
+            kept_archives.push(archive);
+            continue;
+        }
+        let (remaining, kept, removed) = if expired {
+            (String::new(), 0, archive.entries)
+        } else {
+            let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read history archive: {}", e))?;
+            prune_lines(&content, cutoff, budget)
+        };
+        pruned.entries_removed += removed;
+        if kept == 0 {
+            pruned.bytes_freed += std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(format!("Failed to delete history archive: {}", e)),
+                _ => pruned.archives_removed += 1,
+            }
+            continue;
+        }
+        if removed > 0 {
+            replace_file(&path, &remaining)?;
+            pruned.bytes_freed += archive.bytes.saturating_sub(remaining.len() as u64);
+            archive.entries = kept;
+            archive.bytes = remaining.len() as u64;
+            archive.first_timestamp = remaining.lines().find_map(|l| serde_json::from_str::<UploadLogEntry>(l).ok()).map(|e| e.timestamp);
+        }
+        budget = budget.map(|b| b.saturating_sub(kept));
+        kept_archives.push(archive);
+    }
+    if pruned.entries_removed > 0 || pruned.archives_removed > 0 {
+        kept_archives.reverse();
+        write_index(user_id, &kept_archives, app_handle)?;
+        println!("🗄️ Pruned {} history entries for {}", pruned.entries_removed, user_id);
+    }
+    Ok(pruned)
+}
+
+/// Drop unparseable history lines, rotate an oversized log, and dedupe the public link file
+#[tauri::command]
+pub async fn compact_local_data(user_id: String, app_handle: AppHandle) -> Result<CompactionReport, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut report = CompactionReport::default();
+    super::history_log::flush(&app_handle).await;
+
+    let log_path = history_log_path(&user_id, &app_handle)?;
+    if log_path.exists() {
+        let content = std::fs::read_to_string(&log_path).map_err(|e| format!("Failed to read log file: {}", e))?;
+        let (entries, dropped) = parse_entries(&content);
+        if dropped > 0 {
+            let mut compacted = String::new();
+            for entry in &entries {
+                compacted.push_str(&serde_json::to_string(entry).map_err(|e| format!("Failed to serialize log entry: {}", e))?);
+                compacted.push('\n');
+            }
+            std::fs::write(&log_path, compacted).map_err(|e| format!("Failed to rewrite log file: {}", e))?;
+        }
+        report.history_entries_kept = entries.len();
+        report.history_lines_dropped = dropped;
+        report.history_rotated = rotate_history_if_needed(&user_id, &app_handle)?;
+    }
+
+    let link_path = get_link_file_path(&user_id, &app_handle)?;
+    if link_path.exists() {
+        report.link_file_bytes_before = std::fs::metadata(&link_path).map(|m| m.len()).unwrap_or(0);
+        report.duplicate_links_removed = update_public_links(&user_id, &app_handle, |links| {
+            let before = links.len();
+            // keep the newest entry per hash, in original order
+            let mut deduped = Vec::with_capacity(links.len());
+            for (i, link) in links.iter().enumerate() {
+                if !links[i + 1..].iter().any(|l| l.link_hash == link.link_hash) {
+                    deduped.push(link.clone());
+                }
+            }
+            *links = deduped;
+            before - links.len()
+        })?;
+        report.link_file_bytes_after = std::fs::metadata(&link_path).map(|m| m.len()).unwrap_or(0);
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_history_archives(user_id: String, app_handle: AppHandle) -> Result<Vec<HistoryArchive>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    Ok(read_index(&user_id, &app_handle))
+}
+
+#[tauri::command]
+pub async fn get_archived_history(user_id: String, file: String, app_handle: AppHandle) -> Result<Vec<UploadLogEntry>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    if !read_index(&user_id, &app_handle).iter().any(|a| a.file == file) {
+        return Err(format!("Unknown history archive: {}", file));
+    }
+    let path = archive_dir(&user_id, &app_handle)?.join(&file);
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read history archive: {}", e))?;
+    Ok(parse_entries(&content).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(timestamp: &str) -> String {
+        serde_json::json!({
+            "local_path": "/tmp/a", "remote_path": "a", "status": "SUCCESS", "message": "",
+            "blake3_hash": "", "file_size": 1, "timestamp": timestamp,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn pruning_drops_old_and_excess_entries() {
+        let content = [line("2024-01-01T00:00:00Z"), "not json".to_string(), line("2024-06-01T00:00:00Z"), line("2024-07-01T00:00:00Z")]
+            .join("\n");
+        let cutoff = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z").unwrap().with_timezone(&Utc);
+
+        let (remaining, kept, removed) = prune_lines(&content, Some(cutoff), None);
+        assert_eq!((kept, removed), (2, 1));
+        assert!(remaining.starts_with("not json\n"));
+
+        let (remaining, kept, removed) = prune_lines(&content, None, Some(1));
+        assert_eq!((kept, removed), (1, 2));
+        assert_eq!(remaining, format!("not json\n{}\n", line("2024-07-01T00:00:00Z")));
+
+        assert_eq!(prune_lines(&content, None, None).2, 0);
+    }
+}
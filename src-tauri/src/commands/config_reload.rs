@@ -0,0 +1,89 @@
+// demo builds serve endpoints from the mock backend and never read the file
+#![cfg_attr(feature = "demo", allow(dead_code))]
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{data_dir, ApiConfig, ApiConfigState};
+
+// =============================================================================================================
+// ============================================== ENDPOINT CONFIG ==============================================
+// =============================================================================================================
+//
+// The bundled `api_endpoints.json` can be overridden by a file of the same name in the app config dir; any key
+// it sets replaces the bundled value. The file is watched (by modification time) and changes are loaded into
+// `ApiConfigState` without a restart. A file that doesn't parse is reported and the endpoints in effect stay.
+
+const ENDPOINTS_FILE: &str = "api_endpoints.json";
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+pub fn endpoints_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir::app_config_dir(app_handle)?.join(ENDPOINTS_FILE))
+}
+
+/// Bundled endpoints plus the external file's overrides; bundled only when there is no file
+fn read_config(path: &Path) -> Result<ApiConfig, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => ApiConfig::with_overrides(&content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ApiConfig::default()),
+        Err(e) => Err(format!("Failed to read endpoints file: {}", e)),
+    }
+}
+
+/// Config to start with. Called once in setup, before the state is managed.
+pub fn load_api_config(app_handle: &AppHandle) -> ApiConfig {
+    let loaded = endpoints_file_path(app_handle).and_then(|path| read_config(&path));
+    loaded.unwrap_or_else(|e| {
+        println!("[CONFIG] {}, using bundled endpoints", e);
+        ApiConfig::default()
+    })
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn reload(path: &Path, app_handle: &AppHandle) {
+    let config = match read_config(path) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("[CONFIG] {}; keeping the current endpoints", e);
+            app_handle.emit("api_config_error", e).ok();
+            return;
+        }
+    };
+    let state = app_handle.state::<ApiConfigState>();
+    {
+        let mut current = state.lock().unwrap();
+        if serde_json::to_value(&*current).ok() == serde_json::to_value(&config).ok() {
+            return;
+        }
+        *current = config.clone();
+    }
+    println!("🔧 Reloaded endpoints from {}", path.display());
+    app_handle.emit("api_config_changed", config).ok();
+}
+
+/// Poll the endpoints file and reload it when it changes, appears or goes away. Called once in setup.
+pub fn start_config_watcher(app_handle: &AppHandle) {
+    let path = match endpoints_file_path(app_handle) {
+        Ok(path) => path,
+        Err(e) => {
+            println!("[CONFIG] {}", e);
+            return;
+        }
+    };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last = modified(&path);
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+            let current = modified(&path);
+            if current != last {
+                last = current;
+                reload(&path, &app_handle);
+            }
+        }
+    });
+}
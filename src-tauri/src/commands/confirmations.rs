@@ -0,0 +1,287 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Webview};
+
+use super::secret::SecretString;
+use super::{
+    account, account_scope, audit, current_api_config, ensure_valid_token, ipc_guard, lifecycle, load_credentials, network,
+    remote_trash, vault, withdraw_sol, ApiConfig,
+};
+
+// =============================================================================================================
+// =============================================== CONFIRMATIONS ===============================================
+// =============================================================================================================
+//
+// Two-phase confirmation for irreversible actions. A `request_*` command validates the action and registers it,
+// returning a token and a summary to show the user; nothing happens until `confirm_action(token)` is called
+// within `CONFIRM_TTL`. Tokens are single use and carry the action kind, so a token for one kind of action
+// can't be confused with another. Secrets (passwords, passphrases) stay in memory here and never go back out.
+
+const CONFIRM_TTL: Duration = Duration::from_secs(60);
+
+enum PendingAction {
+    Withdraw { to_address: String, amount: f64 },
+    DeleteAccount { password: SecretString, confirmation_phrase: String },
+    DeleteRemoteFiles { user_id: String, names: Vec<String> },
+    RotateVaultKey { user_id: String, key_id: String, old_passphrase: SecretString, new_passphrase: SecretString },
+    /// `None` empties the whole trash
+    PurgeRemoteTrash { user_id: String, names: Option<Vec<String>> },
+}
+
+impl PendingAction {
+    fn kind(&self) -> &'static str {
+        match self {
+            PendingAction::Withdraw { .. } => "withdraw",
+            PendingAction::DeleteAccount { .. } => "delete_account",
+            PendingAction::DeleteRemoteFiles { .. } => "delete_remote_files",
+            PendingAction::RotateVaultKey { .. } => "rotate_vault_key",
+            PendingAction::PurgeRemoteTrash { .. } => "purge_remote_trash",
+        }
+    }
+
+    fn summary(&self) -> String {
+        match self {
+            PendingAction::Withdraw { to_address, amount } => format!("Withdraw {} SOL to {}", amount, to_address),
+            PendingAction::DeleteAccount { .. } => "Permanently delete this account and its local data".to_string(),
+            PendingAction::DeleteRemoteFiles { names, .. } => format!("Permanently delete {} remote file(s)", names.len()),
+            PendingAction::RotateVaultKey { key_id, .. } => format!("Change the passphrase of vault key {}", key_id),
+            PendingAction::PurgeRemoteTrash { names: Some(names), .. } => {
+                format!("Permanently delete {} file(s) from the remote trash", names.len())
+            }
+            PendingAction::PurgeRemoteTrash { names: None, .. } => "Permanently delete everything in the remote trash".to_string(),
+        }
+    }
+}
+
+/// Endpoints the confirmed actions call. The generic proxies refuse them (`ipc_guard::check_proxy_target`), or
+/// the frontend could skip the confirmation by calling them directly.
+pub fn gated_endpoints(api_config: &ApiConfig) -> Vec<&str> {
+    let optional = [&api_config.delete_account, &api_config.delete_file, &api_config.purge_trash];
+    std::iter::once(api_config.withdraw_sol.as_str())
+        .chain(optional.into_iter().filter_map(|e| e.as_deref()))
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+pub struct Pending {
+    action: PendingAction,
+    registered: Instant,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PendingActionInfo {
+    pub token: String,
+    pub kind: String,
+    pub summary: String,
+    pub expires_at: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RemoteDeleteFailure {
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RemoteDeleteResult {
+    pub deleted: Vec<String>,
+    pub failed: Vec<RemoteDeleteFailure>,
+}
+
+pub type ConfirmationsState = Mutex<HashMap<String, Pending>>;
+pub fn new_confirmations_state() -> ConfirmationsState { Mutex::new(HashMap::new()) }
+
+fn register(action: PendingAction, app_handle: &AppHandle) -> PendingActionInfo {
+    let token = format!("{}-{}", action.kind(), uuid::Uuid::new_v4().simple());
+    let expires_at: DateTime<Utc> = Utc::now() + chrono::Duration::from_std(CONFIRM_TTL).unwrap_or_default();
+    let info = PendingActionInfo {
+        token: token.clone(),
+        kind: action.kind().to_string(),
+        summary: action.summary(),
+        expires_at: expires_at.to_rfc3339(),
+    };
+    let state = app_handle.state::<ConfirmationsState>();
+    let mut pending = state.lock().unwrap();
+    pending.retain(|_, p| p.registered.elapsed() < CONFIRM_TTL);
+    pending.insert(token, Pending { action, registered: Instant::now() });
+    println!("⏳ Awaiting confirmation: {}", info.summary);
+    info
+}
+
+/// Remove and return the action for `token` if it hasn't expired
+fn take(token: &str, app_handle: &AppHandle) -> Result<PendingAction, String> {
+    let state = app_handle.state::<ConfirmationsState>();
+    let mut pending = state.lock().unwrap();
+    let entry = pending.remove(token).ok_or("Unknown or already used confirmation token")?;
+    if entry.registered.elapsed() >= CONFIRM_TTL {
+        return Err("Confirmation expired; start the action again".to_string());
+    }
+    Ok(entry.action)
+}
+
+#[tauri::command]
+pub async fn request_withdrawal(to_address: String, amount: f64, app_handle: AppHandle) -> Result<PendingActionInfo, String> {
+    let to_address = to_address.trim().to_string();
+    if to_address.is_empty() || !amount.is_finite() || amount <= 0.0 {
+        return Err("Invalid address or amount".to_string());
+    }
+    Ok(register(PendingAction::Withdraw { to_address, amount }, &app_handle))
+}
+
+#[tauri::command]
+pub async fn request_account_deletion(password: SecretString, confirmation_phrase: String, app_handle: AppHandle) -> Result<PendingActionInfo, String> {
+    if password.is_empty() {
+        return Err("Password is required".to_string());
+    }
+    let credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let expected = account::deletion_phrase(&credentials);
+    if confirmation_phrase.trim() != expected {
+        return Err(format!("Type '{}' to confirm", expected));
+    }
+    Ok(register(PendingAction::DeleteAccount { password, confirmation_phrase }, &app_handle))
+}
+
+#[tauri::command]
+pub async fn request_remote_delete(user_id: String, names: Vec<String>, app_handle: AppHandle) -> Result<PendingActionInfo, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut names: Vec<String> = names.into_iter().map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect();
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Err("No files to delete".to_string());
+    }
+    let api_config = current_api_config(&app_handle);
+    api_config.optional_url(&api_config.delete_file, "Delete file")?;
+    Ok(register(PendingAction::DeleteRemoteFiles { user_id, names }, &app_handle))
+}
+
+/// Ask to purge `names` from the remote trash, or all of it when `None`; runs once confirmed
+#[tauri::command]
+pub async fn purge_remote_trash(user_id: String, names: Option<Vec<String>>, app_handle: AppHandle) -> Result<PendingActionInfo, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let names = names.map(|names| {
+        let mut names: Vec<String> = names.into_iter().map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect();
+        names.sort();
+        names.dedup();
+        names
+    });
+    if names.as_ref().is_some_and(Vec::is_empty) {
+        return Err("No files to purge".to_string());
+    }
+    let api_config = current_api_config(&app_handle);
+    api_config.optional_url(&api_config.purge_trash, "Purge trash")?;
+    Ok(register(PendingAction::PurgeRemoteTrash { user_id, names }, &app_handle))
+}
+
+#[tauri::command]
+pub async fn request_key_rotation(
+    user_id: String,
+    key_id: String,
+    old_passphrase: SecretString,
+    new_passphrase: SecretString,
+    app_handle: AppHandle,
+) -> Result<PendingActionInfo, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    if new_passphrase.expose().len() < 8 {
+        return Err("Passphrase must be at least 8 characters".to_string());
+    }
+    // fail now rather than after confirming
+    vault::unlock_key(&user_id, &key_id, old_passphrase.expose(), &app_handle)?;
+    Ok(register(PendingAction::RotateVaultKey { user_id, key_id, old_passphrase, new_passphrase }, &app_handle))
+}
+
+async fn delete_remote_files(user_id: &str, names: Vec<String>, app_handle: &AppHandle) -> Result<RemoteDeleteResult, String> {
+    let api_config = current_api_config(app_handle);
+    let client = network::client(app_handle);
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    if credentials.user_id != user_id {
+        return Err("Signed in as a different user".to_string());
+    }
+    ensure_valid_token(&client, &api_config, &mut credentials, app_handle).await?;
+
+    let mut result = RemoteDeleteResult { deleted: Vec::new(), failed: Vec::new() };
+    for name in names {
+        let deleted = lifecycle::delete_remote(&client, &api_config, &credentials, &name, app_handle).await;
+        audit::record(app_handle, "remote_delete", &name, Some(user_id), Some("bulk".to_string()), &deleted);
+        match deleted {
+            Ok(()) => result.deleted.push(name),
+            Err(error) => result.failed.push(RemoteDeleteFailure { name, error }),
+        }
+    }
+    Ok(result)
+}
+
+/// Run a registered action. The token is used up whether or not the action succeeds.
+#[tauri::command]
+pub async fn confirm_action(token: String, ipc_nonce: String, webview: Webview, app_handle: AppHandle) -> Result<serde_json::Value, String> {
+    ipc_guard::check_sensitive("confirm_action", &webview, &ipc_nonce, &app_handle)?;
+    let action = take(&token, &app_handle)?;
+    let kind = action.kind();
+    let result = match action {
+        PendingAction::Withdraw { to_address, amount } => withdraw_sol(app_handle.clone(), to_address, amount).await,
+        PendingAction::DeleteAccount { password, confirmation_phrase } => {
+            account::delete_account(password, confirmation_phrase, app_handle.clone()).await.map(serde_json::Value::String)
+        }
+        PendingAction::DeleteRemoteFiles { user_id, names } => delete_remote_files(&user_id, names, &app_handle)
+            .await
+            .and_then(|r| serde_json::to_value(r).map_err(|e| format!("Failed to serialize result: {}", e))),
+        PendingAction::RotateVaultKey { user_id, key_id, old_passphrase, new_passphrase } => {
+            let rotated = vault::rewrap_vault_key(&user_id, &key_id, old_passphrase.expose(), new_passphrase.expose(), &app_handle);
+            audit::record(&app_handle, "vault_key_rotate", &key_id, Some(&user_id), None, &rotated);
+            rotated.and_then(|info| serde_json::to_value(info).map_err(|e| format!("Failed to serialize result: {}", e)))
+        }
+        PendingAction::PurgeRemoteTrash { user_id, names } => remote_trash::purge(&user_id, names, &app_handle)
+            .await
+            .and_then(|purged| serde_json::to_value(purged).map_err(|e| format!("Failed to serialize result: {}", e))),
+    };
+    println!("✅ Confirmed {} ({})", kind, if result.is_ok() { "done" } else { "failed" });
+    result
+}
+
+#[tauri::command]
+pub async fn cancel_action(token: String, app_handle: AppHandle) -> Result<(), String> {
+    app_handle.state::<ConfirmationsState>().lock().unwrap().remove(&token);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxies_refuse_every_gated_endpoint() {
+        let api_config = ApiConfig {
+            api_base_url: "https://api.example.com".to_string(),
+            withdraw_sol: "/withdraw_sol".to_string(),
+            delete_account: Some("/account/delete".to_string()),
+            delete_file: Some("/files/delete".to_string()),
+            purge_trash: Some("/trash/purge".to_string()),
+            ..ApiConfig::default()
+        };
+        let endpoints = gated_endpoints(&api_config);
+        assert_eq!(endpoints.len(), 4);
+        for endpoint in endpoints {
+            let url = api_config.resolve_url(endpoint);
+            assert!(ipc_guard::check_proxy_target(&url, &api_config).is_err(), "allowed {}", url);
+        }
+    }
+
+    #[test]
+    fn proxies_refuse_other_origins() {
+        let api_config = ApiConfig { api_base_url: "https://api.example.com".to_string(), ..ApiConfig::default() };
+        for url in [
+            "https://attacker.example/whatever",
+            "http://api.example.com/files/list",
+            "https://api.example.com:8443/files/list",
+            "https://api.example.com.attacker.example/files/list",
+            "https://user@attacker.example/api.example.com",
+            "file:///etc/passwd",
+        ] {
+            assert!(ipc_guard::check_proxy_target(url, &api_config).is_err(), "allowed {}", url);
+        }
+        assert!(ipc_guard::check_proxy_target("https://API.example.com/files/list?x=1", &api_config).is_ok());
+    }
+}
@@ -0,0 +1,266 @@
+use percent_encoding::utf8_percent_encode;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::redaction::println_redacted;
+use super::{
+    current_api_config, ensure_valid_token, is_upload_in_flight, load_credentials, network, read_upload_history,
+    workspaces, ApiConfig, SavedCredentials, QUERY_ENCODE_SET,
+};
+
+// =============================================================================================================
+// ============================================== NAME CONFLICTS ===============================================
+// =============================================================================================================
+//
+// The API has no listing or HEAD endpoint, so existence is probed with a one-byte ranged download.
+// If the probe itself fails, the local upload history is used instead.
+
+/// How many `name (n).ext` candidates auto-rename tries
+const MAX_RENAME_ATTEMPTS: u32 = 50;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RemoteExistence {
+    pub name: String,
+    pub exists: bool,
+    pub size: Option<u64>,
+    /// "remote" when the API answered, "history" when it couldn't be reached
+    pub source: String,
+    /// First free `name (n).ext` when `exists`
+    pub suggested_name: Option<String>,
+}
+
+/// `Ok(Some(size))` if the object exists, `Ok(None)` on 404
+pub(super) async fn probe_remote(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: &str,
+) -> Result<Option<Option<u64>>, String> {
+    use reqwest::header::{CONTENT_RANGE, RANGE};
+
+    let url = format!(
+        "{}{}?file_name={}",
+        api_config.api_base_url,
+        api_config.download,
+        utf8_percent_encode(name, QUERY_ENCODE_SET)
+    );
+    let response = workspaces::scope(client.get(&url), credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .header(RANGE, "bytes=0-0")
+        .send()
+        .await
+        .map_err(|e| format!("Existence check failed: {}", e))?;
+
+    let status = response.status();
+    if status.as_u16() == 404 {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(format!("Existence check failed - Status: {}", status));
+    }
+    // dropping the response closes the connection, even if Range was ignored
+    let size = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit('/').next())
+        .and_then(|v| v.parse::<u64>().ok())
+        .or_else(|| if status.as_u16() == 200 { response.content_length() } else { None });
+    Ok(Some(size))
+}
+
+async fn exists_in_history(user_id: &str, name: &str, app_handle: &AppHandle) -> bool {
+    read_upload_history(user_id.to_string(), app_handle.clone())
+        .await
+        .map(|entries| entries.iter().any(|e| e.status == "success" && e.remote_path == name))
+        .unwrap_or(false)
+}
+
+/// `photo (3)` -> (`photo`, Some(3))
+fn split_number(stem: &str) -> (&str, Option<u32>) {
+    stem.strip_suffix(')')
+        .and_then(|s| s.rsplit_once(" ("))
+        .and_then(|(base, n)| Some((base, n.parse().ok()?)))
+        .filter(|(base, _)| !base.is_empty())
+        .map_or((stem, None), |(base, n)| (base, Some(n)))
+}
+
+/// `photo.jpg` -> `photo (n).jpg`, keeping any folder prefix. An existing ` (k)` suffix is replaced, not nested.
+fn numbered_name(name: &str, n: u32) -> String {
+    let (dir, file) = match name.rsplit_once('/') {
+        Some((dir, file)) => (format!("{}/", dir), file),
+        None => (String::new(), name),
+    };
+    match file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}{} ({}).{}", dir, split_number(stem).0, n, ext),
+        _ => format!("{}{} ({})", dir, split_number(file).0, n),
+    }
+}
+
+/// Number the first candidate gets: 2 for `photo.jpg`, k + 1 for `photo (k).jpg`
+fn first_number(name: &str) -> u32 {
+    let file = name.rsplit_once('/').map_or(name, |(_, file)| file);
+    let stem = match file.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => file,
+    };
+    split_number(stem).1.map_or(2, |n| n.saturating_add(1))
+}
+
+/// "rename" also accepts the UI's "auto-rename"; unset keeps the old behaviour, "overwrite"
+fn normalize_strategy(on_conflict: Option<&str>) -> &str {
+    match on_conflict.unwrap_or("overwrite") {
+        "auto-rename" | "auto_rename" => "rename",
+        other => other,
+    }
+}
+
+async fn check_existence(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: &str,
+    app_handle: &AppHandle,
+) -> (bool, Option<u64>, &'static str) {
+    match probe_remote(client, api_config, credentials, name).await {
+        Ok(found) => (found.is_some(), found.flatten(), "remote"),
+        Err(e) => {
+            println_redacted!("[CONFLICT] {}, falling back to history", e);
+            (exists_in_history(&credentials.user_id, name, app_handle).await, None, "history")
+        }
+    }
+}
+
+async fn next_free_name(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: &str,
+    app_handle: &AppHandle,
+) -> Result<String, String> {
+    let start = first_number(name);
+    for n in (start..).take(MAX_RENAME_ATTEMPTS as usize) {
+        let candidate = numbered_name(name, n);
+        // a name another upload is about to take counts as taken
+        if is_upload_in_flight(&candidate, app_handle) {
+            continue;
+        }
+        if !check_existence(client, api_config, credentials, &candidate, app_handle).await.0 {
+            return Ok(candidate);
+        }
+    }
+    Err(format!("No free name found for '{}'", name))
+}
+
+/// Final remote name for an upload given `on_conflict`: "overwrite" (default) | "rename" (or "auto-rename") |
+/// "abort". Rename picks the next free `name (n).ext`, checked against the server, or the history when it's
+/// unreachable.
+pub async fn resolve_upload_conflict(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: String,
+    on_conflict: Option<&str>,
+    app_handle: &AppHandle,
+) -> Result<String, String> {
+    let strategy = normalize_strategy(on_conflict);
+    if strategy == "overwrite" {
+        return Ok(name);
+    }
+    let taken = (strategy == "rename" && is_upload_in_flight(&name, app_handle))
+        || check_existence(client, api_config, credentials, &name, app_handle).await.0;
+    if !taken {
+        return Ok(name);
+    }
+    match strategy {
+        "rename" => {
+            let renamed = next_free_name(client, api_config, credentials, &name, app_handle).await?;
+            println!("✏️ '{}' exists, uploading as '{}'", name, renamed);
+            Ok(renamed)
+        }
+        "abort" => {
+            let suggestion = next_free_name(client, api_config, credentials, &name, app_handle).await.unwrap_or_default();
+            Err(format!(
+                "Conflict: '{}' already exists. Choose overwrite, rename (next free name: '{}') or abort.",
+                name, suggestion
+            ))
+        }
+        other => Err(format!("Unknown conflict strategy: {}", other)),
+    }
+}
+
+/// What `resolve_upload_conflict` would do, without failing on "abort"
+#[derive(Serialize, Debug, Clone)]
+pub struct ConflictPreview {
+    pub exists: bool,
+    /// "none" | "overwrite" | "rename" | "abort"
+    pub action: String,
+    /// Name the upload would use, `None` if it would abort
+    pub final_name: Option<String>,
+}
+
+pub(super) async fn preview_upload_conflict(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: &str,
+    on_conflict: Option<&str>,
+    app_handle: &AppHandle,
+) -> Result<ConflictPreview, String> {
+    let exists = check_existence(client, api_config, credentials, name, app_handle).await.0;
+    let (action, final_name) = match (exists, normalize_strategy(on_conflict)) {
+        (false, _) => ("none", Some(name.to_string())),
+        (true, "overwrite") => ("overwrite", Some(name.to_string())),
+        (true, "rename") => ("rename", Some(next_free_name(client, api_config, credentials, name, app_handle).await?)),
+        (true, "abort") => ("abort", None),
+        (true, other) => return Err(format!("Unknown conflict strategy: {}", other)),
+    };
+    Ok(ConflictPreview { exists, action: action.to_string(), final_name })
+}
+
+#[tauri::command]
+pub async fn check_remote_exists(name: String, app_handle: AppHandle) -> Result<RemoteExistence, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let (exists, size, source) = check_existence(&client, &api_config, &credentials, &name, &app_handle).await;
+    let suggested_name = if exists {
+        next_free_name(&client, &api_config, &credentials, &name, &app_handle).await.ok()
+    } else {
+        None
+    };
+    Ok(RemoteExistence { name, exists, size, source: source.to_string(), suggested_name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbered_names_keep_folder_and_extension() {
+        assert_eq!(numbered_name("photo.jpg", 2), "photo (2).jpg");
+        assert_eq!(numbered_name("trips/2024/photo.tar.gz", 3), "trips/2024/photo.tar (3).gz");
+        assert_eq!(numbered_name("README", 2), "README (2)");
+        assert_eq!(numbered_name(".bashrc", 2), ".bashrc (2)");
+        assert_eq!(numbered_name("v1.2/notes", 2), "v1.2/notes (2)");
+    }
+
+    #[test]
+    fn existing_numbers_are_replaced_not_nested() {
+        assert_eq!(numbered_name("photo (3).jpg", 4), "photo (4).jpg");
+        assert_eq!(numbered_name("docs/report (9)", 10), "docs/report (10)");
+        assert_eq!(numbered_name("draft (final).txt", 2), "draft (final) (2).txt");
+        assert_eq!(numbered_name(" (2).txt", 3), " (2) (3).txt");
+    }
+
+    #[test]
+    fn numbering_continues_from_the_name_given() {
+        assert_eq!(first_number("photo.jpg"), 2);
+        assert_eq!(first_number("album/photo (7).jpg"), 8);
+        assert_eq!(first_number("photo (x).jpg"), 2);
+        assert_eq!(first_number(&format!("a ({})", u32::MAX)), u32::MAX);
+    }
+}
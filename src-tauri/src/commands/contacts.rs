@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{account_scope, get_user_data_dir, link_email, PublicLinkEntry};
+
+// =============================================================================================================
+// ================================================= CONTACTS ==================================================
+// =============================================================================================================
+//
+// People this account shares links with, kept in `contacts-<user>.json`: name, email and an optional default
+// message that share flows use as the email body template. Every share to a contact is appended to its share
+// history (newest kept, `MAX_SHARES_PER_CONTACT` at most), and contacts shared with recently list first.
+
+const MAX_SHARES_PER_CONTACT: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContactShare {
+    pub link_hash: String,
+    pub remote_path: String,
+    pub shared_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    /// Body template for emails to this contact, with the same fields as `share_link_via_email`
+    #[serde(default)]
+    pub default_message: Option<String>,
+    pub created_at: String,
+    /// Oldest first
+    #[serde(default)]
+    pub shares: Vec<ContactShare>,
+}
+
+impl Contact {
+    fn last_shared(&self) -> &str {
+        self.shares.last().map(|s| s.shared_at.as_str()).unwrap_or("")
+    }
+}
+
+fn contacts_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join(format!("contacts-{}.json", user_id)))
+}
+
+fn read_contacts(user_id: &str, app_handle: &AppHandle) -> Result<Vec<Contact>, String> {
+    let path = contacts_path(user_id, app_handle)?;
+    if !path.exists() { return Ok(vec![]); }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read contacts: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse contacts: {}", e))
+}
+
+fn write_contacts(user_id: &str, contacts: &[Contact], app_handle: &AppHandle) -> Result<(), String> {
+    let path = contacts_path(user_id, app_handle)?;
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create user dir: {}", e))?; }
+    let json = serde_json::to_string_pretty(contacts).map_err(|e| format!("Failed to serialize contacts: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write contacts: {}", e))
+}
+
+/// A single plain address, lowercased domain; nothing a `mailto:` URL would need to escape beyond `@`
+fn normalize_email(email: &str) -> Result<String, String> {
+    let email = email.trim();
+    let invalid = || format!("'{}' is not an email address", email);
+    let (local, domain) = email.rsplit_once('@').ok_or_else(invalid)?;
+    let allowed = |c: char| c.is_ascii_alphanumeric() || "!#$'*+-/=^_`{|}~.".contains(c);
+    if local.is_empty() || local.len() > 64 || !local.chars().all(allowed) || local.starts_with('.') || local.ends_with('.') {
+        return Err(invalid());
+    }
+    let labels_ok = domain.split('.').all(|l| {
+        !l.is_empty() && !l.starts_with('-') && !l.ends_with('-') && l.chars().all(|c| c.is_alphanumeric() || c == '-')
+    });
+    if !domain.contains('.') || !labels_ok {
+        return Err(invalid());
+    }
+    Ok(format!("{}@{}", local, domain.to_lowercase()))
+}
+
+/// Contact `contact_id` of `user_id`
+pub(super) fn find_contact(user_id: &str, contact_id: &str, app_handle: &AppHandle) -> Result<Contact, String> {
+    read_contacts(user_id, app_handle)?
+        .into_iter()
+        .find(|c| c.id == contact_id)
+        .ok_or_else(|| format!("Contact not found: {}", contact_id))
+}
+
+/// Add `link` to the share history of contact `contact_id`
+pub(super) fn record_share(user_id: &str, contact_id: &str, link: &PublicLinkEntry, app_handle: &AppHandle) -> Result<(), String> {
+    let mut contacts = read_contacts(user_id, app_handle)?;
+    let contact = contacts.iter_mut().find(|c| c.id == contact_id).ok_or_else(|| format!("Contact not found: {}", contact_id))?;
+    contact.shares.push(ContactShare {
+        link_hash: link.link_hash.clone(),
+        remote_path: link.remote_path.clone(),
+        shared_at: Utc::now().to_rfc3339(),
+    });
+    let excess = contact.shares.len().saturating_sub(MAX_SHARES_PER_CONTACT);
+    contact.shares.drain(..excess);
+    write_contacts(user_id, &contacts, app_handle)
+}
+
+/// Add a contact, or update name and default message of the one with the same email
+#[tauri::command]
+pub async fn add_contact(
+    user_id: String,
+    name: String,
+    email: String,
+    default_message: Option<String>,
+    app_handle: AppHandle,
+) -> Result<Contact, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let email = normalize_email(&email)?;
+    let name = match name.trim() {
+        "" => email.split('@').next().unwrap_or(&email).to_string(),
+        name => name.to_string(),
+    };
+    let default_message = default_message.filter(|m| !m.trim().is_empty());
+    if let Some(message) = &default_message {
+        link_email::check_template(message)?;
+    }
+
+    let mut contacts = read_contacts(&user_id, &app_handle)?;
+    let contact = match contacts.iter_mut().find(|c| c.email.eq_ignore_ascii_case(&email)) {
+        Some(existing) => {
+            existing.name = name;
+            existing.default_message = default_message;
+            existing.clone()
+        }
+        None => {
+            let contact = Contact {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                email,
+                default_message,
+                created_at: Utc::now().to_rfc3339(),
+                shares: Vec::new(),
+            };
+            contacts.push(contact.clone());
+            contact
+        }
+    };
+    write_contacts(&user_id, &contacts, &app_handle)?;
+    Ok(contact)
+}
+
+/// Contacts, most recently shared with first, then by name
+#[tauri::command]
+pub async fn list_contacts(user_id: String, app_handle: AppHandle) -> Result<Vec<Contact>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut contacts = read_contacts(&user_id, &app_handle)?;
+    contacts.sort_by(|a, b| b.last_shared().cmp(a.last_shared()).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    Ok(contacts)
+}
+
+#[tauri::command]
+pub async fn remove_contact(user_id: String, contact_id: String, app_handle: AppHandle) -> Result<bool, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut contacts = read_contacts(&user_id, &app_handle)?;
+    let before = contacts.len();
+    contacts.retain(|c| c.id != contact_id);
+    if contacts.len() == before {
+        return Ok(false);
+    }
+    write_contacts(&user_id, &contacts, &app_handle)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_plain_addresses_are_accepted() {
+        assert_eq!(normalize_email("  Jane.Doe+files@Example.COM ").unwrap(), "Jane.Doe+files@example.com");
+        for email in ["", "jane", "@example.com", "jane@", "jane@localhost", "a b@example.com", "jane@example.com?cc=x", "j&x@example.com", "jane@-x.com", ".j@example.com"] {
+            assert!(normalize_email(email).is_err(), "accepted {:?}", email);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::AppHandle;
+
+use super::transfers::try_queue_depth;
+use super::{app_data_root, current_api_config, network, redaction};
+
+// =============================================================================================================
+// =============================================== CRASH REPORTS ===============================================
+// =============================================================================================================
+//
+// A panic hook writes one JSON report per panic to `crash-reports/` in the app data dir: message, location,
+// backtrace and a small state summary. Nothing from credentials or settings goes in, and token-like strings in
+// the message and backtrace are masked. Reports stay local until the user sends one with
+// `submit_crash_report`; the upload carries only the report itself, no account headers.
+
+const CRASH_DIR: &str = "crash-reports";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CrashStateSummary {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub uptime_secs: u64,
+    /// (queued, uploading) transfer jobs, if the queue could be read without blocking
+    pub queue_depth: Option<(usize, usize)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub state: CrashStateSummary,
+    #[serde(default)]
+    pub submitted_at: Option<String>,
+}
+
+pub(super) fn crash_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join(CRASH_DIR))
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create crash dir: {}", e))?;
+    let path = dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_string_pretty(report).map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write crash report: {}", e))?;
+    Ok(path)
+}
+
+/// Write a crash report for every panic, then hand over to the default hook. Called once in setup.
+pub fn install_panic_hook(app_handle: &AppHandle) {
+    let dir = match crash_dir(app_handle) {
+        Ok(dir) => dir,
+        Err(e) => {
+            println!("[CRASH] {}", e);
+            return;
+        }
+    };
+    let app_handle = app_handle.clone();
+    let started = Instant::now();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let now = Utc::now();
+        let report = CrashReport {
+            id: format!("crash-{}-{}", now.format("%Y%m%d-%H%M%S"), &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            timestamp: now.to_rfc3339(),
+            message: redaction::redact(&message),
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: redaction::redact(&std::backtrace::Backtrace::force_capture().to_string()),
+            state: CrashStateSummary {
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+                uptime_secs: started.elapsed().as_secs(),
+                queue_depth: try_queue_depth(&app_handle),
+            },
+            submitted_at: None,
+        };
+        match write_report(&dir, &report) {
+            Ok(path) => println!("💥 Crash report written to {}", path.display()),
+            Err(e) => println!("[CRASH] {}", e),
+        }
+        previous(info);
+    }));
+}
+
+fn read_report(path: &Path) -> Option<CrashReport> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Saved crash reports, newest first
+#[tauri::command]
+pub async fn list_crash_reports(app_handle: AppHandle) -> Result<Vec<CrashReport>, String> {
+    let dir = crash_dir(&app_handle)?;
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read crash dir: {}", e))?;
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| read_report(&entry.path()))
+        .collect();
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(reports)
+}
+
+/// Send one report to the crash endpoint. Only ever called on the user's request.
+#[tauri::command]
+pub async fn submit_crash_report(report_id: String, app_handle: AppHandle) -> Result<CrashReport, String> {
+    if report_id.is_empty() || report_id.contains(['/', '\\', '.']) {
+        return Err("Invalid crash report id".to_string());
+    }
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.crash_report, "Crash report")?;
+    let dir = crash_dir(&app_handle)?;
+    let path = dir.join(format!("{}.json", report_id));
+    let mut report = read_report(&path).ok_or_else(|| format!("Crash report {} not found", report_id))?;
+
+    let client = network::client(&app_handle);
+    let resp = client.post(&url).json(&report).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        return Err(format!("HTTP {}: {}", status, text));
+    }
+
+    report.submitted_at = Some(Utc::now().to_rfc3339());
+    write_report(&dir, &report)?;
+    println!("💥 Submitted crash report {}", report_id);
+    Ok(report)
+}
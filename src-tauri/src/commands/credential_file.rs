@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::SavedCredentials;
+
+// =============================================================================================================
+// ============================================== CREDENTIAL FILE ==============================================
+// =============================================================================================================
+//
+// `<user_id>/<user_id>.json` holds one `SavedCredentials` plus a `schema_version`. Reading a file runs the
+// migrations from its version up to `CREDENTIALS_SCHEMA_VERSION` on the raw JSON before it is parsed; reading
+// never writes, since the account scan picks the active account by file mtime, so an upgraded file reaches disk
+// with the next `save_credentials`. Fields the app doesn't know (written by a newer version) are ignored, so an
+// older install can still sign in with a newer file. A change to the saved shape adds a migration here.
+
+pub const CREDENTIALS_SCHEMA_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` turns a version `n` file into version `n + 1`
+const MIGRATIONS: &[fn(&mut serde_json::Map<String, Value>)] = &[v0_to_v1];
+
+/// v0: unversioned files from before the wrapper, with the same fields; only the version is new
+fn v0_to_v1(_file: &mut serde_json::Map<String, Value>) {}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "Value")]
+pub struct CredentialFile {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub credentials: SavedCredentials,
+    /// Version the file had on disk, when it was migrated while reading
+    #[serde(skip)]
+    pub migrated_from: Option<u32>,
+}
+
+impl TryFrom<Value> for CredentialFile {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, String> {
+        let Value::Object(mut file) = value else { return Err("Credentials file is not a JSON object".to_string()) };
+        let found = file.get("schema_version").and_then(Value::as_u64).unwrap_or(0) as u32;
+        for migrate in MIGRATIONS.iter().skip(found as usize) {
+            migrate(&mut file);
+        }
+        let credentials: SavedCredentials = serde_json::from_value(Value::Object(file)).map_err(|e| e.to_string())?;
+        Ok(CredentialFile {
+            schema_version: found.max(CREDENTIALS_SCHEMA_VERSION),
+            credentials,
+            migrated_from: (found < CREDENTIALS_SCHEMA_VERSION).then_some(found),
+        })
+    }
+}
+
+pub fn credentials_path(user_dir: &Path, user_id: &str) -> PathBuf {
+    user_dir.join(format!("{}.json", user_id))
+}
+
+/// Credentials saved at `path`, upgraded to the current schema in memory only
+pub fn read(path: &Path) -> Result<SavedCredentials, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read credentials file: {}", e))?;
+    let file: CredentialFile = serde_json::from_str(&content).map_err(|e| format!("Invalid credentials file: {}", e))?;
+    if let Some(from) = file.migrated_from {
+        println!("🔁 Read credentials for {} as schema v{}; saved as v{} on the next save", file.credentials.user_id, from, CREDENTIALS_SCHEMA_VERSION);
+    }
+    Ok(file.credentials)
+}
+
+pub fn write(path: &Path, credentials: &SavedCredentials) -> Result<(), String> {
+    let file = CredentialFile { schema_version: CREDENTIALS_SCHEMA_VERSION, credentials: credentials.clone(), migrated_from: None };
+    let json = serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to serialize credentials: {}", e))?;
+    // write-then-rename so a crash mid-write never truncates the existing file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write credentials file: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to write credentials file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_files_are_migrated() {
+        let v0 = r#"{"user_id":"u1","user_app_key":"k","auth_tokens":{"access_token":"a","refresh_token":"r","token_type":"Bearer","expires_in":60,"csrf_token":null}}"#;
+        let file: CredentialFile = serde_json::from_str(v0).unwrap();
+        assert_eq!(file.migrated_from, Some(0));
+        assert_eq!(file.schema_version, CREDENTIALS_SCHEMA_VERSION);
+        assert_eq!(file.credentials.user_id, "u1");
+        assert!(file.credentials.auth_tokens.unwrap().csrf_token.is_none());
+    }
+
+    #[test]
+    fn current_files_round_trip() {
+        let v0 = r#"{"user_id":"u1","user_app_key":"k","username":"ann"}"#;
+        let file: CredentialFile = serde_json::from_str(v0).unwrap();
+        let written = serde_json::to_value(&file).unwrap();
+        assert_eq!(written["schema_version"], CREDENTIALS_SCHEMA_VERSION);
+        let again: CredentialFile = serde_json::from_value(written).unwrap();
+        assert_eq!(again.migrated_from, None);
+        assert_eq!(again.credentials.username.as_deref(), Some("ann"));
+    }
+
+    #[test]
+    fn newer_files_still_load() {
+        let v9 = r#"{"schema_version":9,"user_id":"u1","user_app_key":"k","device_id":"d","scopes":["read"]}"#;
+        let file: CredentialFile = serde_json::from_str(v9).unwrap();
+        assert_eq!(file.schema_version, 9);
+        assert_eq!(file.migrated_from, None);
+    }
+}
@@ -0,0 +1,115 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::StatusCode;
+use tauri::{AppHandle, Manager};
+
+use super::secret::SecretString;
+use super::{api_trace, refresh_auth_tokens, save_credentials, token_refresh, ApiConfig, AuthTokens, SavedCredentials};
+
+// =============================================================================================================
+// =================================================== CSRF ====================================================
+// =============================================================================================================
+//
+// The server's CSRF token lives with the auth tokens (`AuthTokens.csrf_token`) and follows them: every login,
+// 2FA and refresh response is searched for a new one (body field or header), and when none comes back and the
+// `auth_csrf` endpoint is configured a fresh one is requested there. Requests sent with a bearer token carry
+// it. A 403/419 that names the CSRF token renews it (from `auth_csrf`, else with a token refresh) and the
+// request is sent once more.
+
+pub const CSRF_HEADER: &str = "X-Csrf-Token";
+
+/// CSRF token handed out in a response's headers or JSON body
+pub fn from_response(headers: &HeaderMap, json: Option<&serde_json::Value>) -> Option<SecretString> {
+    let from_header = headers.get(CSRF_HEADER).and_then(|v| v.to_str().ok()).map(str::trim).filter(|v| !v.is_empty());
+    let from_body = json.and_then(|json| {
+        [json.get("csrf_token"), json.get("auth_tokens").and_then(|t| t.get("csrf_token"))]
+            .into_iter()
+            .flatten()
+            .find_map(|v| v.as_str().filter(|v| !v.is_empty()))
+    });
+    from_header.or(from_body).map(SecretString::from)
+}
+
+/// Add the CSRF header when the tokens carry one
+pub fn attach(headers: &mut HeaderMap, tokens: &AuthTokens) -> Result<(), String> {
+    if let Some(csrf) = &tokens.csrf_token {
+        headers.insert(CSRF_HEADER, HeaderValue::from_str(csrf.expose()).map_err(|e| format!("Invalid CSRF token: {}", e))?);
+    }
+    Ok(())
+}
+
+/// Whether a failed response is the server refusing the CSRF token
+pub fn is_rejection(status: StatusCode, body: &str) -> bool {
+    matches!(status.as_u16(), 403 | 419) && body.to_ascii_lowercase().contains("csrf")
+}
+
+/// Same check on an `HTTP <status>: <body>` error string
+pub fn is_rejection_error(error: &str) -> bool {
+    (error.starts_with("HTTP 403") || error.starts_with("HTTP 419")) && error.to_ascii_lowercase().contains("csrf")
+}
+
+/// Ask the `auth_csrf` endpoint for a new token
+async fn fetch(client: &reqwest::Client, api_config: &ApiConfig, tokens: &AuthTokens, app_handle: &AppHandle) -> Result<SecretString, String> {
+    let url = api_config.optional_url(&api_config.auth_csrf, "CSRF")?;
+    let started = std::time::Instant::now();
+    let resp = client.get(&url).bearer_auth(tokens.access_token.expose()).send().await.map_err(|e| {
+        api_trace::record_failure(app_handle, "GET", &url, &e.to_string(), started);
+        format!("CSRF request failed: {}", e)
+    })?;
+    let status = resp.status();
+    let request_id = api_trace::record(app_handle, "GET", &url, status, resp.headers(), started);
+    let headers = resp.headers().clone();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() {
+        return Err(api_trace::tag_error(format!("HTTP {}: {}", status, text), request_id.as_deref()));
+    }
+    let json = serde_json::from_str::<serde_json::Value>(&text).ok();
+    from_response(&headers, json.as_ref()).ok_or_else(|| "No CSRF token in response".to_string())
+}
+
+/// Take the CSRF token from a login/refresh response, or fetch one when it brought none
+pub async fn after_auth(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    tokens: &mut AuthTokens,
+    headers: &HeaderMap,
+    json: Option<&serde_json::Value>,
+    app_handle: &AppHandle,
+) {
+    if let Some(csrf) = from_response(headers, json) {
+        tokens.csrf_token = Some(csrf);
+        return;
+    }
+    if api_config.auth_csrf.as_deref().is_some_and(|p| !p.is_empty()) {
+        match fetch(client, api_config, tokens, app_handle).await {
+            Ok(csrf) => tokens.csrf_token = Some(csrf),
+            Err(e) => println!("[CSRF] {}", e),
+        }
+    }
+}
+
+/// New CSRF token after the server refused the current one; saved with the credentials
+pub async fn renew(client: &reqwest::Client, api_config: &ApiConfig, credentials: &mut SavedCredentials, app_handle: &AppHandle) -> Result<(), String> {
+    println!("🔄 CSRF token refused, renewing...");
+    let fetched = match &credentials.auth_tokens {
+        Some(tokens) if api_config.auth_csrf.as_deref().is_some_and(|p| !p.is_empty()) => Some(fetch(client, api_config, tokens, app_handle).await?),
+        Some(_) => None,
+        None => return Err("No valid auth tokens".to_string()),
+    };
+    match fetched {
+        Some(csrf) => {
+            if let Some(tokens) = credentials.auth_tokens.as_mut() {
+                tokens.csrf_token = Some(csrf);
+            }
+            save_credentials(credentials.clone(), app_handle.clone()).await
+        }
+        None => {
+            // no CSRF endpoint: a token refresh hands out a new one
+            let refresh_lock = app_handle.try_state::<token_refresh::TokenRefreshLock>();
+            let _guard = match &refresh_lock {
+                Some(lock) => Some(lock.lock().await),
+                None => None,
+            };
+            refresh_auth_tokens(client, api_config, credentials, app_handle).await
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::ipc_guard;
+
+// =============================================================================================================
+// ============================================== DATA DIRECTORY ===============================================
+// =============================================================================================================
+//
+// Where credentials, history, settings and caches live. In order: the `FIRESTARTER_DATA_DIR` environment
+// variable, portable mode, the override saved with `set_data_dir_override`, then the platform's app data dir.
+// The override is kept in the app config dir because settings.json itself lives in the data dir. Sandboxed
+// installs (Flatpak, Snap) sometimes can't write the chosen dir: startup then uses a private temporary dir
+// (random name, owner-only) for the session and, once the main window exists, emits `storage_unavailable` so
+// the UI can ask for another location. The UI also reads `get_data_dir_status` on load in case it missed it.
+//
+// Portable mode is for running from a USB stick: started with `--portable`, with `FIRESTARTER_PORTABLE=1`, or
+// next to a file named `portable`, the app keeps data, cache and config in `data/` beside the executable and
+// doesn't register itself with the system (no shell integration).
+
+pub const DATA_DIR_ENV: &str = "FIRESTARTER_DATA_DIR";
+#[cfg(desktop)]
+pub const PORTABLE_FLAG: &str = "--portable";
+#[cfg(desktop)]
+pub const PORTABLE_ENV: &str = "FIRESTARTER_PORTABLE";
+#[cfg(desktop)]
+const PORTABLE_MARKER: &str = "portable";
+const OVERRIDE_FILE: &str = "data-dir.json";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DataDirStatus {
+    pub path: PathBuf,
+    /// "env" | "portable" | "override" | "default" | "fallback"
+    pub source: String,
+    /// Why the configured location isn't used, when `source` is "fallback"
+    pub error: Option<String>,
+    pub unavailable_path: Option<PathBuf>,
+}
+
+pub type DataDirState = Mutex<DataDirStatus>;
+
+#[derive(Serialize, Deserialize, Default)]
+struct DataDirOverride {
+    data_dir: Option<PathBuf>,
+}
+
+fn override_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_config_dir(app_handle)?.join(OVERRIDE_FILE))
+}
+
+fn read_override(app_handle: &AppHandle) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(override_file_path(app_handle).ok()?).ok()?;
+    match serde_json::from_str::<DataDirOverride>(&content) {
+        Ok(saved) => saved.data_dir,
+        Err(e) => {
+            println!("[DATA] Ignoring unreadable {}: {}", OVERRIDE_FILE, e);
+            None
+        }
+    }
+}
+
+/// The `data/` folder next to the executable, when running portable
+#[cfg(desktop)]
+fn portable_root() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let portable = std::env::args_os().any(|a| a == PORTABLE_FLAG)
+        || std::env::var_os(PORTABLE_ENV).is_some_and(|v| !v.is_empty() && v != "0")
+        || exe_dir.join(PORTABLE_MARKER).is_file();
+    portable.then(|| exe_dir.join("data"))
+}
+
+#[cfg(mobile)]
+fn portable_root() -> Option<PathBuf> {
+    None
+}
+
+pub fn is_portable() -> bool {
+    portable_root().is_some()
+}
+
+/// Cache dir: `data/cache` when portable, the platform's otherwise
+pub fn app_cache_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    match portable_root() {
+        Some(root) => Ok(root.join("cache")),
+        None => app_handle.path().app_cache_dir().map_err(|e| format!("Failed to get cache directory: {}", e)),
+    }
+}
+
+/// Config dir (endpoints file, data dir override): `data/config` when portable, the platform's otherwise
+pub fn app_config_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    match portable_root() {
+        Some(root) => Ok(root.join("config")),
+        None => app_handle.path().app_config_dir().map_err(|e| format!("Failed to get config directory: {}", e)),
+    }
+}
+
+/// The configured location and where the choice came from
+fn configured(app_handle: &AppHandle) -> Result<(PathBuf, &'static str), String> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|v| !v.is_empty()) {
+        return Ok((PathBuf::from(dir), "env"));
+    }
+    if let Some(dir) = portable_root() {
+        return Ok((dir, "portable"));
+    }
+    if let Some(dir) = read_override(app_handle) {
+        return Ok((dir, "override"));
+    }
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| (dir, "default"))
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+/// Create `dir` and write a probe file to it
+fn check_writable(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let probe = dir.join(format!(".write-probe-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::write(&probe, b"firestarter").map_err(|e| format!("Failed to write to {}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// A fresh, randomly named dir under the system temp dir that only this user can open
+fn private_temp_dir() -> Result<PathBuf, String> {
+    let path = std::env::temp_dir().join(format!("firestarter-data-{}", uuid::Uuid::new_v4().simple()));
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    // `create`, not `create_all`: fails rather than reuse a dir someone else made
+    builder.create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Pick and check the data dir. Called first in setup, before anything reads or writes app data.
+pub fn init_data_dir(app_handle: &AppHandle) -> DataDirState {
+    let checked = match configured(app_handle) {
+        Ok((dir, source)) => match check_writable(&dir) {
+            Ok(()) => Ok((dir, source)),
+            Err(e) => Err((e, Some(dir))),
+        },
+        Err(e) => Err((e, None)),
+    };
+    let status = match checked {
+        Ok((path, source)) => DataDirStatus { path, source: source.to_string(), error: None, unavailable_path: None },
+        Err((error, unavailable_path)) => {
+            let path = private_temp_dir().unwrap_or_else(|e| {
+                println!("[DATA] {}", e);
+                std::env::temp_dir().join(format!("firestarter-data-{}", uuid::Uuid::new_v4().simple()))
+            });
+            println!("⚠️ App data dir unavailable ({}), using {} for this session", error, path.display());
+            DataDirStatus { path, source: "fallback".to_string(), error: Some(error), unavailable_path }
+        }
+    };
+    if status.error.is_none() {
+        println!("📁 App data in {} ({})", status.path.display(), status.source);
+    }
+    Mutex::new(status)
+}
+
+/// Tell the UI the session runs on the fallback dir. Called once the main window exists; events sent earlier
+/// have no listener.
+pub fn announce_fallback(app_handle: &AppHandle) {
+    let status = app_handle.state::<DataDirState>().lock().unwrap().clone();
+    if status.error.is_some() {
+        app_handle.emit("storage_unavailable", &status).ok();
+    }
+}
+
+/// Data dir picked at startup
+pub fn current_data_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.try_state::<DataDirState>().map(|state| state.lock().unwrap().path.clone())
+}
+
+#[tauri::command]
+pub async fn get_data_dir_status(app_handle: AppHandle) -> Result<DataDirStatus, String> {
+    Ok(app_handle.state::<DataDirState>().lock().unwrap().clone())
+}
+
+/// Keep app data in `path` from the next start; `None` goes back to the default location. Existing data isn't
+/// moved, and the environment variable and portable mode still take precedence.
+#[tauri::command]
+pub async fn set_data_dir_override(
+    path: Option<String>,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    ipc_guard::check_sensitive("set_data_dir_override", &webview, &ipc_nonce, &app_handle)?;
+    let data_dir = match path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let dir = PathBuf::from(path);
+            if !dir.is_absolute() {
+                return Err(format!("Data directory must be an absolute path: {}", path));
+            }
+            check_writable(&dir)?;
+            Some(dir)
+        }
+        None => None,
+    };
+    let file = override_file_path(&app_handle)?;
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&DataDirOverride { data_dir: data_dir.clone() })
+        .map_err(|e| format!("Failed to serialize data dir override: {}", e))?;
+    std::fs::write(&file, json).map_err(|e| format!("Failed to write {}: {}", OVERRIDE_FILE, e))?;
+    match &data_dir {
+        Some(dir) => println!("📁 App data moves to {} after a restart", dir.display()),
+        None => println!("📁 App data goes back to the default location after a restart"),
+    }
+    Ok(())
+}
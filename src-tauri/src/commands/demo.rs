@@ -0,0 +1,439 @@
+use chrono::Utc;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+use super::{
+    history_log, load_credentials, read_upload_history, save_credentials, ApiConfig, AuthTokens, SavedCredentials, UploadLogEntry,
+};
+
+// =============================================================================================================
+// =============================================== DEMO BACKEND ================================================
+// =============================================================================================================
+//
+// Built with `--features demo`, the app never touches the network: a loopback server in the app process stands
+// in for the storage API, and the endpoint config points at it. Every command runs unchanged against canned data
+// (sample files, wallet balances, tier pricing, usage). Any username and password signs in as the demo user.
+// Uploads, links, deletes and wallet operations change an in-memory store that is gone when the app quits.
+
+pub const DEMO_USER_ID: &str = "demo-user";
+const DEMO_APP_KEY: &str = "demo-app-key";
+const DEMO_WALLET: &str = "DemoWa11et1111111111111111111111111111111111";
+const TOKEN_LIFETIME_SECS: i64 = 60 * 60;
+/// PIPE received per SOL exchanged
+const EXCHANGE_RATE: f64 = 1000.0;
+
+const SAMPLE_FILES: &[(&str, &str)] = &[
+    ("welcome.txt", "Welcome to Firestarter!\n\nThis is demo mode: files, links and balances are sample data kept in memory.\n"),
+    ("notes/roadmap.md", "# Roadmap\n\n- [x] Uploads and downloads\n- [x] Public links\n- [ ] Shared folders\n"),
+    ("reports/usage-2024.csv", "month,gb_uploaded,gb_downloaded\n2024-01,12.4,30.1\n2024-02,9.8,22.7\n2024-03,15.2,41.0\n"),
+    ("config/settings.json", "{\n  \"theme\": \"dark\",\n  \"notifications\": true\n}\n"),
+];
+
+struct DemoLink {
+    file_name: String,
+    max_downloads: Option<u64>,
+    downloads: u64,
+}
+
+struct DemoStore {
+    files: HashMap<String, Vec<u8>>,
+    /// Deleted files: name -> (content, deleted at)
+    trash: HashMap<String, (Vec<u8>, String)>,
+    links: HashMap<String, DemoLink>,
+    sol_balance: f64,
+    pipe_balance: f64,
+}
+
+struct DemoBackend {
+    /// request path -> endpoint key in `ApiConfig`
+    routes: HashMap<String, String>,
+    store: Mutex<DemoStore>,
+}
+
+/// Bundled endpoints moved onto `base_url`, with every optional endpoint turned on at `/<key>`
+fn demo_config(base_url: &str) -> Result<ApiConfig, String> {
+    let mut value = serde_json::to_value(ApiConfig::default()).map_err(|e| format!("Failed to build demo endpoints: {}", e))?;
+    if let Value::Object(map) = &mut value {
+        for (key, endpoint) in map.iter_mut() {
+            if key == "api_base_url" {
+                *endpoint = Value::String(base_url.to_string());
+            } else if endpoint.as_str().map(str::is_empty).unwrap_or(true) {
+                *endpoint = Value::String(format!("/{}", key));
+            }
+        }
+    }
+    serde_json::from_value(value).map_err(|e| format!("Failed to build demo endpoints: {}", e))
+}
+
+fn routes(config: &ApiConfig) -> HashMap<String, String> {
+    let Ok(Value::Object(map)) = serde_json::to_value(config) else { return HashMap::new() };
+    map.into_iter()
+        .filter(|(key, _)| key != "api_base_url")
+        .filter_map(|(key, path)| Some((path.as_str()?.to_string(), key)))
+        .collect()
+}
+
+fn query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let decode = |s: &str| percent_decode_str(&s.replace('+', " ")).decode_utf8_lossy().to_string();
+            Some((decode(key), decode(value))).filter(|(k, _)| !k.is_empty())
+        })
+        .collect()
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .header("Date", httpdate(Utc::now()))
+        .body(Body::from(body.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, json!({ "error": message }))
+}
+
+fn httpdate(at: chrono::DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn new_tokens() -> AuthTokens {
+    AuthTokens {
+        access_token: format!("demo-access-{}", uuid::Uuid::new_v4().simple()).into(),
+        refresh_token: format!("demo-refresh-{}", uuid::Uuid::new_v4().simple()).into(),
+        token_type: "Bearer".to_string(),
+        expires_in: TOKEN_LIFETIME_SECS,
+        expires_at: None,
+        csrf_token: None,
+    }
+}
+
+/// Both login shapes the client understands: tokens at the top level and under `auth_tokens`
+fn login_response(username: &str) -> Value {
+    let tokens = new_tokens();
+    let mut body = serde_json::to_value(&tokens).unwrap_or_else(|_| json!({}));
+    body["user_id"] = json!(DEMO_USER_ID);
+    body["user_app_key"] = json!(DEMO_APP_KEY);
+    body["username"] = json!(username);
+    body["auth_tokens"] = serde_json::to_value(&tokens).unwrap_or(Value::Null);
+    body
+}
+
+fn tier_pricing() -> Value {
+    json!([
+        { "name": "normal", "current_price": 1.0, "base_price": 1.0, "active_users": 12, "concurrency": 4 },
+        { "name": "priority", "current_price": 2.5, "base_price": 2.0, "active_users": 5, "concurrency": 8 },
+        { "name": "premium", "current_price": 5.0, "base_price": 5.0, "active_users": 3, "concurrency": 12 },
+        { "name": "ultra", "current_price": 10.0, "base_price": 10.0, "active_users": 1, "concurrency": 16 },
+        { "name": "enterprise", "current_price": 20.0, "base_price": 20.0, "active_users": 0, "concurrency": 32 }
+    ])
+}
+
+fn token_usage(period: &str) -> Value {
+    let breakdown = |gb: f64, spent: f64, count: u64| {
+        json!({
+            "gb_transferred": gb,
+            "tokens_spent": spent,
+            "tokens_burned": spent * 0.5,
+            "tokens_to_treasury": spent * 0.5,
+            "transfer_count": count
+        })
+    };
+    json!({
+        "period": period,
+        "user_id": DEMO_USER_ID,
+        "breakdown": {
+            "bandwidth": breakdown(37.4, 41.2, 128),
+            "storage": breakdown(12.9, 12.9, 42),
+            "total": breakdown(50.3, 54.1, 170)
+        }
+    })
+}
+
+/// `bytes=start-end` (either side may be open) against a body of `len` bytes
+fn parse_range(range: &str, len: usize) -> Option<(usize, usize)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split(',').next()?.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => (len.saturating_sub(suffix.parse().ok()?), len.checked_sub(1)?),
+        (start, "") => (start.parse().ok()?, len.checked_sub(1)?),
+        (start, end) => (start.parse().ok()?, end.parse::<usize>().ok()?.min(len.checked_sub(1)?)),
+    };
+    Some((start, end)).filter(|(s, e)| s <= e)
+}
+
+fn file_response(data: &[u8], range: Option<&str>, head: bool) -> Response<Body> {
+    let mut builder = Response::builder()
+        .header("Content-Type", "application/octet-stream")
+        .header("Accept-Ranges", "bytes")
+        .header("Date", httpdate(Utc::now()));
+    let body = match range.map(|r| parse_range(r, data.len())) {
+        Some(Some((start, end))) => {
+            builder = builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header("Content-Range", format!("bytes {}-{}/{}", start, end, data.len()));
+            &data[start..=end]
+        }
+        Some(None) => return error_response(StatusCode::RANGE_NOT_SATISFIABLE, "Invalid range"),
+        None => data,
+    };
+    builder = builder.header("Content-Length", body.len());
+    let body = if head { Body::empty() } else { Body::from(body.to_vec()) };
+    builder.body(body).unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+fn handle_endpoint(
+    endpoint: &str,
+    query: &HashMap<String, String>,
+    body: &[u8],
+    range: Option<&str>,
+    head: bool,
+    store: &mut DemoStore,
+) -> Response<Body> {
+    let json_body: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+    let text = |key: &str| json_body.get(key).and_then(|v| v.as_str()).or_else(|| query.get(key).map(String::as_str));
+    let amount = |key: &str| json_body.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+    match endpoint {
+        "auth_login" => json_response(StatusCode::OK, login_response(text("username").unwrap_or("demo"))),
+        "auth_2fa_verify" => json_response(StatusCode::OK, login_response("demo")),
+        "auth_refresh" => {
+            let tokens = new_tokens();
+            json_response(StatusCode::OK, json!({ "access_token": tokens.access_token, "expires_in": tokens.expires_in }))
+        }
+        "auth_register" => json_response(
+            StatusCode::OK,
+            json!({ "user_id": DEMO_USER_ID, "user_app_key": DEMO_APP_KEY, "username": text("username").unwrap_or("demo") }),
+        ),
+        "upload" | "priority_upload" => {
+            let Some(file_name) = text("file_name").map(str::to_string) else {
+                return error_response(StatusCode::BAD_REQUEST, "file_name is required");
+            };
+            let hash = blake3::hash(body).to_hex().to_string();
+            let size = body.len();
+            store.files.insert(file_name.clone(), body.to_vec());
+            json_response(StatusCode::OK, json!({ "file_name": file_name, "size": size, "blake3_hash": hash }))
+        }
+        "upload_status" => json_response(StatusCode::OK, json!({ "status": "committed" })),
+        "download" => match text("file_name").and_then(|name| store.files.get(name)) {
+            Some(data) => file_response(data, range, head),
+            None => error_response(StatusCode::NOT_FOUND, "File not found"),
+        },
+        "public_download" => {
+            let Some(link) = text("hash").and_then(|hash| store.links.get_mut(hash)) else {
+                return error_response(StatusCode::NOT_FOUND, "Link not found");
+            };
+            if link.max_downloads.map(|max| link.downloads >= max).unwrap_or(false) {
+                return error_response(StatusCode::GONE, "Link has no downloads left");
+            }
+            let Some(data) = store.files.get(&link.file_name) else {
+                return error_response(StatusCode::NOT_FOUND, "File not found");
+            };
+            if range.is_none() && !head {
+                link.downloads += 1;
+            }
+            file_response(data, range, head)
+        }
+        "delete_file" => match text("file_name").and_then(|name| Some((name.to_string(), store.files.remove(name)?))) {
+            Some((name, data)) => {
+                store.trash.insert(name, (data, Utc::now().to_rfc3339()));
+                json_response(StatusCode::OK, json!({ "deleted": true, "restorable": true }))
+            }
+            None => error_response(StatusCode::NOT_FOUND, "File not found"),
+        },
+        "list_trash" => {
+            let files: Vec<Value> = store
+                .trash
+                .iter()
+                .map(|(name, (data, deleted_at))| json!({ "name": name, "size": data.len(), "deleted_at": deleted_at }))
+                .collect();
+            json_response(StatusCode::OK, json!({ "files": files }))
+        }
+        "restore_file" => {
+            let Some(name) = text("file_name").filter(|name| store.trash.contains_key(*name)).map(str::to_string) else {
+                return error_response(StatusCode::NOT_FOUND, "File not in trash");
+            };
+            if store.files.contains_key(&name) {
+                return error_response(StatusCode::CONFLICT, "A file with that name exists");
+            }
+            if let Some((data, _)) = store.trash.remove(&name) {
+                store.files.insert(name, data);
+            }
+            json_response(StatusCode::OK, json!({ "restored": true }))
+        }
+        "purge_trash" => {
+            let names: Vec<String> = json_body
+                .get("file_names")
+                .and_then(|v| v.as_array())
+                .map(|names| names.iter().filter_map(|n| n.as_str().map(str::to_string)).collect())
+                .unwrap_or_else(|| store.trash.keys().cloned().collect());
+            let purged: Vec<String> = names.into_iter().filter(|name| store.trash.remove(name).is_some()).collect();
+            json_response(StatusCode::OK, json!({ "purged": purged }))
+        }
+        "create_public_link" => {
+            let Some(file_name) = text("file_name").filter(|name| store.files.contains_key(*name)).map(str::to_string) else {
+                return error_response(StatusCode::NOT_FOUND, "File not found");
+            };
+            let link_hash = uuid::Uuid::new_v4().simple().to_string();
+            let max_downloads = json_body.get("max_downloads").and_then(|v| v.as_u64());
+            store.links.insert(link_hash.clone(), DemoLink { file_name, max_downloads, downloads: 0 });
+            json_response(StatusCode::OK, json!({ "link_hash": link_hash, "custom_slug": text("custom_slug") }))
+        }
+        "delete_public_link" => match text("link_hash").and_then(|hash| store.links.remove(hash)) {
+            Some(_) => json_response(StatusCode::OK, json!({ "deleted": true })),
+            None => error_response(StatusCode::NOT_FOUND, "Link not found"),
+        },
+        "link_stats" => match text("link_hash").and_then(|hash| store.links.get(hash)) {
+            Some(link) => json_response(StatusCode::OK, json!({ "download_count": link.downloads, "max_downloads": link.max_downloads })),
+            None => error_response(StatusCode::NOT_FOUND, "Link not found"),
+        },
+        "check_wallet" => json_response(
+            StatusCode::OK,
+            json!({
+                "user_id": DEMO_USER_ID,
+                "public_key": DEMO_WALLET,
+                "balance_sol": store.sol_balance,
+                "balance_lamports": (store.sol_balance * 1e9) as u64
+            }),
+        ),
+        "check_custom_token" => json_response(
+            StatusCode::OK,
+            json!({ "token_mint": text("token").unwrap_or("PIPE"), "ui_amount": store.pipe_balance, "decimals": 6 }),
+        ),
+        "exchange_sol_for_tokens" => {
+            let sol = amount("amount");
+            if sol <= 0.0 || sol > store.sol_balance {
+                return error_response(StatusCode::BAD_REQUEST, "Insufficient SOL balance");
+            }
+            store.sol_balance -= sol;
+            store.pipe_balance += sol * EXCHANGE_RATE;
+            json_response(StatusCode::OK, json!({ "sol_spent": sol, "tokens_minted": sol * EXCHANGE_RATE }))
+        }
+        "withdraw_sol" => {
+            let sol = amount("amount_sol");
+            if sol <= 0.0 || sol > store.sol_balance {
+                return error_response(StatusCode::BAD_REQUEST, "Insufficient SOL balance");
+            }
+            store.sol_balance -= sol;
+            json_response(StatusCode::OK, json!({ "signature": format!("demo-{}", uuid::Uuid::new_v4().simple()), "amount_sol": sol }))
+        }
+        "get_tier_pricing" => json_response(StatusCode::OK, tier_pricing()),
+        "token_usage" => json_response(StatusCode::OK, token_usage(text("period").unwrap_or("30d"))),
+        "auth_sessions" => json_response(
+            StatusCode::OK,
+            json!({
+                "sessions": [{ "session_id": "demo-session", "device": "This device", "current": true, "last_seen": Utc::now().to_rfc3339() }]
+            }),
+        ),
+        "account_profile" | "update_account_profile" => json_response(
+            StatusCode::OK,
+            json!({ "email": "demo@example.com", "email_verified": true, "display_name": "Demo User", "notification_prefs": {} }),
+        ),
+        "scoped_key_create" => json_response(
+            StatusCode::OK,
+            json!({ "key_id": uuid::Uuid::new_v4().simple().to_string(), "secret": format!("demo-key-{}", uuid::Uuid::new_v4().simple()) }),
+        ),
+        _ => json_response(StatusCode::OK, json!({ "success": true, "message": "Demo mode" })),
+    }
+}
+
+async fn handle(req: Request<Body>, backend: Arc<DemoBackend>) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path().to_string();
+    let query = query_params(req.uri().query().unwrap_or(""));
+    let head = req.method() == Method::HEAD;
+    let range = req.headers().get(hyper::header::RANGE).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let body = hyper::body::to_bytes(req.into_body()).await.map(|b| b.to_vec()).unwrap_or_default();
+
+    if path == "/health" {
+        return Ok(json_response(StatusCode::OK, json!({ "status": "ok", "demo": true })));
+    }
+    let Some(endpoint) = backend.routes.get(&path) else {
+        return Ok(error_response(StatusCode::NOT_FOUND, "Not found"));
+    };
+    let mut store = backend.store.lock().unwrap();
+    Ok(handle_endpoint(endpoint, &query, &body, range.as_deref(), head, &mut store))
+}
+
+fn sample_store() -> DemoStore {
+    DemoStore {
+        files: SAMPLE_FILES.iter().map(|(name, content)| (name.to_string(), content.as_bytes().to_vec())).collect(),
+        trash: HashMap::new(),
+        links: HashMap::new(),
+        sol_balance: 2.5,
+        pipe_balance: 1250.0,
+    }
+}
+
+/// Sign in as the demo user and give them upload history for the sample files, unless already done
+async fn seed_demo_account(app_handle: AppHandle) -> Result<(), String> {
+    let signed_in = load_credentials(app_handle.clone()).await?.map(|c| c.user_id == DEMO_USER_ID).unwrap_or(false);
+    if !signed_in {
+        let mut tokens = new_tokens();
+        tokens.expires_at = Some((Utc::now() + chrono::Duration::seconds(TOKEN_LIFETIME_SECS)).to_rfc3339());
+        let credentials = SavedCredentials {
+            user_id: DEMO_USER_ID.to_string(),
+            user_app_key: DEMO_APP_KEY.into(),
+            auth_tokens: Some(tokens),
+            username: Some("demo".to_string()),
+            workspace_id: None,
+            gateway_id: None,
+        };
+        save_credentials(credentials, app_handle.clone()).await?;
+    }
+    if !read_upload_history(DEMO_USER_ID.to_string(), app_handle.clone()).await.unwrap_or_default().is_empty() {
+        return Ok(());
+    }
+    for (name, content) in SAMPLE_FILES {
+        let entry = UploadLogEntry {
+            blake3_hash: blake3::hash(content.as_bytes()).to_hex().to_string(),
+            file_size: content.len() as u64,
+            tier: Some("normal".to_string()),
+            route: Some("standard".to_string()),
+            verified: Some(true),
+            ..UploadLogEntry::new(name, name, "success", "Sample file".to_string())
+        };
+        let line = serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+        history_log::append_line(DEMO_USER_ID, line, &app_handle)?;
+    }
+    Ok(())
+}
+
+/// Start the mock backend and return endpoints pointing at it. Called once in setup, in place of loading the
+/// endpoints file.
+pub fn start_demo_backend(app_handle: &AppHandle) -> Result<ApiConfig, String> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(|e| format!("Failed to bind demo backend: {}", e))?;
+    listener.set_nonblocking(true).map_err(|e| format!("Failed to configure demo backend: {}", e))?;
+    let port = listener.local_addr().map_err(|e| format!("Failed to read demo backend address: {}", e))?.port();
+
+    let config = demo_config(&format!("http://127.0.0.1:{}", port))?;
+    let backend = Arc::new(DemoBackend { routes: routes(&config), store: Mutex::new(sample_store()) });
+    let make_svc = make_service_fn(move |_conn| {
+        let backend = backend.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, backend.clone()))) }
+    });
+    let server = Server::from_tcp(listener).map_err(|e| format!("Failed to start demo backend: {}", e))?.serve(make_svc);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = server.await {
+            println!("❌ Demo backend stopped: {}", e);
+        }
+    });
+
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = seed_demo_account(handle).await {
+            println!("[DEMO] Failed to seed demo account: {}", e);
+        }
+    });
+
+    println!("🎭 Demo mode: mock backend listening on 127.0.0.1:{}", port);
+    Ok(config)
+}
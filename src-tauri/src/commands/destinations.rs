@@ -0,0 +1,146 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::settings::{current_settings, update_settings};
+use super::{filenames, is_plain_path, local_file_name};
+
+// =============================================================================================================
+// ============================================ RECENT DESTINATIONS ============================================
+// =============================================================================================================
+//
+// Most recently used local download folders and remote upload folders, kept in settings for quick picks, plus an
+// optional template for default upload names, e.g. `{date}-{basename}`.
+
+const MAX_RECENT: usize = 10;
+const TEMPLATE_FIELDS: [&str; 5] = ["basename", "stem", "ext", "date", "time"];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RecentDestinations {
+    pub download_dirs: Vec<String>,
+    pub remote_dirs: Vec<String>,
+    pub upload_name_template: Option<String>,
+}
+
+/// Move `value` to the front of `list`, keeping at most `MAX_RECENT`
+fn push_recent(list: &mut Vec<String>, value: String) {
+    list.retain(|v| *v != value);
+    list.insert(0, value);
+    list.truncate(MAX_RECENT);
+}
+
+/// Remember the folder a download was saved to. Picker URIs on mobile aren't reusable, so they're skipped.
+pub fn record_download(output_path: &str, app_handle: &AppHandle) {
+    if !is_plain_path(output_path) {
+        return;
+    }
+    let Some(dir) = std::path::Path::new(output_path).parent().map(|d| d.to_string_lossy().to_string()) else {
+        return;
+    };
+    if dir.is_empty() {
+        return;
+    }
+    if let Err(e) = update_settings(app_handle, |s| push_recent(&mut s.recent_download_dirs, dir)) {
+        println!("[SETTINGS] {}", e);
+    }
+}
+
+/// Remember the remote folder of a finished upload; root uploads aren't recorded
+pub fn record_upload(remote_name: &str, app_handle: &AppHandle) {
+    let Some((dir, _)) = remote_name.rsplit_once('/') else {
+        return;
+    };
+    let dir = dir.to_string();
+    if let Err(e) = update_settings(app_handle, |s| push_recent(&mut s.recent_remote_dirs, dir)) {
+        println!("[SETTINGS] {}", e);
+    }
+}
+
+fn check_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..].find('}').ok_or_else(|| format!("Unclosed '{{' in template '{}'", template))?;
+        let field = &rest[open + 1..open + close];
+        if !TEMPLATE_FIELDS.contains(&field) {
+            return Err(format!("Unknown template field '{{{}}}', expected one of {:?}", field, TEMPLATE_FIELDS));
+        }
+        rest = &rest[open + close + 1..];
+    }
+    Ok(())
+}
+
+/// Expand `{basename}`, `{stem}`, `{ext}`, `{date}` (YYYY-MM-DD) and `{time}` (HHMMSS) for a local file name
+pub fn expand_name_template(template: &str, file_name: &str) -> String {
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, ext),
+        _ => (file_name, ""),
+    };
+    let now = chrono::Local::now();
+    let expanded = template
+        .replace("{basename}", file_name)
+        .replace("{stem}", stem)
+        .replace("{ext}", ext)
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string());
+    // "{stem}.{ext}" on a file without an extension
+    expanded.trim_end_matches('.').to_string()
+}
+
+/// Remote name for an upload that doesn't specify one: the local file name, run through the template if set
+pub fn default_remote_name(file_path: &str, app_handle: &AppHandle) -> Result<String, String> {
+    // a file saved under a sanitized name goes back up under its original one
+    let file_name = filenames::remote_name_for(&local_file_name(file_path).ok_or("Invalid file name")?);
+    Ok(match current_settings(app_handle).upload_name_template {
+        Some(template) => expand_name_template(&template, &file_name),
+        None => file_name,
+    })
+}
+
+#[tauri::command]
+pub async fn get_recent_destinations(app_handle: AppHandle) -> Result<RecentDestinations, String> {
+    let settings = current_settings(&app_handle);
+    Ok(RecentDestinations {
+        download_dirs: settings.recent_download_dirs,
+        remote_dirs: settings.recent_remote_dirs,
+        upload_name_template: settings.upload_name_template,
+    })
+}
+
+/// Set the default upload name template; `None` or "" keeps local file names as they are
+#[tauri::command]
+pub async fn set_upload_name_template(template: Option<String>, app_handle: AppHandle) -> Result<Option<String>, String> {
+    let template = template.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if let Some(t) = &template {
+        check_template(t)?;
+    }
+    update_settings(&app_handle, |s| s.upload_name_template = template.clone())?;
+    Ok(template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_expand_name_parts() {
+        assert_eq!(expand_name_template("backups/{stem}-copy.{ext}", "notes.tar.gz"), "backups/notes.tar-copy.gz");
+        assert_eq!(expand_name_template("{basename}", "photo.jpg"), "photo.jpg");
+        assert_eq!(expand_name_template("{stem}.{ext}", "Makefile"), "Makefile");
+        assert_eq!(expand_name_template("{stem}.{ext}", ".env"), ".env");
+    }
+
+    #[test]
+    fn dates_and_times_are_filled_in() {
+        let expanded = expand_name_template("{date}/{time}-{basename}", "a.txt");
+        let (date, rest) = expanded.split_once('/').unwrap();
+        assert!(chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok(), "{}", expanded);
+        assert!(rest.len() == "HHMMSS-a.txt".len() && rest.ends_with("-a.txt") && rest[..6].bytes().all(|b| b.is_ascii_digit()));
+    }
+
+    #[test]
+    fn only_known_fields_are_accepted() {
+        assert!(check_template("{date}/{stem}.{ext}").is_ok());
+        assert!(check_template("no fields").is_ok());
+        assert!(check_template("{user}/{basename}").is_err());
+        assert!(check_template("{stem").is_err());
+    }
+}
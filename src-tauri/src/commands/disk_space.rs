@@ -0,0 +1,151 @@
+use std::path::Path;
+use serde::Serialize;
+
+use super::local_paths;
+
+// =============================================================================================================
+// ================================================= DISK SPACE ================================================
+// =============================================================================================================
+//
+// A download that runs out of disk space (or quota) fails with a `Disk full` error instead of the raw write
+// error: the partial file is removed and `download_disk_full` carries how many bytes were still to come and how
+// much space the target volume has, so the UI can say how much to free.
+
+/// Prefix of every write error caused by a full disk, so callers up the chain can tell them apart
+const DISK_FULL: &str = "Disk full";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiskFull {
+    pub file_name: String,
+    pub output_path: String,
+    /// Bytes of the download that were not written yet, when the size is known
+    pub required_bytes: Option<u64>,
+    /// Free space on the target volume after removing the partial file
+    pub available_bytes: Option<u64>,
+}
+
+impl DiskFull {
+    pub fn message(&self) -> String {
+        let needed = match self.required_bytes {
+            Some(bytes) => format!("{} more bytes needed", bytes),
+            None => "more space needed".to_string(),
+        };
+        let free = match self.available_bytes {
+            Some(bytes) => format!(", {} bytes free", bytes),
+            None => String::new(),
+        };
+        format!("{}: '{}' could not be saved ({}{})", DISK_FULL, self.file_name, needed, free)
+    }
+}
+
+#[cfg(unix)]
+fn is_out_of_space(e: &std::io::Error) -> bool {
+    matches!(e.raw_os_error(), Some(libc::ENOSPC) | Some(libc::EDQUOT))
+}
+
+#[cfg(windows)]
+fn is_out_of_space(e: &std::io::Error) -> bool {
+    // ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL
+    matches!(e.raw_os_error(), Some(39) | Some(112))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_out_of_space(_e: &std::io::Error) -> bool {
+    false
+}
+
+/// `context: e`, or a `Disk full` error when the write failed for lack of space
+pub fn write_error(context: &str, e: std::io::Error) -> String {
+    if is_out_of_space(&e) {
+        format!("{}: {}", DISK_FULL, e)
+    } else {
+        format!("{}: {}", context, e)
+    }
+}
+
+/// Bytes of a download of `total_size` still to come once `written` are on disk
+pub fn required_bytes(total_size: Option<u64>, written: u64) -> Option<u64> {
+    total_size.map(|len| len.saturating_sub(written))
+}
+
+pub fn is_disk_full(error: &str) -> bool {
+    error.starts_with(DISK_FULL)
+}
+
+/// Free space for unprivileged writes on the volume holding `path`
+pub fn available_bytes(path: &str) -> Option<u64> {
+    let path = local_paths::to_fs_path(path);
+    let dir = if path.is_dir() { path.as_path() } else { path.parent()? };
+    free_space(dir)
+}
+
+#[cfg(unix)]
+fn free_space(dir: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs is plain data, zero is a valid value; `c_path` is NUL-terminated and `stat` outlives the call
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // both are 32-bit on some targets
+    #[allow(clippy::useless_conversion)]
+    let available = u64::from(stat.f_bavail) * u64::from(stat.f_frsize);
+    Some(available)
+}
+
+#[cfg(windows)]
+fn free_space(dir: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(dir: *const u16, available: *mut u64, total: *mut u64, free: *mut u64) -> i32;
+    }
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available = 0u64;
+    // SAFETY: `wide` is NUL-terminated; null is allowed for the totals we don't need
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut()) };
+    (ok != 0).then_some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn free_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_bytes_is_what_is_left_to_write() {
+        assert_eq!(required_bytes(Some(10_000), 2_500), Some(7_500));
+        assert_eq!(required_bytes(Some(100), 250), Some(0));
+        assert_eq!(required_bytes(None, 250), None);
+    }
+
+    #[test]
+    fn disk_full_errors_are_recognized_up_the_chain() {
+        let full = DiskFull {
+            file_name: "video.mp4".into(),
+            output_path: "/tmp/video.mp4".into(),
+            required_bytes: Some(7_500),
+            available_bytes: Some(120),
+        };
+        assert_eq!(full.message(), "Disk full: 'video.mp4' could not be saved (7500 more bytes needed, 120 bytes free)");
+        assert!(is_disk_full(&full.message()));
+        let unknown = DiskFull { required_bytes: None, available_bytes: None, ..full };
+        assert_eq!(unknown.message(), "Disk full: 'video.mp4' could not be saved (more space needed)");
+
+        let denied = write_error("Failed to write file", std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(denied.starts_with("Failed to write file: ") && !is_disk_full(&denied));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn out_of_space_write_errors_become_disk_full() {
+        for code in [libc::ENOSPC, libc::EDQUOT] {
+            assert!(is_disk_full(&write_error("Failed to write file", std::io::Error::from_raw_os_error(code))));
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use super::{data_dir, read_upload_history};
+use super::settings::{current_settings, update_settings};
+use super::verification::{hash_local_file, looks_like_blake3, server_reported_hash};
+
+// =============================================================================================================
+// =============================================== DOWNLOAD CACHE ==============================================
+// =============================================================================================================
+//
+// Opt-in copy of finished downloads under `<cache>/downloads/<blake3>`. Before a download is served from here,
+// a HEAD request asks the server for the current content hash (its blake3 header, or an ETag holding the hash
+// the upload history knows), so a file replaced remotely is fetched again. The file's mtime doubles as the last
+// access time; the least recently used entries go first when the cache is over its size limit.
+
+const DEFAULT_MAX_BYTES: u64 = 1024 * 1024 * 1024;
+
+fn cache_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    data_dir::app_cache_dir(app_handle).map(|d| d.join("downloads"))
+}
+
+pub fn enabled(app_handle: &AppHandle) -> bool {
+    current_settings(app_handle).download_cache_enabled
+}
+
+fn max_bytes(app_handle: &AppHandle) -> u64 {
+    current_settings(app_handle).download_cache_max_bytes.unwrap_or(DEFAULT_MAX_BYTES)
+}
+
+/// Path of the cached copy of `hash`, marking it as recently used
+fn lookup(hash: &str, app_handle: &AppHandle) -> Option<PathBuf> {
+    let hash = hash.to_lowercase();
+    if !looks_like_blake3(&hash) {
+        return None;
+    }
+    let path = cache_dir(app_handle).ok()?.join(hash);
+    let file = std::fs::File::options().write(true).open(&path).ok()?;
+    file.set_modified(SystemTime::now()).ok();
+    Some(path)
+}
+
+/// Content hash of the last successful upload named `file_name`, if this device uploaded it
+pub async fn known_hash(user_id: &str, file_name: &str, app_handle: &AppHandle) -> Option<String> {
+    read_upload_history(user_id.to_string(), app_handle.clone())
+        .await
+        .ok()?
+        .into_iter()
+        .rev()
+        .find(|e| e.status == "success" && e.remote_path == file_name)
+        .map(|e| e.blake3_hash.to_lowercase())
+        .filter(|h| looks_like_blake3(h))
+}
+
+/// ETag value, unquoted and without the weak prefix, when it is a blake3 hash
+fn etag_hash(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let etag = headers.get(reqwest::header::ETAG)?.to_str().ok()?.trim();
+    let etag = etag.strip_prefix("W/").unwrap_or(etag).trim_matches('"').to_lowercase();
+    looks_like_blake3(&etag).then_some(etag)
+}
+
+/// Hash of the content the server has now, from a HEAD `request` for the file: the hash it reports, or `known`
+/// when the ETag matches it. `None` when the server doesn't vouch for any hash, and the cache isn't used.
+pub async fn current_hash(request: reqwest::RequestBuilder, known: Option<&str>) -> Option<String> {
+    let response = match request.send().await {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            println!("[CACHE] HEAD answered {}, not using the cache", response.status());
+            return None;
+        }
+        Err(e) => {
+            println!("[CACHE] HEAD failed, not using the cache: {}", e);
+            return None;
+        }
+    };
+    let headers = response.headers();
+    if let Some(hash) = server_reported_hash(headers, "") {
+        return Some(hash);
+    }
+    let known = known?.to_lowercase();
+    (etag_hash(headers).as_deref() == Some(known.as_str())).then_some(known)
+}
+
+/// Copy the cached object `hash` to `dest`; `None` on a miss or when the cache is off
+pub async fn restore(hash: &str, dest: &str, app_handle: &AppHandle) -> Option<u64> {
+    if !enabled(app_handle) {
+        return None;
+    }
+    let cached = lookup(hash, app_handle)?;
+    let copied: Result<u64, String> = async {
+        let mut src = tokio::fs::File::open(&cached).await.map_err(|e| format!("Failed to open cached file: {}", e))?;
+        let mut file = super::create_local_file(dest, app_handle).await?;
+        let copied = tokio::io::copy(&mut src, &mut file).await.map_err(|e| format!("Failed to copy cached file: {}", e))?;
+        file.sync_all().await.ok();
+        Ok(copied)
+    }
+    .await;
+    match copied {
+        Ok(bytes) => {
+            println!("📦 Served {} from download cache ({} bytes)", dest, bytes);
+            Some(bytes)
+        }
+        Err(e) => {
+            println!("[CACHE] {}", e);
+            None
+        }
+    }
+}
+
+/// Add a finished download to the cache and evict down to the size limit. When `expected` is given, the file is
+/// only cached if its content matches.
+pub async fn store(path: &str, expected: Option<&str>, app_handle: &AppHandle) -> Result<(), String> {
+    if !enabled(app_handle) {
+        return Ok(());
+    }
+    let (hash, size) = hash_local_file(path, app_handle).await?;
+    if expected.is_some_and(|e| !e.eq_ignore_ascii_case(&hash)) {
+        return Err(format!("{} doesn't match its expected hash, not caching", path));
+    }
+    let limit = max_bytes(app_handle);
+    if size > limit {
+        return Ok(());
+    }
+    let dir = cache_dir(app_handle)?;
+    let target = dir.join(&hash);
+    if lookup(&hash, app_handle).is_none() {
+        tokio::fs::create_dir_all(&dir).await.map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        // copy under a temp name so a crash never leaves a truncated entry behind a valid hash
+        let tmp = dir.join(format!("{}.part", hash));
+        tokio::fs::copy(path, &tmp).await.map_err(|e| format!("Failed to cache download: {}", e))?;
+        tokio::fs::rename(&tmp, &target).await.map_err(|e| format!("Failed to cache download: {}", e))?;
+    }
+    evict(&dir, limit);
+    Ok(())
+}
+
+/// Remove least recently used entries until the cache fits in `limit`
+fn evict(dir: &Path, limit: u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            meta.is_file().then(|| (meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), meta.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort_by_key(|(modified, _, _)| *modified);
+    for (_, len, path) in files {
+        if total <= limit {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            println!("[CACHE] Evicted {}", path.display());
+            total -= len;
+        }
+    }
+}
+
+/// Local path of the cached object with this blake3 hash, if present
+#[tauri::command]
+pub async fn get_cached_path(hash: String, app_handle: AppHandle) -> Result<Option<String>, String> {
+    if !enabled(&app_handle) {
+        return Ok(None);
+    }
+    Ok(lookup(&hash, &app_handle).map(|p| p.to_string_lossy().to_string()))
+}
+
+/// Turn the download cache on or off and set its size limit (`None` = 1 GB). Shrinking evicts right away and
+/// turning it off empties it.
+#[tauri::command]
+pub async fn set_download_cache(enabled: bool, max_bytes: Option<u64>, app_handle: AppHandle) -> Result<(), String> {
+    let settings = update_settings(&app_handle, |s| {
+        s.download_cache_enabled = enabled;
+        s.download_cache_max_bytes = max_bytes;
+    })?;
+    let dir = cache_dir(&app_handle)?;
+    evict(&dir, if enabled { settings.download_cache_max_bytes.unwrap_or(DEFAULT_MAX_BYTES) } else { 0 });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG};
+
+    #[test]
+    fn etags_holding_a_hash() {
+        let hash = "ab".repeat(32);
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_str(&format!("W/\"{}\"", hash.to_uppercase())).unwrap());
+        assert_eq!(etag_hash(&headers), Some(hash));
+        headers.insert(ETAG, HeaderValue::from_static("\"33a64df5\""));
+        assert_eq!(etag_hash(&headers), None);
+    }
+}
@@ -0,0 +1,124 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::conflicts::{preview_upload_conflict, ConflictPreview};
+use super::settings::current_settings;
+use super::sparse::sparse_sizes_at;
+use super::verification::hash_local_file;
+use super::{
+    current_api_config, destinations, ensure_valid_token, estimate_upload_cost, folders, load_credentials, local_file_exists,
+    network, read_upload_history, upload_route, ApiConfig, SavedCredentials,
+};
+
+// =============================================================================================================
+// ================================================= DRY RUN ===================================================
+// =============================================================================================================
+//
+// `preview_upload` walks an upload through naming, hashing, dedup and conflict checks and cost estimation without
+// writing anything remotely or to the local history. Hooks are reported but not run, since they may have side effects.
+// Directory uploads and sync don't exist yet; they should build on `plan_upload` when they land.
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UploadPlan {
+    pub local_path: String,
+    pub remote_name: String,
+    pub file_size: u64,
+    pub blake3_hash: String,
+    /// Remote names of earlier successful uploads with the same content
+    pub duplicates: Vec<String>,
+    pub conflict: ConflictPreview,
+    pub tier: Option<String>,
+    /// "standard" | "priority"
+    pub route: String,
+    pub estimated_cost: Option<f64>,
+    /// Bytes on disk when the file is sparse; the upload sends and is billed for `file_size`
+    pub allocated_size: Option<u64>,
+    /// Program of the pre-upload hook that would run
+    pub pre_upload_hook: Option<String>,
+    /// False when the upload would abort (e.g. on a name conflict)
+    pub would_upload: bool,
+}
+
+pub struct UploadPlanRequest<'a> {
+    pub file_path: &'a str,
+    pub remote_file_name: Option<&'a str>,
+    pub remote_dir: Option<&'a str>,
+    pub tier: Option<&'a str>,
+    pub on_conflict: Option<&'a str>,
+    pub priority: bool,
+}
+
+pub async fn plan_upload(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    request: UploadPlanRequest<'_>,
+    app_handle: &AppHandle,
+) -> Result<UploadPlan, String> {
+    if !local_file_exists(request.file_path) {
+        return Err(format!("File not found: {}", request.file_path));
+    }
+    let file_name = match request.remote_file_name {
+        Some(custom) if !custom.trim().is_empty() => custom.to_string(),
+        _ => destinations::default_remote_name(request.file_path, app_handle)?,
+    };
+    let remote_name = folders::resolve_remote_name(&file_name, request.remote_dir, app_handle)?;
+
+    let (blake3_hash, file_size) = hash_local_file(request.file_path, app_handle).await?;
+    let duplicates = read_upload_history(credentials.user_id.clone(), app_handle.clone())
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|e| e.status == "success" && e.blake3_hash == blake3_hash)
+        .map(|e| e.remote_path)
+        .fold(Vec::new(), |mut names, name| {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+            names
+        });
+
+    let conflict = preview_upload_conflict(client, api_config, credentials, &remote_name, request.on_conflict, app_handle).await?;
+    let (route, _) = upload_route(api_config, request.priority);
+
+    Ok(UploadPlan {
+        local_path: request.file_path.to_string(),
+        remote_name,
+        file_size,
+        blake3_hash,
+        duplicates,
+        would_upload: conflict.final_name.is_some(),
+        conflict,
+        tier: request.tier.map(str::to_string),
+        route: route.to_string(),
+        estimated_cost: estimate_upload_cost(request.tier, file_size, app_handle).await,
+        allocated_size: sparse_sizes_at(request.file_path).map(|s| s.allocated),
+        pre_upload_hook: current_settings(app_handle).pre_upload_hook.map(|h| h.program),
+    })
+}
+
+/// What `upload_file` with the same arguments would do, without uploading
+#[tauri::command]
+pub async fn preview_upload(
+    file_path: String,
+    tier: Option<String>,
+    remote_file_name: Option<String>,
+    remote_dir: Option<String>,
+    on_conflict: Option<String>,
+    priority: Option<bool>,
+    app_handle: AppHandle,
+) -> Result<UploadPlan, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+    let request = UploadPlanRequest {
+        file_path: &file_path,
+        remote_file_name: remote_file_name.as_deref(),
+        remote_dir: remote_dir.as_deref(),
+        tier: tier.as_deref(),
+        on_conflict: on_conflict.as_deref(),
+        priority: priority.unwrap_or(false),
+    };
+    plan_upload(&client, &api_config, &credentials, request, &app_handle).await
+}
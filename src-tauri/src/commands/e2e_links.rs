@@ -0,0 +1,265 @@
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use futures_util::StreamExt;
+use rand::RngCore;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use zeroize::Zeroizing;
+
+use super::{
+    account_scope, bandwidth, create_local_file, create_public_link, current_api_config, data_dir, history_log,
+    local_file_name, network, open_local_file, output_paths, tuning, update_public_links, upload, PublicLinkEntry,
+    UploadOptions,
+};
+
+// =============================================================================================================
+// ========================================= END-TO-END ENCRYPTED LINKS ========================================
+// =============================================================================================================
+//
+// The file is encrypted here with a fresh key before upload, and the key only ever travels in the link's URL
+// fragment (`#key=<hex>`), which browsers and HTTP clients never send to the server.
+//
+// Format: "FSE1" + 7-byte nonce prefix, then 64 KiB plaintext chunks sealed with ChaCha20-Poly1305. Each chunk's
+// nonce is the prefix, a big-endian chunk counter and a last-chunk flag, so reordered, dropped or truncated
+// chunks fail to decrypt.
+
+const MAGIC: &[u8; 4] = b"FSE1";
+const PREFIX_LEN: usize = 7;
+const HEADER_LEN: usize = MAGIC.len() + PREFIX_LEN;
+const CHUNK: usize = 64 * 1024;
+const SEALED_CHUNK: usize = CHUNK + 16;
+const KEY_FRAGMENT: &str = "key=";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EncryptedPublicLink {
+    pub link: PublicLinkEntry,
+    /// Full share URL including the key fragment. Only returned here, it is not stored anywhere.
+    pub url: String,
+}
+
+fn chunk_nonce(prefix: &[u8], counter: u32, last: bool) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[..PREFIX_LEN].copy_from_slice(prefix);
+    nonce[PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    *Nonce::from_slice(&nonce)
+}
+
+/// Read until `buf` is full or the input ends
+async fn fill<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> Result<usize, String> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await.map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+async fn encrypt_file(src: &str, dest: &std::path::Path, key: &[u8; 32], app_handle: &AppHandle) -> Result<(), String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let mut prefix = [0u8; PREFIX_LEN];
+    OsRng.fill_bytes(&mut prefix);
+
+    let mut reader = open_local_file(src, app_handle).await?;
+    let out = tokio::fs::File::create(dest).await.map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut out = tokio::io::BufWriter::new(out);
+    out.write_all(MAGIC).await.map_err(|e| format!("Failed to write file: {}", e))?;
+    out.write_all(&prefix).await.map_err(|e| format!("Failed to write file: {}", e))?;
+
+    let (mut current, mut next) = (vec![0u8; CHUNK], vec![0u8; CHUNK]);
+    let mut len = fill(&mut reader, &mut current).await?;
+    let mut counter = 0u32;
+    loop {
+        // read ahead so the final chunk can be flagged
+        let next_len = if len == CHUNK { fill(&mut reader, &mut next).await? } else { 0 };
+        let last = next_len == 0;
+        let sealed = cipher
+            .encrypt(&chunk_nonce(&prefix, counter, last), &current[..len])
+            .map_err(|e| format!("Encryption failed: {}", e))?;
+        out.write_all(&sealed).await.map_err(|e| format!("Failed to write file: {}", e))?;
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+        counter = counter.checked_add(1).ok_or("File too large to encrypt")?;
+    }
+    out.flush().await.map_err(|e| format!("Failed to write file: {}", e))
+}
+
+/// Split a share URL into the plain download URL and the key from its fragment
+fn parse_share_url(link: &str) -> Result<(String, Zeroizing<[u8; 32]>), String> {
+    let (url, fragment) = link.split_once('#').ok_or("Link has no key fragment")?;
+    let key_hex = fragment
+        .split('&')
+        .find_map(|part| part.strip_prefix(KEY_FRAGMENT))
+        .ok_or("Link has no key fragment")?;
+    let decoded = hex::decode(key_hex).map(Zeroizing::new).map_err(|_| "Invalid key in link")?;
+    if decoded.len() != 32 {
+        return Err("Invalid key in link".to_string());
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&decoded);
+    Ok((url.to_string(), key))
+}
+
+/// Encrypt `file_path` with a new key, upload it and create a public link for it. The returned URL carries the key.
+#[tauri::command]
+pub async fn create_encrypted_public_link(
+    user_id: String,
+    file_path: String,
+    remote_file_name: Option<String>,
+    tier: Option<String>,
+    custom_title: Option<String>,
+    custom_description: Option<String>,
+    app_handle: AppHandle,
+) -> Result<EncryptedPublicLink, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let api_config = current_api_config(&app_handle);
+    let download_url = api_config.optional_url(&api_config.public_download, "Public download")?;
+    let remote_name = match remote_file_name {
+        Some(name) if !name.trim().is_empty() => name,
+        _ => format!("{}.fse", local_file_name(&file_path).ok_or("Invalid file name")?),
+    };
+
+    let dir = data_dir::app_cache_dir(&app_handle)?.join("e2e");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create encryption dir: {}", e))?;
+    let sealed_path = dir.join(format!("{}.fse", uuid::Uuid::new_v4()));
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(key.as_mut());
+    let uploaded = async {
+        encrypt_file(&file_path, &sealed_path, &key, &app_handle).await?;
+        let options = UploadOptions {
+            tier,
+            remote_file_name: Some(remote_name),
+            on_conflict: Some("rename".to_string()),
+            ..Default::default()
+        };
+        upload(sealed_path.to_string_lossy().to_string(), options, app_handle.clone()).await
+    }
+    .await;
+    let _ = std::fs::remove_file(&sealed_path);
+    // the name it was stored under, after the default folder and conflict renaming
+    let remote_path = uploaded?.remote_name;
+
+    // history should point at the original file, not the temporary ciphertext
+    let (sealed, original) = (sealed_path.to_string_lossy().to_string(), file_path.clone());
+    history_log::update_entries(
+        &user_id,
+        move |entry| {
+            if entry.local_path != sealed {
+                return false;
+            }
+            entry.local_path = original.clone();
+            true
+        },
+        &app_handle,
+    )
+    .await?;
+
+    let mut link = create_public_link(user_id.clone(), remote_path, custom_title, custom_description, None, None, None, app_handle.clone()).await?;
+    link.encrypted = true;
+    update_public_links(&user_id, &app_handle, |links| {
+        if let Some(stored) = links.iter_mut().find(|l| l.link_hash == link.link_hash) {
+            stored.encrypted = true;
+        }
+    })?;
+
+    let url = format!("{}?hash={}#{}{}", download_url, link.link_hash, KEY_FRAGMENT, hex::encode(key.as_slice()));
+    println!("🔐 Created encrypted link for {}", link.remote_path);
+    Ok(EncryptedPublicLink { link, url })
+}
+
+/// Download an encrypted public link and decrypt it to `output_path` with the key from the URL fragment
+#[tauri::command]
+pub async fn download_public_encrypted(link: String, output_path: String, app_handle: AppHandle) -> Result<String, String> {
+    let (url, key) = parse_share_url(&link)?;
+    let output_path = output_paths::check_output_path(&output_path, &app_handle)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_slice()));
+
+    // the fragment is never part of the request
+    let response = network::client(&app_handle)
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Download request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed - Status: {}", response.status()));
+    }
+    let total = response.content_length();
+    let mut stream = response.bytes_stream();
+    let mut file = tokio::io::BufWriter::new(create_local_file(&output_path, &app_handle).await?);
+
+    let mut throttle = tuning::ProgressThrottle::new(&app_handle);
+    let mut prefix: Option<Vec<u8>> = None;
+    let mut pending: Vec<u8> = Vec::with_capacity(2 * SEALED_CHUNK);
+    let (mut counter, mut received, mut written) = (0u32, 0u64, 0u64);
+    let decrypted: Result<(), String> = async {
+        loop {
+            let chunk = stream.next().await.transpose().map_err(|e| format!("Download chunk error: {}", e))?;
+            let done = chunk.is_none();
+            if let Some(chunk) = chunk {
+                bandwidth::pace(&app_handle, chunk.len()).await;
+                received += chunk.len() as u64;
+                pending.extend_from_slice(&chunk);
+            }
+            if prefix.is_none() && pending.len() >= HEADER_LEN {
+                if &pending[..MAGIC.len()] != MAGIC {
+                    return Err("Not an encrypted Firestarter file".to_string());
+                }
+                let header: Vec<u8> = pending.drain(..HEADER_LEN).collect();
+                prefix = Some(header[MAGIC.len()..].to_vec());
+            }
+            let Some(prefix) = &prefix else {
+                if done {
+                    return Err("Encrypted file is truncated".to_string());
+                }
+                continue;
+            };
+            // a full chunk is only known not to be the last once more data follows it
+            while pending.len() > SEALED_CHUNK || (done && !pending.is_empty()) {
+                let take = pending.len().min(SEALED_CHUNK);
+                let last = done && take == pending.len();
+                let plain = cipher
+                    .decrypt(&chunk_nonce(prefix, counter, last), &pending[..take])
+                    .map_err(|_| "Decryption failed: wrong key or corrupted file".to_string())?;
+                pending.drain(..take);
+                file.write_all(&plain).await.map_err(|e| format!("Failed to write chunk: {}", e))?;
+                written += plain.len() as u64;
+                counter = counter.checked_add(1).ok_or("Encrypted file has too many chunks")?;
+                if last {
+                    break;
+                }
+            }
+            let percent = total.map(|t| (received as f64 / t.max(1) as f64 * 100.0).min(100.0)).unwrap_or(0.0);
+            if throttle.should_emit(percent, done) {
+                let payload = serde_json::json!({
+                    "file_name": url,
+                    "downloaded": received,
+                    "total": total,
+                    "percent": percent,
+                    "output_path": output_path
+                });
+                app_handle.emit("download_progress", payload).ok();
+            }
+            if done {
+                return Ok(());
+            }
+        }
+    }
+    .await;
+    file.flush().await.map_err(|e| format!("Failed to write file: {}", e))?;
+    if let Err(e) = decrypted {
+        // don't leave partial plaintext behind
+        drop(file);
+        let _ = tokio::fs::remove_file(&output_path).await;
+        return Err(e);
+    }
+    println!("🔓 Decrypted {} bytes to {}", written, output_path);
+    Ok(format!("Decrypted file saved to '{}'", output_path))
+}
@@ -0,0 +1,159 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+
+// =============================================================================================================
+// ============================================ REMOTE NAME ENCODING ===========================================
+// =============================================================================================================
+//
+// Remote names travel as query values (`?file_name=...`). Everything that would end the value or change how it
+// decodes is escaped: the query delimiters `&`, `=`, `;`, `#`, a `+` (decoded as a space by form parsers) and `%`
+// itself. Non-ASCII is always percent-encoded as UTF-8. `/` stays readable since names use it for folders.
+// Names the backend can't store are rejected up front by `check_remote_name`.
+
+pub const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'|')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'[')
+    .add(b']')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'=')
+    .add(b';');
+
+/// `name` escaped with `QUERY_ENCODE_SET`, for a query value or the end of a URL path
+pub fn encode_remote_name(name: &str) -> String {
+    utf8_percent_encode(name, QUERY_ENCODE_SET).to_string()
+}
+
+/// Inverse of `encode_remote_name`; bytes that aren't UTF-8 are an error, not replaced
+pub fn decode_remote_name(encoded: &str) -> Result<String, String> {
+    percent_decode_str(encoded)
+        .decode_utf8()
+        .map(|name| name.into_owned())
+        .map_err(|_| "Remote name is not valid UTF-8".to_string())
+}
+
+/// Longest remote name the backend accepts, in UTF-8 bytes
+pub const MAX_REMOTE_NAME_BYTES: usize = 1024;
+
+/// Why `name` can't be stored remotely, if it can't
+pub fn check_remote_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Remote name is empty".to_string());
+    }
+    if name.len() > MAX_REMOTE_NAME_BYTES {
+        return Err(format!("Remote name is {} bytes long (at most {})", name.len(), MAX_REMOTE_NAME_BYTES));
+    }
+    if let Some(c) = name.chars().find(|c| c.is_control()) {
+        return Err(format!("Remote name contains control character U+{:04X}", c as u32));
+    }
+    if name.contains('\\') {
+        return Err("Remote name contains '\\'; use '/' to separate folders".to_string());
+    }
+    if name.starts_with('/') || name.ends_with('/') {
+        return Err("Remote name can't start or end with '/'".to_string());
+    }
+    for segment in name.split('/') {
+        if segment.is_empty() {
+            return Err("Remote name contains an empty folder ('//')".to_string());
+        }
+        if segment == "." || segment == ".." {
+            return Err(format!("Remote name contains a '{}' segment", segment));
+        }
+        if segment.trim() != segment {
+            return Err(format!("'{}' starts or ends with whitespace", segment));
+        }
+    }
+    Ok(())
+}
+
+/// Check a remote name before uploading to it; the error says what to change
+#[tauri::command]
+pub async fn validate_remote_name(name: String) -> Result<(), String> {
+    check_remote_name(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn encode(value: &str) -> String {
+        utf8_percent_encode(value, QUERY_ENCODE_SET).to_string()
+    }
+
+    /// The query as a server sees it
+    fn query_pairs(query: &str) -> Vec<(String, String)> {
+        let url = reqwest::Url::parse(&format!("https://example.com/download?{}", query)).unwrap();
+        url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect()
+    }
+
+    fn segment() -> impl Strategy<Value = String> {
+        "[^/\\\\\\p{Cc}]{1,40}".prop_filter("no surrounding whitespace, no dot segments", |s| {
+            s.trim() == s && s != "." && s != ".."
+        })
+    }
+
+    #[test]
+    fn reserved_characters_are_escaped() {
+        assert_eq!(encode("a&b=c+d;e#f?g%h i"), "a%26b%3Dc%2Bd%3Be%23f%3Fg%25h%20i");
+        assert_eq!(encode("photos/2024/été.jpg"), "photos/2024/%C3%A9t%C3%A9.jpg");
+    }
+
+    #[test]
+    fn rejects_names_the_backend_cannot_store() {
+        for name in ["", "   ", "/a", "a/", "a//b", "a/../b", "./a", "a\\b", "a\nb", "a\0b", " a", "a/b "] {
+            assert!(check_remote_name(name).is_err(), "accepted {:?}", name);
+        }
+        assert!(check_remote_name(&"x".repeat(MAX_REMOTE_NAME_BYTES + 1)).is_err());
+        for name in ["a", "a b.txt", "folder/a&b=c+d.txt", "日本語/ファイル.txt", "emoji 🔥.png", "100%.csv", "..hidden"] {
+            assert!(check_remote_name(name).is_ok(), "rejected {:?}", name);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn encoding_round_trips(value in any::<String>()) {
+            let encoded = encode(&value);
+            prop_assert_eq!(percent_decode_str(&encoded).decode_utf8().unwrap(), value.as_str());
+            prop_assert_eq!(decode_remote_name(&encode_remote_name(&value)), Ok(value));
+        }
+
+        #[test]
+        fn encoded_value_is_one_query_value(value in any::<String>()) {
+            let encoded = encode(&value);
+            prop_assert!(encoded.bytes().all(|b| b.is_ascii_graphic()));
+            prop_assert!(!encoded.contains(['&', '=', '+', ';', '#', '?', ' ']));
+            let pairs = query_pairs(&format!("file_name={}&upload_id=1", encoded));
+            prop_assert_eq!(pairs, vec![("file_name".to_string(), value), ("upload_id".to_string(), "1".to_string())]);
+        }
+
+        #[test]
+        fn check_never_panics(name in any::<String>()) {
+            let _ = check_remote_name(&name);
+        }
+
+        #[test]
+        fn folder_paths_of_valid_segments_are_accepted(segments in proptest::collection::vec(segment(), 1..5)) {
+            let name = segments.join("/");
+            prop_assume!(name.len() <= MAX_REMOTE_NAME_BYTES);
+            prop_assert!(check_remote_name(&name).is_ok(), "rejected {:?}", name);
+        }
+
+        #[test]
+        fn control_characters_are_rejected(prefix in segment(), c in proptest::char::range('\0', '\u{1f}'), suffix in segment()) {
+            let name = format!("{}{}{}", prefix, c, suffix);
+            prop_assert!(check_remote_name(&name).is_err(), "accepted {:?}", name);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::Mutex;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use tauri::{AppHandle, Manager};
+
+use super::metrics::{api_latency_histograms, lifetime_totals, LATENCY_BUCKETS};
+use super::settings::{current_settings, update_settings};
+use super::transfers::queue_depth;
+
+// =============================================================================================================
+// ============================================= METRICS ENDPOINT ==============================================
+// =============================================================================================================
+//
+// Opt-in, loopback-only scrape target for self-hosters: `/metrics` in Prometheus text format and
+// `/metrics.json` with the same data. Off by default; the choice is persisted in app settings.
+
+pub const DEFAULT_METRICS_PORT: u16 = 9464;
+
+pub struct MetricsExporter {
+    port: u16,
+    shutdown: tokio::sync::oneshot::Sender<()>,
+}
+
+pub type MetricsExporterState = Mutex<Option<MetricsExporter>>;
+pub fn new_metrics_exporter_state() -> MetricsExporterState { Mutex::new(None) }
+
+fn render_prometheus(app_handle: &AppHandle) -> String {
+    let totals = lifetime_totals(app_handle);
+    let (queued, active) = queue_depth(app_handle);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# HELP firestarter_transfers_total Transfers attempted, by direction and result");
+    let _ = writeln!(out, "# TYPE firestarter_transfers_total counter");
+    for (direction, result, value) in [
+        ("upload", "success", totals.uploads),
+        ("upload", "failure", totals.failed_uploads),
+        ("download", "success", totals.downloads),
+        ("download", "failure", totals.failed_downloads),
+    ] {
+        let _ = writeln!(out, "firestarter_transfers_total{{direction=\"{}\",result=\"{}\"}} {}", direction, result, value);
+    }
+
+    let _ = writeln!(out, "# HELP firestarter_transferred_bytes_total Bytes moved by successful transfers");
+    let _ = writeln!(out, "# TYPE firestarter_transferred_bytes_total counter");
+    let _ = writeln!(out, "firestarter_transferred_bytes_total{{direction=\"upload\"}} {}", totals.bytes_uploaded);
+    let _ = writeln!(out, "firestarter_transferred_bytes_total{{direction=\"download\"}} {}", totals.bytes_downloaded);
+
+    let _ = writeln!(out, "# HELP firestarter_transfer_queue_jobs Jobs in the upload queue");
+    let _ = writeln!(out, "# TYPE firestarter_transfer_queue_jobs gauge");
+    let _ = writeln!(out, "firestarter_transfer_queue_jobs{{state=\"queued\"}} {}", queued);
+    let _ = writeln!(out, "firestarter_transfer_queue_jobs{{state=\"uploading\"}} {}", active);
+
+    let _ = writeln!(out, "# HELP firestarter_api_request_duration_seconds API response time since launch");
+    let _ = writeln!(out, "# TYPE firestarter_api_request_duration_seconds histogram");
+    for (endpoint, histogram) in api_latency_histograms(app_handle) {
+        let label = endpoint.replace('\\', "\\\\").replace('"', "\\\"");
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+            let _ = writeln!(out, "firestarter_api_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}", label, bound, count);
+        }
+        let _ = writeln!(out, "firestarter_api_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}", label, histogram.count);
+        let _ = writeln!(out, "firestarter_api_request_duration_seconds_sum{{endpoint=\"{}\"}} {}", label, histogram.sum_secs);
+        let _ = writeln!(out, "firestarter_api_request_duration_seconds_count{{endpoint=\"{}\"}} {}", label, histogram.count);
+    }
+    out
+}
+
+fn render_json(app_handle: &AppHandle) -> String {
+    let (queued, active) = queue_depth(app_handle);
+    serde_json::json!({
+        "transfers": lifetime_totals(app_handle),
+        "queue": { "queued": queued, "uploading": active },
+        "api_latency": {
+            "bucket_bounds_secs": LATENCY_BUCKETS,
+            "endpoints": api_latency_histograms(app_handle),
+        },
+    })
+    .to_string()
+}
+
+async fn serve(req: Request<Body>, app_handle: AppHandle) -> Result<Response<Body>, Infallible> {
+    let (content_type, body) = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => ("text/plain; version=0.0.4", render_prometheus(&app_handle)),
+        (&Method::GET, "/metrics.json") => ("application/json", render_json(&app_handle)),
+        _ => {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::from("Not found"))
+                .unwrap_or_else(|_| Response::new(Body::empty())))
+        }
+    };
+    Ok(Response::builder()
+        .header("Content-Type", content_type)
+        .body(Body::from(body))
+        .unwrap_or_else(|_| Response::new(Body::empty())))
+}
+
+/// Must be called from inside the async runtime
+fn start_exporter(app_handle: &AppHandle, port: u16) -> Result<MetricsExporter, String> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port)).map_err(|e| format!("Failed to bind metrics endpoint: {}", e))?;
+    listener.set_nonblocking(true).map_err(|e| format!("Failed to configure metrics endpoint: {}", e))?;
+
+    let handle = app_handle.clone();
+    let make_svc = make_service_fn(move |_conn| {
+        let handle = handle.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| serve(req, handle.clone()))) }
+    });
+    let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let server = Server::from_tcp(listener)
+        .map_err(|e| format!("Failed to start metrics endpoint: {}", e))?
+        .serve(make_svc)
+        .with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = server.await {
+            println!("❌ Metrics endpoint stopped: {}", e);
+        }
+    });
+
+    println!("📈 Metrics endpoint listening on 127.0.0.1:{}", port);
+    Ok(MetricsExporter { port, shutdown })
+}
+
+fn stop_exporter(app_handle: &AppHandle) {
+    if let Some(exporter) = app_handle.state::<MetricsExporterState>().lock().unwrap().take() {
+        let _ = exporter.shutdown.send(());
+        println!("📈 Metrics endpoint on port {} stopped", exporter.port);
+    }
+}
+
+/// Start the endpoint at launch when the user opted in
+pub fn restore_metrics_endpoint(app_handle: &AppHandle) {
+    let settings = current_settings(app_handle);
+    if !settings.metrics_endpoint_enabled {
+        return;
+    }
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let port = settings.metrics_endpoint_port.unwrap_or(DEFAULT_METRICS_PORT);
+        match start_exporter(&handle, port) {
+            Ok(exporter) => *handle.state::<MetricsExporterState>().lock().unwrap() = Some(exporter),
+            Err(e) => println!("[METRICS] {}", e),
+        }
+    });
+}
+
+/// Turn the endpoint on or off. Returns the scrape URL while enabled.
+#[tauri::command]
+pub async fn set_metrics_endpoint(enabled: bool, port: Option<u16>, app_handle: AppHandle) -> Result<Option<String>, String> {
+    stop_exporter(&app_handle);
+    let port = port.unwrap_or(DEFAULT_METRICS_PORT);
+    if enabled {
+        let exporter = start_exporter(&app_handle, port)?;
+        *app_handle.state::<MetricsExporterState>().lock().unwrap() = Some(exporter);
+    }
+    update_settings(&app_handle, |s| {
+        s.metrics_endpoint_enabled = enabled;
+        s.metrics_endpoint_port = Some(port);
+    })?;
+    Ok(enabled.then(|| format!("http://127.0.0.1:{}/metrics", port)))
+}
+
+#[tauri::command]
+pub async fn get_metrics_endpoint_url(app_handle: AppHandle) -> Result<Option<String>, String> {
+    Ok(app_handle
+        .state::<MetricsExporterState>()
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|e| format!("http://127.0.0.1:{}/metrics", e.port)))
+}
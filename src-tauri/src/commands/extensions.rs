@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use percent_encoding::utf8_percent_encode;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{
+    app_data_root, bearer_headers, current_api_config, ensure_valid_token, ipc_guard, load_credentials, metrics, network,
+    QUERY_ENCODE_SET,
+};
+
+// =============================================================================================================
+// ============================================ EXTRA NAMED ENDPOINTS ==========================================
+// =============================================================================================================
+//
+// Advanced users can describe backend routes the GUI doesn't know about yet in `extra_endpoints.json`
+// (app data dir), keyed by name:
+//   { "list_buckets": { "method": "GET", "path": "/buckets/{bucket_id}", "auth": "bearer" } }
+// `{param}` placeholders in the path are filled from `params`; leftover params become the query string.
+// Paths are always relative to the configured API base URL, so credentials never leave for another host.
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NamedEndpoint {
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub path: String,
+    /// "bearer" | "app_key" | "body" | "none"
+    #[serde(default = "default_auth")]
+    pub auth: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+fn default_method() -> String { "GET".to_string() }
+fn default_auth() -> String { "bearer".to_string() }
+
+fn extra_endpoints_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join("extra_endpoints.json"))
+}
+
+/// Re-read on every call so edits apply without a restart
+fn load_extra_endpoints(app_handle: &AppHandle) -> Result<BTreeMap<String, NamedEndpoint>, String> {
+    let path = extra_endpoints_path(app_handle)?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read extra_endpoints.json: {}", e))?;
+    let endpoints: BTreeMap<String, NamedEndpoint> =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid extra_endpoints.json: {}", e))?;
+    for (name, endpoint) in &endpoints {
+        if !endpoint.path.starts_with('/') {
+            return Err(format!("Endpoint '{}': path must start with '/'", name));
+        }
+    }
+    Ok(endpoints)
+}
+
+fn param_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Fill `{param}` placeholders and append the rest as a query string
+fn build_path(template: &str, params: &serde_json::Map<String, serde_json::Value>) -> Result<String, String> {
+    let mut path = template.to_string();
+    let mut query = Vec::new();
+    for (key, value) in params {
+        let placeholder = format!("{{{}}}", key);
+        let value = param_to_string(value);
+        if path.contains(&placeholder) {
+            path = path.replace(&placeholder, &utf8_percent_encode(&value, percent_encoding::NON_ALPHANUMERIC).to_string());
+        } else {
+            query.push(format!(
+                "{}={}",
+                utf8_percent_encode(key, QUERY_ENCODE_SET),
+                utf8_percent_encode(&value, QUERY_ENCODE_SET)
+            ));
+        }
+    }
+    if let Some(start) = path.find('{') {
+        let name = path[start + 1..].split('}').next().unwrap_or_default();
+        return Err(format!("Missing path parameter: {}", name));
+    }
+    if !query.is_empty() {
+        path.push(if path.contains('?') { '&' } else { '?' });
+        path.push_str(&query.join("&"));
+    }
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn list_named_endpoints(app_handle: AppHandle) -> Result<BTreeMap<String, NamedEndpoint>, String> {
+    load_extra_endpoints(&app_handle)
+}
+
+#[tauri::command]
+pub async fn call_named_endpoint(
+    name: String,
+    params: Option<serde_json::Map<String, serde_json::Value>>,
+    body: Option<serde_json::Value>,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let endpoints = load_extra_endpoints(&app_handle)?;
+    let endpoint = endpoints.get(&name).ok_or(format!("Unknown endpoint: {}", name))?;
+    let method = reqwest::Method::from_bytes(endpoint.method.to_uppercase().as_bytes())
+        .map_err(|_| format!("Invalid method for '{}': {}", name, endpoint.method))?;
+
+    let api_config = current_api_config(&app_handle);
+    let url = format!("{}{}", api_config.api_base_url, build_path(&endpoint.path, &params.unwrap_or_default())?);
+    ipc_guard::check_proxy_target(&url, &api_config)?;
+    let client = network::builder(&app_handle)
+        .timeout(std::time::Duration::from_secs(120))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut body = body;
+    let mut req = client.request(method, &url);
+    if endpoint.auth != "none" {
+        let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+        match endpoint.auth.as_str() {
+            "bearer" => {
+                ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+                req = req.headers(bearer_headers(&credentials)?);
+            }
+            "app_key" => {
+                req = req
+                    .header("X-User-Id", &credentials.user_id)
+                    .header("X-User-App-Key", credentials.user_app_key.expose());
+            }
+            "body" => {
+                let payload = body.get_or_insert_with(|| serde_json::json!({}));
+                let obj = payload.as_object_mut().ok_or("Body auth requires a JSON object body")?;
+                obj.entry("user_id").or_insert(serde_json::Value::String(credentials.user_id.clone()));
+                obj.entry("user_app_key").or_insert(serde_json::Value::String(credentials.user_app_key.expose().to_string()));
+            }
+            other => return Err(format!("Unknown auth style for '{}': {}", name, other)),
+        }
+    }
+    if let Some(b) = &body {
+        req = req.json(b);
+    }
+
+    let started = std::time::Instant::now();
+    let resp = req.send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    metrics::record_api_latency(&app_handle, &format!("named:{}", name), started.elapsed());
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status, text));
+    }
+    Ok(serde_json::from_str(&text).unwrap_or(serde_json::json!({ "text": text })))
+}
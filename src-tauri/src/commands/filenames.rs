@@ -0,0 +1,146 @@
+// =============================================================================================================
+// ============================================= LOCAL FILE NAMES ==============================================
+// =============================================================================================================
+//
+// Remote names can hold characters Windows won't put in a file name (`"*:<>?\|`, control characters, a trailing
+// dot or space) or be a reserved device name (`CON`, `NUL`, `COM1`...). On Windows each such character is swapped
+// for its full-width lookalike (`:` -> `：`) and reserved names get a leading `‛`. A literal lookalike or `‛` in
+// the remote name is itself prefixed with `‛`, so the mapping can always be undone: a downloaded file uploads back
+// under its original remote name. Elsewhere names are kept as they are. On every platform `.` and `..` segments
+// are replaced so a remote name can't climb out of the download folder.
+
+const ESCAPE: char = '\u{201B}';
+const RESERVED_NAMES: &[&str] = &["CON", "PRN", "AUX", "NUL"];
+
+/// Full-width stand-in for a character Windows doesn't allow anywhere in a name
+fn substitute(c: char) -> Option<char> {
+    match c {
+        '"' | '*' | ':' | '<' | '>' | '?' | '\\' | '|' => char::from_u32(c as u32 + 0xFEE0),
+        c if (c as u32) < 0x20 => char::from_u32(0x2400 + c as u32),
+        _ => None,
+    }
+}
+
+/// Stand-in for a trailing dot or space
+fn substitute_trailing(c: char) -> Option<char> {
+    match c {
+        '.' => Some('\u{FF0E}'),
+        ' ' => Some('\u{3000}'),
+        _ => None,
+    }
+}
+
+/// The character `c` stands in for, if it is a stand-in
+fn original_of(c: char) -> Option<char> {
+    match c {
+        '\u{FF0E}' => Some('.'),
+        '\u{3000}' => Some(' '),
+        '\u{2400}'..='\u{241F}' => char::from_u32(c as u32 - 0x2400),
+        _ => char::from_u32((c as u32).wrapping_sub(0xFEE0)).filter(|o| substitute(*o) == Some(c)),
+    }
+}
+
+fn is_reserved(segment: &str) -> bool {
+    let base = segment.split('.').next().unwrap_or("").trim_end().to_ascii_uppercase();
+    RESERVED_NAMES.contains(&base.as_str())
+        || (base.len() == 4
+            && (base.starts_with("COM") || base.starts_with("LPT"))
+            && base.as_bytes()[3].is_ascii_digit()
+            && base.as_bytes()[3] != b'0')
+}
+
+fn encode_segment(segment: &str, windows: bool) -> String {
+    if !windows {
+        return match segment {
+            "." | ".." => segment.replace('.', "\u{FF0E}"),
+            _ => segment.to_string(),
+        };
+    }
+    let keep = segment.trim_end_matches(['.', ' ']).chars().count();
+    let mut out = String::with_capacity(segment.len());
+    if is_reserved(segment) {
+        out.push(ESCAPE);
+    }
+    for (i, c) in segment.chars().enumerate() {
+        if c == ESCAPE || original_of(c).is_some() {
+            out.push(ESCAPE);
+            out.push(c);
+        } else if let Some(sub) = substitute(c).or_else(|| substitute_trailing(c).filter(|_| i >= keep)) {
+            out.push(sub);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn decode_segment(segment: &str, windows: bool) -> String {
+    if !windows {
+        return segment.to_string();
+    }
+    let mut out = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ESCAPE => out.push(chars.next().unwrap_or(ESCAPE)),
+            c => out.push(original_of(c).unwrap_or(c)),
+        }
+    }
+    out
+}
+
+/// Relative local path for a remote name: one sanitized component per folder, `/`-separated
+pub fn local_path_for(remote_name: &str) -> String {
+    remote_name
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| encode_segment(s, cfg!(windows)))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Remote name a local file name maps back to (the inverse of `local_path_for` for a single component)
+pub fn remote_name_for(local_name: &str) -> String {
+    decode_segment(local_name, cfg!(windows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn windows_names_are_legal() {
+        assert_eq!(encode_segment("a:b*c?.txt", true), "a：b＊c？.txt");
+        assert_eq!(encode_segment("report. ", true), "report．\u{3000}");
+        assert_eq!(encode_segment("CON", true), "\u{201B}CON");
+        assert_eq!(encode_segment("nul.tar.gz", true), "\u{201B}nul.tar.gz");
+        assert_eq!(encode_segment("COM0", true), "COM0");
+        assert_eq!(encode_segment("..", true), "．．");
+        assert_eq!(encode_segment("..", false), "．．");
+        assert_eq!(encode_segment("a:b", false), "a:b");
+    }
+
+    #[test]
+    fn paths_stay_inside_the_download_folder() {
+        assert!(!local_path_for("../../etc/passwd").split('/').any(|s| s == ".." || s == "."));
+        assert_eq!(local_path_for("/photos//2024/a.jpg"), "photos/2024/a.jpg");
+    }
+
+    proptest! {
+        #[test]
+        fn windows_mapping_round_trips(segment in "[^/]{1,60}") {
+            let encoded = encode_segment(&segment, true);
+            prop_assert_eq!(decode_segment(&encoded, true), segment);
+        }
+
+        #[test]
+        fn windows_names_have_no_illegal_characters(segment in "[^/]{1,60}") {
+            let encoded = encode_segment(&segment, true);
+            prop_assert!(!encoded.contains(['"', '*', ':', '<', '>', '?', '\\', '|']));
+            prop_assert!(!encoded.chars().any(|c| (c as u32) < 0x20));
+            prop_assert!(!encoded.ends_with(['.', ' ']));
+            prop_assert!(!is_reserved(&encoded));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::settings::{current_settings, update_settings};
+use super::{get_upload_history, UploadLogEntry};
+
+// =============================================================================================================
+// ============================================== REMOTE FOLDERS ===============================================
+// =============================================================================================================
+//
+// The backend stores flat object names, so folders are just `/`-separated prefixes of the remote name.
+// Uploads take an optional `remote_dir`; without one the default prefix from settings is used.
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RemoteFolder {
+    /// "" for files at the root
+    pub prefix: String,
+    pub entries: Vec<UploadLogEntry>,
+}
+
+/// Normalize a folder: no leading/trailing or doubled slashes, no `.` / `..` segments
+pub fn normalize_remote_dir(dir: &str) -> Result<String, String> {
+    let mut segments = Vec::new();
+    for segment in dir.split(['/', '\\']).map(str::trim).filter(|s| !s.is_empty()) {
+        if segment == "." || segment == ".." {
+            return Err(format!("Invalid folder segment '{}' in '{}'", segment, dir));
+        }
+        segments.push(segment);
+    }
+    Ok(segments.join("/"))
+}
+
+/// Folder an upload named `file_name` goes to: `remote_dir`, or the default prefix ("" at the root).
+/// Names that already contain a folder only get one when `remote_dir` is given explicitly.
+pub fn resolve_remote_dir(file_name: &str, remote_dir: Option<&str>, app_handle: &AppHandle) -> Result<String, String> {
+    match remote_dir {
+        Some(dir) => normalize_remote_dir(dir),
+        None if file_name.contains('/') => Ok(String::new()),
+        None => normalize_remote_dir(&current_settings(app_handle).default_remote_prefix.unwrap_or_default()),
+    }
+}
+
+/// Remote name for an upload: the folder from `resolve_remote_dir` joined with the file name
+pub fn resolve_remote_name(file_name: &str, remote_dir: Option<&str>, app_handle: &AppHandle) -> Result<String, String> {
+    let dir = resolve_remote_dir(file_name, remote_dir, app_handle)?;
+    let name = file_name.trim_start_matches('/');
+    Ok(if dir.is_empty() { name.to_string() } else { format!("{}/{}", dir, name) })
+}
+
+/// Folder part of a remote name ("" at the root)
+fn folder_of(remote_path: &str) -> &str {
+    remote_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+#[tauri::command]
+pub async fn set_default_remote_prefix(prefix: Option<String>, app_handle: AppHandle) -> Result<Option<String>, String> {
+    let prefix = prefix.map(|p| normalize_remote_dir(&p)).transpose()?.filter(|p| !p.is_empty());
+    update_settings(&app_handle, |s| s.default_remote_prefix = prefix.clone())?;
+    Ok(prefix)
+}
+
+/// Upload history grouped by remote folder, folders sorted by name
+#[tauri::command]
+pub async fn get_upload_history_by_folder(user_id: String, app_handle: AppHandle) -> Result<Vec<RemoteFolder>, String> {
+    let mut folders: BTreeMap<String, Vec<UploadLogEntry>> = BTreeMap::new();
+    for entry in get_upload_history(user_id, app_handle).await? {
+        folders.entry(folder_of(&entry.remote_path).to_string()).or_default().push(entry);
+    }
+    Ok(folders.into_iter().map(|(prefix, entries)| RemoteFolder { prefix, entries }).collect())
+}
@@ -0,0 +1,210 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::redaction::println_redacted;
+use super::{
+    bearer_headers, current_api_config, ensure_valid_token, load_credentials, network, save_credentials, ApiConfig,
+    SavedCredentials,
+};
+
+// =============================================================================================================
+// ================================================= GATEWAYS ==================================================
+// =============================================================================================================
+//
+// Backends with regional gateways list them on the `gateways` endpoint. Uploads, downloads and streams go to
+// the gateway with the lowest measured latency, or to the one pinned for the account (`gateway_id`, saved with
+// its credentials). Probes are cached for a while so transfers don't wait on them. Without the endpoint, or
+// when no gateway answers, transfers use `api_base_url` as before.
+
+/// How long probe results pick the fastest gateway before transfers probe again
+const PROBE_TTL: Duration = Duration::from_secs(10 * 60);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Gateway {
+    pub id: String,
+    pub name: String,
+    pub region: Option<String>,
+    /// Base URL transfers use instead of `api_base_url`
+    pub url: String,
+    /// Round trip of the health probe; `None` when it didn't answer
+    pub latency_ms: Option<u64>,
+    /// Pinned for the signed-in account
+    pub pinned: bool,
+    /// Where transfers go right now
+    pub selected: bool,
+}
+
+pub struct ProbedGateways {
+    gateways: Vec<Gateway>,
+    probed_at: Instant,
+}
+
+pub type GatewayState = Mutex<Option<ProbedGateways>>;
+pub fn new_gateway_state() -> GatewayState { Mutex::new(None) }
+
+/// Gateways in a list response: a bare array or `{ "gateways": [...] }`. Entries without an http(s) URL are dropped.
+fn parse_gateways(json: &serde_json::Value) -> Vec<Gateway> {
+    let items = json.as_array().or_else(|| json.get("gateways").and_then(|g| g.as_array()));
+    let text = |item: &serde_json::Value, keys: &[&str]| keys.iter().find_map(|k| item.get(*k).and_then(|v| v.as_str()).map(str::to_string));
+    items
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let id = text(item, &["id", "gateway_id"])?;
+            let url = text(item, &["url", "base_url"]).filter(|u| u.starts_with("https://") || u.starts_with("http://"))?;
+            Some(Gateway {
+                name: text(item, &["name", "display_name"]).unwrap_or_else(|| id.clone()),
+                region: text(item, &["region"]),
+                url: url.trim_end_matches('/').to_string(),
+                latency_ms: None,
+                pinned: false,
+                selected: false,
+                id,
+            })
+        })
+        .collect()
+}
+
+/// The pinned gateway when it's still listed, otherwise the fastest one that answered
+fn choose<'a>(gateways: &'a [Gateway], pinned: Option<&str>) -> Option<&'a Gateway> {
+    pinned
+        .and_then(|id| gateways.iter().find(|g| g.id == id))
+        .or_else(|| gateways.iter().filter(|g| g.latency_ms.is_some()).min_by_key(|g| g.latency_ms))
+}
+
+async fn probe(client: &reqwest::Client, gateway: &mut Gateway) {
+    let started = Instant::now();
+    let answered = client.get(format!("{}/health", gateway.url)).timeout(PROBE_TIMEOUT).send().await;
+    gateway.latency_ms = match answered {
+        Ok(response) if response.status().is_success() => Some(started.elapsed().as_millis() as u64),
+        Ok(response) => {
+            println_redacted!("[GATEWAY] {} answered {}", gateway.url, response.status());
+            None
+        }
+        Err(e) => {
+            println_redacted!("[GATEWAY] {} unreachable: {}", gateway.url, e);
+            None
+        }
+    };
+}
+
+/// Fetch the gateway list, probe every gateway and cache the result
+async fn probe_gateways(api_config: &ApiConfig, credentials: &SavedCredentials, app_handle: &AppHandle) -> Result<Vec<Gateway>, String> {
+    let url = api_config.optional_url(&api_config.gateways, "Gateways")?;
+    let client = network::client(app_handle);
+    let resp = client.get(&url).headers(bearer_headers(credentials)?).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let mut gateways = parse_gateways(&json);
+    futures_util::future::join_all(gateways.iter_mut().map(|g| probe(&client, g))).await;
+    println!("🛰️ Probed {} gateway(s)", gateways.len());
+    *app_handle.state::<GatewayState>().lock().unwrap() = Some(ProbedGateways { gateways: gateways.clone(), probed_at: Instant::now() });
+    Ok(gateways)
+}
+
+fn cached_gateways(app_handle: &AppHandle) -> Option<Vec<Gateway>> {
+    let state = app_handle.state::<GatewayState>();
+    let cache = state.lock().unwrap();
+    cache.as_ref().filter(|c| c.probed_at.elapsed() < PROBE_TTL).map(|c| c.gateways.clone())
+}
+
+/// `api_config` with its base moved to the gateway transfers should use
+pub async fn transfer_config(api_config: &ApiConfig, credentials: &SavedCredentials, app_handle: &AppHandle) -> ApiConfig {
+    if api_config.optional_url(&api_config.gateways, "Gateways").is_err() {
+        return api_config.clone();
+    }
+    let gateways = match cached_gateways(app_handle) {
+        Some(gateways) => gateways,
+        None => match probe_gateways(api_config, credentials, app_handle).await {
+            Ok(gateways) => gateways,
+            Err(e) => {
+                println_redacted!("[GATEWAY] {}, using {}", e, api_config.api_base_url);
+                return api_config.clone();
+            }
+        },
+    };
+    match choose(&gateways, credentials.gateway_id.as_deref()) {
+        Some(gateway) => ApiConfig { api_base_url: gateway.url.clone(), ..api_config.clone() },
+        None => api_config.clone(),
+    }
+}
+
+fn mark(mut gateways: Vec<Gateway>, pinned: Option<&str>) -> Vec<Gateway> {
+    let selected = choose(&gateways, pinned).map(|g| g.id.clone());
+    for gateway in &mut gateways {
+        gateway.pinned = pinned == Some(gateway.id.as_str());
+        gateway.selected = selected.as_ref() == Some(&gateway.id);
+    }
+    gateways
+}
+
+/// Regional gateways with freshly measured latency
+#[tauri::command]
+pub async fn list_gateways(app_handle: AppHandle) -> Result<Vec<Gateway>, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    ensure_valid_token(&network::client(&app_handle), &api_config, &mut credentials, &app_handle).await?;
+    let gateways = probe_gateways(&api_config, &credentials, &app_handle).await?;
+    Ok(mark(gateways, credentials.gateway_id.as_deref()))
+}
+
+/// Send this account's transfers to `gateway_id`; `None` goes back to the fastest gateway
+#[tauri::command]
+pub async fn pin_gateway(gateway_id: Option<String>, app_handle: AppHandle) -> Result<Vec<Gateway>, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let gateway_id = gateway_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty());
+    let gateways = match cached_gateways(&app_handle) {
+        Some(gateways) => gateways,
+        None => {
+            let api_config = current_api_config(&app_handle);
+            ensure_valid_token(&network::client(&app_handle), &api_config, &mut credentials, &app_handle).await?;
+            probe_gateways(&api_config, &credentials, &app_handle).await?
+        }
+    };
+    if let Some(id) = &gateway_id {
+        if !gateways.iter().any(|g| &g.id == id) {
+            return Err(format!("Unknown gateway {}", id));
+        }
+    }
+
+    credentials.gateway_id = gateway_id;
+    save_credentials(credentials.clone(), app_handle.clone()).await?;
+    match &credentials.gateway_id {
+        Some(id) => println!("🛰️ Pinned {} to gateway {}", credentials.user_id, id),
+        None => println!("🛰️ {} uses the fastest gateway", credentials.user_id),
+    }
+    let _ = app_handle.emit(
+        "gateway-changed",
+        serde_json::json!({ "user_id": credentials.user_id, "gateway_id": credentials.gateway_id }),
+    );
+    Ok(mark(gateways, credentials.gateway_id.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinned_gateway_wins_over_the_fastest() {
+        let json = serde_json::json!({ "gateways": [
+            { "id": "eu", "url": "https://eu.example.com/", "region": "eu-west" },
+            { "id": "us", "base_url": "https://us.example.com" },
+            { "id": "bad", "url": "ftp://example.com" },
+        ]});
+        let mut gateways = parse_gateways(&json);
+        assert_eq!(gateways.iter().map(|g| g.url.as_str()).collect::<Vec<_>>(), ["https://eu.example.com", "https://us.example.com"]);
+        assert_eq!(choose(&gateways, None), None);
+
+        gateways[0].latency_ms = Some(80);
+        gateways[1].latency_ms = Some(20);
+        assert_eq!(choose(&gateways, None).map(|g| g.id.as_str()), Some("us"));
+        assert_eq!(choose(&gateways, Some("eu")).map(|g| g.id.as_str()), Some("eu"));
+        assert_eq!(choose(&gateways, Some("gone")).map(|g| g.id.as_str()), Some("us"));
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use chrono::Utc;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+// =============================================================================================================
+// ============================================== TRANSFER GROUPS ==============================================
+// =============================================================================================================
+//
+// Uploads tagged with the same `group_id` (e.g. one backup run) are tracked together. Queued uploads join
+// their group when enqueued; direct uploads join when they start. `group_completed` is emitted once, when
+// every member known at that point has finished.
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GroupMember {
+    pub upload_id: String,
+    pub file_name: String,
+    /// "queued" | "uploading" | "success" | "failed"
+    pub status: String,
+    pub total_bytes: u64,
+    pub uploaded_bytes: u64,
+    pub message: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GroupStatus {
+    pub group_id: String,
+    pub members: Vec<GroupMember>,
+    pub completed_files: usize,
+    pub failed_files: usize,
+    pub total_bytes: u64,
+    pub uploaded_bytes: u64,
+    pub bytes_per_sec: Option<f64>,
+    pub eta_secs: Option<u64>,
+    pub done: bool,
+}
+
+#[derive(Default)]
+struct TransferGroup {
+    members: Vec<GroupMember>,
+    /// First time a member started uploading, for the aggregate rate
+    started: Option<Instant>,
+    completed_emitted: bool,
+}
+
+impl TransferGroup {
+    fn member(&mut self, upload_id: &str) -> Option<&mut GroupMember> {
+        self.members.iter_mut().find(|m| m.upload_id == upload_id)
+    }
+
+    fn status(&self, group_id: &str) -> GroupStatus {
+        let finished = |m: &&GroupMember| m.status == "success" || m.status == "failed";
+        let total_bytes: u64 = self.members.iter().map(|m| m.total_bytes).sum();
+        let uploaded_bytes: u64 = self.members.iter().map(|m| m.uploaded_bytes).sum();
+        let done = !self.members.is_empty() && self.members.iter().all(|m| finished(&m));
+        let bytes_per_sec = self
+            .started
+            .map(|s| s.elapsed().as_secs_f64())
+            .filter(|secs| *secs > 0.0 && uploaded_bytes > 0)
+            .map(|secs| uploaded_bytes as f64 / secs);
+        let eta_secs = match bytes_per_sec {
+            _ if done => Some(0),
+            Some(rate) => Some((total_bytes.saturating_sub(uploaded_bytes) as f64 / rate).ceil() as u64),
+            None => None,
+        };
+        GroupStatus {
+            group_id: group_id.to_string(),
+            members: self.members.clone(),
+            completed_files: self.members.iter().filter(|m| m.status == "success").count(),
+            failed_files: self.members.iter().filter(|m| m.status == "failed").count(),
+            total_bytes,
+            uploaded_bytes,
+            bytes_per_sec,
+            eta_secs,
+            done,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TransferGroups {
+    groups: HashMap<String, TransferGroup>,
+}
+
+pub type TransferGroupsState = Mutex<TransferGroups>;
+pub fn new_transfer_groups_state() -> TransferGroupsState { Mutex::new(TransferGroups::default()) }
+
+fn with_group(app_handle: &AppHandle, group_id: &str, f: impl FnOnce(&mut TransferGroup)) {
+    let state = app_handle.state::<TransferGroupsState>();
+    let mut groups = state.lock().unwrap();
+    if let Some(group) = groups.groups.get_mut(group_id) {
+        f(group);
+    }
+}
+
+/// Register a member as queued. Re-adding a known member is a no-op.
+pub fn add_member(app_handle: &AppHandle, group_id: &str, upload_id: &str, file_name: &str) {
+    let state = app_handle.state::<TransferGroupsState>();
+    let mut groups = state.lock().unwrap();
+    let group = groups.groups.entry(group_id.to_string()).or_default();
+    if group.member(upload_id).is_none() {
+        group.members.push(GroupMember {
+            upload_id: upload_id.to_string(),
+            file_name: file_name.to_string(),
+            status: "queued".to_string(),
+            total_bytes: 0,
+            uploaded_bytes: 0,
+            message: None,
+            finished_at: None,
+        });
+        // a member added after completion reopens the group
+        group.completed_emitted = false;
+    }
+}
+
+pub fn set_progress(app_handle: &AppHandle, group_id: &str, upload_id: &str, uploaded: u64) {
+    with_group(app_handle, group_id, |group| {
+        if let Some(m) = group.member(upload_id) {
+            m.uploaded_bytes = uploaded;
+        }
+    });
+}
+
+/// Mark a member finished and emit `group_completed` if it was the last one. Finishing twice is a no-op.
+pub fn finish_member(app_handle: &AppHandle, group_id: &str, upload_id: &str, result: &Result<String, String>) {
+    let completed = {
+        let state = app_handle.state::<TransferGroupsState>();
+        let mut groups = state.lock().unwrap();
+        let Some(group) = groups.groups.get_mut(group_id) else {
+            return;
+        };
+        let Some(member) = group.member(upload_id) else {
+            return;
+        };
+        if member.finished_at.is_some() {
+            return;
+        }
+        member.status = if result.is_ok() { "success" } else { "failed" }.to_string();
+        member.message = Some(match result {
+            Ok(msg) | Err(msg) => msg.clone(),
+        });
+        member.finished_at = Some(Utc::now().to_rfc3339());
+        if result.is_ok() {
+            member.uploaded_bytes = member.total_bytes;
+        }
+        let status = group.status(group_id);
+        if status.done && !group.completed_emitted {
+            group.completed_emitted = true;
+            Some(status)
+        } else {
+            None
+        }
+    };
+    if let Some(status) = completed {
+        println!("📦 Transfer group '{}' finished ({} ok, {} failed)", status.group_id, status.completed_files, status.failed_files);
+        app_handle.emit("group_completed", &status).ok();
+    }
+}
+
+/// Tracks one upload inside a group; an upload that bails out early is recorded as failed on drop
+pub struct GroupMemberGuard {
+    app_handle: AppHandle,
+    group_id: String,
+    upload_id: String,
+    finished: bool,
+}
+
+impl GroupMemberGuard {
+    pub fn start(app_handle: &AppHandle, group_id: Option<&str>, upload_id: &str, file_name: &str) -> Option<Self> {
+        let group_id = group_id.filter(|g| !g.trim().is_empty())?;
+        add_member(app_handle, group_id, upload_id, file_name);
+        with_group(app_handle, group_id, |group| {
+            group.started.get_or_insert_with(Instant::now);
+            if let Some(m) = group.member(upload_id) {
+                m.status = "uploading".to_string();
+                m.uploaded_bytes = 0;
+            }
+        });
+        Some(Self {
+            app_handle: app_handle.clone(),
+            group_id: group_id.to_string(),
+            upload_id: upload_id.to_string(),
+            finished: false,
+        })
+    }
+
+    pub fn group_id(&self) -> &str {
+        &self.group_id
+    }
+
+    /// Final remote name and size, once known
+    pub fn describe(&self, file_name: &str, total_bytes: u64) {
+        with_group(&self.app_handle, &self.group_id, |group| {
+            if let Some(m) = group.member(&self.upload_id) {
+                m.file_name = file_name.to_string();
+                m.total_bytes = total_bytes;
+            }
+        });
+    }
+
+    pub fn finish(mut self, result: &Result<String, String>) {
+        self.finished = true;
+        finish_member(&self.app_handle, &self.group_id, &self.upload_id, result);
+    }
+}
+
+impl Drop for GroupMemberGuard {
+    fn drop(&mut self) {
+        if !self.finished {
+            finish_member(&self.app_handle, &self.group_id, &self.upload_id, &Err("Upload did not complete".to_string()));
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_group_status(group_id: String, app_handle: AppHandle) -> Result<GroupStatus, String> {
+    let state = app_handle.state::<TransferGroupsState>();
+    let groups = state.lock().unwrap();
+    groups
+        .groups
+        .get(&group_id)
+        .map(|g| g.status(&group_id))
+        .ok_or_else(|| format!("Unknown transfer group: {}", group_id))
+}
@@ -0,0 +1,133 @@
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+// =============================================================================================================
+// ============================================== HASHING PIPELINE =============================================
+// =============================================================================================================
+//
+// Upload chunks are hashed on a blocking worker fed by a channel, so the network stream only bumps counters and
+// clones refcounted buffers. The worker reports how long hashing actually took, which is kept per session next to
+// the network rate so it's visible which side is the bottleneck.
+
+const MB: f64 = 1024.0 * 1024.0;
+
+pub struct HashReport {
+    pub hash: String,
+    pub bytes: u64,
+    /// Time spent inside `Hasher::update`, not waiting for chunks
+    pub busy: Duration,
+}
+
+/// Start a hashing worker; it finishes when every sender is dropped
+pub fn spawn_hasher<T: AsRef<[u8]> + Send + 'static>() -> (mpsc::Sender<T>, tokio::task::JoinHandle<HashReport>) {
+    let (tx, rx) = mpsc::channel::<T>();
+    let worker = tokio::task::spawn_blocking(move || {
+        let mut hasher = blake3::Hasher::new();
+        let mut bytes = 0u64;
+        let mut busy = Duration::ZERO;
+        for chunk in rx {
+            let started = Instant::now();
+            hasher.update(chunk.as_ref());
+            busy += started.elapsed();
+            bytes += chunk.as_ref().len() as u64;
+        }
+        HashReport { hash: hasher.finalize().to_hex().to_string(), bytes, busy }
+    });
+    (tx, worker)
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct HashingRun {
+    pub bytes: u64,
+    pub hash_mb_per_sec: Option<f64>,
+    pub network_mb_per_sec: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct HashingStats {
+    pub runs: u64,
+    pub total_bytes: u64,
+    pub total_hash_secs: f64,
+    pub last: Option<HashingRun>,
+}
+
+pub type HashingStatsState = Mutex<HashingStats>;
+pub fn new_hashing_stats_state() -> HashingStatsState { Mutex::new(HashingStats::default()) }
+
+fn rate(bytes: u64, elapsed: Duration) -> Option<f64> {
+    let secs = elapsed.as_secs_f64();
+    (secs > 0.0 && bytes > 0).then(|| bytes as f64 / MB / secs)
+}
+
+/// Record one finished upload: hashing time from the worker, wall time of the network transfer
+pub fn record(app_handle: &AppHandle, report: &HashReport, network: Duration) {
+    let state = app_handle.state::<HashingStatsState>();
+    let mut stats = state.lock().unwrap();
+    stats.runs += 1;
+    stats.total_bytes += report.bytes;
+    stats.total_hash_secs += report.busy.as_secs_f64();
+    stats.last = Some(HashingRun {
+        bytes: report.bytes,
+        hash_mb_per_sec: rate(report.bytes, report.busy),
+        network_mb_per_sec: rate(report.bytes, network),
+    });
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HashingBenchmark {
+    pub bytes: u64,
+    pub chunk_bytes: usize,
+    /// Old approach: hashing inline under a mutex, per chunk
+    pub inline_mb_per_sec: Option<f64>,
+    /// Producer side of the pipeline: what the network stream is held up by
+    pub pipelined_send_mb_per_sec: Option<f64>,
+    /// Worker throughput
+    pub pipelined_hash_mb_per_sec: Option<f64>,
+}
+
+#[tauri::command]
+pub async fn get_hashing_stats(app_handle: AppHandle) -> Result<HashingStats, String> {
+    Ok(app_handle.state::<HashingStatsState>().lock().unwrap().clone())
+}
+
+/// Hash `size_mb` (default 256) of in-memory data both ways and report the throughput
+#[tauri::command]
+pub async fn benchmark_hashing(size_mb: Option<u32>) -> Result<HashingBenchmark, String> {
+    const CHUNK: usize = 1024 * 1024;
+    let chunks = size_mb.unwrap_or(256).clamp(1, 4096) as usize;
+    // shared like the `Bytes` chunks of an upload stream, so sending only bumps a refcount
+    let chunk: Arc<[u8]> = Arc::from(vec![0xA5u8; CHUNK]);
+    let bytes = (chunks * CHUNK) as u64;
+
+    let inline_chunk = chunk.clone();
+    let inline = tokio::task::spawn_blocking(move || {
+        let hasher = Mutex::new(blake3::Hasher::new());
+        let started = Instant::now();
+        for _ in 0..chunks {
+            hasher.lock().unwrap().update(&inline_chunk);
+        }
+        let _ = hasher.into_inner().unwrap().finalize();
+        started.elapsed()
+    })
+    .await
+    .map_err(|e| format!("Benchmark failed: {}", e))?;
+
+    let (tx, worker) = spawn_hasher();
+    let started = Instant::now();
+    for _ in 0..chunks {
+        tx.send(chunk.clone()).map_err(|_| "Hash worker stopped".to_string())?;
+    }
+    let send_time = started.elapsed();
+    drop(tx);
+    let report = worker.await.map_err(|e| format!("Benchmark failed: {}", e))?;
+
+    Ok(HashingBenchmark {
+        bytes,
+        chunk_bytes: CHUNK,
+        inline_mb_per_sec: rate(bytes, inline),
+        pipelined_send_mb_per_sec: rate(bytes, send_time),
+        pipelined_hash_mb_per_sec: rate(report.bytes, report.busy),
+    })
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::compaction::HistoryArchive;
+use super::contacts::Contact;
+use super::credential_file::{self, CredentialFile};
+use super::metrics::TransferTotals;
+use super::settings::AppSettings;
+use super::starred::StarredFile;
+use super::transfers::QueuedUpload;
+use super::vault::VaultFile;
+use super::{app_data_root, PublicLinkEntry, UploadLogEntry};
+
+// =============================================================================================================
+// ============================================= LOCAL DATA HEALTH =============================================
+// =============================================================================================================
+//
+// Runs once at startup, before anything else reads local files. Unparseable JSON documents are moved to
+// `corrupt/` (timestamped) so the app starts from defaults; JSONL logs keep their good lines and only the
+// broken ones are quarantined. Findings are kept for the `local_data_health` command.
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LocalDataIssue {
+    pub path: String,
+    /// "credentials" | "history" | "links" | "vault" | "settings" | "queue" | "metrics" | "hooks"
+    pub kind: String,
+    pub problem: String,
+    /// "quarantined" | "repaired"
+    pub action: String,
+    pub quarantined_to: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LocalDataHealth {
+    pub checked_at: String,
+    pub files_checked: usize,
+    pub issues: Vec<LocalDataIssue>,
+}
+
+pub type LocalDataHealthState = Mutex<LocalDataHealth>;
+
+fn quarantine_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join("corrupt"))
+}
+
+/// Target path in `corrupt/` that keeps the original location readable, e.g. `20250101T120000-<user>_list-upload-<user>.json`
+fn quarantine_target(path: &Path, root: &Path, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let relative = path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace(['/', '\\'], "_");
+    let dir = quarantine_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create quarantine dir: {}", e))?;
+    Ok(dir.join(format!("{}-{}", Utc::now().format("%Y%m%dT%H%M%S"), relative)))
+}
+
+struct Checker<'a> {
+    app_handle: &'a AppHandle,
+    root: PathBuf,
+    report: LocalDataHealth,
+}
+
+impl Checker<'_> {
+    /// Whole-file JSON document: quarantine it if it doesn't deserialize as `T`
+    fn check_json<T: DeserializeOwned>(&mut self, path: &Path, kind: &str) {
+        if !path.exists() {
+            return;
+        }
+        self.report.files_checked += 1;
+        let problem = match std::fs::read_to_string(path) {
+            Err(e) => format!("Unreadable: {}", e),
+            Ok(content) => match serde_json::from_str::<T>(&content) {
+                Ok(_) => return,
+                Err(e) => format!("Invalid JSON: {}", e),
+            },
+        };
+        let target = quarantine_target(path, &self.root, self.app_handle)
+            .and_then(|t| std::fs::rename(path, &t).map(|_| t).map_err(|e| format!("Failed to quarantine: {}", e)));
+        let (action, quarantined_to) = match target {
+            Ok(t) => ("quarantined", Some(t.to_string_lossy().to_string())),
+            Err(e) => {
+                println!("[HEALTH] {}", e);
+                ("left in place", None)
+            }
+        };
+        self.issue(path, kind, problem, action, quarantined_to);
+    }
+
+    /// JSONL log: keep lines that parse as `T`, move the rest to a quarantine file
+    fn check_jsonl<T: DeserializeOwned>(&mut self, path: &Path, kind: &str) {
+        if !path.exists() {
+            return;
+        }
+        self.report.files_checked += 1;
+        let content = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.issue(path, kind, format!("Unreadable: {}", e), "left in place", None);
+                return;
+            }
+        };
+        let (good, bad) = split_jsonl::<T>(&content);
+        if bad.is_empty() {
+            return;
+        }
+
+        // lines are copied as they were read, so nothing outside the dropped ones changes
+        let repaired = quarantine_target(path, &self.root, self.app_handle).and_then(|target| {
+            std::fs::write(&target, join_lines(&bad)).map_err(|e| format!("Failed to write quarantine file: {}", e))?;
+            std::fs::write(path, join_lines(&good)).map_err(|e| format!("Failed to rewrite log: {}", e))?;
+            Ok(target)
+        });
+        let problem = format!("{} corrupt line(s) of {}", bad.len(), bad.len() + good.len());
+        match repaired {
+            Ok(t) => self.issue(path, kind, problem, "repaired", Some(t.to_string_lossy().to_string())),
+            Err(e) => {
+                println!("[HEALTH] {}", e);
+                self.issue(path, kind, problem, "left in place", None)
+            }
+        }
+    }
+
+    fn issue(&mut self, path: &Path, kind: &str, problem: String, action: &str, quarantined_to: Option<String>) {
+        println!("⚠️ Local data issue in {:?}: {} ({})", path, problem, action);
+        self.report.issues.push(LocalDataIssue {
+            path: path.to_string_lossy().to_string(),
+            kind: kind.to_string(),
+            problem,
+            action: action.to_string(),
+            quarantined_to,
+        });
+    }
+}
+
+/// Non-blank lines of a JSONL file, split into those that parse as `T` and those that don't. Lines stay raw
+/// bytes: one with invalid UTF-8 is simply a bad line.
+fn split_jsonl<T: DeserializeOwned>(content: &[u8]) -> (Vec<&[u8]>, Vec<&[u8]>) {
+    content
+        .split(|&b| b == b'\n')
+        .filter(|l| !l.iter().all(u8::is_ascii_whitespace))
+        .partition(|l| serde_json::from_slice::<T>(l).is_ok())
+}
+
+fn join_lines(lines: &[&[u8]]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for line in lines {
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+    out
+}
+
+fn run_checks(app_handle: &AppHandle) -> Result<LocalDataHealth, String> {
+    let root = app_data_root(app_handle)?;
+    let mut checker = Checker {
+        app_handle,
+        root: root.clone(),
+        report: LocalDataHealth { checked_at: Utc::now().to_rfc3339(), ..Default::default() },
+    };
+    if !root.exists() {
+        return Ok(checker.report);
+    }
+
+    checker.check_json::<AppSettings>(&root.join("settings.json"), "settings");
+    checker.check_json::<Vec<QueuedUpload>>(&root.join("transfer-queue.json"), "queue");
+    checker.check_json::<BTreeMap<String, TransferTotals>>(&root.join("transfer-metrics.json"), "metrics");
+    checker.check_jsonl::<super::hooks::HookRun>(&root.join("hook-runs.jsonl"), "hooks");
+
+    let user_dirs = std::fs::read_dir(&root)
+        .map_err(|e| format!("Failed to read app data dir: {}", e))?
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter(|e| e.file_name() != "corrupt");
+    for entry in user_dirs {
+        let user_id = entry.file_name().to_string_lossy().to_string();
+        let dir = entry.path();
+        checker.check_json::<CredentialFile>(&credential_file::credentials_path(&dir, &user_id), "credentials");
+        checker.check_json::<Vec<PublicLinkEntry>>(&dir.join(format!("link-{}.json", user_id)), "links");
+        checker.check_json::<VaultFile>(&dir.join(format!("keyvault-{}.json", user_id)), "vault");
+        checker.check_jsonl::<UploadLogEntry>(&dir.join(format!("list-upload-{}.json", user_id)), "history");
+        checker.check_json::<Vec<HistoryArchive>>(&dir.join(format!("history-index-{}.json", user_id)), "history");
+        checker.check_json::<Vec<StarredFile>>(&dir.join(format!("starred-{}.json", user_id)), "starred");
+        checker.check_json::<Vec<Contact>>(&dir.join(format!("contacts-{}.json", user_id)), "contacts");
+    }
+    Ok(checker.report)
+}
+
+/// Validate local data files. Called in setup before any state is loaded from disk.
+pub fn check_local_data(app_handle: &AppHandle) -> LocalDataHealthState {
+    let report = run_checks(app_handle).unwrap_or_else(|e| {
+        println!("[HEALTH] {}", e);
+        LocalDataHealth { checked_at: Utc::now().to_rfc3339(), ..Default::default() }
+    });
+    if report.issues.is_empty() {
+        println!("✅ Local data check passed ({} files)", report.files_checked);
+    }
+    Mutex::new(report)
+}
+
+/// Findings from the startup check, or a fresh check with `recheck`
+#[tauri::command]
+pub async fn local_data_health(recheck: Option<bool>, app_handle: AppHandle) -> Result<LocalDataHealth, String> {
+    if recheck.unwrap_or(false) {
+        let report = run_checks(&app_handle)?;
+        *app_handle.state::<LocalDataHealthState>().lock().unwrap() = report;
+    }
+    Ok(app_handle.state::<LocalDataHealthState>().lock().unwrap().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_jsonl_keeps_valid_lines_byte_for_byte() {
+        let content = b"{\"a\":\"caf\xc3\xa9\"}\r\n\xff\xfe not json\n\n{broken\n{\"a\":\"x\"}";
+        let (good, bad) = split_jsonl::<serde_json::Value>(content);
+        assert_eq!(good, vec![&b"{\"a\":\"caf\xc3\xa9\"}\r"[..], &b"{\"a\":\"x\"}"[..]]);
+        assert_eq!(bad, vec![&b"\xff\xfe not json"[..], &b"{broken"[..]]);
+        assert_eq!(join_lines(&bad), b"\xff\xfe not json\n{broken\n".to_vec());
+    }
+}
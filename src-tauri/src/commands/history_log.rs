@@ -0,0 +1,214 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+
+use super::compaction::{self, HistoryPrune};
+use super::{get_user_data_dir, UploadLogEntry};
+
+// =============================================================================================================
+// ============================================= HISTORY LOG WRITER ============================================
+// =============================================================================================================
+//
+// All upload log appends go through one writer task, so concurrent uploads can't interleave partial lines.
+// Lines are batched for a short window, written per user file, and fsynced on `flush` (also run on exit).
+// In-place edits (`update_entries`) and retention pruning (`prune`) run on the same task, after everything
+// queued before them.
+
+/// How long the writer waits for more lines before writing a batch
+const BATCH_WINDOW: Duration = Duration::from_millis(50);
+const MAX_BATCH: usize = 256;
+
+type EntryEdit = Box<dyn FnMut(&mut UploadLogEntry) -> bool + Send>;
+
+enum HistoryCommand {
+    Append { user_id: String, line: String },
+    Flush(oneshot::Sender<()>),
+    Update { user_id: String, edit: EntryEdit, done: oneshot::Sender<Result<usize, String>> },
+    Prune {
+        user_id: String,
+        cutoff: Option<DateTime<Utc>>,
+        keep_last: Option<usize>,
+        done: oneshot::Sender<Result<HistoryPrune, String>>,
+    },
+}
+
+pub struct HistoryWriter {
+    tx: mpsc::UnboundedSender<HistoryCommand>,
+}
+
+/// Start the writer task. Called once in setup.
+pub fn start_history_writer(app_handle: &AppHandle) -> HistoryWriter {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(run_writer(app_handle.clone(), rx));
+    HistoryWriter { tx }
+}
+
+async fn write_batch(lines: BTreeMap<String, Vec<String>>, dirty: &mut HashSet<PathBuf>, app_handle: &AppHandle) {
+    for (user_id, lines) in lines {
+        let result: Result<(), String> = async {
+            let user_dir = get_user_data_dir(&user_id, app_handle)?;
+            tokio::fs::create_dir_all(&user_dir).await.map_err(|e| format!("Failed to create user dir: {}", e))?;
+            let log_path = user_dir.join(format!("list-upload-{}.json", user_id));
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&log_path)
+                .await
+                .map_err(|e| format!("Failed to open log file: {}", e))?;
+            let mut buf = String::with_capacity(lines.iter().map(|l| l.len() + 1).sum());
+            for line in &lines {
+                buf.push_str(line);
+                buf.push('\n');
+            }
+            file.write_all(buf.as_bytes()).await.map_err(|e| format!("Failed to write log: {}", e))?;
+            file.flush().await.map_err(|e| format!("Failed to write log: {}", e))?;
+            dirty.insert(log_path);
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            println!("[LOG] {}", e);
+            continue;
+        }
+        if let Err(e) = compaction::rotate_history_if_needed(&user_id, app_handle) {
+            println!("[LOG] {}", e);
+        }
+    }
+}
+
+/// Apply `edit` to every parseable entry of the current log; unparseable lines are kept as they are
+async fn update_log(user_id: &str, mut edit: EntryEdit, app_handle: &AppHandle) -> Result<usize, String> {
+    let log_path = get_user_data_dir(user_id, app_handle)?.join(format!("list-upload-{}.json", user_id));
+    let content = match tokio::fs::read_to_string(&log_path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read log file: {}", e)),
+    };
+    let mut changed = 0;
+    let mut buf = String::with_capacity(content.len());
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<UploadLogEntry>(line) {
+            Ok(mut entry) => {
+                if edit(&mut entry) {
+                    changed += 1;
+                    buf.push_str(&serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize log entry: {}", e))?);
+                } else {
+                    buf.push_str(line);
+                }
+            }
+            Err(_) => buf.push_str(line),
+        }
+        buf.push('\n');
+    }
+    if changed > 0 {
+        let tmp = log_path.with_extension("json.tmp");
+        let mut file = tokio::fs::File::create(&tmp).await.map_err(|e| format!("Failed to write log file: {}", e))?;
+        file.write_all(buf.as_bytes()).await.map_err(|e| format!("Failed to write log file: {}", e))?;
+        file.sync_data().await.map_err(|e| format!("Failed to write log file: {}", e))?;
+        tokio::fs::rename(&tmp, &log_path).await.map_err(|e| format!("Failed to replace log file: {}", e))?;
+    }
+    Ok(changed)
+}
+
+async fn sync_dirty(dirty: &mut HashSet<PathBuf>) {
+    for path in dirty.drain() {
+        // the file may have been rotated away since it was written
+        if let Ok(file) = tokio::fs::File::open(&path).await {
+            let _ = file.sync_data().await;
+        }
+    }
+}
+
+async fn run_writer(app_handle: AppHandle, mut rx: mpsc::UnboundedReceiver<HistoryCommand>) {
+    let mut dirty = HashSet::new();
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + BATCH_WINDOW;
+        // flushes and updates end the batch so they see every line queued before them
+        while batch.len() < MAX_BATCH && matches!(batch.last(), Some(HistoryCommand::Append { .. })) {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(cmd)) => batch.push(cmd),
+                _ => break,
+            }
+        }
+
+        let mut lines: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut waiters = Vec::new();
+        let mut updates = Vec::new();
+        let mut prunes = Vec::new();
+        for cmd in batch {
+            match cmd {
+                HistoryCommand::Append { user_id, line } => lines.entry(user_id).or_default().push(line),
+                HistoryCommand::Flush(done) => waiters.push(done),
+                HistoryCommand::Update { user_id, edit, done } => updates.push((user_id, edit, done)),
+                HistoryCommand::Prune { user_id, cutoff, keep_last, done } => prunes.push((user_id, cutoff, keep_last, done)),
+            }
+        }
+        write_batch(lines, &mut dirty, &app_handle).await;
+        if !waiters.is_empty() {
+            sync_dirty(&mut dirty).await;
+            for done in waiters {
+                let _ = done.send(());
+            }
+        }
+        for (user_id, edit, done) in updates {
+            let _ = done.send(update_log(&user_id, edit, &app_handle).await);
+        }
+        for (user_id, cutoff, keep_last, done) in prunes {
+            let _ = done.send(compaction::prune_history(&user_id, cutoff, keep_last, &app_handle));
+        }
+    }
+    sync_dirty(&mut dirty).await;
+}
+
+/// Queue one serialized entry for `user_id`'s upload log
+pub fn append_line(user_id: &str, line: String, app_handle: &AppHandle) -> Result<(), String> {
+    app_handle
+        .state::<HistoryWriter>()
+        .tx
+        .send(HistoryCommand::Append { user_id: user_id.to_string(), line })
+        .map_err(|_| "History writer is not running".to_string())
+}
+
+/// Wait until everything queued so far is written and synced to disk
+pub async fn flush(app_handle: &AppHandle) {
+    let (done, wait) = oneshot::channel();
+    if app_handle.state::<HistoryWriter>().tx.send(HistoryCommand::Flush(done)).is_ok() {
+        let _ = wait.await;
+    }
+}
+
+/// Edit entries of `user_id`'s current upload log in place; `edit` returns whether it changed the entry.
+/// Archived history is left untouched.
+pub async fn update_entries<F>(user_id: &str, edit: F, app_handle: &AppHandle) -> Result<usize, String>
+where
+    F: FnMut(&mut UploadLogEntry) -> bool + Send + 'static,
+{
+    let (done, wait) = oneshot::channel();
+    app_handle
+        .state::<HistoryWriter>()
+        .tx
+        .send(HistoryCommand::Update { user_id: user_id.to_string(), edit: Box::new(edit), done })
+        .map_err(|_| "History writer is not running".to_string())?;
+    wait.await.map_err(|_| "History writer stopped".to_string())?
+}
+
+/// Drop `user_id`'s history entries older than `cutoff` and all but the newest `keep_last`, archives included
+pub async fn prune(
+    user_id: &str,
+    cutoff: Option<DateTime<Utc>>,
+    keep_last: Option<usize>,
+    app_handle: &AppHandle,
+) -> Result<HistoryPrune, String> {
+    let (done, wait) = oneshot::channel();
+    app_handle
+        .state::<HistoryWriter>()
+        .tx
+        .send(HistoryCommand::Prune { user_id: user_id.to_string(), cutoff, keep_last, done })
+        .map_err(|_| "History writer is not running".to_string())?;
+    wait.await.map_err(|_| "History writer stopped".to_string())?
+}
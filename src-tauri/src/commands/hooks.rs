@@ -0,0 +1,264 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{app_data_root, data_dir, ipc_guard};
+use super::redaction::println_redacted;
+use super::settings::{current_settings, update_settings};
+
+// =============================================================================================================
+// ============================================== SCRIPTING HOOKS ==============================================
+// =============================================================================================================
+//
+// Optional user commands run around transfers (desktop only). Args are templates:
+//   {path}      local file being uploaded / downloaded file
+//   {file_name} remote file name
+//   {output}    pre-upload only: if the hook writes this file, it is uploaded instead (e.g. `gpg -o {output} -c {path}`)
+//   {status}    post-download only: "success" | "failed"
+//   {hash}      post-download only: blake3 of the downloaded file
+// The program itself is never run through a shell, so placeholders can't inject extra commands.
+
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+/// Captured stdout/stderr is truncated to keep history files small
+const MAX_CAPTURED_OUTPUT: usize = 8 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HookCommand {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HookRun {
+    /// "pre_upload" | "post_download"
+    pub hook: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+}
+
+impl HookRun {
+    pub fn succeeded(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+
+    /// One-line summary stored alongside upload history entries
+    pub fn summary(&self) -> String {
+        let outcome = if self.timed_out {
+            "timed out".to_string()
+        } else {
+            match self.exit_code {
+                Some(code) => format!("exit {}", code),
+                None => "killed".to_string(),
+            }
+        };
+        let output = if self.stderr.trim().is_empty() { self.stdout.trim() } else { self.stderr.trim() };
+        format!("{} {} ({}): {}", self.hook, self.program, outcome, output)
+    }
+}
+
+pub(super) fn hook_log_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join("hook-runs.jsonl"))
+}
+
+fn append_hook_run(run: &HookRun, app_handle: &AppHandle) -> Result<(), String> {
+    use std::io::Write;
+    let path = hook_log_path(app_handle)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open hook log: {}", e))?;
+    let json = serde_json::to_string(run).map_err(|e| format!("Failed to serialize hook run: {}", e))?;
+    writeln!(file, "{}", json).map_err(|e| format!("Failed to write hook log: {}", e))
+}
+
+fn captured(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    if text.len() <= MAX_CAPTURED_OUTPUT {
+        return text.to_string();
+    }
+    let mut end = MAX_CAPTURED_OUTPUT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &text[..end])
+}
+
+fn expand(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |acc, (key, value)| acc.replace(&format!("{{{}}}", key), value))
+}
+
+async fn run_hook(hook: &str, command: &HookCommand, vars: &[(&str, &str)], app_handle: &AppHandle) -> HookRun {
+    let args: Vec<String> = command.args.iter().map(|a| expand(a, vars)).collect();
+    let timeout = Duration::from_secs(command.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+    let started_at = Utc::now().to_rfc3339();
+    let started = Instant::now();
+
+    let child = tokio::process::Command::new(&command.program)
+        .args(&args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+
+    let (exit_code, timed_out, stdout, stderr) = match child {
+        Err(e) => (None, false, String::new(), format!("Failed to start hook: {}", e)),
+        Ok(child) => match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Err(_) => (None, true, String::new(), format!("Hook timed out after {}s", timeout.as_secs())),
+            Ok(Err(e)) => (None, false, String::new(), format!("Hook failed: {}", e)),
+            Ok(Ok(output)) => (output.status.code(), false, captured(&output.stdout), captured(&output.stderr)),
+        },
+    };
+
+    let run = HookRun {
+        hook: hook.to_string(),
+        program: command.program.clone(),
+        args,
+        exit_code,
+        timed_out,
+        stdout,
+        stderr,
+        started_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    if let Err(e) = append_hook_run(&run, app_handle) {
+        println_redacted!("[HOOK] {}", e);
+    }
+    println!("🪝 {}", run.summary());
+    run
+}
+
+/// Outcome of the pre-upload hook
+pub struct PreUploadHook {
+    pub run: HookRun,
+    /// File the hook produced via `{output}`, to be uploaded instead of the original
+    pub replacement: Option<HookOutput>,
+}
+
+/// A file written by a hook. Removed when dropped, so it goes away however the upload ends.
+pub struct HookOutput(String);
+
+impl HookOutput {
+    pub fn path(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for HookOutput {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Run the configured pre-upload hook, if any. A failing hook is returned as an error so the upload is skipped.
+pub async fn run_pre_upload_hook(file_path: &str, file_name: &str, app_handle: &AppHandle) -> Result<Option<PreUploadHook>, String> {
+    let Some(command) = current_settings(app_handle).pre_upload_hook else {
+        return Ok(None);
+    };
+    let output_dir = data_dir::app_cache_dir(app_handle)?.join("hooks");
+    std::fs::create_dir_all(&output_dir).map_err(|e| format!("Failed to create hook output dir: {}", e))?;
+    let output = output_dir
+        .join(format!("{}-{}", Utc::now().timestamp_millis(), file_name.replace(['/', '\\'], "_")))
+        .to_string_lossy()
+        .to_string();
+
+    let run = run_hook(
+        "pre_upload",
+        &command,
+        &[("path", file_path), ("file_name", file_name), ("output", &output)],
+        app_handle,
+    )
+    .await;
+    if !run.succeeded() {
+        let _ = std::fs::remove_file(&output);
+        return Err(format!("Pre-upload hook failed: {}", run.summary()));
+    }
+    let replacement = std::path::Path::new(&output).exists().then_some(HookOutput(output));
+    Ok(Some(PreUploadHook { run, replacement }))
+}
+
+/// Run the configured post-download hook, if any. Failures are only logged.
+pub async fn run_post_download_hook(file_path: &str, file_name: &str, success: bool, app_handle: &AppHandle) {
+    let Some(command) = current_settings(app_handle).post_download_hook else {
+        return;
+    };
+    let hash = if success {
+        let path = file_path.to_string();
+        tokio::task::spawn_blocking(move || {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_reader(std::fs::File::open(path)?)?;
+            Ok::<_, std::io::Error>(hasher.finalize().to_hex().to_string())
+        })
+        .await
+        .ok()
+        .and_then(|r| r.ok())
+        .unwrap_or_default()
+    } else {
+        String::new()
+    };
+    let status = if success { "success" } else { "failed" };
+    run_hook(
+        "post_download",
+        &command,
+        &[("path", file_path), ("file_name", file_name), ("status", status), ("hash", &hash)],
+        app_handle,
+    )
+    .await;
+}
+
+/// Set or clear (`None`) the two hook commands
+#[tauri::command]
+pub async fn set_transfer_hooks(
+    pre_upload: Option<HookCommand>,
+    post_download: Option<HookCommand>,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    ipc_guard::check_sensitive("set_transfer_hooks", &webview, &ipc_nonce, &app_handle)?;
+    #[cfg(mobile)]
+    if pre_upload.is_some() || post_download.is_some() {
+        return Err("Transfer hooks are only available on desktop".to_string());
+    }
+    for command in pre_upload.iter().chain(post_download.iter()) {
+        if command.program.trim().is_empty() {
+            return Err("Hook program cannot be empty".to_string());
+        }
+    }
+    update_settings(&app_handle, |s| {
+        s.pre_upload_hook = pre_upload;
+        s.post_download_hook = post_download;
+    })?;
+    Ok(())
+}
+
+/// Most recent hook runs, newest first
+#[tauri::command]
+pub async fn get_hook_runs(limit: Option<usize>, app_handle: AppHandle) -> Result<Vec<HookRun>, String> {
+    let path = hook_log_path(&app_handle)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read hook log: {}", e))?;
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit.unwrap_or(100))
+        .collect())
+}
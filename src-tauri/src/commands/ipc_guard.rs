@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, Webview, WebviewWindowBuilder};
+
+use super::window_state::MAIN_WINDOW;
+use super::{confirmations, ApiConfig};
+
+// =============================================================================================================
+// ================================================= IPC GUARD =================================================
+// =============================================================================================================
+//
+// Commands that wipe credentials, move funds, run programs or write outside the app's data are only allowed from
+// the app's own main window. Three layers: the ACL in `permissions/` grants them to the `main` window's local
+// origin only (capabilities/default.json), each checks here that the calling webview is the main window showing
+// app content, and each takes a nonce Rust put into the main frame when it built the window. Frames and pages
+// loaded later never see the nonce.
+// The generic proxies (`proxy_api_get`, `proxy_api_post`, `call_named_endpoint`) attach the saved credentials to
+// whatever they're pointed at, so they only reach the configured API origin (`api_base_url`), and there refuse
+// the endpoints the guarded commands call, including every endpoint behind a confirmation
+// (`confirmations::gated_endpoints`).
+
+/// Global the nonce is published under, read-only and main frame only
+const NONCE_GLOBAL: &str = "__FIRESTARTER_IPC__";
+
+pub type IpcNonceState = Mutex<HashMap<String, String>>;
+pub fn new_ipc_nonce_state() -> IpcNonceState { Mutex::new(HashMap::new()) }
+
+fn init_script(nonce: &str) -> String {
+    format!(
+        "Object.defineProperty(window, '{}', {{ value: Object.freeze({{ nonce: '{}' }}), writable: false, configurable: false }});",
+        NONCE_GLOBAL, nonce
+    )
+}
+
+/// Build the main window from `tauri.conf.json` with a fresh nonce for sensitive commands
+pub fn create_main_window<R: Runtime>(app: &tauri::App<R>) -> Result<(), String> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == MAIN_WINDOW)
+        .cloned()
+        .ok_or("Main window is missing from the app config")?;
+    let nonce = uuid::Uuid::new_v4().simple().to_string();
+    WebviewWindowBuilder::from_config(app, &config)
+        .map_err(|e| format!("Failed to configure main window: {}", e))?
+        .initialization_script(init_script(&nonce))
+        .build()
+        .map_err(|e| format!("Failed to create main window: {}", e))?;
+    app.state::<IpcNonceState>().lock().unwrap().insert(MAIN_WINDOW.to_string(), nonce);
+    Ok(())
+}
+
+/// Whether `url` is the bundled frontend (or the dev server in debug builds)
+fn is_app_origin(url: &tauri::Url, app_handle: &AppHandle) -> bool {
+    match url.scheme() {
+        "tauri" => true,
+        "http" | "https" if url.host_str() == Some("tauri.localhost") => true,
+        _ => {
+            cfg!(debug_assertions)
+                && app_handle
+                    .config()
+                    .build
+                    .dev_url
+                    .as_ref()
+                    .is_some_and(|dev| dev.origin() == url.origin())
+        }
+    }
+}
+
+/// Same length and bytes, without bailing out at the first difference
+fn nonce_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len() && expected.bytes().zip(given.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Ok when a sensitive command comes from the main window's app content with that window's nonce
+pub fn check_sensitive(command: &str, webview: &Webview, nonce: &str, app_handle: &AppHandle) -> Result<(), String> {
+    let label = webview.label().to_string();
+    let url = webview.url().map_err(|e| format!("Failed to read caller URL: {}", e))?;
+    let expected = app_handle.state::<IpcNonceState>().lock().unwrap().get(&label).cloned();
+    let allowed = label == MAIN_WINDOW
+        && is_app_origin(&url, app_handle)
+        && expected.is_some_and(|expected| nonce_matches(&expected, nonce));
+    if !allowed {
+        println!("[IPC] Rejected {} from '{}' ({})", command, label, url.origin().ascii_serialization());
+        return Err(format!("{} is not allowed from this window", command));
+    }
+    Ok(())
+}
+
+/// Endpoints only the guarded commands may call: those behind a confirmation, and scoped key revocation
+fn guarded_endpoints(api_config: &ApiConfig) -> Vec<&str> {
+    let mut endpoints = confirmations::gated_endpoints(api_config);
+    endpoints.extend(api_config.scoped_key_revoke.as_deref().filter(|e| !e.is_empty()));
+    endpoints
+}
+
+/// Path of `url` as the server routes it: no query, no repeated or trailing slashes, lowercase, `%xx` decoded
+fn route(url: &str) -> Option<String> {
+    let path = reqwest::Url::parse(url).ok()?.path().to_string();
+    let decoded = percent_encoding::percent_decode_str(&path).decode_utf8_lossy().to_lowercase();
+    Some(decoded.split('/').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("/"))
+}
+
+/// Ok when `url` is on the API's origin (scheme, host and port) and isn't an endpoint of a guarded command
+pub fn check_proxy_target(url: &str, api_config: &ApiConfig) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| format!("Invalid URL: {}", url))?;
+    let api_origin = reqwest::Url::parse(&api_config.api_base_url)
+        .map_err(|e| format!("Invalid API base URL: {}", e))?
+        .origin();
+    if parsed.origin() != api_origin || !api_origin.is_tuple() {
+        println!("[IPC] Rejected proxy call to {}", parsed.origin().ascii_serialization());
+        return Err(format!("Proxy calls are limited to {}", api_origin.ascii_serialization()));
+    }
+    let target = route(url).ok_or_else(|| format!("Invalid URL: {}", url))?;
+    let guarded = guarded_endpoints(api_config)
+        .into_iter()
+        .any(|endpoint| route(&api_config.resolve_url(endpoint)).as_deref() == Some(target.as_str()));
+    if guarded {
+        println!("[IPC] Rejected proxy call to guarded endpoint /{}", target);
+        return Err(format!("/{} can only be called through its own command", target));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxies_cannot_reach_guarded_endpoints() {
+        let api_config = ApiConfig {
+            api_base_url: "https://api.example.com".to_string(),
+            withdraw_sol: "/withdraw_sol".to_string(),
+            delete_file: Some("/files/delete".to_string()),
+            ..ApiConfig::default()
+        };
+        for url in ["https://api.example.com/withdraw_sol", "https://api.example.com//Withdraw_SOL/?x=1", "https://api.example.com/files/%64elete"] {
+            assert!(check_proxy_target(url, &api_config).is_err(), "allowed {}", url);
+        }
+        assert!(check_proxy_target("https://api.example.com/files/list", &api_config).is_ok());
+        assert!(check_proxy_target("https://api.example.com:443/files/list", &api_config).is_ok());
+        assert!(check_proxy_target("not a url", &api_config).is_err());
+    }
+}
@@ -0,0 +1,588 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::local_trash::move_to_trash;
+use super::local_walk::walk_local;
+use super::redaction::println_redacted;
+use super::settings::current_settings;
+use super::stability::wait_until_stable;
+use super::tiers::tier_price;
+use super::{
+    account_scope, app_data_root, audit, current_api_config, ensure_valid_token, get_tier_pricing, history_log,
+    link_batch, load_credentials, local_paths, network, read_upload_history, remote_trash, upload_file, ApiConfig,
+    SavedCredentials, UploadOptions,
+};
+
+// =============================================================================================================
+// ============================================= LIFECYCLE RULES ===============================================
+// =============================================================================================================
+//
+// User-defined rules that a background task applies every hour, e.g. "files in a local folder older than 30 days
+// -> upload at the cold tier and trash locally" or "remote *.log older than 90 days -> delete". Remote files are
+// the ones in this device's upload history, since the API has no listing. Rules live in `lifecycle-rules.json`;
+// `evaluate_lifecycle_rules` reports what would happen without touching anything. Local folders are walked with
+// the symlink policy from settings; links, hard links and special files left out show up in the report.
+//
+// Each rule is bound to an account (and upload rules to a tier) when it is created. A rule only runs while its
+// account is the signed-in one. When that account's credentials stop working (refresh refused, 401/403, or
+// the saved credentials removed) the rule is paused, `lifecycle_rule_paused` is emitted, and it stays paused
+// until `resume_lifecycle_rule`.
+
+const LIFECYCLE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Give the app time to settle before the first pass
+const LIFECYCLE_START_DELAY: Duration = Duration::from_secs(120);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleSource {
+    /// Files in a local folder
+    Local { folder: String, #[serde(default)] recursive: bool },
+    /// Successful uploads in the history
+    Remote,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleAction {
+    Upload {
+        tier: Option<String>,
+        remote_dir: Option<String>,
+        #[serde(default)]
+        trash_local: bool,
+    },
+    DeleteRemote,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LifecycleRule {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub source: RuleSource,
+    /// `*` / `?` wildcard on the file name; every file when unset
+    pub pattern: Option<String>,
+    pub older_than_days: u32,
+    pub action: RuleAction,
+    pub created_at: String,
+    /// Account the rule acts for; rules saved before accounts were bound follow the signed-in one
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Why the rule was paused automatically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NewLifecycleRule {
+    pub name: String,
+    pub source: RuleSource,
+    pub pattern: Option<String>,
+    pub older_than_days: u32,
+    pub action: RuleAction,
+    /// Defaults to the signed-in account
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RuleOutcome {
+    pub rule_id: String,
+    /// Local path or remote name
+    pub target: String,
+    /// "upload" | "delete_remote"
+    pub action: String,
+    /// "planned" | "done" | "failed", "skipped" / "linked" for items the folder walk left out, or "paused"
+    pub status: String,
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LifecycleReport {
+    pub dry_run: bool,
+    pub evaluated_at: String,
+    pub outcomes: Vec<RuleOutcome>,
+}
+
+#[derive(Default)]
+pub struct LifecycleRunner {
+    running: bool,
+    last_report: Option<LifecycleReport>,
+}
+
+pub type LifecycleState = Mutex<LifecycleRunner>;
+pub fn new_lifecycle_state() -> LifecycleState { Mutex::new(LifecycleRunner::default()) }
+
+fn rules_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join("lifecycle-rules.json"))
+}
+
+fn load_rules(app_handle: &AppHandle) -> Result<Vec<LifecycleRule>, String> {
+    let path = rules_path(app_handle)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read lifecycle rules: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse lifecycle rules: {}", e))
+}
+
+fn save_rules(rules: &[LifecycleRule], app_handle: &AppHandle) -> Result<(), String> {
+    let path = rules_path(app_handle)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(rules).map_err(|e| format!("Failed to serialize lifecycle rules: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write lifecycle rules: {}", e))
+}
+
+/// Case-insensitive match with `*` (any run) and `?` (one character)
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn name_matches(rule: &LifecycleRule, path: &str) -> bool {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match rule.pattern.as_deref() {
+        Some(pattern) => matches_pattern(pattern, file_name),
+        None => true,
+    }
+}
+
+fn action_name(action: &RuleAction) -> &'static str {
+    match action {
+        RuleAction::Upload { .. } => "upload",
+        RuleAction::DeleteRemote => "delete_remote",
+    }
+}
+
+fn left_out(rule: &LifecycleRule, target: String, status: &str, message: String) -> RuleOutcome {
+    RuleOutcome {
+        rule_id: rule.id.clone(),
+        target,
+        action: action_name(&rule.action).to_string(),
+        status: status.to_string(),
+        message: Some(message),
+    }
+}
+
+/// Files a rule applies to right now, plus outcomes for what the folder walk left out
+async fn rule_targets(rule: &LifecycleRule, user_id: &str, app_handle: &AppHandle) -> (Vec<String>, Vec<RuleOutcome>) {
+    let cutoff = Utc::now() - chrono::Duration::days(rule.older_than_days as i64);
+    let history = read_upload_history(user_id.to_string(), app_handle.clone()).await.unwrap_or_default();
+    match &rule.source {
+        RuleSource::Local { folder, recursive } => {
+            let walk = walk_local(&local_paths::to_fs_path(folder), *recursive, current_settings(app_handle).symlink_policy);
+            let mut left = Vec::new();
+            for item in walk.skipped {
+                // unlistable folders are reported whatever the pattern
+                if item.kind == "unreadable" || name_matches(rule, &item.path) {
+                    left.push(left_out(rule, item.path, "skipped", item.reason));
+                }
+            }
+            for (link, target) in walk.links {
+                let link = local_paths::to_wire(&link);
+                if name_matches(rule, &link) {
+                    left.push(left_out(rule, link, "linked", format!("Symbolic link to {}", target.display())));
+                }
+            }
+            let targets = walk
+                .files
+                .into_iter()
+                .filter(|(_, modified)| DateTime::<Utc>::from(*modified) < cutoff)
+                .map(|(path, _)| local_paths::to_wire(&path))
+                .filter(|path| name_matches(rule, path))
+                // already offloaded on an earlier pass
+                .filter(|path| !history.iter().any(|e| e.status == "success" && e.local_path == *path))
+                .collect();
+            (targets, left)
+        }
+        RuleSource::Remote => {
+            let mut names: Vec<String> = Vec::new();
+            for entry in &history {
+                let old = DateTime::parse_from_rfc3339(&entry.timestamp).is_ok_and(|t| t.with_timezone(&Utc) < cutoff);
+                if entry.status == "success" && old && name_matches(rule, &entry.remote_path) && !names.contains(&entry.remote_path) {
+                    names.push(entry.remote_path.clone());
+                }
+            }
+            // a newer upload under the same name restarts the clock
+            names.retain(|name| {
+                !history.iter().any(|e| {
+                    e.status == "success"
+                        && e.remote_path == *name
+                        && DateTime::parse_from_rfc3339(&e.timestamp).is_ok_and(|t| t.with_timezone(&Utc) >= cutoff)
+                })
+            });
+            (names, Vec::new())
+        }
+    }
+}
+
+pub(super) async fn delete_remote(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: &str,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    let url = api_config.optional_url(&api_config.delete_file, "Delete file")?;
+    let response = client
+        .post(&url)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .json(&serde_json::json!({ "file_name": name }))
+        .send()
+        .await
+        .map_err(|e| format!("Delete request failed: {}", e))?;
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!("Delete failed - Status: {}, Response: {}", status, text));
+    }
+    // backends with a remote trash keep the file restorable for a while; its links go when it's purged
+    let restorable = remote_trash::is_restorable(&text);
+    let new_status = if restorable { remote_trash::TRASHED } else { "deleted" };
+    let deleted = name.to_string();
+    history_log::update_entries(
+        &credentials.user_id,
+        move |entry| {
+            if entry.status != "success" || entry.remote_path != deleted {
+                return false;
+            }
+            entry.status = new_status.to_string();
+            true
+        },
+        app_handle,
+    )
+    .await?;
+    if !restorable {
+        link_batch::revoke_links_to(client, api_config, credentials, name, app_handle).await;
+    }
+    Ok(())
+}
+
+async fn apply(rule: &LifecycleRule, target: &str, credentials: &SavedCredentials, app_handle: &AppHandle) -> Result<(), String> {
+    match &rule.action {
+        RuleAction::Upload { tier, remote_dir, trash_local } => {
+            wait_until_stable(target, app_handle).await?;
+            let options = UploadOptions {
+                tier: tier.clone(),
+                remote_dir: remote_dir.clone(),
+                on_conflict: Some("rename".to_string()),
+                ..Default::default()
+            };
+            upload_file(target.to_string(), options, app_handle.clone()).await?;
+            // the global trash-after-upload policy may have moved it already
+            if *trash_local && local_paths::to_fs_path(target).exists() {
+                move_to_trash(target)?;
+            }
+            Ok(())
+        }
+        RuleAction::DeleteRemote => {
+            let api_config = current_api_config(app_handle);
+            let client = network::client(app_handle);
+            let mut credentials = credentials.clone();
+            ensure_valid_token(&client, &api_config, &mut credentials, app_handle).await?;
+            let result = delete_remote(&client, &api_config, &credentials, target, app_handle).await;
+            audit::record(app_handle, "remote_delete", target, Some(&credentials.user_id), Some(format!("lifecycle rule {}", rule.id)), &result);
+            result
+        }
+    }
+}
+
+/// Errors that mean the account's credentials are no longer accepted, rather than a network or server problem
+fn is_credential_failure(error: &str) -> bool {
+    ["please login again", "Status: 401", "Status: 403", "HTTP 401", "HTTP 403"].iter().any(|m| error.contains(m))
+}
+
+/// The folder or history a rule reads, for rule-wide outcomes
+fn source_label(rule: &LifecycleRule) -> String {
+    match &rule.source {
+        RuleSource::Local { folder, .. } => folder.clone(),
+        RuleSource::Remote => "upload history".to_string(),
+    }
+}
+
+/// Pause a rule whose account can't be used any more and tell the frontend
+fn pause_rule(rule: &LifecycleRule, user_id: &str, reason: &str, app_handle: &AppHandle) -> Result<(), String> {
+    let mut rules = load_rules(app_handle)?;
+    if let Some(saved) = rules.iter_mut().find(|r| r.id == rule.id) {
+        saved.paused = Some(reason.to_string());
+    }
+    save_rules(&rules, app_handle)?;
+    println_redacted!("⏸️ Paused lifecycle rule '{}': {}", rule.name, reason);
+    app_handle
+        .emit("lifecycle_rule_paused", serde_json::json!({ "rule_id": rule.id, "user_id": user_id, "reason": reason }))
+        .ok();
+    Ok(())
+}
+
+/// Usable credentials for `user_id` (and the tier check) before a rule is created or resumed
+async fn check_binding(user_id: &str, action: &RuleAction, app_handle: &AppHandle) -> Result<(), String> {
+    let credentials = account_scope::saved_credentials(user_id, app_handle)?;
+    if credentials.user_app_key.is_empty() && credentials.auth_tokens.is_none() {
+        return Err(format!("Account {} has no usable credentials, sign in again", user_id));
+    }
+    if let RuleAction::Upload { tier: Some(tier), .. } = action {
+        let pricing = get_tier_pricing(app_handle.clone()).await.map_err(|e| format!("Failed to check tier '{}': {}", tier, e))?;
+        if tier_price(&pricing, tier).is_none() {
+            return Err(format!("Unknown tier '{}'", tier));
+        }
+    }
+    Ok(())
+}
+
+/// Marks a pass as running until dropped, so a pass that panics or is cancelled doesn't block later ones
+struct RunningPass<'a>(&'a AppHandle);
+
+impl<'a> RunningPass<'a> {
+    /// `None` while another pass is running
+    fn begin(app_handle: &'a AppHandle) -> Option<Self> {
+        let state = app_handle.state::<LifecycleState>();
+        let mut runner = state.lock().unwrap();
+        if runner.running {
+            return None;
+        }
+        runner.running = true;
+        Some(Self(app_handle))
+    }
+}
+
+impl Drop for RunningPass<'_> {
+    fn drop(&mut self) {
+        self.0.state::<LifecycleState>().lock().unwrap().running = false;
+    }
+}
+
+/// One pass over the enabled rules. Skipped (`None`) while another pass is running.
+async fn run_rules(dry_run: bool, app_handle: &AppHandle) -> Result<Option<LifecycleReport>, String> {
+    let Some(_pass) = RunningPass::begin(app_handle) else {
+        return Ok(None);
+    };
+    let result = async {
+        let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+        // a refused refresh pauses every rule of the account up front
+        let session = if dry_run {
+            Ok(())
+        } else {
+            ensure_valid_token(&network::client(app_handle), &current_api_config(app_handle), &mut credentials, app_handle).await
+        };
+        let mut outcomes = Vec::new();
+        for rule in load_rules(app_handle)?.into_iter().filter(|r| r.enabled && r.paused.is_none()) {
+            let user_id = rule.user_id.clone().unwrap_or_else(|| credentials.user_id.clone());
+            if user_id != credentials.user_id {
+                let reason = match account_scope::is_known(&user_id, app_handle) {
+                    Ok(true) => {
+                        outcomes.push(left_out(&rule, source_label(&rule), "skipped", format!("Account {} is not signed in", user_id)));
+                        continue;
+                    }
+                    _ => format!("Saved credentials for account {} were removed", user_id),
+                };
+                if !dry_run {
+                    pause_rule(&rule, &user_id, &reason, app_handle)?;
+                }
+                outcomes.push(left_out(&rule, source_label(&rule), "paused", reason));
+                continue;
+            }
+            if let Err(e) = &session {
+                if is_credential_failure(e) {
+                    pause_rule(&rule, &user_id, e, app_handle)?;
+                    outcomes.push(left_out(&rule, source_label(&rule), "paused", e.clone()));
+                    continue;
+                }
+            }
+
+            let (targets, left_out) = rule_targets(&rule, &user_id, app_handle).await;
+            outcomes.extend(left_out);
+            for target in targets {
+                let (status, message) = if dry_run {
+                    ("planned", None)
+                } else {
+                    match apply(&rule, &target, &credentials, app_handle).await {
+                        Ok(()) => ("done", None),
+                        Err(e) => ("failed", Some(e)),
+                    }
+                };
+                let refused = message.as_deref().filter(|e| is_credential_failure(e)).map(str::to_string);
+                outcomes.push(RuleOutcome {
+                    rule_id: rule.id.clone(),
+                    target,
+                    action: action_name(&rule.action).to_string(),
+                    status: status.to_string(),
+                    message,
+                });
+                // the rest would be refused the same way
+                if let Some(reason) = refused {
+                    pause_rule(&rule, &user_id, &reason, app_handle)?;
+                    break;
+                }
+            }
+        }
+        Ok(LifecycleReport { dry_run, evaluated_at: Utc::now().to_rfc3339(), outcomes })
+    }
+    .await;
+
+    if let Ok(report) = &result {
+        if !dry_run {
+            app_handle.state::<LifecycleState>().lock().unwrap().last_report = Some(report.clone());
+        }
+    }
+    result.map(Some)
+}
+
+/// Background task applying the rules every hour. Called once in setup.
+pub fn start_lifecycle_scheduler(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(LIFECYCLE_START_DELAY).await;
+        loop {
+            let has_rules = load_rules(&app_handle).map(|rules| rules.iter().any(|r| r.enabled)).unwrap_or(false);
+            if has_rules {
+                match run_rules(false, &app_handle).await {
+                    Ok(Some(report)) => {
+                        if !report.outcomes.is_empty() {
+                            println!("♻️ Lifecycle pass: {} action(s)", report.outcomes.len());
+                            app_handle.emit("lifecycle_report", &report).ok();
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => println_redacted!("[LIFECYCLE] {}", e),
+                }
+            }
+            tokio::time::sleep(LIFECYCLE_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn create_lifecycle_rule(rule: NewLifecycleRule, app_handle: AppHandle) -> Result<LifecycleRule, String> {
+    if rule.name.trim().is_empty() {
+        return Err("Rule name is required".to_string());
+    }
+    if let RuleSource::Local { folder, .. } = &rule.source {
+        if !local_paths::to_fs_path(folder).is_dir() {
+            return Err(format!("Folder not found: {}", folder));
+        }
+    }
+    if matches!((&rule.source, &rule.action), (RuleSource::Remote, RuleAction::Upload { .. }) | (RuleSource::Local { .. }, RuleAction::DeleteRemote)) {
+        return Err("Local rules upload, remote rules delete".to_string());
+    }
+    let user_id = match rule.user_id.filter(|id| !id.trim().is_empty()) {
+        Some(user_id) => user_id,
+        None => load_credentials(app_handle.clone()).await?.ok_or("No account is signed in")?.user_id,
+    };
+    // upload rules are bound to a tier too; without one that is the tier plain uploads go to
+    let action = match rule.action {
+        RuleAction::Upload { tier, remote_dir, trash_local } => RuleAction::Upload {
+            tier: Some(tier.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| "normal".to_string())),
+            remote_dir,
+            trash_local,
+        },
+        action => action,
+    };
+    check_binding(&user_id, &action, &app_handle).await?;
+    let created = LifecycleRule {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: rule.name.trim().to_string(),
+        enabled: true,
+        source: rule.source,
+        pattern: rule.pattern.filter(|p| !p.trim().is_empty()),
+        older_than_days: rule.older_than_days,
+        action,
+        created_at: Utc::now().to_rfc3339(),
+        user_id: Some(user_id),
+        paused: None,
+    };
+    let mut rules = load_rules(&app_handle)?;
+    rules.push(created.clone());
+    save_rules(&rules, &app_handle)?;
+    Ok(created)
+}
+
+#[tauri::command]
+pub async fn list_lifecycle_rules(app_handle: AppHandle) -> Result<Vec<LifecycleRule>, String> {
+    load_rules(&app_handle)
+}
+
+#[tauri::command]
+pub async fn delete_lifecycle_rule(id: String, app_handle: AppHandle) -> Result<(), String> {
+    let mut rules = load_rules(&app_handle)?;
+    let before = rules.len();
+    rules.retain(|r| r.id != id);
+    if rules.len() == before {
+        return Err(format!("Lifecycle rule not found: {}", id));
+    }
+    save_rules(&rules, &app_handle)
+}
+
+/// Un-pause a rule once its account can be used again
+#[tauri::command]
+pub async fn resume_lifecycle_rule(id: String, app_handle: AppHandle) -> Result<LifecycleRule, String> {
+    let mut rules = load_rules(&app_handle)?;
+    let rule = rules.iter_mut().find(|r| r.id == id).ok_or_else(|| format!("Lifecycle rule not found: {}", id))?;
+    if let Some(user_id) = &rule.user_id {
+        check_binding(user_id, &rule.action, &app_handle).await?;
+    }
+    rule.paused = None;
+    let resumed = rule.clone();
+    save_rules(&rules, &app_handle)?;
+    Ok(resumed)
+}
+
+/// What the enabled rules would do now (`dry_run`, the default), or run them immediately
+#[tauri::command]
+pub async fn evaluate_lifecycle_rules(dry_run: Option<bool>, app_handle: AppHandle) -> Result<LifecycleReport, String> {
+    run_rules(dry_run.unwrap_or(true), &app_handle)
+        .await?
+        .ok_or_else(|| "A lifecycle pass is already running".to_string())
+}
+
+/// Report of the last scheduled or manual (non-dry) pass
+#[tauri::command]
+pub async fn get_last_lifecycle_report(app_handle: AppHandle) -> Result<Option<LifecycleReport>, String> {
+    Ok(app_handle.state::<LifecycleState>().lock().unwrap().last_report.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_match_whole_names_ignoring_case() {
+        assert!(matches_pattern("*.MOV", "holiday.mov"));
+        assert!(matches_pattern("IMG_????.jpg", "img_0042.JPG"));
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("report*final*.pdf", "report-v2-final-final.pdf"));
+        assert!(matches_pattern("ÉTÉ*", "été 2024.png"));
+        assert!(!matches_pattern("*.mov", "holiday.mov.part"));
+        assert!(!matches_pattern("IMG_????.jpg", "IMG_42.jpg"));
+        assert!(!matches_pattern("a", "ab"));
+        assert!(!matches_pattern("?", ""));
+    }
+}
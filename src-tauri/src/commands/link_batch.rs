@@ -0,0 +1,159 @@
+use futures_util::StreamExt;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::redaction::println_redacted;
+use super::settings::{current_settings, update_settings};
+use super::{
+    account_scope, audit, current_api_config, ensure_valid_token, load_credentials, network, read_public_links,
+    request_link_deletion, request_public_link, update_public_links, ApiConfig, LinkOptions, PublicLinkEntry,
+    SavedCredentials,
+};
+
+// =============================================================================================================
+// ============================================= BULK LINK ACTIONS =============================================
+// =============================================================================================================
+//
+// Create or delete many public links at once. Requests run a few at a time; the local link file is updated
+// once at the end with whatever succeeded, and failures are reported per item instead of failing the batch.
+// Deleting a remote file also revokes its links (unless turned off), so they don't outlive what they point at.
+
+const LINK_BATCH_CONCURRENCY: usize = 4;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LinkBatchFailure {
+    /// Remote path or link hash
+    pub item: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LinkBatchResult<T> {
+    pub succeeded: Vec<T>,
+    pub failed: Vec<LinkBatchFailure>,
+}
+
+fn split_results<T>(results: Vec<(String, Result<T, String>)>) -> LinkBatchResult<T> {
+    let mut batch = LinkBatchResult { succeeded: Vec::new(), failed: Vec::new() };
+    for (item, result) in results {
+        match result {
+            Ok(value) => batch.succeeded.push(value),
+            Err(error) => batch.failed.push(LinkBatchFailure { item, error }),
+        }
+    }
+    batch
+}
+
+#[tauri::command]
+pub async fn create_public_links(
+    user_id: String,
+    remote_paths: Vec<String>,
+    app_handle: AppHandle,
+) -> Result<LinkBatchResult<PublicLinkEntry>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let results: Vec<(String, Result<PublicLinkEntry, String>)> = futures_util::stream::iter(remote_paths)
+        .map(|path| {
+            let (client, api_config, credentials) = (&client, &api_config, &credentials);
+            async move {
+                let result = request_public_link(client, api_config, credentials, &path, LinkOptions::default()).await;
+                (path, result)
+            }
+        })
+        .buffer_unordered(LINK_BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    let batch = split_results(results);
+
+    if !batch.succeeded.is_empty() {
+        update_public_links(&user_id, &app_handle, |links| links.extend(batch.succeeded.iter().cloned()))?;
+    }
+    println!("🔗 Created {} link(s), {} failed", batch.succeeded.len(), batch.failed.len());
+    Ok(batch)
+}
+
+/// Returns the hashes that were deleted
+#[tauri::command]
+pub async fn delete_public_links(
+    user_id: String,
+    hashes: Vec<String>,
+    app_handle: AppHandle,
+) -> Result<LinkBatchResult<String>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let results: Vec<(String, Result<String, String>)> = futures_util::stream::iter(hashes)
+        .map(|hash| {
+            let (client, api_config, credentials) = (&client, &api_config, &credentials);
+            async move {
+                let result = request_link_deletion(client, api_config, credentials, &hash).await.map(|_| hash.clone());
+                (hash, result)
+            }
+        })
+        .buffer_unordered(LINK_BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    for (hash, result) in &results {
+        audit::record(&app_handle, "link_delete", hash, Some(&user_id), Some("bulk".to_string()), result);
+    }
+    let batch = split_results(results);
+
+    if !batch.succeeded.is_empty() {
+        update_public_links(&user_id, &app_handle, |links| links.retain(|l| !batch.succeeded.contains(&l.link_hash)))?;
+    }
+    println!("🔗 Deleted {} link(s), {} failed", batch.succeeded.len(), batch.failed.len());
+    Ok(batch)
+}
+
+/// Revoke the saved links to `remote_path` once the file itself is gone for good (deleted without a remote
+/// trash, or purged from it), unless `revoke_links_on_delete` is off. Each revocation is audited; links the
+/// server wouldn't delete stay in the link file. Returns the hashes revoked.
+pub(super) async fn revoke_links_to(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    remote_path: &str,
+    app_handle: &AppHandle,
+) -> Vec<String> {
+    if !current_settings(app_handle).revoke_links_on_delete.unwrap_or(true) {
+        return Vec::new();
+    }
+    let user_id = credentials.user_id.as_str();
+    let hashes: Vec<String> = match read_public_links(user_id, app_handle) {
+        Ok(links) => links.into_iter().filter(|l| l.remote_path == remote_path).map(|l| l.link_hash).collect(),
+        Err(e) => {
+            println!("[LINKS] Not revoking links to deleted {}: {}", remote_path, e);
+            return Vec::new();
+        }
+    };
+    let mut revoked = Vec::new();
+    for hash in hashes {
+        let result = request_link_deletion(client, api_config, credentials, &hash).await;
+        audit::record(app_handle, "link_delete", &hash, Some(user_id), Some(format!("remote file deleted: {}", remote_path)), &result);
+        match result {
+            Ok(()) => revoked.push(hash),
+            Err(e) => println_redacted!("[LINKS] Failed to revoke link {} to deleted {}: {}", hash, remote_path, e),
+        }
+    }
+    if !revoked.is_empty() {
+        if let Err(e) = update_public_links(user_id, app_handle, |links| links.retain(|l| !revoked.contains(&l.link_hash))) {
+            println!("[LINKS] {}", e);
+        }
+        println!("🔗 Revoked {} link(s) to deleted {}", revoked.len(), remote_path);
+    }
+    revoked
+}
+
+/// Whether deleting a remote file also revokes its public links (on by default)
+#[tauri::command]
+pub async fn set_revoke_links_on_delete(enabled: bool, app_handle: AppHandle) -> Result<(), String> {
+    update_settings(&app_handle, |s| s.revoke_links_on_delete = Some(enabled))?;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+use super::link_sheet::{availability, file_name, human_size, uploaded_size};
+use super::{account_scope, contacts, current_api_config, public_link_url, read_public_links};
+
+// =============================================================================================================
+// ============================================== EMAIL A LINK =================================================
+// =============================================================================================================
+//
+// Hands a public link to the default mail client as a `mailto:` draft, so the address, file name, size and
+// availability don't have to be copied over by hand. Subject and body are templates over `{url}`, `{name}`,
+// `{title}`, `{description}`, `{size}` and `{availability}`. Sent to a saved contact, the draft is addressed to
+// them, uses their default message as the body and goes into their share history; otherwise the user picks
+// the recipient in their mail client.
+
+const TEMPLATE_FIELDS: [&str; 6] = ["url", "name", "title", "description", "size", "availability"];
+const DEFAULT_SUBJECT: &str = "Shared file: {title}";
+const DEFAULT_BODY: &str = "{description}\n\n{name} ({size})\n{url}\n\n{availability}.";
+
+/// RFC 6068 leaves only the unreserved characters as they are in `subject=` and `body=` values
+const MAILTO_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+const ADDRESS_ENCODE_SET: &AsciiSet = &MAILTO_ENCODE_SET.remove(b'@');
+
+pub(super) fn check_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..].find('}').ok_or_else(|| format!("Unclosed '{{' in template '{}'", template))?;
+        let field = &rest[open + 1..open + close];
+        if !TEMPLATE_FIELDS.contains(&field) {
+            return Err(format!("Unknown template field '{{{}}}', expected one of {:?}", field, TEMPLATE_FIELDS));
+        }
+        rest = &rest[open + close + 1..];
+    }
+    Ok(())
+}
+
+/// Fill in `template`; blank lines left by empty fields collapse into one
+fn expand(template: &str, fields: &[(&str, &str)]) -> String {
+    let mut expanded = template.to_string();
+    for (field, value) in fields {
+        expanded = expanded.replace(&format!("{{{}}}", field), value);
+    }
+    let mut out = String::with_capacity(expanded.len());
+    let mut blank_run = 0;
+    for line in expanded.trim().lines() {
+        blank_run = if line.trim().is_empty() { blank_run + 1 } else { 0 };
+        if blank_run <= 1 {
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// `mailto:` URL for a draft to `to` (if any) with `subject` and `body`, line breaks as CRLF
+fn mailto_url(to: Option<&str>, subject: &str, body: &str) -> String {
+    let body = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    format!(
+        "mailto:{}?subject={}&body={}",
+        utf8_percent_encode(to.unwrap_or(""), ADDRESS_ENCODE_SET),
+        utf8_percent_encode(subject, MAILTO_ENCODE_SET),
+        utf8_percent_encode(&body, MAILTO_ENCODE_SET)
+    )
+}
+
+/// Open a new email in the default mail client with the public link `link_hash` and its details filled in,
+/// addressed to `contact_id` when given. Returns the `mailto:` URL, which the UI can offer to copy if no mail
+/// client is set up.
+#[tauri::command]
+pub async fn share_link_via_email(
+    user_id: String,
+    link_hash: String,
+    subject: Option<String>,
+    body_template: Option<String>,
+    contact_id: Option<String>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let contact = contact_id.map(|id| contacts::find_contact(&user_id, &id, &app_handle)).transpose()?;
+    let subject = subject.filter(|s| !s.trim().is_empty()).unwrap_or_else(|| DEFAULT_SUBJECT.to_string());
+    let body_template = body_template
+        .or_else(|| contact.as_ref().and_then(|c| c.default_message.clone()))
+        .filter(|b| !b.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_BODY.to_string());
+    check_template(&subject)?;
+    check_template(&body_template)?;
+
+    let link = read_public_links(&user_id, &app_handle)?
+        .into_iter()
+        .find(|l| l.link_hash == link_hash)
+        .ok_or_else(|| format!("Link not found: {}", link_hash))?;
+    let url = public_link_url(&current_api_config(&app_handle), &link)?;
+    let size = uploaded_size(&user_id, &link.remote_path, &app_handle)
+        .await?
+        .map(human_size)
+        .unwrap_or_else(|| "size unknown".to_string());
+    let name = file_name(&link);
+    let title = link.custom_title.as_deref().filter(|t| !t.trim().is_empty()).unwrap_or(name);
+    let availability = availability(&link);
+    let fields = [
+        ("url", url.as_str()),
+        ("name", name),
+        ("title", title),
+        ("description", link.custom_description.as_deref().unwrap_or("")),
+        ("size", size.as_str()),
+        ("availability", availability.as_str()),
+    ];
+
+    let to = contact.as_ref().map(|c| c.email.as_str());
+    let mailto = mailto_url(to, &expand(&subject, &fields).replace('\n', " "), &expand(&body_template, &fields));
+    app_handle
+        .opener()
+        .open_url(mailto.as_str(), None::<&str>)
+        .map_err(|e| format!("Failed to open mail client: {}", e))?;
+    println!("✉️ Email draft opened for {}", link.remote_path);
+    if let Some(contact) = &contact {
+        contacts::record_share(&user_id, &contact.id, &link, &app_handle)?;
+    }
+    Ok(mailto)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draft_is_filled_in_and_encoded() {
+        let fields = [("url", "https://example.com/publicDownload?hash=ab&c"), ("name", "Q3 report.pdf"), ("description", "")];
+        let body = expand("{description}\n\n{name}\n{url}\n\n\nThanks", &fields);
+        assert_eq!(body, "Q3 report.pdf\nhttps://example.com/publicDownload?hash=ab&c\n\nThanks");
+        assert_eq!(
+            mailto_url(None, "Shared: été", "a & b\nc"),
+            "mailto:?subject=Shared%3A%20%C3%A9t%C3%A9&body=a%20%26%20b%0D%0Ac"
+        );
+        assert_eq!(mailto_url(Some("a#b@example.com"), "x", ""), "mailto:a%23b@example.com?subject=x&body=");
+        assert!(check_template("{title} via {url}").is_ok());
+        assert!(check_template("{recipient}").is_err());
+        assert!(check_template("{url").is_err());
+    }
+}
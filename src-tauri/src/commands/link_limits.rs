@@ -0,0 +1,136 @@
+use futures_util::StreamExt;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::redaction::println_redacted;
+use super::{
+    account_scope, current_api_config, ensure_valid_token, link_headers, load_credentials, network, update_public_links,
+    PublicLinkEntry, SavedCredentials,
+};
+
+// =============================================================================================================
+// ============================================ LINK DOWNLOAD LIMITS ===========================================
+// =============================================================================================================
+//
+// Links created with a download limit (or as single-use) are counted by the server. Their remaining uses are
+// fetched from the stats endpoint and written back into the local link file, so exhausted links can be shown
+// as such even offline.
+
+const STATS_CONCURRENCY: usize = 4;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LinkStats {
+    pub link_hash: String,
+    pub downloads: u64,
+    pub max_downloads: Option<u32>,
+    pub remaining_uses: Option<u32>,
+    pub exhausted: bool,
+}
+
+fn u64_field(json: &serde_json::Value, keys: &[&str]) -> Option<u64> {
+    keys.iter().find_map(|k| json.get(*k)?.as_u64())
+}
+
+async fn request_link_stats(
+    client: &reqwest::Client,
+    url: &str,
+    credentials: &SavedCredentials,
+    link_hash: &str,
+) -> Result<LinkStats, String> {
+    let resp = client
+        .get(url)
+        .headers(link_headers(credentials)?)
+        .query(&[("link_hash", link_hash)])
+        .send()
+        .await
+        .map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let downloads = u64_field(&json, &["download_count", "downloads"]).unwrap_or(0);
+    let max_downloads = u64_field(&json, &["max_downloads"]).map(|m| m.min(u32::MAX as u64) as u32);
+    let remaining_uses = u64_field(&json, &["remaining_uses", "remaining"])
+        .map(|r| r.min(u32::MAX as u64) as u32)
+        .or_else(|| max_downloads.map(|max| max.saturating_sub(downloads.min(u32::MAX as u64) as u32)));
+    let exhausted = json.get("exhausted").and_then(|v| v.as_bool()).unwrap_or(remaining_uses == Some(0));
+
+    Ok(LinkStats { link_hash: link_hash.to_string(), downloads, max_downloads, remaining_uses, exhausted })
+}
+
+fn apply_stats(entry: &mut PublicLinkEntry, stats: &LinkStats) {
+    entry.max_downloads = stats.max_downloads.or(entry.max_downloads);
+    entry.remaining_uses = stats.remaining_uses;
+    entry.exhausted = stats.exhausted;
+}
+
+/// Refresh remaining uses for limited links that aren't known to be exhausted yet. Best effort: without a stats
+/// endpoint, credentials or network the stored values are kept. The fetched stats go into `links` and, by link
+/// hash, into the link file as it is after the requests, so links changed meanwhile aren't overwritten.
+pub(super) async fn merge_server_state(user_id: &str, links: &mut [PublicLinkEntry], app_handle: &AppHandle) {
+    let api_config = current_api_config(app_handle);
+    let Ok(url) = api_config.optional_url(&api_config.link_stats, "Link stats") else { return };
+    let hashes: Vec<String> = links
+        .iter()
+        .filter(|l| l.max_downloads.is_some() && !l.exhausted)
+        .map(|l| l.link_hash.clone())
+        .collect();
+    if hashes.is_empty() {
+        return;
+    }
+    let Ok(Some(mut credentials)) = load_credentials(app_handle.clone()).await else { return };
+    let client = network::client(app_handle);
+    if ensure_valid_token(&client, &api_config, &mut credentials, app_handle).await.is_err() {
+        return;
+    }
+
+    let results: Vec<Result<LinkStats, String>> = futures_util::stream::iter(hashes)
+        .map(|hash| {
+            let (client, url, credentials) = (&client, &url, &credentials);
+            async move { request_link_stats(client, url, credentials, &hash).await }
+        })
+        .buffer_unordered(STATS_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut fetched = Vec::new();
+    for result in results {
+        match result {
+            Ok(stats) => fetched.push(stats),
+            Err(e) => println_redacted!("[LINK] Failed to fetch link stats: {}", e),
+        }
+    }
+    if fetched.is_empty() {
+        return;
+    }
+    let apply_all = |links: &mut [PublicLinkEntry]| {
+        for stats in &fetched {
+            if let Some(entry) = links.iter_mut().find(|l| l.link_hash == stats.link_hash) {
+                apply_stats(entry, stats);
+            }
+        }
+    };
+    apply_all(links);
+    if let Err(e) = update_public_links(user_id, app_handle, |stored| apply_all(stored)) {
+        println!("[LINK] Failed to save link stats: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_link_stats(user_id: String, link_hash: String, app_handle: AppHandle) -> Result<LinkStats, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.link_stats, "Link stats")?;
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let stats = request_link_stats(&client, &url, &credentials, &link_hash).await?;
+    update_public_links(&user_id, &app_handle, |links| {
+        if let Some(entry) = links.iter_mut().find(|l| l.link_hash == link_hash) {
+            apply_stats(entry, &stats);
+        }
+    })?;
+    Ok(stats)
+}
@@ -0,0 +1,116 @@
+use tauri::AppHandle;
+use tokio::io::AsyncReadExt;
+
+use super::redaction::println_redacted;
+use super::{
+    account_scope, assets, current_api_config, ensure_valid_token, link_headers, load_credentials, network, open_local_file, read_public_links,
+    update_public_links, upload_bytes, ApiConfig, PublicLinkEntry, SavedCredentials,
+};
+
+// =============================================================================================================
+// ============================================== LINK PREVIEWS ================================================
+// =============================================================================================================
+//
+// Social preview metadata for public links: title, description and a preview image. The image is uploaded under
+// `link-previews/` (without the history, hooks and webhooks of a user upload) and attached to the link by name; a copy goes straight into the asset cache
+// so the links page can show it through `firestarter-asset://` without downloading it back.
+
+const PREVIEW_DIR: &str = "link-previews";
+const MAX_PREVIEW_BYTES: u64 = 5 * 1024 * 1024;
+
+fn preview_content_type(ext: &str) -> Option<&'static str> {
+    match ext {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "webp" => Some("image/webp"),
+        "gif" => Some("image/gif"),
+        _ => None,
+    }
+}
+
+/// Upload the preview image for `link_hash` and cache it locally; returns its remote name
+async fn upload_preview_image(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    link_hash: &str,
+    image_path: &str,
+    app_handle: &AppHandle,
+) -> Result<String, String> {
+    let ext = std::path::Path::new(image_path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let content_type = preview_content_type(&ext).ok_or("Preview image must be a PNG, JPEG, WebP or GIF")?;
+    let mut bytes = Vec::new();
+    open_local_file(image_path, app_handle)
+        .await?
+        .take(MAX_PREVIEW_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|e| format!("Failed to read preview image: {}", e))?;
+    if bytes.len() as u64 > MAX_PREVIEW_BYTES {
+        return Err(format!("Preview image is larger than {} MB", MAX_PREVIEW_BYTES / (1024 * 1024)));
+    }
+
+    // one image per link, replaced on every update
+    let remote_name = format!("{}/{}.{}", PREVIEW_DIR, link_hash, ext);
+    upload_bytes(client, api_config, credentials, &remote_name, bytes.clone()).await?;
+
+    if let Err(e) = assets::prime_asset_cache(app_handle, &credentials.user_id, &remote_name, content_type, &bytes) {
+        println_redacted!("[PREVIEW] {}", e);
+    }
+    Ok(remote_name)
+}
+
+/// Set a link's preview title, description and image. `None` leaves a field as it is, an empty string clears it.
+#[tauri::command]
+pub async fn update_public_link(
+    user_id: String,
+    link_hash: String,
+    custom_title: Option<String>,
+    custom_description: Option<String>,
+    preview_image_path: Option<String>,
+    app_handle: AppHandle,
+) -> Result<PublicLinkEntry, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.update_public_link, "Update public link")?;
+    let mut entry = read_public_links(&user_id, &app_handle)?
+        .into_iter()
+        .find(|l| l.link_hash == link_hash)
+        .ok_or_else(|| format!("Link {} not found", link_hash))?;
+
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let preview_image = match preview_image_path.as_deref() {
+        Some(path) if !path.trim().is_empty() => {
+            Some(upload_preview_image(&client, &api_config, &credentials, &link_hash, path, &app_handle).await?)
+        },
+        _ => None,
+    };
+
+    let mut body = serde_json::json!({ "link_hash": link_hash });
+    if let Some(title) = &custom_title { body["custom_title"] = serde_json::Value::String(title.clone()); }
+    if let Some(desc) = &custom_description { body["custom_description"] = serde_json::Value::String(desc.clone()); }
+    if let Some(image) = &preview_image { body["preview_image"] = serde_json::Value::String(image.clone()); }
+
+    let resp = client.post(&url).headers(link_headers(&credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+
+    if let Some(title) = custom_title { entry.custom_title = Some(title).filter(|t| !t.is_empty()); }
+    if let Some(desc) = custom_description { entry.custom_description = Some(desc).filter(|d| !d.is_empty()); }
+    if preview_image.is_some() { entry.preview_image = preview_image; }
+
+    update_public_links(&user_id, &app_handle, |links| {
+        if let Some(stored) = links.iter_mut().find(|l| l.link_hash == link_hash) {
+            *stored = entry.clone();
+        }
+    })?;
+    println!("🖼️ Updated preview metadata for link {}", link_hash);
+    Ok(entry)
+}
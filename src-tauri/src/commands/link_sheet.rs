@@ -0,0 +1,210 @@
+use std::fmt::Write as _;
+use tauri::AppHandle;
+
+use super::{account_scope, current_api_config, output_paths, public_link_url, read_public_links, read_upload_history, PublicLinkEntry};
+
+// =============================================================================================================
+// ============================================== LINK SHARE SHEET =============================================
+// =============================================================================================================
+//
+// A one-page A4 PDF for handing a public link to someone who'd rather not copy a URL: file name, size, how long
+// the link works, a QR code and the address spelled out. The PDF is written directly (base-14 Helvetica, the QR
+// modules as filled squares), so it needs no fonts or images on the machine. Text outside Latin-1 prints as `?`.
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const QR_SIZE: f32 = 220.0;
+/// Characters per line of the 10 pt URL; Helvetica averages about half the font size per character
+const URL_LINE_CHARS: usize = 90;
+
+#[derive(Debug, Clone)]
+struct LinkSheet {
+    file_name: String,
+    title: Option<String>,
+    url: String,
+    size: Option<u64>,
+    availability: String,
+    created_at: String,
+}
+
+pub(super) fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} bytes", bytes) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
+
+pub(super) fn availability(link: &PublicLinkEntry) -> String {
+    match (link.exhausted, link.max_downloads, link.remaining_uses) {
+        (true, _, _) => "No longer available (download limit reached)".to_string(),
+        (false, Some(1), _) => "Works for one download".to_string(),
+        (false, Some(_), Some(remaining)) => format!("Works for {} more download(s)", remaining),
+        (false, Some(max), None) => format!("Works for {} download(s)", max),
+        (false, None, _) => "Does not expire".to_string(),
+    }
+}
+
+pub(super) fn file_name(link: &PublicLinkEntry) -> &str {
+    link.remote_path.rsplit('/').next().unwrap_or(&link.remote_path)
+}
+
+/// Size of the newest successful upload under `remote_path`, when this device uploaded it
+pub(super) async fn uploaded_size(user_id: &str, remote_path: &str, app_handle: &AppHandle) -> Result<Option<u64>, String> {
+    Ok(read_upload_history(user_id.to_string(), app_handle.clone())
+        .await?
+        .iter()
+        .rev()
+        .find(|e| e.status == "success" && e.remote_path == remote_path)
+        .map(|e| e.file_size))
+}
+
+/// A PDF string literal: Latin-1 as octal escapes, everything else as `?`
+fn pdf_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('(');
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(out, "\\{:03o}", c as u32);
+            }
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+    out
+}
+
+fn text_line(content: &mut String, font: &str, size: f32, x: f32, y: f32, text: &str) {
+    let _ = writeln!(content, "BT /{} {} Tf {:.1} {:.1} Td {} Tj ET", font, size, x, y, pdf_text(text));
+}
+
+/// Page content: text lines from the top, then the QR code centred with the URL under it
+fn page_content(sheet: &LinkSheet) -> Result<String, String> {
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+    text_line(&mut content, "F2", 22.0, MARGIN, y, sheet.title.as_deref().unwrap_or("Shared file"));
+    y -= 34.0;
+    text_line(&mut content, "F2", 14.0, MARGIN, y, &sheet.file_name);
+    y -= 26.0;
+    let size = sheet.size.map(human_size).unwrap_or_else(|| "unknown".to_string());
+    for line in [format!("Size: {}", size), format!("Availability: {}", sheet.availability), format!("Shared: {}", sheet.created_at)] {
+        text_line(&mut content, "F1", 11.0, MARGIN, y, &line);
+        y -= 17.0;
+    }
+
+    let code = qrcode::QrCode::new(sheet.url.as_bytes()).map_err(|e| format!("Failed to build QR code: {}", e))?;
+    let width = code.width();
+    let module = QR_SIZE / width as f32;
+    let (left, top) = ((PAGE_WIDTH - QR_SIZE) / 2.0, y - 30.0);
+    content.push_str("0 g\n");
+    for (i, color) in code.to_colors().iter().enumerate() {
+        if *color == qrcode::Color::Dark {
+            let (col, row) = ((i % width) as f32, (i / width) as f32);
+            let _ = writeln!(content, "{:.2} {:.2} {:.2} {:.2} re", left + col * module, top - (row + 1.0) * module, module, module);
+        }
+    }
+    content.push_str("f\n");
+
+    y = top - QR_SIZE - 36.0;
+    text_line(&mut content, "F1", 11.0, MARGIN, y, "Scan the code or type this address into a browser:");
+    y -= 18.0;
+    let chars: Vec<char> = sheet.url.chars().collect();
+    for chunk in chars.chunks(URL_LINE_CHARS) {
+        text_line(&mut content, "F1", 10.0, MARGIN, y, &chunk.iter().collect::<String>());
+        y -= 14.0;
+    }
+    Ok(content)
+}
+
+/// The whole PDF file for `sheet`
+fn build_pdf(sheet: &LinkSheet) -> Result<Vec<u8>, String> {
+    let content = page_content(sheet)?;
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content),
+    ];
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    }
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(pdf, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref);
+    Ok(pdf.into_bytes())
+}
+
+/// Write a printable one-page PDF for the public link `link_hash` to `output_path`
+#[tauri::command]
+pub async fn export_link_sheet(user_id: String, link_hash: String, output_path: String, app_handle: AppHandle) -> Result<String, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let link = read_public_links(&user_id, &app_handle)?
+        .into_iter()
+        .find(|l| l.link_hash == link_hash)
+        .ok_or_else(|| format!("Link not found: {}", link_hash))?;
+    let url = public_link_url(&current_api_config(&app_handle), &link)?;
+    let sheet = LinkSheet {
+        file_name: file_name(&link).to_string(),
+        title: link.custom_title.clone().filter(|t| !t.trim().is_empty()),
+        url,
+        size: uploaded_size(&user_id, &link.remote_path, &app_handle).await?,
+        availability: availability(&link),
+        created_at: chrono::DateTime::parse_from_rfc3339(&link.created_at)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|_| link.created_at.clone()),
+    };
+
+    let output_path = output_paths::check_output_path(&output_path, &app_handle)?;
+    std::fs::write(&output_path, build_pdf(&sheet)?).map_err(|e| format!("Failed to write share sheet: {}", e))?;
+    println!("🖨️ Share sheet for {} written to {}", link.remote_path, output_path);
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xref_points_at_every_object() {
+        let sheet = LinkSheet {
+            file_name: "Résumé (final).pdf".to_string(),
+            title: None,
+            url: "https://example.com/publicDownload?hash=0123456789abcdef".to_string(),
+            size: Some(1536),
+            availability: "Does not expire".to_string(),
+            created_at: "2024-01-01".to_string(),
+        };
+        let pdf = String::from_utf8(build_pdf(&sheet).unwrap()).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n") && pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("(R\\351sum\\351 \\(final\\).pdf)"));
+        assert!(pdf.contains("(Size: 1.5 KB)"));
+
+        let xref: usize = pdf.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n"));
+        for (i, line) in pdf[xref..].lines().skip(3).take(6).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", i + 1)), "object {} misplaced", i + 1);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+// =============================================================================================================
+// ================================================ LOCAL PATHS ================================================
+// =============================================================================================================
+//
+// Local paths cross the frontend, the transfer queue and the history as strings. A path that is valid Unicode
+// travels as itself. One that isn't (arbitrary bytes on Unix, unpaired surrogates on Windows) travels as
+// `file://` followed by its raw bytes percent-encoded (WTF-8 on Windows), so it comes back exactly. `to_fs_path`
+// turns either form into the OS path; on Windows a path too long for MAX_PATH gets the `\\?\` extended-length
+// prefix, which also turns off the legacy parsing that trims trailing dots and spaces.
+
+const RAW_PREFIX: &str = "file://";
+/// Longest path the Win32 APIs take without the `\\?\` prefix (directories get 12 characters less)
+const MAX_PATH: usize = 260;
+const RAW_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'\\')
+    .remove(b':')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// String form of a local path that `to_fs_path` turns back into the same path
+pub fn to_wire(path: &Path) -> String {
+    match path.to_str() {
+        Some(s) => s.to_string(),
+        None => format!("{}{}", RAW_PREFIX, percent_encode(&os_bytes(path.as_os_str()), RAW_ENCODE_SET)),
+    }
+}
+
+/// The OS path for a string from `to_wire` (or any plain path)
+pub fn to_fs_path(path: &str) -> PathBuf {
+    let path = match path.strip_prefix(RAW_PREFIX) {
+        Some(raw) => {
+            let bytes: Vec<u8> = percent_decode_str(raw).collect();
+            // file:///C:/dir -> C:/dir
+            let bytes = match bytes.as_slice() {
+                [b'/', drive, b':', ..] if cfg!(windows) && drive.is_ascii_alphabetic() => bytes[1..].to_vec(),
+                _ => bytes,
+            };
+            PathBuf::from(os_string(bytes))
+        }
+        None => PathBuf::from(path),
+    };
+    if cfg!(windows) {
+        if let Some(extended) = path.to_str().and_then(extended_length) {
+            return PathBuf::from(extended);
+        }
+    }
+    path
+}
+
+/// `\\?\` form of an absolute Windows path that is too long for the plain Win32 APIs. The prefix turns off all
+/// normalization, so separators and `.`/`..` segments are resolved here.
+fn extended_length(path: &str) -> Option<String> {
+    if path.len() < MAX_PATH - 12 || path.starts_with(r"\\?\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    let (prefix, rest) = if let Some(unc) = path.strip_prefix(r"\\") {
+        (r"\\?\UNC\", unc)
+    } else {
+        let bytes = path.as_bytes();
+        if bytes.len() < 3 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' || bytes[2] != b'\\' {
+            return None;
+        }
+        (r"\\?\", path.as_str())
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for (i, part) in rest.split('\\').enumerate() {
+        match part {
+            "" | "." if i > 0 => {}
+            ".." => {
+                // never above the drive or the share
+                if parts.len() > if prefix.ends_with(r"UNC\") { 2 } else { 1 } {
+                    parts.pop();
+                }
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(format!("{}{}", prefix, parts.join("\\")))
+}
+
+/// File name of a local path for display and default remote names; undecodable bytes become U+FFFD
+pub fn file_name_lossy(path: &str) -> Option<String> {
+    let path = to_fs_path(path);
+    match path.components().next_back()? {
+        Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(unix)]
+fn os_bytes(s: &std::ffi::OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    s.as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+fn os_string(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+/// WTF-8: UTF-8 that also encodes unpaired surrogates
+#[cfg(windows)]
+fn os_bytes(s: &std::ffi::OsStr) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    let mut out = Vec::new();
+    for unit in char::decode_utf16(s.encode_wide()) {
+        match unit {
+            Ok(c) => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            Err(e) => {
+                let u = e.unpaired_surrogate();
+                out.extend_from_slice(&[0xE0 | (u >> 12) as u8, 0x80 | ((u >> 6) & 0x3F) as u8, 0x80 | (u & 0x3F) as u8]);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(windows)]
+fn os_string(bytes: Vec<u8>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    let mut wide = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let (len, init) = match b {
+            0x00..=0x7F => (1, b as u32),
+            0xC0..=0xDF => (2, (b & 0x1F) as u32),
+            0xE0..=0xEF => (3, (b & 0x0F) as u32),
+            0xF0..=0xF7 => (4, (b & 0x07) as u32),
+            _ => (1, 0xFFFD),
+        };
+        let cont = bytes.get(i + 1..i + len).filter(|c| c.len() == len - 1 && c.iter().all(|b| b & 0xC0 == 0x80));
+        let code = match cont {
+            Some(cont) if len > 1 => cont.iter().fold(init, |acc, b| (acc << 6) | (b & 0x3F) as u32),
+            Some(_) => init,
+            None => 0xFFFD,
+        };
+        i += if cont.is_some() { len } else { 1 };
+        match char::from_u32(code) {
+            Some(c) => wide.extend_from_slice(c.encode_utf16(&mut [0; 2])),
+            // a surrogate
+            None if code <= 0xFFFF => wide.push(code as u16),
+            None => wide.push(0xFFFD),
+        }
+    }
+    OsString::from_wide(&wide)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unicode_paths_travel_as_themselves() {
+        for path in ["/tmp/a.txt", "/home/ünïcödé/日本語 ファイル.txt", r"C:\Users\me\🔥.png", "relative/dir/x"] {
+            assert_eq!(to_wire(Path::new(path)), path);
+            assert_eq!(file_name_lossy(path).as_deref(), Path::new(path).file_name().and_then(|n| n.to_str()));
+        }
+    }
+
+    #[test]
+    fn long_windows_paths_get_the_extended_prefix() {
+        let long = format!(r"C:\data\{}\file.txt", "d".repeat(300));
+        assert_eq!(extended_length(&long), Some(format!(r"\\?\{}", long)));
+        let messy = format!("C:/data/./{}/../{}/x.bin", "a".repeat(150), "b".repeat(150));
+        assert_eq!(extended_length(&messy), Some(format!(r"\\?\C:\data\{}\x.bin", "b".repeat(150))));
+        let unc = format!(r"\\server\share\{}", "c".repeat(300));
+        assert_eq!(extended_length(&unc), Some(format!(r"\\?\UNC\server\share\{}", "c".repeat(300))));
+        assert_eq!(extended_length(&format!(r"\\?\{}", long)), None);
+        assert_eq!(extended_length(r"C:\short.txt"), None);
+        assert_eq!(extended_length(&"r".repeat(300)), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/caf\xe9 %41 \xff\xfe.txt"));
+        let wire = to_wire(path);
+        assert!(wire.starts_with(RAW_PREFIX));
+        assert_eq!(to_fs_path(&wire), path);
+        assert_eq!(file_name_lossy(&wire).as_deref(), Some("caf\u{FFFD} %41 \u{FFFD}\u{FFFD}.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_files_can_be_opened() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir = std::env::temp_dir().join(format!("firestarter-paths-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(std::ffi::OsStr::from_bytes(b"\x80latin1-\xe9t\xe9.txt"));
+        if std::fs::write(&path, b"exotic").is_ok() {
+            assert_eq!(std::fs::read(to_fs_path(&to_wire(&path))).unwrap(), b"exotic");
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
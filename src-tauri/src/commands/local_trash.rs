@@ -0,0 +1,71 @@
+use tauri::AppHandle;
+
+use super::is_plain_path;
+use super::settings::{current_settings, update_settings};
+
+// =============================================================================================================
+// ============================================= TRASH AFTER UPLOAD ============================================
+// =============================================================================================================
+//
+// Optional offload policy: once an upload is confirmed, the local file goes to the OS trash (not deleted), so it
+// can still be brought back. Only plain desktop paths are handled; mobile picker URIs are left alone.
+
+/// Move `path` to the trash when the policy is on and the upload is confirmed: committed, with a server hash
+/// matching ours. Without a server hash the file stays. Returns whether the file was trashed.
+pub fn trash_after_upload(path: &str, verified: Option<bool>, app_handle: &AppHandle) -> bool {
+    if !current_settings(app_handle).trash_after_upload || verified != Some(true) || !is_plain_path(path) {
+        return false;
+    }
+    match move_to_trash(path) {
+        Ok(()) => {
+            println!("🗑️ Moved {} to trash after upload", path);
+            true
+        }
+        Err(e) => {
+            println!("[TRASH] {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(desktop)]
+pub(super) fn move_to_trash(path: &str) -> Result<(), String> {
+    trash::delete(super::local_paths::to_fs_path(path)).map_err(|e| format!("Failed to move {} to trash: {}", path, e))
+}
+
+#[cfg(not(desktop))]
+pub(super) fn move_to_trash(_path: &str) -> Result<(), String> {
+    Err("Trash isn't available on this platform".to_string())
+}
+
+#[tauri::command]
+pub async fn set_trash_after_upload(enabled: bool, app_handle: AppHandle) -> Result<(), String> {
+    update_settings(&app_handle, |s| s.trash_after_upload = enabled)?;
+    Ok(())
+}
+
+/// Put the most recently trashed file that came from `local_path` back in place.
+/// The trash can only be read back on Windows and Linux (freedesktop).
+#[tauri::command]
+pub async fn restore_from_trash(local_path: String) -> Result<(), String> {
+    #[cfg(all(desktop, not(target_os = "macos")))]
+    {
+        let target = std::path::PathBuf::from(&local_path);
+        if target.exists() {
+            return Err(format!("{} already exists", local_path));
+        }
+        let items = trash::os_limited::list().map_err(|e| format!("Failed to read trash: {}", e))?;
+        let item = items
+            .into_iter()
+            .filter(|item| item.original_path() == target)
+            .max_by_key(|item| item.time_deleted)
+            .ok_or_else(|| format!("{} isn't in the trash", local_path))?;
+        trash::os_limited::restore_all([item]).map_err(|e| format!("Failed to restore {}: {}", local_path, e))?;
+        println!("♻️ Restored {} from trash", local_path);
+        Ok(())
+    }
+    #[cfg(not(all(desktop, not(target_os = "macos"))))]
+    {
+        Err(format!("Restoring {} from the trash isn't supported on this platform", local_path))
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{FileType, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::settings::update_settings;
+
+// =============================================================================================================
+// ============================================= LOCAL FOLDER WALK =============================================
+// =============================================================================================================
+//
+// Folder walks (lifecycle rules over a local folder) only pick up regular files. Symbolic links follow the
+// `symlink_policy` setting. A file with several hard links is taken once, under the first path met. Sockets,
+// FIFOs and devices are never read. Anything left out, including folders that can't be listed, is returned as
+// a skipped item so the run report can show it instead of the walk stopping.
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    /// Leave links out and list them as skipped
+    #[default]
+    Skip,
+    /// Walk into linked folders and take linked files, each real file or folder once
+    Follow,
+    /// List links with their target in the report without uploading anything
+    Record,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SkippedItem {
+    pub path: String,
+    /// "symlink" | "hard_link" | "special" | "unreadable"
+    pub kind: String,
+    pub reason: String,
+}
+
+#[derive(Default)]
+pub struct LocalWalk {
+    pub files: Vec<(PathBuf, SystemTime)>,
+    pub skipped: Vec<SkippedItem>,
+    /// Links listed under `SymlinkPolicy::Record`: (link, target)
+    pub links: Vec<(PathBuf, PathBuf)>,
+}
+
+struct Walker {
+    recursive: bool,
+    policy: SymlinkPolicy,
+    /// Real folders already walked, so followed links can't loop
+    visited_dirs: HashSet<PathBuf>,
+    /// (device, inode) of files taken so far, so a file with several names is taken once
+    seen_files: HashMap<(u64, u64), PathBuf>,
+    out: LocalWalk,
+}
+
+/// Regular files under `dir`, plus what was left out and why
+pub fn walk_local(dir: &Path, recursive: bool, policy: SymlinkPolicy) -> LocalWalk {
+    let mut walker = Walker {
+        recursive,
+        policy,
+        visited_dirs: HashSet::new(),
+        seen_files: HashMap::new(),
+        out: LocalWalk::default(),
+    };
+    if let Ok(real) = std::fs::canonicalize(dir) {
+        walker.visited_dirs.insert(real);
+    }
+    walker.walk(dir);
+    walker.out
+}
+
+impl Walker {
+    fn skip(&mut self, path: &Path, kind: &str, reason: String) {
+        self.out.skipped.push(SkippedItem { path: super::local_paths::to_wire(path), kind: kind.to_string(), reason });
+    }
+
+    fn walk(&mut self, dir: &Path) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => return self.skip(dir, "unreadable", format!("Failed to list folder: {}", e)),
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.skip(dir, "unreadable", format!("Failed to read folder entry: {}", e));
+                    continue;
+                }
+            };
+            let path = entry.path();
+            // not following links
+            match std::fs::symlink_metadata(&path) {
+                Ok(meta) if meta.file_type().is_symlink() => self.link(&path),
+                Ok(meta) => self.entry(&path, &meta, false),
+                Err(e) => self.skip(&path, "unreadable", format!("Failed to read metadata: {}", e)),
+            }
+        }
+    }
+
+    fn link(&mut self, path: &Path) {
+        let target = std::fs::read_link(path).unwrap_or_default();
+        match self.policy {
+            SymlinkPolicy::Skip => self.skip(path, "symlink", format!("Symbolic link to {}", target.display())),
+            SymlinkPolicy::Record => self.out.links.push((path.to_path_buf(), target)),
+            SymlinkPolicy::Follow => match std::fs::metadata(path) {
+                Ok(meta) => self.entry(path, &meta, true),
+                Err(e) => self.skip(path, "symlink", format!("Broken symbolic link to {}: {}", target.display(), e)),
+            },
+        }
+    }
+
+    /// `linked`: reached through a followed symbolic link
+    fn entry(&mut self, path: &Path, meta: &Metadata, linked: bool) {
+        if meta.is_dir() {
+            if !self.recursive {
+                return;
+            }
+            // a followed link back into a folder already walked
+            if let Ok(real) = std::fs::canonicalize(path) {
+                if !self.visited_dirs.insert(real) {
+                    return self.skip(path, "symlink", "Folder already included through another path".to_string());
+                }
+            }
+            self.walk(path);
+        } else if meta.is_file() {
+            if let Some(key) = file_id(meta) {
+                if let Some(first) = self.seen_files.get(&key) {
+                    let reason = format!("Same file as {}", first.display());
+                    return self.skip(path, if linked { "symlink" } else { "hard_link" }, reason);
+                }
+                self.seen_files.insert(key, path.to_path_buf());
+            }
+            self.out.files.push((path.to_path_buf(), meta.modified().unwrap_or(SystemTime::now())));
+        } else {
+            self.skip(path, "special", format!("Not a regular file ({})", special_kind(&meta.file_type())));
+        }
+    }
+}
+
+/// Identity of a file, shared by all its names
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+/// Names of one file can't be told apart without the unstable file index, so each name is taken
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(unix)]
+fn special_kind(file_type: &FileType) -> &'static str {
+    use std::os::unix::fs::FileTypeExt;
+    if file_type.is_socket() {
+        "socket"
+    } else if file_type.is_fifo() {
+        "named pipe"
+    } else if file_type.is_block_device() {
+        "block device"
+    } else if file_type.is_char_device() {
+        "character device"
+    } else {
+        "unknown type"
+    }
+}
+
+#[cfg(not(unix))]
+fn special_kind(_file_type: &FileType) -> &'static str {
+    "unknown type"
+}
+
+#[tauri::command]
+pub async fn set_symlink_policy(policy: SymlinkPolicy, app_handle: AppHandle) -> Result<(), String> {
+    update_settings(&app_handle, |s| s.symlink_policy = policy)?;
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    /// `root/walked` holding a file, a hard link to it, a nested file, a link to `root/outside.txt` and a link
+    /// back to itself
+    fn fixture() -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("firestarter-walk-{}", uuid::Uuid::new_v4().simple()));
+        let dir = root.join("walked");
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.txt"), b"a").unwrap();
+        std::fs::hard_link(dir.join("a.txt"), dir.join("a-again.txt")).unwrap();
+        std::fs::write(dir.join("nested/b.txt"), b"b").unwrap();
+        std::fs::write(root.join("outside.txt"), b"c").unwrap();
+        symlink(root.join("outside.txt"), dir.join("link.txt")).unwrap();
+        symlink(&dir, dir.join("loop")).unwrap();
+        (root, dir)
+    }
+
+    fn names(paths: impl Iterator<Item = PathBuf>) -> Vec<String> {
+        let mut names: Vec<String> = paths.map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect();
+        names.sort();
+        names
+    }
+
+    fn kinds(walk: &LocalWalk) -> Vec<&str> {
+        let mut kinds: Vec<&str> = walk.skipped.iter().map(|s| s.kind.as_str()).collect();
+        kinds.sort();
+        kinds
+    }
+
+    #[test]
+    fn links_are_skipped_by_default() {
+        let (root, dir) = fixture();
+        let walk = walk_local(&dir, true, SymlinkPolicy::Skip);
+        let files = names(walk.files.iter().map(|(p, _)| p.clone()));
+        // one of the two names of a.txt, whichever was listed first
+        assert_eq!(files.len(), 2, "{:?}", files);
+        assert!(files.contains(&"b.txt".to_string()));
+        assert_eq!(kinds(&walk), ["hard_link", "symlink", "symlink"]);
+        assert!(walk.links.is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn recorded_links_are_listed_not_taken() {
+        let (root, dir) = fixture();
+        let walk = walk_local(&dir, true, SymlinkPolicy::Record);
+        assert_eq!(names(walk.links.iter().map(|(link, _)| link.clone())), ["link.txt", "loop"]);
+        assert!(walk.links.iter().any(|(_, target)| *target == root.join("outside.txt")));
+        assert_eq!(kinds(&walk), ["hard_link"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn followed_links_are_taken_once_and_cannot_loop() {
+        let (root, dir) = fixture();
+        let walk = walk_local(&dir, true, SymlinkPolicy::Follow);
+        let files = names(walk.files.iter().map(|(p, _)| p.clone()));
+        assert_eq!(files.len(), 3, "{:?}", files);
+        assert!(files.contains(&"b.txt".to_string()) && files.contains(&"link.txt".to_string()));
+        assert_eq!(kinds(&walk), ["hard_link", "symlink"]);
+        assert!(walk.skipped.iter().any(|s| s.reason == "Folder already included through another path"));
+
+        let flat = walk_local(&dir, false, SymlinkPolicy::Follow);
+        assert!(!names(flat.files.into_iter().map(|(p, _)| p)).contains(&"b.txt".to_string()));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
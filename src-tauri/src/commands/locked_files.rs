@@ -0,0 +1,218 @@
+use tauri::AppHandle;
+
+use super::settings::update_settings;
+
+// =============================================================================================================
+// =============================================== LOCKED FILES ================================================
+// =============================================================================================================
+//
+// On Windows an app can open a file without sharing read access (Outlook with its PST, database engines), and
+// every other open then fails with a sharing violation. Opening retries a few times in case the lock is brief.
+// If it persists and `shadow_copy_locked_files` is on, the file is read from a Volume Shadow Copy of its drive:
+// it is copied out of a fresh snapshot into the app cache and the copy is deleted when the upload closes it.
+// Shadow copies need administrator rights. Otherwise the error names the app holding the file, via the Restart
+// Manager. Other platforms have advisory locks only and open files as before.
+
+/// Open `path` for reading, working around exclusive opens by other apps on Windows
+#[cfg(not(mobile))]
+pub async fn open_for_read(path: &std::path::Path, app_handle: &AppHandle) -> Result<tokio::fs::File, String> {
+    #[cfg(windows)]
+    {
+        windows::open_for_read(path, app_handle).await
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = app_handle;
+        tokio::fs::File::open(path).await.map_err(|e| format!("Failed to open file: {}", e))
+    }
+}
+
+/// Read files locked by other apps from a Volume Shadow Copy (Windows, needs administrator rights)
+#[tauri::command]
+pub async fn set_shadow_copy_locked_files(enabled: bool, app_handle: AppHandle) -> Result<(), String> {
+    update_settings(&app_handle, |s| s.shadow_copy_locked_files = enabled)?;
+    Ok(())
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+    use tauri::AppHandle;
+
+    use super::super::data_dir;
+    use super::super::settings::current_settings;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    const FILE_FLAG_DELETE_ON_CLOSE: u32 = 0x0400_0000;
+    const OPEN_ATTEMPTS: u32 = 4;
+    const RETRY_DELAY: Duration = Duration::from_millis(750);
+
+    fn is_locked(e: &std::io::Error) -> bool {
+        matches!(e.raw_os_error(), Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION))
+    }
+
+    pub async fn open_for_read(path: &Path, app_handle: &AppHandle) -> Result<tokio::fs::File, String> {
+        let mut attempt = 1;
+        let error = loop {
+            match tokio::fs::File::open(path).await {
+                Ok(file) => return Ok(file),
+                Err(e) if is_locked(&e) && attempt < OPEN_ATTEMPTS => {
+                    attempt += 1;
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+                Err(e) => break e,
+            }
+        };
+        if !is_locked(&error) {
+            return Err(format!("Failed to open file: {}", error));
+        }
+        let holders = lock_holders(path);
+        let held_by = if holders.is_empty() { "another app".to_string() } else { holders.join(", ") };
+        if !current_settings(app_handle).shadow_copy_locked_files {
+            return Err(format!(
+                "File is in use by {}: close it and retry, or enable reading locked files from a shadow copy",
+                held_by
+            ));
+        }
+        println!("🔒 '{}' is locked by {}; reading it from a shadow copy", path.display(), held_by);
+        let copy = shadow_copy(path, app_handle).await.map_err(|e| format!("File is in use by {} and {}", held_by, e))?;
+        // the copy disappears once the upload closes it
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(FILE_FLAG_DELETE_ON_CLOSE)
+            .open(&copy)
+            .map_err(|e| format!("Failed to open shadow copy: {}", e))?;
+        Ok(tokio::fs::File::from_std(file))
+    }
+
+    /// Copy `path` out of a new shadow copy of its volume into the app cache
+    async fn shadow_copy(path: &Path, app_handle: &AppHandle) -> Result<PathBuf, String> {
+        let path = std::fs::canonicalize(path).map_err(|e| format!("failed to resolve its path: {}", e))?;
+        // \\?\C:\dir\file -> C:\ and dir\file
+        let full = path.to_string_lossy().trim_start_matches(r"\\?\").to_string();
+        if full.len() < 4 || full.as_bytes()[1..3] != *b":\\" {
+            return Err("shadow copies only cover local drives".to_string());
+        }
+        let (volume, relative) = full.split_at(3);
+        let target_dir = data_dir::app_cache_dir(app_handle)?.join("shadow-copies");
+        std::fs::create_dir_all(&target_dir).map_err(|e| format!("failed to create cache directory: {}", e))?;
+        let target = target_dir.join(uuid::Uuid::new_v4().simple().to_string());
+
+        // create the snapshot, copy the file out, always remove the snapshot
+        let script = format!(
+            "$ErrorActionPreference = 'Stop'; \
+             $r = ([wmiclass]'Win32_ShadowCopy').Create('{volume}', 'ClientAccessible'); \
+             if ($r.ReturnValue -ne 0) {{ throw \"shadow copy failed with code $($r.ReturnValue)\" }}; \
+             $s = Get-WmiObject Win32_ShadowCopy | Where-Object {{ $_.ID -eq $r.ShadowID }}; \
+             try {{ [System.IO.File]::Copy($s.DeviceObject + '\\{relative}', '{target}', $true) }} finally {{ $s.Delete() }}",
+            volume = ps_quote(volume),
+            relative = ps_quote(relative),
+            target = ps_quote(&target.to_string_lossy()),
+        );
+        let output = tokio::process::Command::new("powershell.exe")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+            .await
+            .map_err(|e| format!("failed to run PowerShell: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("the shadow copy failed (administrator rights are required): {}", stderr.trim()));
+        }
+        Ok(target)
+    }
+
+    /// Escape for a single-quoted PowerShell string
+    fn ps_quote(s: &str) -> String {
+        s.replace('\'', "''")
+    }
+
+    // Restart Manager, to name the apps holding a file
+    const CCH_RM_SESSION_KEY: usize = 32;
+    const CCH_RM_MAX_APP_NAME: usize = 255;
+    const CCH_RM_MAX_SVC_NAME: usize = 63;
+    const ERROR_MORE_DATA: u32 = 234;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    #[allow(dead_code)]
+    struct RmUniqueProcess {
+        process_id: u32,
+        /// FILETIME
+        start_time: [u32; 2],
+    }
+
+    /// Only the pid and app name are read; the rest is there for the layout
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    #[allow(dead_code)]
+    struct RmProcessInfo {
+        process: RmUniqueProcess,
+        app_name: [u16; CCH_RM_MAX_APP_NAME + 1],
+        service_short_name: [u16; CCH_RM_MAX_SVC_NAME + 1],
+        application_type: i32,
+        app_status: u32,
+        ts_session_id: u32,
+        restartable: i32,
+    }
+
+    #[link(name = "rstrtmgr")]
+    extern "system" {
+        fn RmStartSession(session: *mut u32, flags: u32, session_key: *mut u16) -> u32;
+        fn RmRegisterResources(
+            session: u32,
+            n_files: u32,
+            file_names: *const *const u16,
+            n_applications: u32,
+            applications: *const RmUniqueProcess,
+            n_services: u32,
+            service_names: *const *const u16,
+        ) -> u32;
+        fn RmGetList(
+            session: u32,
+            needed: *mut u32,
+            count: *mut u32,
+            infos: *mut RmProcessInfo,
+            reboot_reasons: *mut u32,
+        ) -> u32;
+        fn RmEndSession(session: u32) -> u32;
+    }
+
+    /// Names of the apps that have `path` open, best effort
+    fn lock_holders(path: &Path) -> Vec<String> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut session = 0u32;
+        let mut key = [0u16; CCH_RM_SESSION_KEY + 1];
+        // SAFETY: every pointer refers to a live local buffer of the size the API documents; the session is ended
+        // before returning
+        unsafe {
+            if RmStartSession(&mut session, 0, key.as_mut_ptr()) != 0 {
+                return Vec::new();
+            }
+            let files = [wide.as_ptr()];
+            let mut names = Vec::new();
+            if RmRegisterResources(session, 1, files.as_ptr(), 0, std::ptr::null(), 0, std::ptr::null()) == 0 {
+                let mut needed = 0u32;
+                let mut count = 0u32;
+                let mut reasons = 0u32;
+                let rc = RmGetList(session, &mut needed, &mut count, std::ptr::null_mut(), &mut reasons);
+                if rc == ERROR_MORE_DATA && needed > 0 {
+                    let mut infos: Vec<RmProcessInfo> = vec![std::mem::zeroed(); needed as usize];
+                    count = needed;
+                    if RmGetList(session, &mut needed, &mut count, infos.as_mut_ptr(), &mut reasons) == 0 {
+                        for info in &infos[..count as usize] {
+                            let len = info.app_name.iter().position(|c| *c == 0).unwrap_or(info.app_name.len());
+                            let name = String::from_utf16_lossy(&info.app_name[..len]);
+                            names.push(format!("{} (pid {})", name, info.process.process_id));
+                        }
+                    }
+                }
+            }
+            RmEndSession(session);
+            names
+        }
+    }
+}
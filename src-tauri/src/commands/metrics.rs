@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::app_data_root;
+
+// =============================================================================================================
+// ============================================= TRANSFER METRICS ==============================================
+// =============================================================================================================
+//
+// Daily buckets (local time) are persisted to `transfer-metrics.json`; the current app session is kept in
+// memory only. Each bucket also splits transfers by hour of day so the stats page can suggest good upload times.
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct HourBucket {
+    pub transfers: u64,
+    pub bytes: u64,
+    pub secs: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TransferTotals {
+    pub uploads: u64,
+    pub downloads: u64,
+    pub failed_uploads: u64,
+    pub failed_downloads: u64,
+    pub bytes_uploaded: u64,
+    pub bytes_downloaded: u64,
+    /// Wall time spent in successful transfers
+    pub transfer_secs: f64,
+    pub by_hour: [HourBucket; 24],
+}
+
+impl TransferTotals {
+    fn add(&mut self, other: &TransferTotals) {
+        self.uploads += other.uploads;
+        self.downloads += other.downloads;
+        self.failed_uploads += other.failed_uploads;
+        self.failed_downloads += other.failed_downloads;
+        self.bytes_uploaded += other.bytes_uploaded;
+        self.bytes_downloaded += other.bytes_downloaded;
+        self.transfer_secs += other.transfer_secs;
+        for (bucket, o) in self.by_hour.iter_mut().zip(other.by_hour.iter()) {
+            bucket.transfers += o.transfers;
+            bucket.bytes += o.bytes;
+            bucket.secs += o.secs;
+        }
+    }
+
+    fn record(&mut self, upload: bool, bytes: u64, elapsed: Duration, success: bool, hour: usize) {
+        match (upload, success) {
+            (true, true) => {
+                self.uploads += 1;
+                self.bytes_uploaded += bytes;
+            }
+            (false, true) => {
+                self.downloads += 1;
+                self.bytes_downloaded += bytes;
+            }
+            (true, false) => self.failed_uploads += 1,
+            (false, false) => self.failed_downloads += 1,
+        }
+        if success {
+            self.transfer_secs += elapsed.as_secs_f64();
+            let bucket = &mut self.by_hour[hour];
+            bucket.transfers += 1;
+            bucket.bytes += bytes;
+            bucket.secs += elapsed.as_secs_f64();
+        }
+    }
+}
+
+/// Upper bounds (seconds) of the API latency histogram buckets
+pub const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    /// Cumulative counts per entry of `LATENCY_BUCKETS`
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    pub count: u64,
+    pub sum_secs: f64,
+}
+
+#[derive(Default)]
+pub struct TransferMetrics {
+    /// Daily totals keyed by local date (YYYY-MM-DD), loaded on first use
+    days: Option<BTreeMap<String, TransferTotals>>,
+    session: TransferTotals,
+    /// Since launch only, keyed by endpoint name
+    api_latency: BTreeMap<String, LatencyHistogram>,
+}
+
+pub type TransferMetricsState = Mutex<TransferMetrics>;
+pub fn new_transfer_metrics_state() -> TransferMetricsState { Mutex::new(TransferMetrics::default()) }
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HourStat {
+    pub hour: u32,
+    pub transfers: u64,
+    pub average_bytes_per_sec: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransferMetricsReport {
+    pub range: String,
+    pub totals: TransferTotals,
+    /// Successful / attempted, `None` when nothing was attempted
+    pub success_rate: Option<f64>,
+    pub average_bytes_per_sec: f64,
+    pub by_hour: Vec<HourStat>,
+}
+
+fn metrics_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join("transfer-metrics.json"))
+}
+
+fn load_days(app_handle: &AppHandle) -> BTreeMap<String, TransferTotals> {
+    metrics_file_path(app_handle)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_days(days: &BTreeMap<String, TransferTotals>, app_handle: &AppHandle) -> Result<(), String> {
+    let path = metrics_file_path(app_handle)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let json = serde_json::to_string(days).map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write metrics file: {}", e))
+}
+
+/// Record a finished (or failed) transfer in the session and daily stats
+pub fn record_transfer(app_handle: &AppHandle, upload: bool, bytes: u64, elapsed: Duration, success: bool) {
+    let now = Local::now();
+    let hour = now.hour() as usize;
+    let state = app_handle.state::<TransferMetricsState>();
+    let mut metrics = state.lock().unwrap();
+    metrics.session.record(upload, bytes, elapsed, success, hour);
+
+    let days = metrics.days.get_or_insert_with(|| load_days(app_handle));
+    days.entry(now.format("%Y-%m-%d").to_string())
+        .or_default()
+        .record(upload, bytes, elapsed, success, hour);
+    if let Err(e) = save_days(days, app_handle) {
+        println!("[METRICS] {}", e);
+    }
+}
+
+/// Record how long an API call took to answer (time to response headers)
+pub fn record_api_latency(app_handle: &AppHandle, endpoint: &str, elapsed: Duration) {
+    let secs = elapsed.as_secs_f64();
+    let state = app_handle.state::<TransferMetricsState>();
+    let mut metrics = state.lock().unwrap();
+    let histogram = metrics.api_latency.entry(endpoint.to_string()).or_default();
+    histogram.count += 1;
+    histogram.sum_secs += secs;
+    for (bucket, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+        if secs <= bound {
+            *bucket += 1;
+        }
+    }
+}
+
+pub fn api_latency_histograms(app_handle: &AppHandle) -> BTreeMap<String, LatencyHistogram> {
+    app_handle.state::<TransferMetricsState>().lock().unwrap().api_latency.clone()
+}
+
+/// Totals over every recorded day
+pub fn lifetime_totals(app_handle: &AppHandle) -> TransferTotals {
+    let state = app_handle.state::<TransferMetricsState>();
+    let mut metrics = state.lock().unwrap();
+    let days = metrics.days.get_or_insert_with(|| load_days(app_handle));
+    let mut totals = TransferTotals::default();
+    for day in days.values() {
+        totals.add(day);
+    }
+    totals
+}
+
+/// `range`: "session" | "day" | "week" | "month" | "year" | "all"
+#[tauri::command]
+pub async fn get_transfer_metrics(range: String, app_handle: AppHandle) -> Result<TransferMetricsReport, String> {
+    let days_back = match range.as_str() {
+        "session" => {
+            let totals = app_handle.state::<TransferMetricsState>().lock().unwrap().session.clone();
+            return Ok(report(range, totals));
+        }
+        "all" => return Ok(report(range, lifetime_totals(&app_handle))),
+        "day" => 0,
+        "week" => 6,
+        "month" => 29,
+        "year" => 364,
+        other => return Err(format!("Unknown metrics range: {}", other)),
+    };
+
+    let since = (Local::now() - chrono::Duration::days(days_back)).format("%Y-%m-%d").to_string();
+    let totals = {
+        let state = app_handle.state::<TransferMetricsState>();
+        let mut metrics = state.lock().unwrap();
+        let days = metrics.days.get_or_insert_with(|| load_days(&app_handle));
+        let mut totals = TransferTotals::default();
+        for (_, day) in days.range(since..) {
+            totals.add(day);
+        }
+        totals
+    };
+    Ok(report(range, totals))
+}
+
+fn report(range: String, totals: TransferTotals) -> TransferMetricsReport {
+    let succeeded = totals.uploads + totals.downloads;
+    let attempted = succeeded + totals.failed_uploads + totals.failed_downloads;
+    let bytes = totals.bytes_uploaded + totals.bytes_downloaded;
+    let by_hour = totals
+        .by_hour
+        .iter()
+        .enumerate()
+        .map(|(hour, b)| HourStat {
+            hour: hour as u32,
+            transfers: b.transfers,
+            average_bytes_per_sec: if b.secs > 0.0 { b.bytes as f64 / b.secs } else { 0.0 },
+        })
+        .collect();
+
+    TransferMetricsReport {
+        range,
+        success_rate: if attempted > 0 { Some(succeeded as f64 / attempted as f64) } else { None },
+        average_bytes_per_sec: if totals.transfer_secs > 0.0 { bytes as f64 / totals.transfer_secs } else { 0.0 },
+        by_hour,
+        totals,
+    }
+}
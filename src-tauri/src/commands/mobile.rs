@@ -0,0 +1,67 @@
+// =============================================================================================================
+// ============================================ MOBILE FILE ACCESS =============================================
+// =============================================================================================================
+//
+// On Android the dialog plugin hands back SAF `content://` URIs and on iOS the document picker hands back
+// security-scoped `file://` URLs. Neither can be opened with plain `std::fs`, so everything goes through the
+// fs plugin which resolves them to real file descriptors.
+
+use std::path::PathBuf;
+use percent_encoding::percent_decode_str;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_fs::{FilePath, FsExt, OpenOptions};
+
+/// Turn a picker result (URI or plain path) into something the fs plugin can open
+fn resolve_file_path(path: &str) -> FilePath {
+    let file_path: FilePath = path.parse().unwrap_or_else(|e| match e {});
+    match file_path {
+        // iOS document picker URLs are regular files inside a security scope
+        FilePath::Url(url) if url.scheme() == "file" => match url.to_file_path() {
+            Ok(p) => FilePath::Path(p),
+            Err(_) => FilePath::Url(url),
+        },
+        other => other,
+    }
+}
+
+/// True when the frontend passed a picker URI rather than a filesystem path
+pub fn is_uri(path: &str) -> bool {
+    path.contains("://")
+}
+
+pub fn open_for_read(path: &str, app_handle: &AppHandle) -> Result<std::fs::File, String> {
+    let mut opts = OpenOptions::new();
+    opts.read(true);
+    app_handle
+        .fs()
+        .open(resolve_file_path(path), opts)
+        .map_err(|e| format!("Failed to open file: {}", e))
+}
+
+pub fn open_for_write(path: &str, app_handle: &AppHandle) -> Result<std::fs::File, String> {
+    let mut opts = OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    app_handle
+        .fs()
+        .open(resolve_file_path(path), opts)
+        .map_err(|e| format!("Failed to create file: {}", e))
+}
+
+/// Best effort display name for a picker URI.
+/// SAF document ids look like `primary%3ADownload%2Fphoto.jpg`, so decode and keep the last segment.
+pub fn display_name(path: &str) -> Option<String> {
+    let last = path.trim_end_matches('/').rsplit('/').next()?;
+    let decoded = percent_decode_str(last).decode_utf8_lossy();
+    let name = decoded.rsplit(['/', ':']).next()?.trim();
+    if name.is_empty() { None } else { Some(name.to_string()) }
+}
+
+/// Default download location when the user did not pick one.
+/// Never points at shared external storage, which scoped storage does not let us write to directly.
+pub fn default_download_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .document_dir()
+        .or_else(|_| app_handle.path().app_data_dir())
+        .map_err(|e| format!("Failed to get download directory: {}", e))
+}
@@ -1,16 +1,105 @@
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Mutex;
 use chrono::{DateTime, Utc};
-use percent_encoding::{AsciiSet, CONTROLS};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager, State, Emitter};
 
+use encoding::QUERY_ENCODE_SET;
+use redaction::println_redacted;
+use secret::SecretString;
+
+#[cfg(mobile)]
+mod mobile;
+pub mod account;
+pub mod account_scope;
+pub mod api_trace;
+pub mod assets;
+pub mod audit;
+pub mod auth_upgrade;
+pub mod backup;
+pub mod bandwidth;
+pub mod cli;
+pub mod benchmark;
+pub mod clock_skew;
+pub mod compaction;
+pub mod config_reload;
+pub mod confirmations;
+pub mod conflicts;
+pub mod contacts;
+pub mod crash_reports;
+pub mod credential_file;
+pub mod csrf;
+pub mod data_dir;
+#[cfg(feature = "demo")]
+pub mod demo;
+pub mod destinations;
+pub mod download_cache;
+pub mod disk_space;
+pub mod dry_run;
+pub mod e2e_links;
+pub mod encoding;
+pub mod exporter;
+pub mod extensions;
+pub mod filenames;
+pub mod folders;
+pub mod gateways;
+pub mod groups;
+pub mod hashing;
+pub mod health;
+pub mod history_log;
+pub mod hooks;
+pub mod ipc_guard;
+pub mod lifecycle;
+pub mod link_batch;
+pub mod link_email;
+pub mod link_limits;
+pub mod link_previews;
+pub mod link_sheet;
+pub mod local_paths;
+pub mod local_trash;
+pub mod local_walk;
+pub mod locked_files;
+pub mod metrics;
+pub mod network;
+pub mod opener;
+pub mod output_paths;
+pub mod polling;
+pub mod receipts;
+pub mod redaction;
+pub mod remote_trash;
+pub mod retention;
+pub mod scoped_keys;
+pub mod search;
+pub mod secret;
+pub mod segmented;
+pub mod self_test;
+pub mod settings;
+pub mod shell_integration;
+pub mod shortcuts;
+pub mod sparse;
+pub mod stability;
+pub mod starred;
+pub mod tags;
+pub mod taskbar;
+pub mod storage;
+pub mod streaming;
+pub mod tiers;
+pub mod token_refresh;
+pub mod transfer_details;
+pub mod transfers;
+pub mod tuning;
+pub mod upload_limits;
+pub mod vault;
+pub mod verification;
+pub mod webhooks;
+pub mod window_state;
+pub mod workspaces;
+
 // =============================================================================================================
 // ============================================== UTIL & TYPES =================================================
 // =============================================================================================================
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UploadLogEntry {
     pub local_path: String,
     pub remote_path: String,
@@ -19,58 +108,179 @@ pub struct UploadLogEntry {
     pub blake3_hash: String,
     pub file_size: u64,
     pub timestamp: String,
+    /// Summary of the pre-upload hook run, when one is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook_output: Option<String>,
+    /// Client-generated idempotency key sent with the upload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_id: Option<String>,
+    /// Storage tier the object was uploaded to (or later moved to)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
+    /// Upload route used: "standard" | "priority"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// Cost reported by the backend, or estimated from tier pricing (PIPE)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    /// Whether the server-reported hash matched ours; unset when the server didn't report one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    /// Local file moved to the OS trash after the upload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trashed: Option<bool>,
+    /// Bytes allocated on disk, for sparse files (`file_size` is what was sent and billed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocated_size: Option<u64>,
+    /// Duration, speed, HTTP status and request id of the attempt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<transfer_details::TransferDetails>,
+    /// Workspace the file was uploaded to; `None` for the personal space
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// User labels, e.g. the backup run the file belongs to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Free-text note from the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
-/// Helper to get user data dir for a given user_id, using app_handle for base path
-fn get_user_data_dir(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
-    let base = app_handle
+impl UploadLogEntry {
+    /// Entry for `local_path` -> `remote_path` stamped now; the optional details are left for the caller to fill in
+    pub fn new(local_path: &str, remote_path: &str, status: &str, message: String) -> Self {
+        Self {
+            local_path: local_path.to_string(),
+            remote_path: remote_path.to_string(),
+            status: status.to_string(),
+            message,
+            blake3_hash: String::new(),
+            file_size: 0,
+            timestamp: Utc::now().to_rfc3339(),
+            hook_output: None,
+            upload_id: None,
+            tier: None,
+            route: None,
+            cost: None,
+            verified: None,
+            trashed: None,
+            allocated_size: None,
+            details: None,
+            workspace_id: None,
+            tags: Vec::new(),
+            note: None,
+        }
+    }
+
+    /// Stable id of the entry: the upload id, or the timestamp for entries written before uploads had one
+    pub fn entry_id(&self) -> &str {
+        self.upload_id.as_deref().unwrap_or(&self.timestamp)
+    }
+}
+
+/// Root directory for per-user credentials, history and links, as picked at startup (see `data_dir`).
+/// On mobile this is the app-private sandbox, so no scoped storage permissions are involved.
+fn app_data_root(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    if let Some(dir) = data_dir::current_data_dir(app_handle) {
+        return Ok(dir);
+    }
+    app_handle
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+/// Helper to get user data dir for a given user_id, using app_handle for base path
+fn get_user_data_dir(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    account_scope::check_user_id(user_id)?;
+    let base = app_data_root(app_handle)?;
     let user_dir = base.join(user_id);
     Ok(user_dir)
 }
 
-/// Append upload log entry to users upload log file
+/// Append upload log entry to users upload log file (written by the history writer task)
 pub fn append_upload_log(user_id: &str, entry: &UploadLogEntry, app_handle: &AppHandle) -> Result<(), String> {
-    use std::fs::{create_dir_all, OpenOptions};
-    use std::io::Write;
+    let entry = UploadLogEntry { message: redaction::redact(&entry.message), ..entry.clone() };
+    let json = serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize log entry: {}", e))?;
+    history_log::append_line(user_id, json, app_handle)
+}
 
-    let user_dir = get_user_data_dir(user_id, app_handle)?;
-    if !user_dir.exists() {
-        create_dir_all(&user_dir).map_err(|e| format!("Failed to create user dir: {}", e))?;
+/// Check a local path before uploading. Picker URIs on mobile are only resolved when opened.
+fn local_file_exists(path: &str) -> bool {
+    #[cfg(mobile)]
+    if mobile::is_uri(path) {
+        return true;
     }
+    local_paths::to_fs_path(path).exists()
+}
 
-    let log_path = user_dir.join(format!("list-upload-{}.json", user_id));
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)
-        .map_err(|e| format!("Failed to open log file: {}", e))?;
-
-    let json = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize log entry: {}", e))?;
-    file.write_all(json.as_bytes())
-        .and_then(|_| file.write_all(b"\n"))
-        .map_err(|e| format!("Failed to write log: {}", e))?;
-    Ok(())
+/// False for picker URIs on mobile, which can only be opened once through the platform
+fn is_plain_path(path: &str) -> bool {
+    #[cfg(mobile)]
+    if mobile::is_uri(path) {
+        return false;
+    }
+    let _ = path;
+    true
+}
+
+/// File name to use as the default remote name
+fn local_file_name(path: &str) -> Option<String> {
+    #[cfg(mobile)]
+    if mobile::is_uri(path) {
+        return mobile::display_name(path);
+    }
+    local_paths::file_name_lossy(path)
+}
+
+/// Open a local file for streaming, accepting SAF / document picker URIs on mobile
+async fn open_local_file(path: &str, app_handle: &AppHandle) -> Result<tokio::fs::File, String> {
+    #[cfg(mobile)]
+    {
+        mobile::open_for_read(path, app_handle).map(tokio::fs::File::from_std)
+    }
+    #[cfg(not(mobile))]
+    {
+        locked_files::open_for_read(&local_paths::to_fs_path(path), app_handle).await
+    }
+}
+
+/// Create (or truncate) a download target, creating parent dirs for plain paths
+async fn create_local_file(path: &str, app_handle: &AppHandle) -> Result<tokio::fs::File, String> {
+    #[cfg(mobile)]
+    if mobile::is_uri(path) {
+        return mobile::open_for_write(path, app_handle).map(tokio::fs::File::from_std);
+    }
+    let _ = app_handle;
+    let path = local_paths::to_fs_path(path);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    tokio::fs::File::create(&path).await.map_err(|e| format!("Failed to create file: {}", e))
+}
+
+/// Err unless the server answered the download with a success status
+fn check_download_status(status: reqwest::StatusCode) -> Result<(), String> {
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(format!("Download failed - Status: {}", status))
+    }
 }
 
-const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS
-    .add(b' ')
-    .add(b'"')
-    .add(b'#')
-    .add(b'<')
-    .add(b'>')
-    .add(b'?')
-    .add(b'`')
-    .add(b'{')
-    .add(b'}')
-    .add(b'|')
-    .add(b'\\')
-    .add(b'^')
-    .add(b'[')
-    .add(b']')
-    .add(b'%');
+/// Where a download goes when the frontend passes no output path
+fn default_download_path(file_name: &str, app_handle: &AppHandle) -> Result<String, String> {
+    #[cfg(mobile)]
+    {
+        let dir = mobile::default_download_dir(app_handle)?;
+        Ok(dir.join(file_name).to_string_lossy().to_string())
+    }
+    #[cfg(not(mobile))]
+    {
+        let dir = output_paths::default_download_dir(app_handle)?;
+        Ok(local_paths::to_wire(&dir.join(file_name)))
+    }
+}
 
 // =============================================================================================================
 // ========================================== GENERIC API PROXIES ==============================================
@@ -78,9 +288,16 @@ const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS
 
 #[tauri::command]
 pub async fn get_upload_history(user_id: String, app_handle: AppHandle) -> Result<Vec<UploadLogEntry>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    read_upload_history(user_id, app_handle).await
+}
+
+/// A user's upload history, for callers that already know which account they act for
+pub(super) async fn read_upload_history(user_id: String, app_handle: AppHandle) -> Result<Vec<UploadLogEntry>, String> {
     use std::fs::File;
     use std::io::{BufRead, BufReader};
 
+    history_log::flush(&app_handle).await;
     let user_dir = get_user_data_dir(&user_id, &app_handle)?;
     let log_path = user_dir.join(format!("list-upload-{}.json", user_id));
     if !log_path.exists() {
@@ -103,6 +320,13 @@ pub async fn get_upload_history(user_id: String, app_handle: AppHandle) -> Resul
     Ok(entries)
 }
 
+/// Histogram label for a proxied URL: its path, without host or query
+fn api_latency_label(full_url: &str) -> String {
+    reqwest::Url::parse(full_url)
+        .map(|u| u.path().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
 #[tauri::command]
 pub async fn proxy_api_get(
     url: String,
@@ -111,10 +335,11 @@ pub async fn proxy_api_get(
 ) -> Result<serde_json::Value, String> {
     use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
 
-    let api_config = ApiConfig::default();
-    let full_url = if url.starts_with("http") { url.clone() } else { format!("{}{}", api_config.api_base_url, url) };
+    let api_config = current_api_config(&app_handle);
+    let full_url = api_config.resolve_url(&url);
+    ipc_guard::check_proxy_target(&full_url, &api_config)?;
 
-    let client = reqwest::Client::builder()
+    let client = network::builder(&app_handle)
         .timeout(std::time::Duration::from_secs(60))
         .build()
         .map_err(|e| e.to_string())?;
@@ -137,27 +362,39 @@ pub async fn proxy_api_get(
     if !header_map.contains_key(AUTHORIZATION) {
         if let Some(ref creds) = credentials {
             if let Some(ref tokens) = creds.auth_tokens {
-                header_map.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).map_err(|e| e.to_string())?);
+                header_map.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token.expose())).map_err(|e| e.to_string())?);
             } else {
                 header_map.insert("X-User-Id", HeaderValue::from_str(&creds.user_id).map_err(|e| e.to_string())?);
-                header_map.insert("X-User-App-Key", HeaderValue::from_str(&creds.user_app_key).map_err(|e| e.to_string())?);
+                header_map.insert("X-User-App-Key", HeaderValue::from_str(creds.user_app_key.expose()).map_err(|e| e.to_string())?);
             }
+            workspaces::attach(&mut header_map, creds)?;
         }
     }
 
     let request_once = |hm: HeaderMap| async {
-        let resp = client.get(&full_url).headers(hm).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+        let sent = Utc::now();
+        let started = std::time::Instant::now();
+        let resp = match client.get(&full_url).headers(hm).send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                api_trace::record_failure(&app_handle, "GET", &full_url, &e.to_string(), started);
+                return Err(format!("HTTP error: {}", redaction::redact(&e.to_string())));
+            }
+        };
+        clock_skew::observe(&app_handle, resp.headers(), sent, Utc::now());
         let status = resp.status();
+        let request_id = api_trace::record(&app_handle, "GET", &full_url, status, resp.headers(), started);
         let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
         let json = serde_json::from_str::<serde_json::Value>(&text);
         if status.is_success() {
-            json.map_err(|_| format!("Success but response is not valid JSON: {}", text))
+            json.map_err(|_| api_trace::tag_error(format!("Success but response is not valid JSON: {}", text), request_id.as_deref()))
         } else {
-            Err(format!("HTTP {}: {}", status, text))
+            Err(api_trace::tag_error(format!("HTTP {}: {}", status, text), request_id.as_deref()))
         }
     };
 
-    match request_once(header_map.clone()).await {
+    let started = std::time::Instant::now();
+    let result = match request_once(header_map.clone()).await {
         Ok(val) => Ok(val),
         Err(e) if e.starts_with("HTTP 401") && credentials.as_ref().and_then(|c| c.auth_tokens.as_ref()).is_some() => {
             // refresh and retry
@@ -166,13 +403,15 @@ pub async fn proxy_api_get(
             if let Some(ref creds) = credentials {
                 if let Some(ref tokens) = creds.auth_tokens {
                     hm.remove(AUTHORIZATION);
-                    hm.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).map_err(|e| e.to_string())?);
+                    hm.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token.expose())).map_err(|e| e.to_string())?);
                 }
             }
             request_once(hm).await
         }
         Err(e) => Err(e),
-    }
+    };
+    metrics::record_api_latency(&app_handle, &api_latency_label(&full_url), started.elapsed());
+    result
 }
 
 #[tauri::command]
@@ -184,10 +423,11 @@ pub async fn proxy_api_post(
 ) -> Result<serde_json::Value, String> {
     use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 
-    let api_config = ApiConfig::default();
-    let full_url = if url.starts_with("http") { url.clone() } else { format!("{}{}", api_config.api_base_url, url) };
+    let api_config = current_api_config(&app_handle);
+    let full_url = api_config.resolve_url(&url);
+    ipc_guard::check_proxy_target(&full_url, &api_config)?;
 
-    let client = reqwest::Client::builder()
+    let client = network::builder(&app_handle)
         .timeout(std::time::Duration::from_secs(120))
         .build()
         .map_err(|e| e.to_string())?;
@@ -213,32 +453,49 @@ pub async fn proxy_api_post(
     if !header_map.contains_key(AUTHORIZATION) {
         if let Some(ref creds) = credentials {
             if let Some(ref tokens) = creds.auth_tokens {
-                header_map.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).map_err(|e| e.to_string())?);
+                header_map.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token.expose())).map_err(|e| e.to_string())?);
+                csrf::attach(&mut header_map, tokens)?;
             } else {
                 // legacy: Pipe expects creds in body for POST
                 if !effective_body.get("user_id").is_some() {
                     effective_body["user_id"] = serde_json::Value::String(creds.user_id.clone());
                 }
                 if !effective_body.get("user_app_key").is_some() {
-                    effective_body["user_app_key"] = serde_json::Value::String(creds.user_app_key.clone());
+                    effective_body["user_app_key"] = serde_json::Value::String(creds.user_app_key.expose().to_string());
                 }
             }
+            workspaces::attach(&mut header_map, creds)?;
         }
     }
 
-    async fn request_once(client: &reqwest::Client, full_url: &str, hm: HeaderMap, b: serde_json::Value) -> Result<serde_json::Value, String> {
-        let resp = client.post(full_url).headers(hm).json(&b).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    async fn request_once(
+        client: &reqwest::Client,
+        full_url: &str,
+        hm: HeaderMap,
+        b: serde_json::Value,
+        app_handle: &AppHandle,
+    ) -> Result<serde_json::Value, String> {
+        let started = std::time::Instant::now();
+        let resp = match client.post(full_url).headers(hm).json(&b).send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                api_trace::record_failure(app_handle, "POST", full_url, &e.to_string(), started);
+                return Err(format!("HTTP error: {}", redaction::redact(&e.to_string())));
+            }
+        };
         let status = resp.status();
+        let request_id = api_trace::record(app_handle, "POST", full_url, status, resp.headers(), started);
         let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
         let json = serde_json::from_str::<serde_json::Value>(&text);
         if status.is_success() {
-            json.map_err(|_| format!("Success but response is not valid JSON: {}", text))
+            json.map_err(|_| api_trace::tag_error(format!("Success but response is not valid JSON: {}", text), request_id.as_deref()))
         } else {
-            Err(format!("HTTP {}: {}", status, text))
+            Err(api_trace::tag_error(format!("HTTP {}: {}", status, text), request_id.as_deref()))
         }
     }
 
-    match request_once(&client, &full_url, header_map.clone(), effective_body.clone()).await {
+    let started = std::time::Instant::now();
+    let result = match request_once(&client, &full_url, header_map.clone(), effective_body.clone(), &app_handle).await {
         Ok(val) => Ok(val),
         Err(e) if e.starts_with("HTTP 401") && credentials.as_ref().and_then(|c| c.auth_tokens.as_ref()).is_some() => {
             // refresh and retry
@@ -247,13 +504,26 @@ pub async fn proxy_api_post(
             if let Some(ref creds) = credentials {
                 if let Some(ref tokens) = creds.auth_tokens {
                     hm.remove(AUTHORIZATION);
-                    hm.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).map_err(|e| e.to_string())?);
+                    hm.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token.expose())).map_err(|e| e.to_string())?);
+                    csrf::attach(&mut hm, tokens)?;
                 }
             }
-            request_once(&client, &full_url, hm, effective_body).await
+            request_once(&client, &full_url, hm, effective_body, &app_handle).await
+        }
+        Err(e) if csrf::is_rejection_error(&e) && credentials.as_ref().and_then(|c| c.auth_tokens.as_ref()).is_some() => {
+            // renew the CSRF token and retry once
+            csrf::renew(&client, &api_config, credentials.as_mut().unwrap(), &app_handle).await?;
+            let mut hm = header_map;
+            if let Some(tokens) = credentials.as_ref().and_then(|c| c.auth_tokens.as_ref()) {
+                hm.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token.expose())).map_err(|e| e.to_string())?);
+                csrf::attach(&mut hm, tokens)?;
+            }
+            request_once(&client, &full_url, hm, effective_body, &app_handle).await
         }
         Err(e) => Err(e),
-    }
+    };
+    metrics::record_api_latency(&app_handle, &api_latency_label(&full_url), started.elapsed());
+    result
 }
 
 // =============================================================================================================
@@ -261,12 +531,12 @@ pub async fn proxy_api_post(
 // =============================================================================================================
 
 #[tauri::command]
-pub async fn get_token_usage(period: String, credentials: Option<SavedCredentials>) -> Result<serde_json::Value, String> {
+pub async fn get_token_usage(period: String, credentials: Option<SavedCredentials>, app_handle: AppHandle) -> Result<serde_json::Value, String> {
     use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
-    let client = reqwest::Client::new();
+    let client = network::client(&app_handle);
 
     let user_id = credentials.as_ref().ok_or("user_id parameter is required")?.user_id.clone();
-    let api_config = ApiConfig::default();
+    let api_config = current_api_config(&app_handle);
     let url = format!(
         "{}{}?user_id={}&period={}&detailed=true",
         api_config.api_base_url,
@@ -278,7 +548,7 @@ pub async fn get_token_usage(period: String, credentials: Option<SavedCredential
     let mut req = client.get(&url).header(CONTENT_TYPE, "application/json");
     if let Some(creds) = credentials {
         if let Some(tokens) = creds.auth_tokens {
-            req = req.header(AUTHORIZATION, format!("Bearer {}", tokens.access_token));
+            req = req.header(AUTHORIZATION, format!("Bearer {}", tokens.access_token.expose()));
         }
     }
 
@@ -295,21 +565,21 @@ pub async fn get_token_usage(period: String, credentials: Option<SavedCredential
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct AuthTokens {
-    pub access_token: String,
-    pub refresh_token: String,
+    pub access_token: SecretString,
+    pub refresh_token: SecretString,
     pub token_type: String,
     pub expires_in: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub csrf_token: Option<String>,
+    pub csrf_token: Option<SecretString>,
 }
 
 #[tauri::command]
-pub async fn register_user(username: String, password: String, app_handle: AppHandle) -> Result<SavedCredentials, String> {
-    let api_config = ApiConfig::default();
+pub async fn register_user(username: String, password: SecretString, app_handle: AppHandle) -> Result<SavedCredentials, String> {
+    let api_config = current_api_config(&app_handle);
     let url = format!("{}{}", api_config.api_base_url, api_config.auth_register);
-    let client = reqwest::Client::new();
+    let client = network::client(&app_handle);
     let request_body = serde_json::json!({ "username": username.clone(), "password": password.clone() });
 
     let response = client.post(&url).json(&request_body).send().await.map_err(|e| format!("Register request failed: {}", e))?;
@@ -326,29 +596,92 @@ pub async fn register_user(username: String, password: String, app_handle: AppHa
 
     let creds = SavedCredentials {
         user_id,
-        user_app_key,
+        user_app_key: user_app_key.into(),
         auth_tokens: None,
         username: username_resp,
+        workspace_id: None,
+        gateway_id: None,
     };
     save_credentials(creds.clone(), app_handle).await?;
     Ok(creds)
 }
 
+/// Outcome of a login attempt. When `requires_2fa` is set, finish with `submit_2fa_code`.
+#[derive(Serialize, Debug, Clone)]
+pub struct LoginResult {
+    pub requires_2fa: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<SavedCredentials>,
+}
+
+/// Challenge id if the auth API answered with a TOTP challenge instead of tokens
+fn two_factor_challenge(json: &serde_json::Value) -> Option<String> {
+    let required = json.get("requires_2fa").and_then(|v| v.as_bool()).unwrap_or(false);
+    if !required { return None; }
+    json.get("challenge_id").and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
 #[tauri::command]
-pub async fn login_user(username: String, password: String, app_handle: AppHandle) -> Result<SavedCredentials, String> {
-    let api_config = ApiConfig::default();
+pub async fn login_user(username: String, password: SecretString, app_handle: AppHandle) -> Result<LoginResult, String> {
+    let api_config = current_api_config(&app_handle);
     let url = format!("{}{}", api_config.api_base_url, api_config.auth_login);
-    let client = reqwest::Client::new();
+    let client = network::client(&app_handle);
     let request_body = serde_json::json!({ "username": username.clone(), "password": password.clone() });
 
     let response = client.post(&url).json(&request_body).send().await.map_err(|e| format!("Login request failed: {}", e))?;
     let status = response.status();
+    let headers = response.headers().clone();
     let text = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    let parsed = serde_json::from_str::<serde_json::Value>(&text);
+
+    // a TOTP challenge may come back as 200 or 401 depending on the backend
+    if let Some(challenge_id) = parsed.as_ref().ok().and_then(two_factor_challenge) {
+        // without a verify endpoint the code could never be sent
+        api_config.optional_url(&api_config.auth_2fa_verify, "2FA verify")?;
+        println!("🔐 Login for {} requires a 2FA code", username);
+        return Ok(LoginResult { requires_2fa: true, challenge_id: Some(challenge_id), credentials: None });
+    }
     if !status.is_success() {
         return Err(format!("Login failed - Status: {}, Response: {}", status, text));
     }
 
+    let json = parsed.map_err(|e| format!("Invalid JSON: {}", e))?;
+    let mut creds = credentials_from_login_json(&json)?;
+    if let Some(tokens) = creds.auth_tokens.as_mut() {
+        csrf::after_auth(&client, &api_config, tokens, &headers, Some(&json), &app_handle).await;
+    }
+    save_credentials(creds.clone(), app_handle).await?;
+    Ok(LoginResult { requires_2fa: false, challenge_id: None, credentials: Some(creds) })
+}
+
+#[tauri::command]
+pub async fn submit_2fa_code(challenge_id: String, code: String, app_handle: AppHandle) -> Result<SavedCredentials, String> {
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.auth_2fa_verify, "2FA verify")?;
+    let client = network::client(&app_handle);
+    let request_body = serde_json::json!({ "challenge_id": challenge_id, "code": code.trim() });
+
+    let response = client.post(&url).json(&request_body).send().await.map_err(|e| format!("2FA request failed: {}", e))?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let text = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("2FA verification failed - Status: {}, Response: {}", status, text));
+    }
+
     let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let mut creds = credentials_from_login_json(&json)?;
+    if let Some(tokens) = creds.auth_tokens.as_mut() {
+        csrf::after_auth(&client, &api_config, tokens, &headers, Some(&json), &app_handle).await;
+    }
+    save_credentials(creds.clone(), app_handle).await?;
+    Ok(creds)
+}
+
+/// Build saved credentials out of a successful login / 2FA verify response
+fn credentials_from_login_json(json: &serde_json::Value) -> Result<SavedCredentials, String> {
     let user_id = json.get("user_id").and_then(|v| v.as_str()).ok_or("No user_id in response")?.to_string();
     let user_app_key = json.get("user_app_key").and_then(|v| v.as_str()).ok_or("No user_app_key in response")?.to_string();
     let username_resp = json.get("username").and_then(|v| v.as_str()).map(|s| s.to_string());
@@ -360,55 +693,104 @@ pub async fn login_user(username: String, password: String, app_handle: AppHandl
         None
     };
 
-    let creds = SavedCredentials {
+    Ok(SavedCredentials {
         user_id,
-        user_app_key,
+        user_app_key: user_app_key.into(),
         auth_tokens,
         username: username_resp,
-    };
-    save_credentials(creds.clone(), app_handle).await?;
-    Ok(creds)
+        workspace_id: None,
+        gateway_id: None,
+    })
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ExtendedAuthTokens {
-    pub access_token: String,
-    pub refresh_token: String,
-    pub token_type: String,
-    pub expires_in: i64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expires_at: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub csrf_token: Option<String>,
+/// Render an otpauth:// provisioning URI as an SVG QR code for the enrollment screen
+fn provisioning_qr_svg(uri: &str) -> Result<String, String> {
+    use qrcode::render::svg;
+
+    let code = qrcode::QrCode::new(uri.as_bytes()).map_err(|e| format!("Failed to build QR code: {}", e))?;
+    Ok(code.render::<svg::Color>().min_dimensions(200, 200).build())
+}
+
+/// Start (no code) or confirm (with code) TOTP enrollment.
+/// The response is passed through with a `qr_svg` field added when it carries a provisioning URI.
+#[tauri::command]
+pub async fn enable_2fa(code: Option<String>, app_handle: AppHandle) -> Result<serde_json::Value, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?
+        .ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.auth_2fa_enable, "2FA enable")?;
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let mut body = serde_json::json!({});
+    if let Some(c) = &code { body["code"] = serde_json::Value::String(c.trim().to_string()); }
+
+    let resp = client.post(&url).headers(bearer_headers(&credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+
+    let mut json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let uri = ["otpauth_url", "provisioning_uri", "otpauth_uri"]
+        .iter()
+        .find_map(|k| json.get(*k).and_then(|v| v.as_str()))
+        .map(|s| s.to_string());
+    if let Some(uri) = uri {
+        json["qr_svg"] = serde_json::Value::String(provisioning_qr_svg(&uri)?);
+    }
+    Ok(json)
+}
+
+#[tauri::command]
+pub async fn disable_2fa(code: String, app_handle: AppHandle) -> Result<serde_json::Value, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?
+        .ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.auth_2fa_disable, "2FA disable")?;
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let body = serde_json::json!({ "code": code.trim() });
+    let resp = client.post(&url).headers(bearer_headers(&credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SavedCredentials {
     pub user_id: String,
-    pub user_app_key: String,
+    pub user_app_key: SecretString,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auth_tokens: Option<AuthTokens>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub username: Option<String>,
+    /// Team workspace requests are scoped to; `None` is the personal space
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Regional gateway pinned for transfers; `None` picks the fastest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway_id: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
 pub struct CreateUserRequest { pub username: String }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct CreateUserResponse { pub user_id: String, pub user_app_key: String, pub solana_pubkey: String }
+pub struct CreateUserResponse { pub user_id: String, pub user_app_key: SecretString, pub solana_pubkey: String }
 
 #[derive(Serialize, Debug)]
-pub struct LoginRequest { pub username: String, pub password: String }
+pub struct LoginRequest { pub username: String, pub password: SecretString }
 
 #[derive(Serialize, Debug)]
-pub struct SetPasswordRequest { pub user_id: String, pub user_app_key: String, pub new_password: String }
+pub struct SetPasswordRequest { pub user_id: String, pub user_app_key: SecretString, pub new_password: SecretString }
 
 #[derive(Serialize, Debug)]
-pub struct RefreshTokenRequest { pub refresh_token: String }
+pub struct RefreshTokenRequest { pub refresh_token: SecretString }
 
 #[derive(Deserialize, Debug)]
-pub struct RefreshTokenResponse { pub access_token: String, pub expires_in: i64 }
+pub struct RefreshTokenResponse { pub access_token: SecretString, pub expires_in: i64 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ApiConfig {
@@ -428,9 +810,60 @@ pub struct ApiConfig {
     pub withdraw_sol: String,
     pub create_public_link: String,
     pub delete_public_link: String,
+    pub auth_sessions: Option<String>,
+    pub auth_revoke_session: Option<String>,
+    pub auth_2fa_verify: Option<String>,
+    pub auth_2fa_enable: Option<String>,
+    pub auth_2fa_disable: Option<String>,
+    pub change_tier: Option<String>,
+    pub priority_upload: Option<String>,
+    pub upload_status: Option<String>,
+    pub delete_file: Option<String>,
+    pub public_download: Option<String>,
+    pub update_public_link: Option<String>,
+    pub link_stats: Option<String>,
+    pub account_profile: Option<String>,
+    pub update_account_profile: Option<String>,
+    pub change_username: Option<String>,
+    pub delete_account: Option<String>,
+    pub email_verification_request: Option<String>,
+    pub email_verification_confirm: Option<String>,
+    pub scoped_key_create: Option<String>,
+    pub scoped_key_revoke: Option<String>,
+    pub crash_report: Option<String>,
+    pub auth_csrf: Option<String>,
+    pub workspaces: Option<String>,
+    pub gateways: Option<String>,
+    pub list_files: Option<String>,
+    pub list_trash: Option<String>,
+    pub restore_file: Option<String>,
+    pub purge_trash: Option<String>,
 }
 
 impl ApiConfig {
+    /// Full URL for an endpoint that not every backend exposes (missing or empty means unsupported)
+    pub fn optional_url(&self, endpoint: &Option<String>, name: &str) -> Result<String, String> {
+        match endpoint.as_deref() {
+            Some(path) if !path.is_empty() => Ok(format!("{}{}", self.api_base_url, path)),
+            _ => Err(format!("{} endpoint not configured", name)),
+        }
+    }
+
+    /// Absolute URL for a path or URL sent by the frontend. Its endpoint helper builds URLs on the bundled base,
+    /// so those are moved onto the base in effect (endpoints file, demo backend).
+    pub fn resolve_url(&self, url: &str) -> String {
+        if !url.starts_with("http") {
+            return format!("{}{}", self.api_base_url, url);
+        }
+        let bundled = ApiConfig::default().api_base_url;
+        match url.strip_prefix(bundled.trim_end_matches('/')) {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('?') => {
+                format!("{}{}", self.api_base_url.trim_end_matches('/'), rest)
+            }
+            _ => url.to_string(),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn load_from_file(path: std::path::PathBuf) -> Result<Self, String> {
         let data = std::fs::read_to_string(&path)
@@ -440,23 +873,46 @@ impl ApiConfig {
     }
 }
 
-impl Default for ApiConfig {
-    fn default() -> Self {
-        const JSON: &str = include_str!("../../../src/api_endpoints.json");
-        let mut config: ApiConfig = serde_json::from_str(JSON).expect("Failed to parse api_endpoints.json");
+const BUNDLED_ENDPOINTS: &str = include_str!("../../../src/api_endpoints.json");
+
+impl ApiConfig {
+    fn from_value(value: serde_json::Value) -> Result<Self, String> {
+        let mut config: ApiConfig = serde_json::from_value(value).map_err(|e| format!("Failed to parse endpoints: {}", e))?;
         // Convert get_tier_pricing to Option if empty string
         if config.get_tier_pricing.as_deref() == Some("") {
             config.get_tier_pricing = None;
         }
-        config
+        Ok(config)
+    }
+
+    /// Bundled endpoints with the keys of `overrides` (a full or partial endpoints file) applied on top
+    #[cfg_attr(feature = "demo", allow(dead_code))]
+    pub fn with_overrides(overrides: &str) -> Result<Self, String> {
+        let mut value: serde_json::Value = serde_json::from_str(BUNDLED_ENDPOINTS).expect("Failed to parse api_endpoints.json");
+        let overrides: serde_json::Value = serde_json::from_str(overrides).map_err(|e| format!("Invalid endpoints file: {}", e))?;
+        let serde_json::Value::Object(overrides) = overrides else { return Err("Endpoints file must be a JSON object".to_string()) };
+        if let serde_json::Value::Object(base) = &mut value {
+            base.extend(overrides);
+        }
+        Self::from_value(value)
+    }
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self::from_value(serde_json::from_str(BUNDLED_ENDPOINTS).expect("Failed to parse api_endpoints.json"))
+            .expect("Failed to parse api_endpoints.json")
     }
 }
 
-fn is_token_expired(auth_tokens: &AuthTokens) -> bool {
+/// Tokens are refreshed once they're this close to expiring
+const TOKEN_REFRESH_BUFFER_SECS: i64 = 5 * 60;
+
+/// `now` is server time (`clock_skew::server_now`), the clock `expires_at` is kept in
+fn is_token_expired(auth_tokens: &AuthTokens, now: DateTime<Utc>) -> bool {
     if let Some(expires_at_str) = &auth_tokens.expires_at {
         if let Ok(expires_at) = DateTime::parse_from_rfc3339(expires_at_str) {
-            let now = Utc::now();
-            let buffer = chrono::Duration::minutes(5);
+            let buffer = chrono::Duration::seconds(TOKEN_REFRESH_BUFFER_SECS);
             now + buffer >= expires_at.with_timezone(&Utc)
         } else {
             println!("⚠️ Failed to parse expires_at: {}", expires_at_str);
@@ -473,51 +929,85 @@ async fn ensure_valid_token(
     credentials: &mut SavedCredentials,
     app_handle: &AppHandle,
 ) -> Result<(), String> {
-    if let Some(ref auth_tokens) = credentials.auth_tokens {
-        if is_token_expired(auth_tokens) {
-            println!("🔄 Token expired or expiring soon, refreshing...");
-
-            let refresh_url = format!("{}{}", api_config.api_base_url, api_config.auth_refresh);
-            let req_body = RefreshTokenRequest { refresh_token: auth_tokens.refresh_token.clone() };
-
-            let response = client
-                .post(&refresh_url)
-                .json(&req_body)
-                .send()
-                .await
-                .map_err(|e| format!("Token refresh request failed: {}", e))?;
-
-            if response.status().is_success() {
-                let refresh_response: RefreshTokenResponse = response
-                    .json()
-                    .await
-                    .map_err(|e| format!("Failed to parse refresh response: {}", e))?;
-
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map_err(|e| format!("System time error: {}", e))?
-                    .as_secs() as i64;
-                let expires_at = DateTime::<Utc>::from_timestamp(now + refresh_response.expires_in, 0)
-                    .ok_or_else(|| "Invalid expiration timestamp".to_string())?;
-
-                if let Some(ref mut tokens) = credentials.auth_tokens {
-                    tokens.access_token = refresh_response.access_token;
-                    tokens.expires_in = refresh_response.expires_in;
-                    tokens.expires_at = Some(expires_at.to_rfc3339());
-                }
+    let expired = |c: &SavedCredentials| c.auth_tokens.as_ref().is_some_and(|t| is_token_expired(t, clock_skew::server_now(app_handle)));
+    if !expired(credentials) {
+        return Ok(());
+    }
+    // One refresh at a time; whoever waited picks up the tokens the other one saved instead of spending the
+    // refresh token again
+    let refresh_lock = app_handle.try_state::<token_refresh::TokenRefreshLock>();
+    let _guard = match &refresh_lock {
+        Some(lock) => Some(lock.lock().await),
+        None => None,
+    };
+    if let Some(saved) = load_credentials(app_handle.clone()).await.ok().flatten() {
+        if saved.user_id == credentials.user_id && saved.auth_tokens.is_some() && !expired(&saved) {
+            *credentials = saved;
+            return Ok(());
+        }
+    }
+    refresh_auth_tokens(client, api_config, credentials, app_handle).await
+}
 
-                save_credentials(credentials.clone(), app_handle.clone()).await
-                    .map_err(|e| format!("Failed to save refreshed credentials: {}", e))?;
-                println!("✅ Token refreshed successfully!");
-            } else {
-                let error_text = response.text().await.unwrap_or_default();
-                println!("❌ Token refresh failed: {}", error_text);
-                credentials.auth_tokens = None;
-                save_credentials(credentials.clone(), app_handle.clone()).await
-                    .map_err(|e| format!("Failed to clear invalid credentials: {}", e))?;
-                return Err("Token refresh failed, please login again".to_string());
-            }
+/// Spend the refresh token for a new access (and CSRF) token and save it; callers hold the refresh lock
+async fn refresh_auth_tokens(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &mut SavedCredentials,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    let Some(refresh_token) = credentials.auth_tokens.as_ref().map(|t| t.refresh_token.clone()) else { return Ok(()) };
+
+    println!("🔄 Token expired or expiring soon, refreshing...");
+
+    let refresh_url = format!("{}{}", api_config.api_base_url, api_config.auth_refresh);
+    let req_body = RefreshTokenRequest { refresh_token };
+
+    let sent = Utc::now();
+    let started = std::time::Instant::now();
+    let response = client.post(&refresh_url).json(&req_body).send().await.map_err(|e| {
+        api_trace::record_failure(app_handle, "POST", &refresh_url, &e.to_string(), started);
+        format!("Token refresh request failed: {}", redaction::redact(&e.to_string()))
+    })?;
+    clock_skew::observe(app_handle, response.headers(), sent, Utc::now());
+    let request_id = api_trace::record(app_handle, "POST", &refresh_url, response.status(), response.headers(), started);
+
+    if response.status().is_success() {
+        let headers = response.headers().clone();
+        let raw: serde_json::Value = response.json().await.map_err(|e| format!("Failed to parse refresh response: {}", e))?;
+        let refresh_response: RefreshTokenResponse =
+            serde_json::from_value(raw.clone()).map_err(|e| format!("Failed to parse refresh response: {}", e))?;
+
+        let now = clock_skew::server_now(app_handle).timestamp();
+        let expires_at = DateTime::<Utc>::from_timestamp(now + refresh_response.expires_in, 0)
+            .ok_or_else(|| "Invalid expiration timestamp".to_string())?;
+
+        if let Some(ref mut tokens) = credentials.auth_tokens {
+            tokens.access_token = refresh_response.access_token;
+            tokens.expires_in = refresh_response.expires_in;
+            tokens.expires_at = Some(expires_at.to_rfc3339());
+            csrf::after_auth(client, api_config, tokens, &headers, Some(&raw), app_handle).await;
+        }
+
+        save_credentials(credentials.clone(), app_handle.clone()).await
+            .map_err(|e| format!("Failed to save refreshed credentials: {}", e))?;
+        println!("✅ Token refreshed successfully!");
+        app_handle
+            .emit("token_refreshed", serde_json::json!({ "user_id": credentials.user_id, "expires_at": expires_at.to_rfc3339() }))
+            .ok();
+    } else {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        println_redacted!("❌ Token refresh failed ({}): {}", status, error_text);
+        // only a rejected refresh token ends the session; 429s and server errors leave it for a later attempt
+        if status != reqwest::StatusCode::BAD_REQUEST && status != reqwest::StatusCode::UNAUTHORIZED {
+            let message = format!("Token refresh failed - Status: {}, try again later", status);
+            return Err(api_trace::tag_error(message, request_id.as_deref()));
         }
+        credentials.auth_tokens = None;
+        save_credentials(credentials.clone(), app_handle.clone()).await
+            .map_err(|e| format!("Failed to clear invalid credentials: {}", e))?;
+        return Err(api_trace::tag_error("Token refresh failed, please login again".to_string(), request_id.as_deref()));
     }
     Ok(())
 }
@@ -525,38 +1015,276 @@ async fn ensure_valid_token(
 pub type ApiConfigState = Mutex<ApiConfig>;
 pub fn new_api_config_state(config: ApiConfig) -> ApiConfigState { Mutex::new(config) }
 
+/// Endpoints in effect: the managed config, which follows the external endpoints file
+pub fn current_api_config(app_handle: &AppHandle) -> ApiConfig {
+    app_handle
+        .try_state::<ApiConfigState>()
+        .map(|state| state.lock().unwrap().clone())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_api_config(app_handle: AppHandle) -> Result<ApiConfig, String> { Ok(current_api_config(&app_handle)) }
+
+/// Whether this build serves everything from the in-process demo backend (`--features demo`)
 #[tauri::command]
-pub async fn get_api_config() -> Result<ApiConfig, String> { Ok(ApiConfig::default()) }
+pub async fn is_demo_mode() -> Result<bool, String> { Ok(cfg!(feature = "demo")) }
 
 #[tauri::command]
-pub async fn get_config_path() -> Result<String, String> { Ok("src/api_endpoints.json".to_string()) }
+pub async fn get_config_path(app_handle: AppHandle) -> Result<String, String> {
+    Ok(config_reload::endpoints_file_path(&app_handle)?.display().to_string())
+}
 
 // =============================================================================================================
 // ============================================== FILE OPERATIONS ==============================================
 // =============================================================================================================
 
 #[tauri::command]
-pub async fn get_file_size(path: String) -> Result<u64, String> {
-    let md = tokio::fs::metadata(&path)
+pub async fn get_file_size(path: String, app_handle: AppHandle) -> Result<u64, String> {
+    let file = open_local_file(&path, &app_handle).await?;
+    let md = file
+        .metadata()
         .await
         .map_err(|e| format!("metadata error: {}", e))?;
     Ok(md.len())
 }
 
-#[tauri::command]
-pub async fn upload_file(
-    file_path: String,
-    tier: Option<String>,
-    epochs: Option<u32>,
-    remote_file_name: Option<String>,
-    id: Option<String>,
-    _config: State<'_, ApiConfigState>,
+/// Upload ids and remote names with an upload currently running
+#[derive(Default)]
+pub struct InFlightUploads {
+    ids: std::collections::HashSet<String>,
+    names: std::collections::HashSet<String>,
+}
+
+pub type InFlightUploadsState = Mutex<InFlightUploads>;
+pub fn new_in_flight_uploads_state() -> InFlightUploadsState { Mutex::new(InFlightUploads::default()) }
+
+/// Marks an upload as running until dropped, rejecting a second one for the same id or remote name
+struct InFlightUpload {
     app_handle: AppHandle,
+    upload_id: String,
+    file_name: String,
+}
+
+impl InFlightUpload {
+    fn begin(app_handle: &AppHandle, upload_id: &str, file_name: &str) -> Result<Self, String> {
+        let state = app_handle.state::<InFlightUploadsState>();
+        let mut in_flight = state.lock().unwrap();
+        if in_flight.ids.contains(upload_id) || in_flight.names.contains(file_name) {
+            return Err(format!("'{}' is already being uploaded", file_name));
+        }
+        in_flight.ids.insert(upload_id.to_string());
+        in_flight.names.insert(file_name.to_string());
+        Ok(Self { app_handle: app_handle.clone(), upload_id: upload_id.to_string(), file_name: file_name.to_string() })
+    }
+}
+
+impl Drop for InFlightUpload {
+    fn drop(&mut self) {
+        let state = self.app_handle.state::<InFlightUploadsState>();
+        let mut in_flight = state.lock().unwrap();
+        in_flight.ids.remove(&self.upload_id);
+        in_flight.names.remove(&self.file_name);
+    }
+}
+
+/// Whether a running upload is writing to `file_name`
+fn is_upload_in_flight(file_name: &str, app_handle: &AppHandle) -> bool {
+    app_handle.state::<InFlightUploadsState>().lock().unwrap().names.contains(file_name)
+}
+
+/// Number of uploads currently running
+fn uploads_in_flight(app_handle: &AppHandle) -> usize {
+    app_handle.state::<InFlightUploadsState>().lock().unwrap().ids.len()
+}
+
+/// Successful history entry for `upload_id`, if that upload already went through
+async fn find_completed_upload(user_id: &str, upload_id: &str, app_handle: &AppHandle) -> Option<UploadLogEntry> {
+    read_upload_history(user_id.to_string(), app_handle.clone())
+        .await
+        .ok()?
+        .into_iter()
+        .find(|e| e.status == "success" && e.upload_id.as_deref() == Some(upload_id))
+}
+
+/// ("priority" | "standard", url): the fast-lane route when requested and the backend exposes one
+fn upload_route(api_config: &ApiConfig, priority: bool) -> (&'static str, String) {
+    let priority_url = if priority {
+        api_config
+            .optional_url(&api_config.priority_upload, "Priority upload")
+            .map_err(|e| println_redacted!("[UPLOAD] {}, using the standard route", e))
+            .ok()
+    } else {
+        None
+    };
+    match priority_url {
+        Some(url) => ("priority", url),
+        None => ("standard", format!("{}{}", api_config.api_base_url, api_config.upload)),
+    }
+}
+
+/// Upload `body` as `remote_name` and return the server's response. For objects the app keeps for itself (link
+/// previews) and stdin uploads: no hooks, history, receipts, webhooks or local trash, and an existing object is
+/// replaced.
+pub(super) async fn upload_bytes(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    remote_name: &str,
+    body: impl Into<reqwest::Body>,
 ) -> Result<String, String> {
+    use percent_encoding::utf8_percent_encode;
+
+    encoding::check_remote_name(remote_name)?;
+    let (_, upload_url) = upload_route(api_config, false);
+    let url = format!("{}?file_name={}", upload_url, utf8_percent_encode(remote_name, QUERY_ENCODE_SET));
+    let response = workspaces::scope(client.post(&url), credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Upload request failed: {}", redaction::redact(&e.to_string())))?;
+    let status = response.status();
+    let text = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Upload failed - Status: {}, Response: {}", status, text));
+    }
+    Ok(text)
+}
+
+const HASH_WORKER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+const COMMIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const COMMIT_POLL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How waiting for the server to commit an upload ended
+#[derive(PartialEq)]
+enum CommitState {
+    Committed,
+    /// Still processing when the wait timed out; the upload was accepted but isn't confirmed yet
+    Pending,
+}
+
+/// Poll the processing-status endpoint, when the backend has one, until the object is committed.
+/// `on_status` gets every intermediate server status. Client errors and non-JSON answers end the wait at once;
+/// a server still processing after `COMMIT_POLL_TIMEOUT` gives `CommitState::Pending`.
+async fn wait_for_commit(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    file_name: &str,
+    upload_id: &str,
+    on_status: impl Fn(&str),
+) -> Result<CommitState, String> {
+    use percent_encoding::utf8_percent_encode;
+
+    let Ok(status_url) = api_config.optional_url(&api_config.upload_status, "Upload status") else {
+        return Ok(CommitState::Committed);
+    };
+    let url = format!(
+        "{}?file_name={}&upload_id={}",
+        status_url,
+        utf8_percent_encode(file_name, QUERY_ENCODE_SET),
+        utf8_percent_encode(upload_id, QUERY_ENCODE_SET)
+    );
+    let started = std::time::Instant::now();
+    while started.elapsed() < COMMIT_POLL_TIMEOUT {
+        let polled = workspaces::scope(client.get(&url), credentials)
+            .header("X-User-Id", &credentials.user_id)
+            .header("X-User-App-Key", credentials.user_app_key.expose())
+            .send()
+            .await;
+        // network errors, 5xx, 429 and 404s (not registered yet) just mean "keep waiting"
+        match polled {
+            Err(e) => println_redacted!("[UPLOAD] Status check failed: {}", e),
+            Ok(resp) if resp.status() == reqwest::StatusCode::NOT_FOUND || resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {}
+            Ok(resp) if resp.status().is_client_error() => {
+                return Err(format!("Upload status check failed - Status: {}", resp.status()));
+            }
+            Ok(resp) if resp.status().is_success() => {
+                let json = resp
+                    .json::<serde_json::Value>()
+                    .await
+                    .map_err(|e| format!("Upload status check returned an unreadable response: {}", e))?;
+                let server_status = json.get("status").and_then(|v| v.as_str()).unwrap_or("processing").to_lowercase();
+                match server_status.as_str() {
+                    "committed" | "complete" | "completed" | "done" | "stored" => return Ok(CommitState::Committed),
+                    "failed" | "error" => {
+                        let reason = json.get("message").or_else(|| json.get("error")).and_then(|v| v.as_str()).unwrap_or("unknown error");
+                        return Err(format!("Upload failed during server processing: {}", reason));
+                    }
+                    other => on_status(other),
+                }
+            }
+            Ok(resp) => println!("[UPLOAD] Status check failed - Status: {}", resp.status()),
+        }
+        tokio::time::sleep(COMMIT_POLL_INTERVAL).await;
+    }
+    println!("[UPLOAD] '{}' still processing after {:?}, not waiting any longer", file_name, COMMIT_POLL_TIMEOUT);
+    Ok(CommitState::Pending)
+}
+
+/// Cost the backend reports in the upload response, otherwise an estimate from the tier price
+async fn upload_cost(response_text: &str, tier: Option<&str>, file_size: u64, app_handle: &AppHandle) -> Option<f64> {
+    let reported = serde_json::from_str::<serde_json::Value>(response_text)
+        .ok()
+        .and_then(|json| ["cost", "total_cost", "tokens_charged"].iter().find_map(|k| json.get(*k)?.as_f64()));
+    if reported.is_some() {
+        return reported;
+    }
+    estimate_upload_cost(tier, file_size, app_handle).await
+}
+
+/// Tier price per GB times size; uploads without a tier go to "normal". Prices come from the pricing cache, so
+/// a batch of uploads doesn't fetch them once per file.
+async fn estimate_upload_cost(tier: Option<&str>, file_size: u64, app_handle: &AppHandle) -> Option<f64> {
+    let pricing = tiers::cached_pricing(app_handle).await.ok()?;
+    let price = tiers::tier_price(&pricing, tier.unwrap_or("normal"))?;
+    Some(price * file_size as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+/// How to upload a file. Everything is optional: by default the file goes to the default folder under its local
+/// name (or the name template), at the default tier, replacing a remote file of the same name.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct UploadOptions {
+    pub tier: Option<String>,
+    pub epochs: Option<u32>,
+    pub remote_file_name: Option<String>,
+    /// Remote folder; `None` uses the default folder, "" the root
+    pub remote_dir: Option<String>,
+    /// "overwrite" | "rename" | "abort" when the remote name is taken
+    pub on_conflict: Option<String>,
+    /// Use the priority upload route when the backend has one
+    #[serde(default)]
+    pub priority: bool,
+    /// Transfer group this upload reports to
+    pub group_id: Option<String>,
+    /// Create a public link once the upload succeeds (reported via `upload_link_created`)
+    #[serde(default)]
+    pub create_link: bool,
+    /// Id the progress events carry
+    pub id: Option<String>,
+    /// Idempotency key; callers that retry pass the same one
+    pub upload_id: Option<String>,
+}
+
+/// Upload `file_path`. To see what an upload would do without running it, use `dry_run::preview_upload`.
+#[tauri::command]
+pub async fn upload_file(file_path: String, options: UploadOptions, app_handle: AppHandle) -> Result<String, String> {
+    Ok(upload(file_path, options, app_handle).await?.message)
+}
+
+/// A finished upload
+pub struct UploadedFile {
+    /// Name the file was stored under, after the default folder, template and conflict handling
+    pub remote_name: String,
+    pub message: String,
+}
+
+/// Upload `file_path` as `upload_file` does, returning the name it was stored under
+pub(super) async fn upload(file_path: String, options: UploadOptions, app_handle: AppHandle) -> Result<UploadedFile, String> {
     use futures_util::TryStreamExt;
     use percent_encoding::utf8_percent_encode;
-    use reqwest::Client;
-    use std::path::Path;
     use tauri::Emitter;
     use tokio_util::io::ReaderStream;
 
@@ -565,45 +1293,61 @@ pub async fn upload_file(
         .await
         .map_err(|e| format!("No credentials found: {}", e))?;
     let mut credentials = credentials_opt.ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
-    let client = Client::new();
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
 
     // Ensure token valid
     ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
 
+    let UploadOptions { tier, epochs, remote_file_name, remote_dir, on_conflict, priority, group_id, create_link, id, upload_id } = options;
+    // Idempotency key: callers that retry pass the same id, otherwise a fresh one per upload
+    let resumed_id = upload_id.filter(|u| !u.trim().is_empty());
+    let upload_id = resumed_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let group = groups::GroupMemberGuard::start(
+        &app_handle,
+        group_id.as_deref(),
+        &upload_id,
+        remote_file_name.as_deref().unwrap_or(&file_path),
+    );
+    if resumed_id.is_some() {
+        if let Some(done) = find_completed_upload(&credentials.user_id, &upload_id, &app_handle).await {
+            let result = Ok(format!("File '{}' was already uploaded", done.remote_path));
+            if let Some(group) = group {
+                group.finish(&result);
+            }
+            return result.map(|message| UploadedFile { remote_name: done.remote_path, message });
+        }
+    }
+    let retry_count = match &resumed_id {
+        Some(_) => transfer_details::previous_attempts(&credentials.user_id, &upload_id, &app_handle).await,
+        None => 0,
+    };
+
     // Validate file
-    let path = Path::new(&file_path);
-    if !path.exists() {
+    if !local_file_exists(&file_path) {
         let entry = UploadLogEntry {
-            local_path: file_path.clone(),
-            remote_path: "".to_string(),
-            status: "failed".to_string(),
-            message: format!("File not found: {}", file_path),
-            blake3_hash: "".to_string(),
-            file_size: 0,
-            timestamp: Utc::now().to_rfc3339(),
+            upload_id: Some(upload_id.clone()),
+            tier: tier.clone(),
+            workspace_id: credentials.workspace_id.clone(),
+            ..UploadLogEntry::new(&file_path, "", "failed", format!("File not found: {}", file_path))
         };
         let _ = append_upload_log(&credentials.user_id, &entry, &app_handle);
         return Err(format!("File not found: {}", file_path));
     }
 
-    // Remote name
-    let file_name = if let Some(ref custom) = remote_file_name {
-        if !custom.trim().is_empty() {
-            custom.as_str()
-        } else {
-            path.file_name()
-                .and_then(|n| n.to_str())
-                .ok_or("Invalid file name")?
-        }
-    } else {
-        path.file_name()
-            .and_then(|n| n.to_str())
-            .ok_or("Invalid file name")?
+    // Remote name, inside `remote_dir` or the default folder
+    let file_name = match remote_file_name.as_deref() {
+        Some(custom) if !custom.trim().is_empty() => custom.to_string(),
+        _ => destinations::default_remote_name(&file_path, &app_handle)?,
     };
+    let file_name = folders::resolve_remote_name(&file_name, remote_dir.as_deref(), &app_handle)?;
+    encoding::check_remote_name(&file_name)?;
+    let file_name = conflicts::resolve_upload_conflict(&client, &api_config, &credentials, file_name, on_conflict.as_deref(), &app_handle).await?;
+    let _in_flight = InFlightUpload::begin(&app_handle, &upload_id, &file_name)?;
 
-    let encoded_name = utf8_percent_encode(file_name, QUERY_ENCODE_SET);
-    let upload_url = format!("{}{}", api_config.api_base_url, api_config.upload);
+    let encoded_name = utf8_percent_encode(&file_name, QUERY_ENCODE_SET);
+    let transfer_config = gateways::transfer_config(&api_config, &credentials, &app_handle).await;
+    let (route, upload_url) = upload_route(&transfer_config, priority);
 
     let mut params = vec![format!("file_name={}", encoded_name)];
     if let Some(t) = &tier {
@@ -614,74 +1358,247 @@ pub async fn upload_file(
     }
     let full_url = format!("{}?{}", upload_url, params.join("&"));
 
-    // Open file for streaming
-    let file = tokio::fs::File::open(&file_path)
-        .await
-        .map_err(|e| format!("Failed to open file: {}", e))?;
-    let file_size = std::fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+    // Optional user hook, which may hand back a processed copy to upload instead
+    let pre_hook = match hooks::run_pre_upload_hook(&file_path, &file_name, &app_handle).await {
+        Ok(hook) => hook,
+        Err(e) => {
+            let entry = UploadLogEntry {
+                hook_output: Some(e.clone()),
+                upload_id: Some(upload_id.clone()),
+                tier: tier.clone(),
+                workspace_id: credentials.workspace_id.clone(),
+                ..UploadLogEntry::new(&file_path, &file_name, "failed", e.clone())
+            };
+            let _ = append_upload_log(&credentials.user_id, &entry, &app_handle);
+            return Err(e);
+        }
+    };
+    // the hook's output is removed when `pre_hook` is dropped at the end of this function
+    let upload_path = match pre_hook.as_ref().and_then(|h| h.replacement.as_ref()) {
+        Some(replacement) => replacement.path().to_string(),
+        None => file_path.clone(),
+    };
+
+    // Optional pre-hash, sent so the server can check the body against it
+    let expected_hash = if settings::current_settings(&app_handle).send_expected_hash {
+        Some(verification::hash_local_file(&upload_path, &app_handle).await?.0)
+    } else {
+        None
+    };
 
-    let uploaded: u64 = 0;
-    let hasher = Arc::new(Mutex::new(blake3::Hasher::new()));
+    // Open file for streaming
+    let file = open_local_file(&upload_path, &app_handle).await?;
+    let metadata = file.metadata().await.ok();
+    let file_size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+    // Holes are sent (and billed) as zeros
+    let sparse = metadata.as_ref().filter(|_| is_plain_path(&upload_path)).and_then(|m| sparse::sparse_sizes(&upload_path, m));
+    if let Some(sizes) = sparse {
+        println!("⚠️ '{}' is sparse: {} bytes on disk, {} bytes to upload", file_name, sizes.allocated, sizes.apparent);
+        let _ = app_handle.emit(
+            "upload_sparse_warning",
+            serde_json::json!({
+                "id": id,
+                "file_name": file_name,
+                "apparent": sizes.apparent,
+                "allocated": sizes.allocated
+            }),
+        );
+    }
+    if let Some(group) = &group {
+        group.describe(&file_name, file_size);
+    }
+
+    // Hashing runs on a worker; the stream only counts bytes and hands over refcounted chunks
+    let (hash_tx, hash_worker) = hashing::spawn_hasher();
+    let mut uploaded: u64 = 0;
 
     // Progress stream
     let app_handle_clone = app_handle.clone();
-    let uploaded_arc = Arc::new(Mutex::new(uploaded));
-    let hasher_clone = hasher.clone();
-    let uploaded_clone = uploaded_arc.clone();
     let id_clone = id.clone();
-
-    let stream = ReaderStream::with_capacity(file, 1024 * 1024).inspect_ok(move |chunk| {
-        if let Ok(mut h) = hasher_clone.lock() {
-            h.update(&chunk);
-        }
-        if let Ok(mut up) = uploaded_clone.lock() {
-            *up += chunk.len() as u64;
+    let group_clone = group.as_ref().map(|g| g.group_id().to_string());
+    let upload_id_clone = upload_id.clone();
+    let mut throttle = tuning::ProgressThrottle::new(&app_handle);
+
+    let buffer_size = tuning::upload_buffer_size(file_size, &app_handle);
+    // Stops the body if the file is still being written
+    let expected_size = metadata.as_ref().map(|m| m.len());
+    let (body, size_change) = stability::guard_size(ReaderStream::with_capacity(file, buffer_size), expected_size, &file_name);
+    let pacer = app_handle.clone();
+    let stream = body
+        .and_then(move |chunk| {
+            let pacer = pacer.clone();
+            async move {
+                bandwidth::pace(&pacer, chunk.len()).await;
+                Ok(chunk)
+            }
+        })
+        .inspect_ok(move |chunk| {
+            let _ = hash_tx.send(chunk.clone());
+            uploaded += chunk.len() as u64;
             let percent = if file_size > 0 {
-                ((*up as f64 / file_size as f64) * 100.0).min(100.0)
+                ((uploaded as f64 / file_size as f64) * 100.0).min(100.0)
             } else {
                 0.0
             };
+            if !throttle.should_emit(percent, uploaded >= file_size) {
+                return;
+            }
             let _ = app_handle_clone.emit(
                 "upload_progress",
                 serde_json::json!({
                     "id": id_clone,
                     "percent": percent as u32,
-                    "uploaded": *up,
-                    "total": file_size
+                    "uploaded": uploaded,
+                    "total": file_size,
+                    // the last chunk is read before the server has answered
+                    "phase": if uploaded >= file_size { "processing" } else { "uploading" }
                 }),
             );
-        }
-    });
+            if let Some(group_id) = &group_clone {
+                groups::set_progress(&app_handle_clone, group_id, &upload_id_clone, uploaded);
+            }
+            if let Some(job_id) = &id_clone {
+                taskbar::job_progress(&app_handle_clone, job_id, uploaded, file_size);
+            }
+        });
 
     // Build request: always use X-User-Id and X-User-App-Key, never JWT
-    let request = client
+    let mut request = client
         .post(&full_url)
         .header("X-User-Id", &credentials.user_id)
-        .header("X-User-App-Key", &credentials.user_app_key);
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .header("Idempotency-Key", &upload_id);
+    request = workspaces::scope(request, &credentials);
+    if let Some(hash) = &expected_hash {
+        request = request.header(verification::EXPECTED_HASH_HEADER, hash);
+    }
 
-    let response = request
-        .body(reqwest::Body::wrap_stream(stream))
-        .send()
-        .await
-        .map_err(|e| format!("Upload request failed: {}", e))?;
+    let started = std::time::Instant::now();
+    let response = match request.body(reqwest::Body::wrap_stream(stream)).send().await {
+        Ok(r) => r,
+        Err(e) => {
+            metrics::record_transfer(&app_handle, true, 0, started.elapsed(), false);
+            api_trace::record_failure(&app_handle, "POST", &full_url, &e.to_string(), started);
+            let result = match size_change.lock().unwrap().take() {
+                Some(changed) => Err(format!("Upload aborted: {}", changed)),
+                None => Err(format!("Upload request failed: {}", redaction::redact(&e.to_string()))),
+            };
+            webhooks::notify_transfer(&app_handle, "upload", &file_name, file_size, &result);
+            if let Some(group) = group {
+                group.finish(&result);
+            }
+            return result.map(|message| UploadedFile { remote_name: file_name, message });
+        }
+    };
 
     let status = response.status();
+    let response_headers = response.headers().clone();
+    let request_id = api_trace::record(&app_handle, "POST", &full_url, status, &response_headers, started);
     let response_text = response.text().await.unwrap_or_default();
-    let blake3_hash = hasher.lock().unwrap().finalize().to_hex().to_string();
+    let elapsed = started.elapsed();
+    // the body (and with it the sender) is dropped once the exchange completes; without a hash nothing is compared
+    let blake3_hash = match tokio::time::timeout(HASH_WORKER_TIMEOUT, hash_worker).await {
+        Ok(Ok(report)) => {
+            hashing::record(&app_handle, &report, elapsed);
+            Some(report.hash)
+        }
+        _ => {
+            println!("[UPLOAD] Hash worker for '{}' did not finish; skipping hash checks", file_name);
+            None
+        }
+    };
 
+    // Compare what we sent with what the server (and the pre-hash) saw
+    let server_hash = verification::server_reported_hash(&response_headers, &response_text);
+    let mismatch = match (&blake3_hash, &server_hash, &expected_hash) {
+        (Some(sent), Some(server), _) if server != sent => {
+            Some(format!("Hash mismatch: server {} / client {}", server, sent))
+        }
+        (Some(sent), _, Some(expected)) if expected != sent => {
+            Some(format!("File changed during upload: expected {} / sent {}", expected, sent))
+        }
+        _ => None,
+    };
+
+    // The body is fully sent, but the server may still be processing the object
+    let mut still_processing = false;
+    let committed = if let (true, Some(mismatch)) = (status.is_success(), &mismatch) {
+        Err(mismatch.clone())
+    } else if status.is_success() {
+        let progress_handle = app_handle.clone();
+        let progress_id = id.clone();
+        wait_for_commit(&client, &api_config, &credentials, &file_name, &upload_id, move |server_status| {
+            let _ = progress_handle.emit(
+                "upload_progress",
+                serde_json::json!({
+                    "id": progress_id,
+                    "percent": 100,
+                    "uploaded": file_size,
+                    "total": file_size,
+                    "phase": "processing",
+                    "server_status": server_status
+                }),
+            );
+        })
+        .await
+        .map(|state| still_processing = state == CommitState::Pending)
+    } else if status == reqwest::StatusCode::PAYLOAD_TOO_LARGE {
+        let limit = upload_limits::server_limit(&response_headers, &response_text);
+        let _ = app_handle.emit(
+            "upload_too_large",
+            serde_json::json!({ "id": id, "file_name": file_name, "file_size": file_size, "limit": limit }),
+        );
+        Err(api_trace::tag_error(upload_limits::too_large_message(&file_name, file_size, limit), request_id.as_deref()))
+    } else {
+        Err(api_trace::tag_error(format!("Upload failed - Status: {}, Response: {}", status, response_text), request_id.as_deref()))
+    };
+    let succeeded = committed.is_ok();
+    let verified = match (&blake3_hash, &server_hash) {
+        (Some(sent), Some(server)) if status.is_success() => Some(server == sent),
+        _ => None,
+    };
+    let trashed = succeeded && local_trash::trash_after_upload(&file_path, verified, &app_handle);
+    metrics::record_transfer(&app_handle, true, file_size, elapsed, succeeded);
+    let cost = if succeeded { upload_cost(&response_text, tier.as_deref(), file_size, &app_handle).await } else { None };
+
+    let entry_status = match (succeeded, &mismatch) {
+        (true, _) => "success",
+        (false, Some(_)) if status.is_success() => "hash_mismatch",
+        (false, _) => "failed",
+    };
+    let message = match &committed {
+        Err(e) if status.is_success() || status == reqwest::StatusCode::PAYLOAD_TOO_LARGE => e.clone(),
+        Err(_) => transfer_details::failure_summary(status, &response_text),
+        Ok(()) if still_processing => {
+            format!("Uploaded {} bytes in {:.1} s; the server was still processing it", file_size, elapsed.as_secs_f64())
+        }
+        Ok(()) => format!("Uploaded {} bytes in {:.1} s", file_size, elapsed.as_secs_f64()),
+    };
     let entry = UploadLogEntry {
-        local_path: file_path.clone(),
-        remote_path: file_name.to_string(),
-        status: if status.is_success() { "success" } else { "failed" }.to_string(),
-        message: response_text.clone(),
-        blake3_hash: blake3_hash.clone(),
+        blake3_hash: blake3_hash.unwrap_or_default(),
         file_size,
-        timestamp: Utc::now().to_rfc3339(),
+        hook_output: pre_hook.as_ref().map(|h| h.run.summary()),
+        upload_id: Some(upload_id.clone()),
+        tier: tier.clone(),
+        route: Some(route.to_string()),
+        cost,
+        verified,
+        trashed: trashed.then_some(true),
+        allocated_size: sparse.map(|s| s.allocated),
+        details: Some(
+            transfer_details::TransferDetails::new(file_size, elapsed, retry_count)
+                .with_response(status, request_id.clone(), &response_text, &app_handle),
+        ),
+        workspace_id: credentials.workspace_id.clone(),
+        ..UploadLogEntry::new(&file_path, &file_name, entry_status, message)
     };
 
     let _ = append_upload_log(&credentials.user_id, &entry, &app_handle);
 
-    if status.is_success() {
+    let result = if succeeded {
+        if let Err(e) = receipts::issue(&credentials.user_id, &entry, &response_text, &app_handle) {
+            println!("[RECEIPT] Failed to issue receipt for {}: {}", file_name, e);
+        }
         // Emit progress final (100%)
         let _ = app_handle.emit(
             "upload_progress",
@@ -689,7 +1606,9 @@ pub async fn upload_file(
                 "id": id,
                 "percent": 100,
                 "uploaded": file_size,
-                "total": file_size
+                "total": file_size,
+                "phase": "done",
+                "completed": true
             }),
         );
 
@@ -708,14 +1627,33 @@ pub async fn upload_file(
                 }),
             )
             .ok();
+        destinations::record_upload(&file_name, &app_handle);
+        if create_link {
+            // a failed link doesn't fail the upload
+            match request_public_link(&client, &api_config, &credentials, &file_name, LinkOptions::default()).await {
+                Ok(link) => {
+                    if let Err(e) = update_public_links(&credentials.user_id, &app_handle, |links| links.push(link.clone())) {
+                        println!("[LINK] Failed to save link for {}: {}", file_name, e);
+                    }
+                    app_handle.emit("upload_link_created", serde_json::json!({ "id": id, "link": link })).ok();
+                }
+                Err(e) => println_redacted!("[LINK] Failed to create link for {}: {}", file_name, e),
+            }
+        }
 
-        Ok(format!("File '{}' uploaded successfully", file_name))
+        if still_processing {
+            Ok(format!("File '{}' uploaded; the server is still processing it", file_name))
+        } else {
+            Ok(format!("File '{}' uploaded successfully", file_name))
+        }
     } else {
-        Err(format!(
-            "Upload failed - Status: {}, Response: {}",
-            status, response_text
-        ))
+        Err(committed.err().unwrap_or_default())
+    };
+    webhooks::notify_transfer(&app_handle, "upload", &file_name, file_size, &result);
+    if let Some(group) = group {
+        group.finish(&result);
     }
+    result.map(|message| UploadedFile { remote_name: file_name, message })
 }
 
 #[tauri::command]
@@ -726,28 +1664,89 @@ pub async fn download_file(
     app_handle: AppHandle,
 ) -> Result<String, String> {
     use percent_encoding::utf8_percent_encode;
-    use reqwest::Client;
     use std::path::Path;
 
     let credentials_opt = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?;
     let mut credentials = credentials_opt.ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
-    let client = Client::new();
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
 
     ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
 
     let encoded_name = utf8_percent_encode(&file_name, QUERY_ENCODE_SET);
-    let download_url = format!("{}{}", api_config.api_base_url, api_config.download);
+    let transfer_config = gateways::transfer_config(&api_config, &credentials, &app_handle).await;
+    let download_url = format!("{}{}", transfer_config.api_base_url, transfer_config.download);
     let full_url = format!("{}?file_name={}", download_url, encoded_name);
 
-    println!("📥 Downloading {} from {}", file_name, download_url);
+    // the remote name can hold characters (or be a name) the local filesystem won't take
+    let local_path = filenames::local_path_for(&file_name);
+    let (final_path, renamed) = if output_path.is_empty() {
+        (default_download_path(&local_path, &app_handle)?, local_path != file_name)
+    } else {
+        let path = Path::new(&output_path);
+        if path.is_dir() || output_path.ends_with('/') || output_path.ends_with('\\') {
+            (format!("{}/{}", output_path.trim_end_matches('/').trim_end_matches('\\'), local_path), local_path != file_name)
+        } else {
+            (output_path, false)
+        }
+    };
+    let final_path = output_paths::check_output_path(&final_path, &app_handle)?;
+    let local_name = local_file_name(&final_path).unwrap_or_else(|| local_path.clone());
+    let saved_as = |message: String| if renamed { format!("{} (saved as '{}')", message, local_name) } else { message };
+
+    let (known_hash, current_hash) = if download_cache::enabled(&app_handle) {
+        let known_hash = download_cache::known_hash(&credentials.user_id, &file_name, &app_handle).await;
+        let head = workspaces::scope(client.head(&full_url), &credentials)
+            .header("X-User-Id", &credentials.user_id)
+            .header("X-User-App-Key", credentials.user_app_key.expose());
+        let current_hash = download_cache::current_hash(head, known_hash.as_deref()).await;
+        (known_hash, current_hash)
+    } else {
+        (None, None)
+    };
+    if let Some(bytes) = match &current_hash {
+        Some(hash) => download_cache::restore(hash, &final_path, &app_handle).await,
+        None => None,
+    } {
+        let payload = serde_json::json!({
+            "file_name": file_name,
+            "downloaded": bytes,
+            "total": bytes,
+            "percent": 100.0,
+            "output_path": final_path,
+            "local_name": local_name,
+            "cached": true
+        });
+        app_handle.emit("download_progress", payload).ok();
+        let result = Ok(saved_as(format!("File '{}' downloaded to '{}'", file_name, final_path)));
+        hooks::run_post_download_hook(&final_path, &file_name, true, &app_handle).await;
+        webhooks::notify_transfer(&app_handle, "download", &file_name, bytes, &result);
+        opener::emit_download_completed(&app_handle, &file_name, &final_path);
+        return result;
+    }
+
+    println_redacted!("📥 Downloading {} from {}", file_name, download_url);
 
-    let request = client.get(&full_url)
+    let request = workspaces::scope(client.get(&full_url), &credentials)
         .header("X-User-Id", &credentials.user_id)
-        .header("X-User-App-Key", &credentials.user_app_key);
+        .header("X-User-App-Key", credentials.user_app_key.expose());
 
-    let response = request.send().await.map_err(|e| format!("Download request failed: {}", e))?;
-    let _status = response.status();
+    let started = std::time::Instant::now();
+    let response = match request.send().await {
+        Ok(r) => r,
+        Err(e) => {
+            metrics::record_transfer(&app_handle, false, 0, started.elapsed(), false);
+            api_trace::record_failure(&app_handle, "GET", &full_url, &e.to_string(), started);
+            return Err(format!("Download request failed: {}", redaction::redact(&e.to_string())));
+        }
+    };
+    metrics::record_api_latency(&app_handle, "download", started.elapsed());
+    api_trace::record(&app_handle, "GET", &full_url, response.status(), response.headers(), started);
+    // an error page must not be saved, cached or reported as the file
+    if let Err(e) = check_download_status(response.status()) {
+        metrics::record_transfer(&app_handle, false, 0, started.elapsed(), false);
+        return Err(e);
+    }
 
     use futures_util::StreamExt;
     use tokio::io::AsyncWriteExt;
@@ -757,96 +1756,300 @@ pub async fn download_file(
         total_size = Some(len);
     }
 
-    let mut stream = response.bytes_stream();
+    let accepts_ranges = response
+        .headers()
+        .get(reqwest::header::ACCEPT_RANGES)
+        .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"bytes"));
+    let expected_hash = verification::server_reported_hash(response.headers(), "").or(current_hash).or(known_hash);
     let mut downloaded: u64 = 0;
-    let _file_bytes: Vec<u8> = Vec::new();
 
-    let final_path = if output_path.is_empty() {
-        file_name.clone()
-    } else {
-        let path = Path::new(&output_path);
-        if path.is_dir() || output_path.ends_with('/') || output_path.ends_with('\\') {
-            format!("{}/{}", output_path.trim_end_matches('/').trim_end_matches('\\'), file_name)
-        } else {
-            output_path
+    let file = create_local_file(&final_path, &app_handle).await?;
+    let mut preallocated = Ok(());
+    if let Some(len) = total_size.filter(|len| *len > 0) {
+        // best effort: SAF streams and some filesystems don't support it, but a full disk stops here
+        preallocated = tuning::preallocate(&file, len).await;
+        if let Err(e) = &preallocated {
+            println_redacted!("[DOWNLOAD] {}", e);
         }
-    };
-
-    if let Some(parent) = Path::new(&final_path).parent() {
-        tokio::fs::create_dir_all(parent).await.map_err(|e| format!("Failed to create directory: {}", e))?;
     }
+    let fsync = settings::current_settings(&app_handle).download_fsync;
+    let segments = tuning::download_segments(&app_handle);
 
-    let mut file = tokio::fs::File::create(&final_path).await.map_err(|e| format!("Failed to create file: {}", e))?;
-
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Download chunk error: {}", e))?;
-        file.write_all(&chunk).await.map_err(|e| format!("Failed to write chunk: {}", e))?;
-        downloaded += chunk.len() as u64;
-
-        // Emit progress event
+    // Emit progress event
+    let mut throttle = tuning::ProgressThrottle::new(&app_handle);
+    let mut emit_progress = |downloaded: u64| {
         let percent = if let Some(size) = total_size {
             ((downloaded as f64 / size as f64) * 100.0).min(100.0)
         } else {
             0.0
         };
+        if !throttle.should_emit(percent, total_size.is_some_and(|size| downloaded >= size)) {
+            return;
+        }
         let payload = serde_json::json!({
             "file_name": file_name,
             "downloaded": downloaded,
             "total": total_size,
             "percent": percent,
-            "output_path": final_path
+            "output_path": final_path,
+            "local_name": local_name
         });
         app_handle.emit("download_progress", payload).ok();
-    }
+    };
 
-    if downloaded > 0 {
-        println!("✅ Download successful: saved to {}", final_path);
-        Ok(format!("File '{}' downloaded to '{}'", file_name, final_path))
-    } else {
-        Err("No file data received".to_string())
+    let streamed: Result<(), String> = match total_size {
+        _ if preallocated.as_ref().is_err_and(|e| disk_space::is_disk_full(e)) => preallocated,
+        // large files from a Range-capable server: parallel segments into the preallocated file
+        Some(total) if accepts_ranges && segments > 1 && total >= segmented::MIN_SEGMENTED_BYTES && is_plain_path(&final_path) => {
+            drop(response);
+            drop(file);
+            async {
+                let segmented = {
+                    let mut track = |done: u64| {
+                        downloaded = done;
+                        emit_progress(done);
+                    };
+                    segmented::download(&client, &full_url, &credentials, &final_path, total, segments, &mut track, &app_handle).await
+                };
+                downloaded = segmented?;
+                if fsync {
+                    let file = tokio::fs::File::open(local_paths::to_fs_path(&final_path))
+                        .await
+                        .map_err(|e| format!("Failed to open file: {}", e))?;
+                    file.sync_all().await.map_err(|e| format!("Failed to sync file: {}", e))?;
+                }
+                Ok(())
+            }
+            .await
+        }
+        _ => {
+            let mut stream = response.bytes_stream();
+            let mut file = tokio::io::BufWriter::with_capacity(tuning::download_write_buffer(&app_handle), file);
+            async {
+                while let Some(chunk) = stream.next().await {
+                    let chunk = chunk.map_err(|e| format!("Download chunk error: {}", e))?;
+                    bandwidth::pace(&app_handle, chunk.len()).await;
+                    file.write_all(&chunk).await.map_err(|e| disk_space::write_error("Failed to write chunk", e))?;
+                    downloaded += chunk.len() as u64;
+                    emit_progress(downloaded);
+                }
+                file.flush().await.map_err(|e| disk_space::write_error("Failed to write file", e))?;
+                let file = file.get_ref();
+                // a short body leaves preallocated space at the end
+                if total_size.is_some_and(|len| len != downloaded) {
+                    file.set_len(downloaded).await.map_err(|e| format!("Failed to truncate file: {}", e))?;
+                }
+                if fsync {
+                    file.sync_all().await.map_err(|e| format!("Failed to sync file: {}", e))?;
+                }
+                Ok(())
+            }
+            .await
+        }
+    };
+    metrics::record_transfer(&app_handle, false, downloaded, started.elapsed(), streamed.is_ok() && downloaded > 0);
+    // a partial or preallocated file is never a usable download, and takes space the user may have to free
+    if (streamed.is_err() || downloaded == 0) && is_plain_path(&final_path) {
+        let _ = tokio::fs::remove_file(local_paths::to_fs_path(&final_path)).await;
     }
+    let result = match streamed {
+        Err(e) if disk_space::is_disk_full(&e) => {
+            let full = disk_space::DiskFull {
+                file_name: file_name.clone(),
+                output_path: final_path.clone(),
+                required_bytes: disk_space::required_bytes(total_size, downloaded),
+                available_bytes: disk_space::available_bytes(&final_path),
+            };
+            println!("💾 {}", e);
+            app_handle.emit("download_disk_full", &full).ok();
+            Err(full.message())
+        }
+        Err(e) => Err(e),
+        Ok(()) if downloaded > 0 => {
+            println!("✅ Download successful: saved to {}", final_path);
+            destinations::record_download(&final_path, &app_handle);
+            if is_plain_path(&final_path) {
+                let (path, app) = (final_path.clone(), app_handle.clone());
+                tokio::spawn(async move {
+                    if let Err(e) = download_cache::store(&path, expected_hash.as_deref(), &app).await {
+                        println!("[CACHE] {}", e);
+                    }
+                });
+            }
+            Ok(saved_as(format!("File '{}' downloaded to '{}'", file_name, final_path)))
+        }
+        Ok(()) => Err("No file data received".to_string()),
+    };
+    hooks::run_post_download_hook(&final_path, &file_name, result.is_ok(), &app_handle).await;
+    webhooks::notify_transfer(&app_handle, "download", &file_name, total_size.unwrap_or(downloaded), &result);
+    if result.is_ok() {
+        opener::emit_download_completed(&app_handle, &file_name, &final_path);
+    }
+    result
+}
+
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FilePreview {
+    pub file_name: String,
+    pub content: String,
+    pub bytes_read: u64,
+    pub total_size: Option<u64>,
+    pub truncated: bool,
+    pub content_type: Option<String>,
 }
 
+const PREVIEW_DEFAULT_BYTES: u64 = 64 * 1024;
+const PREVIEW_MAX_BYTES: u64 = 1024 * 1024;
 
+/// Decode a byte prefix as text without splitting a multi-byte character at the cut
+fn preview_text(bytes: &[u8]) -> Result<String, String> {
+    if bytes.contains(&0) {
+        return Err("File looks binary, preview is only available for text files".to_string());
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text.to_string()),
+        // incomplete sequence at the very end: just drop it
+        Err(e) if e.error_len().is_none() => Ok(String::from_utf8_lossy(&bytes[..e.valid_up_to()]).to_string()),
+        Err(_) => Ok(String::from_utf8_lossy(bytes).to_string()),
+    }
+}
+
+/// Fetch only the first `max_bytes` of a remote text/CSV/JSON file
 #[tauri::command]
-pub async fn user_login(
-    username: String,
-    password: String,
+pub async fn get_file_preview(
+    file_name: String,
+    max_bytes: Option<u64>,
     app_handle: AppHandle,
-) -> Result<String, String> {
-    let _users = list_saved_users(app_handle.clone()).await?;
-    let api_config = ApiConfig::default();
+) -> Result<FilePreview, String> {
+    use futures_util::StreamExt;
+    use percent_encoding::utf8_percent_encode;
+    use reqwest::header::{CONTENT_RANGE, CONTENT_TYPE, RANGE};
+
+    let limit = max_bytes.unwrap_or(PREVIEW_DEFAULT_BYTES).clamp(1, PREVIEW_MAX_BYTES);
+
+    let credentials_opt = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?;
+    let mut credentials = credentials_opt.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let encoded_name = utf8_percent_encode(&file_name, QUERY_ENCODE_SET);
+    let full_url = format!("{}{}?file_name={}", api_config.api_base_url, api_config.download, encoded_name);
+
+    let response = workspaces::scope(client.get(&full_url), &credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .header(RANGE, format!("bytes=0-{}", limit - 1))
+        .send()
+        .await
+        .map_err(|e| format!("Preview request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Preview failed - Status: {}, Response: {}", status, text));
+    }
+
+    // 206 carries the full size in Content-Range; a 200 means the server ignored Range
+    let total_size = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit('/').next())
+        .and_then(|v| v.parse::<u64>().ok())
+        .or_else(|| if status.as_u16() == 200 { response.content_length() } else { None });
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+
+    let mut stream = response.bytes_stream();
+    let mut buf: Vec<u8> = Vec::with_capacity(limit as usize);
+    let mut more_data = false;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Preview chunk error: {}", e))?;
+        let room = limit as usize - buf.len();
+        if chunk.len() > room {
+            buf.extend_from_slice(&chunk[..room]);
+            more_data = true;
+            break;
+        }
+        buf.extend_from_slice(&chunk);
+        if buf.len() as u64 >= limit {
+            break;
+        }
+    }
+
+    let bytes_read = buf.len() as u64;
+    let truncated = more_data || total_size.map(|t| t > bytes_read).unwrap_or(bytes_read >= limit);
+    Ok(FilePreview {
+        file_name,
+        content: preview_text(&buf)?,
+        bytes_read,
+        total_size,
+        truncated,
+        content_type,
+    })
+}
+
+/// Password login. The raw response comes back too, for fields `AuthTokens` doesn't keep.
+async fn login_with_password(username: &str, password: SecretString, app_handle: &AppHandle) -> Result<(AuthTokens, serde_json::Value), String> {
+    let api_config = current_api_config(app_handle);
     let url = format!("{}{}", api_config.api_base_url, api_config.auth_login);
 
-    println!("🔄 Attempting login for user: {} to URL: {}", username, url);
+    println_redacted!("🔄 Attempting login for user: {} to URL: {}", username, url);
 
-    let client = reqwest::Client::new();
-    let request_body = LoginRequest { username: username.clone(), password };
+    let client = network::client(app_handle);
+    let request_body = LoginRequest { username: username.to_string(), password };
 
-    let response = client.post(&url).json(&request_body).send().await.map_err(|e| format!("Request failed: {}", e))?;
+    let sent = Utc::now();
+    let started = std::time::Instant::now();
+    let response = client.post(&url).json(&request_body).send().await.map_err(|e| {
+        api_trace::record_failure(app_handle, "POST", &url, &e.to_string(), started);
+        format!("Request failed: {}", redaction::redact(&e.to_string()))
+    })?;
+    clock_skew::observe(app_handle, response.headers(), sent, Utc::now());
+    let request_id = api_trace::record(app_handle, "POST", &url, response.status(), response.headers(), started);
     println!("📡 Login response status: {}", response.status());
 
     if response.status().is_success() {
-        let mut auth_tokens: AuthTokens = response.json().await.map_err(|e| format!("Failed to parse response: {}", e))?;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| format!("System time error: {}", e))?.as_secs() as i64;
+        let headers = response.headers().clone();
+        let raw: serde_json::Value = response.json().await.map_err(|e| format!("Failed to parse response: {}", e))?;
+        let mut auth_tokens: AuthTokens = serde_json::from_value(raw.clone()).map_err(|e| format!("Failed to parse response: {}", e))?;
+        let now = clock_skew::server_now(app_handle).timestamp();
         let expires_at = DateTime::<Utc>::from_timestamp(now + auth_tokens.expires_in, 0).ok_or_else(|| "Invalid expiration timestamp".to_string())?;
         auth_tokens.expires_at = Some(expires_at.to_rfc3339());
+        csrf::after_auth(&client, &api_config, &mut auth_tokens, &headers, Some(&raw), app_handle).await;
         println!("✅ Login successful, token expires in: {} seconds ({})", auth_tokens.expires_in, expires_at);
-        serde_json::to_string(&auth_tokens).map_err(|e| format!("Failed to serialize auth tokens: {}", e))
+        Ok((auth_tokens, raw))
     } else {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        println!("❌ Login failed - Status: {}, Response: {}", status, error_text);
-        Err(format!("Login failed. Status: {}, Error: {}", status, error_text))
+        println_redacted!("❌ Login failed - Status: {}, Response: {}", status, error_text);
+        Err(api_trace::tag_error(format!("Login failed. Status: {}, Error: {}", status, error_text), request_id.as_deref()))
     }
 }
 
 #[tauri::command]
-pub async fn test_api_connection(base_url: String) -> Result<String, String> {
+pub async fn user_login(
+    username: String,
+    password: SecretString,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let _users = list_saved_users(app_handle.clone()).await?;
+    let (auth_tokens, _) = login_with_password(&username, password, &app_handle).await?;
+    serde_json::to_string(&auth_tokens).map_err(|e| format!("Failed to serialize auth tokens: {}", e))
+}
+
+#[tauri::command]
+pub async fn test_api_connection(base_url: String, app_handle: AppHandle) -> Result<String, String> {
     let test_url = format!("{}/health", base_url.trim_end_matches('/'));
-    println!("Testing connection to: {}", test_url);
+    println_redacted!("Testing connection to: {}", test_url);
 
-    let client = reqwest::Client::new();
+    let client = network::client(&app_handle);
     match client.get(&test_url).send().await {
         Ok(response) => {
             let status = response.status();
@@ -887,10 +2090,10 @@ pub async fn test_api_connection(base_url: String) -> Result<String, String> {
 pub async fn set_user_password(
     state: tauri::State<'_, ApiConfigState>,
     user_id: String,
-    user_app_key: String,
-    new_password: String,
+    user_app_key: SecretString,
+    new_password: SecretString,
+    app_handle: AppHandle,
 ) -> Result<String, String> {
-    use reqwest::Client;
     use serde_json::json;
 
     println!("[set_user_password] Called for user_id: {}", user_id);
@@ -898,14 +2101,14 @@ pub async fn set_user_password(
         let config = state.lock().unwrap();
         format!("{}{}", config.api_base_url, config.auth_set_password)
     };
-    println!("[set_user_password] Endpoint: {}", endpoint);
+    println_redacted!("[set_user_password] Endpoint: {}", endpoint);
     let payload = json!({
         "user_id": user_id,
         "user_app_key": user_app_key,
         "new_password": new_password
     });
-    println!("[set_user_password] Payload: {}", payload);
-    let client = Client::new();
+    println_redacted!("[set_user_password] Payload: {}", payload);
+    let client = network::client(&app_handle);
     let res = client
         .post(&endpoint)
         .header("Content-Type", "application/json")
@@ -913,7 +2116,7 @@ pub async fn set_user_password(
         .send()
         .await
         .map_err(|e| {
-            println!("[set_user_password] Request error: {}", e);
+            println_redacted!("[set_user_password] Request error: {}", e);
             format!("Request error: {}", e)
         })?;
     let status = res.status();
@@ -921,13 +2124,13 @@ pub async fn set_user_password(
         .text()
         .await
         .map_err(|e| {
-            println!("[set_user_password] Read body error: {}", e);
+            println_redacted!("[set_user_password] Read body error: {}", e);
             format!("Read body error: {}", e)
         })?;
     println!("[set_user_password] Response status: {}", status);
-    println!("[set_user_password] Response body: {}", text);
+    println_redacted!("[set_user_password] Response body: {}", text);
     if !status.is_success() {
-        println!("[set_user_password] Failed to set password. HTTP {}: {}", status.as_u16(), text);
+        println_redacted!("[set_user_password] Failed to set password. HTTP {}: {}", status.as_u16(), text);
         return Err(format!(
             "Failed to set password. HTTP {}: {}",
             status.as_u16(),
@@ -945,13 +2148,11 @@ pub async fn save_credentials(credentials: SavedCredentials, app_handle: AppHand
     use std::fs;
     println!("🔄 Saving credentials for user: {}", credentials.user_id);
 
-    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    let user_dir = app_data_dir.join(&credentials.user_id);
+    let user_dir = get_user_data_dir(&credentials.user_id, &app_handle)?;
     fs::create_dir_all(&user_dir).map_err(|e| format!("Failed to create user directory: {}", e))?;
 
-    let credentials_path = user_dir.join(format!("{}.json", credentials.user_id));
-    let json_content = serde_json::to_string_pretty(&credentials).map_err(|e| format!("Failed to serialize credentials: {}", e))?;
-    fs::write(&credentials_path, json_content).map_err(|e| format!("Failed to write credentials file: {}", e))?;
+    let credentials_path = credential_file::credentials_path(&user_dir, &credentials.user_id);
+    credential_file::write(&credentials_path, &credentials)?;
 
     println!("✅ Credentials saved to: {:?}", credentials_path);
     Ok(())
@@ -961,7 +2162,7 @@ pub async fn save_credentials(credentials: SavedCredentials, app_handle: AppHand
 pub async fn load_credentials(app_handle: AppHandle) -> Result<Option<SavedCredentials>, String> {
     use std::fs;
 
-    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    let app_data_dir = app_data_root(&app_handle)?;
     if !app_data_dir.exists() { return Ok(None); }
 
     let mut latest_credentials: Option<SavedCredentials> = None;
@@ -971,17 +2172,15 @@ pub async fn load_credentials(app_handle: AppHandle) -> Result<Option<SavedCrede
         for entry in entries.flatten() {
             if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
                 let user_id = entry.file_name().to_string_lossy().to_string();
-                let credentials_path = entry.path().join(format!("{}.json", user_id));
+                let credentials_path = credential_file::credentials_path(&entry.path(), &user_id);
 
                 if credentials_path.exists() {
                     if let Ok(metadata) = credentials_path.metadata() {
                         if let Ok(modified) = metadata.modified() {
                             if modified > latest_time {
-                                if let Ok(content) = fs::read_to_string(&credentials_path) {
-                                    if let Ok(credentials) = serde_json::from_str::<SavedCredentials>(&content) {
-                                        latest_credentials = Some(credentials);
-                                        latest_time = modified;
-                                    }
+                                if let Ok(credentials) = credential_file::read(&credentials_path) {
+                                    latest_credentials = Some(credentials);
+                                    latest_time = modified;
                                 }
                             }
                         }
@@ -996,12 +2195,22 @@ pub async fn load_credentials(app_handle: AppHandle) -> Result<Option<SavedCrede
 }
 
 #[tauri::command]
-pub async fn clear_credentials(user_id: String, app_handle: AppHandle) -> Result<(), String> {
-    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    let user_dir = app_data_dir.join(&user_id);
+pub async fn clear_credentials(user_id: String, ipc_nonce: String, webview: tauri::Webview, app_handle: AppHandle) -> Result<(), String> {
+    ipc_guard::check_sensitive("clear_credentials", &webview, &ipc_nonce, &app_handle)?;
+    if !account_scope::is_known(&user_id, &app_handle)? {
+        return Err(format!("No saved account {}", user_id));
+    }
+    remove_user_data(user_id, app_handle).await
+}
+
+/// Delete a user's folder: saved credentials, history and everything else kept for them
+pub(super) async fn remove_user_data(user_id: String, app_handle: AppHandle) -> Result<(), String> {
+    let user_dir = get_user_data_dir(&user_id, &app_handle)?;
 
     if user_dir.exists() {
-        std::fs::remove_dir_all(&user_dir).map_err(|e| format!("Failed to remove user directory: {}", e))?;
+        let removed = std::fs::remove_dir_all(&user_dir).map_err(|e| format!("Failed to remove user directory: {}", e));
+        audit::record(&app_handle, "credentials_clear", &user_id, Some(&user_id), None, &removed);
+        removed?;
         println!("✅ User credentials cleared for: {}", user_id);
     }
     Ok(())
@@ -1011,7 +2220,7 @@ pub async fn clear_credentials(user_id: String, app_handle: AppHandle) -> Result
 pub async fn list_saved_users(app_handle: AppHandle) -> Result<Vec<SavedCredentials>, String> {
     use std::fs;
 
-    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    let app_data_dir = app_data_root(&app_handle)?;
     let mut users = Vec::new();
 
     if !app_data_dir.exists() { return Ok(users); }
@@ -1020,12 +2229,10 @@ pub async fn list_saved_users(app_handle: AppHandle) -> Result<Vec<SavedCredenti
         for entry in entries.flatten() {
             if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
                 let user_id = entry.file_name().to_string_lossy().to_string();
-                let credentials_path = entry.path().join(format!("{}.json", user_id));
+                let credentials_path = credential_file::credentials_path(&entry.path(), &user_id);
                 if credentials_path.exists() {
-                    if let Ok(content) = fs::read_to_string(&credentials_path) {
-                        if let Ok(credentials) = serde_json::from_str::<SavedCredentials>(&content) {
-                            users.push(credentials);
-                        }
+                    if let Ok(credentials) = credential_file::read(&credentials_path) {
+                        users.push(credentials);
                     }
                 }
             }
@@ -1043,34 +2250,88 @@ pub async fn list_saved_users(app_handle: AppHandle) -> Result<Vec<SavedCredenti
 
 #[tauri::command]
 pub async fn refresh_token(_config: State<'_, ApiConfigState>, app_handle: AppHandle) -> Result<String, String> {
-    use reqwest::Client;
 
     let credentials_opt = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?;
     let mut credentials = credentials_opt.ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
-    let client = Client::new();
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
 
     ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
     Ok("Token refreshed successfully".to_string())
 }
 
+/// Bearer, CSRF and workspace headers for JWT-authenticated endpoints
+fn bearer_headers(credentials: &SavedCredentials) -> Result<reqwest::header::HeaderMap, String> {
+    use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+    let tokens = credentials.auth_tokens.as_ref().ok_or("No valid auth tokens")?;
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token.expose())).map_err(|e| e.to_string())?);
+    csrf::attach(&mut headers, tokens)?;
+    workspaces::attach(&mut headers, credentials)?;
+    Ok(headers)
+}
+
+// === SESSIONS / DEVICES ===
+
+#[tauri::command]
+pub async fn list_active_sessions(app_handle: AppHandle) -> Result<serde_json::Value, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?
+        .ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.auth_sessions, "Sessions")?;
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let resp = client.get(&url).headers(bearer_headers(&credentials)?).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))
+}
+
+#[tauri::command]
+pub async fn revoke_session(session_id: String, app_handle: AppHandle) -> Result<String, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?
+        .ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let url = api_config.optional_url(&api_config.auth_revoke_session, "Revoke session")?;
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let body = serde_json::json!({ "session_id": session_id });
+    let resp = client.post(&url).headers(bearer_headers(&credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+
+    println!("✅ Revoked session {}", session_id);
+    Ok(format!("Session {} revoked", session_id))
+}
+
 // =============================================================================================================
 // ============================================== WALLET/TOKEN ENDPOINTS =======================================
 // =============================================================================================================
 
+fn tier_pricing_url(api_config: &ApiConfig) -> Result<String, String> {
+    match &api_config.get_tier_pricing {
+        Some(endpoint) => Ok(format!("{}{}", api_config.api_base_url, endpoint)),
+        None => Err("Tier pricing endpoint not configured".to_string()),
+    }
+}
+
 #[tauri::command]
-pub async fn get_tier_pricing(_app_handle: AppHandle) -> Result<serde_json::Value, String> {
-    let api_config = ApiConfig::default();
-    let url = if let Some(endpoint) = &api_config.get_tier_pricing {
-        format!("{}{}", api_config.api_base_url, endpoint)
-    } else {
-        return Err("Tier pricing endpoint not configured".to_string());
-    };
-    let client = reqwest::Client::new();
+pub async fn get_tier_pricing(app_handle: AppHandle) -> Result<serde_json::Value, String> {
+    let url = tier_pricing_url(&current_api_config(&app_handle))?;
+    let client = network::client(&app_handle);
     let resp = client.get(&url).send().await.map_err(|e| format!("HTTP error: {}", e))?;
     let status = resp.status();
     let json: serde_json::Value = resp.json().await.map_err(|e| format!("Invalid JSON: {}", e))?;
-    if status.is_success() { Ok(json) } else { Err(format!("HTTP {}: {}", status, json)) }
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status, json));
+    }
+    tiers::remember_pricing(&app_handle, &url, &json);
+    Ok(json)
 }
 
 #[tauri::command]
@@ -1078,14 +2339,14 @@ pub async fn get_tier_pricing(_app_handle: AppHandle) -> Result<serde_json::Valu
  pub async fn check_wallet(app_handle: AppHandle) -> Result<serde_json::Value, String> {
     let credentials_opt = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?;
     let credentials = credentials_opt.ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
+    let api_config = current_api_config(&app_handle);
     let url = format!("{}{}", api_config.api_base_url, api_config.check_wallet);
-    let client = reqwest::Client::new();
+    let client = network::client(&app_handle);
     let mut req = client.post(&url);
-    if let Some(tokens) = credentials.auth_tokens {
-        req = req.header("Authorization", format!("Bearer {}", tokens.access_token));
+    if credentials.auth_tokens.is_some() {
+        req = req.headers(bearer_headers(&credentials)?);
     } else {
-        req = req.header("X-User-Id", &credentials.user_id).header("X-User-App-Key", &credentials.user_app_key);
+        req = req.header("X-User-Id", &credentials.user_id).header("X-User-App-Key", credentials.user_app_key.expose());
     }
     let body = serde_json::json!({ "user_id": credentials.user_id, "user_app_key": credentials.user_app_key });
     let resp = req.json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
@@ -1099,14 +2360,14 @@ pub async fn get_tier_pricing(_app_handle: AppHandle) -> Result<serde_json::Valu
  pub async fn check_custom_token(app_handle: AppHandle, token: String) -> Result<serde_json::Value, String> {
     let credentials_opt = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?;
     let credentials = credentials_opt.ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
+    let api_config = current_api_config(&app_handle);
     let url = format!("{}{}", api_config.api_base_url, api_config.check_custom_token);
-    let client = reqwest::Client::new();
+    let client = network::client(&app_handle);
     let mut req = client.post(&url);
-    if let Some(tokens) = credentials.auth_tokens {
-        req = req.header("Authorization", format!("Bearer {}", tokens.access_token));
+    if credentials.auth_tokens.is_some() {
+        req = req.headers(bearer_headers(&credentials)?);
     } else {
-        req = req.header("X-User-Id", &credentials.user_id).header("X-User-App-Key", &credentials.user_app_key);
+        req = req.header("X-User-Id", &credentials.user_id).header("X-User-App-Key", credentials.user_app_key.expose());
     }
     let body = serde_json::json!({ "user_id": credentials.user_id, "user_app_key": credentials.user_app_key, "token": token });
     let resp = req.json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
@@ -1120,14 +2381,14 @@ pub async fn get_tier_pricing(_app_handle: AppHandle) -> Result<serde_json::Valu
  pub async fn exchange_sol_for_tokens(app_handle: AppHandle, amount: f64) -> Result<serde_json::Value, String> {
     let credentials_opt = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?;
     let credentials = credentials_opt.ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
+    let api_config = current_api_config(&app_handle);
     let url = format!("{}{}", api_config.api_base_url, api_config.exchange_sol_for_tokens);
-    let client = reqwest::Client::new();
+    let client = network::client(&app_handle);
     let mut req = client.post(&url);
-    if let Some(tokens) = credentials.auth_tokens {
-        req = req.header("Authorization", format!("Bearer {}", tokens.access_token));
+    if credentials.auth_tokens.is_some() {
+        req = req.headers(bearer_headers(&credentials)?);
     } else {
-        req = req.header("X-User-Id", &credentials.user_id).header("X-User-App-Key", &credentials.user_app_key);
+        req = req.header("X-User-Id", &credentials.user_id).header("X-User-App-Key", credentials.user_app_key.expose());
     }
     let body = serde_json::json!({ "user_id": credentials.user_id, "user_app_key": credentials.user_app_key, "amount": amount });
     let resp = req.json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
@@ -1136,25 +2397,32 @@ pub async fn get_tier_pricing(_app_handle: AppHandle) -> Result<serde_json::Valu
     if status.is_success() { Ok(json) } else { Err(format!("HTTP {}: {}", status, json)) }
 }
 
-#[tauri::command]
- #[allow(dead_code)]
- pub async fn withdraw_sol(app_handle: AppHandle, to_address: String, amount: f64) -> Result<serde_json::Value, String> {
+/// Only reachable through a confirmation token (`confirmations`)
+pub(super) async fn withdraw_sol(app_handle: AppHandle, to_address: String, amount: f64) -> Result<serde_json::Value, String> {
     let credentials_opt = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?;
-    let credentials = credentials_opt.ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
+    let mut credentials = credentials_opt.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
     let url = format!("{}{}", api_config.api_base_url, api_config.withdraw_sol);
-    let client = reqwest::Client::new();
-    let mut req = client.post(&url);
-    if let Some(tokens) = credentials.auth_tokens {
-        req = req.header("Authorization", format!("Bearer {}", tokens.access_token));
-    } else {
-        req = req.header("X-User-Id", &credentials.user_id).header("X-User-App-Key", &credentials.user_app_key);
-    }
-    let body = serde_json::json!({ "user_id": credentials.user_id, "user_app_key": credentials.user_app_key, "to_address": to_address, "amount": amount });
-    let resp = req.json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
-    let status = resp.status();
-    let json: serde_json::Value = resp.json().await.map_err(|e| format!("Invalid JSON: {}", e))?;
-    if status.is_success() { Ok(json) } else { Err(format!("HTTP {}: {}", status, json)) }
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+    // same body the wallet page sent through the proxy
+    let body = serde_json::json!({ "to_pubkey": to_address, "amount_sol": amount });
+    let mut renewed = false;
+    let (status, json) = loop {
+        let resp = client.post(&url).headers(bearer_headers(&credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+        let status = resp.status();
+        let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+        if !renewed && csrf::is_rejection(status, &text) {
+            csrf::renew(&client, &api_config, &mut credentials, &app_handle).await?;
+            renewed = true;
+            continue;
+        }
+        let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+        break (status, json);
+    };
+    let result = if status.is_success() { Ok(json) } else { Err(format!("HTTP {}: {}", status, json)) };
+    audit::record(&app_handle, "withdrawal", &to_address, Some(&credentials.user_id), Some(format!("{} SOL", amount)), &result);
+    result
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1164,6 +2432,55 @@ pub struct PublicLinkEntry {
     pub created_at: String,
     pub custom_title: Option<String>,
     pub custom_description: Option<String>,
+    /// Content is end-to-end encrypted; the key is only in the shared URL
+    #[serde(default)]
+    pub encrypted: bool,
+    /// Vanity slug, on backends that support them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_slug: Option<String>,
+    /// Remote name of the social preview image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_image: Option<String>,
+    /// Download limit set at creation (1 for single-use links)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_downloads: Option<u32>,
+    /// Last known remaining downloads, from the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining_uses: Option<u32>,
+    /// The server no longer serves this link because its limit was reached
+    #[serde(default)]
+    pub exhausted: bool,
+    /// Workspace the link was created in; `None` for the personal space
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+}
+
+/// Optional settings for a new public link
+#[derive(Debug, Clone, Default)]
+struct LinkOptions {
+    custom_title: Option<String>,
+    custom_description: Option<String>,
+    custom_slug: Option<String>,
+    max_downloads: Option<u32>,
+    single_use: bool,
+}
+
+const SLUG_MIN_LEN: usize = 3;
+const SLUG_MAX_LEN: usize = 48;
+
+/// Lowercase `a-z`, `0-9`, `-` and `_`, not starting or ending with a separator
+fn validate_link_slug(slug: &str) -> Result<String, String> {
+    let slug = slug.trim().to_lowercase();
+    if !(SLUG_MIN_LEN..=SLUG_MAX_LEN).contains(&slug.len()) {
+        return Err(format!("Slug must be {} to {} characters", SLUG_MIN_LEN, SLUG_MAX_LEN));
+    }
+    if let Some(c) = slug.chars().find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-' || *c == '_')) {
+        return Err(format!("Slug can't contain '{}'; use letters, digits, '-' and '_'", c));
+    }
+    if slug.starts_with(['-', '_']) || slug.ends_with(['-', '_']) {
+        return Err("Slug can't start or end with '-' or '_'".to_string());
+    }
+    Ok(slug)
 }
 
 fn get_link_file_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
@@ -1171,6 +2488,10 @@ fn get_link_file_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf,
     Ok(user_dir.join(format!("link-{}.json", user_id)))
 }
 
+/// Held for every read-modify-write of a link file, so concurrent updates don't drop each other's changes
+pub type LinkFileLock = Mutex<()>;
+pub fn new_link_file_lock() -> LinkFileLock { Mutex::new(()) }
+
 fn read_public_links(user_id: &str, app_handle: &AppHandle) -> Result<Vec<PublicLinkEntry>, String> {
     let path = get_link_file_path(user_id, app_handle)?;
     if !path.exists() { return Ok(vec![]); }
@@ -1183,92 +2504,171 @@ fn write_public_links(user_id: &str, links: &[PublicLinkEntry], app_handle: &App
     let path = get_link_file_path(user_id, app_handle)?;
     if let Some(dir) = path.parent() { if !dir.exists() { fs::create_dir_all(dir).map_err(|e| format!("Failed to create user dir: {}", e))?; } }
     let json = serde_json::to_string_pretty(links).map_err(|e| format!("Failed to serialize links: {}", e))?;
-    std::fs::write(&path, json).map_err(|e| format!("Failed to write link file: {}", e))
+    // write-then-rename so a crash mid-write never truncates the existing file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write link file: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write link file: {}", e))
 }
 
-#[tauri::command]
-pub async fn create_public_link(
-    user_id: String,
-    remote_path: String,
-    custom_title: Option<String>,
-    custom_description: Option<String>,
-    app_handle: AppHandle,
-) -> Result<PublicLinkEntry, String> {
-    use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
-
-    let mut credentials = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?
-        .ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
-    let client = reqwest::Client::new();
-    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+/// Change the saved links of `user_id` under `LinkFileLock`. A link file that can't be read fails the update
+/// rather than being replaced by an edited empty list.
+fn update_public_links<T>(
+    user_id: &str,
+    app_handle: &AppHandle,
+    change: impl FnOnce(&mut Vec<PublicLinkEntry>) -> T,
+) -> Result<T, String> {
+    let lock = app_handle.state::<LinkFileLock>();
+    let _guard = lock.lock().unwrap();
+    let mut links = read_public_links(user_id, app_handle)?;
+    let result = change(&mut links);
+    write_public_links(user_id, &links, app_handle)?;
+    Ok(result)
+}
 
-    let tokens = credentials.auth_tokens.as_ref().ok_or("No valid auth tokens")?;
+/// Headers the link endpoints expect: the bearer ones, scoped to the active workspace
+fn link_headers(credentials: &SavedCredentials) -> Result<reqwest::header::HeaderMap, String> {
+    bearer_headers(credentials)
+}
 
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap());
-    if let Some(csrf) = &tokens.csrf_token { headers.insert("X-Csrf-Token", HeaderValue::from_str(csrf).unwrap()); }
+/// Download address of a saved link. Encrypted links have no stored address: their key only exists in the URL
+/// handed out when they were created.
+fn public_link_url(api_config: &ApiConfig, link: &PublicLinkEntry) -> Result<String, String> {
+    if link.encrypted {
+        return Err("Encrypted links can only be shared with the URL shown when they were created".to_string());
+    }
+    let download_url = api_config.optional_url(&api_config.public_download, "Public download")?;
+    Ok(format!("{}?hash={}", download_url, link.link_hash))
+}
 
+/// Create a link on the server; the local link file is left to the caller
+async fn request_public_link(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    remote_path: &str,
+    options: LinkOptions,
+) -> Result<PublicLinkEntry, String> {
+    let LinkOptions { custom_title, custom_description, custom_slug, max_downloads, single_use } = options;
+    let max_downloads = if single_use { Some(1) } else { max_downloads };
     let mut body = serde_json::json!({ "file_name": remote_path });
     if let Some(title) = &custom_title { body["custom_title"] = serde_json::Value::String(title.clone()); }
     if let Some(desc) = &custom_description { body["custom_description"] = serde_json::Value::String(desc.clone()); }
+    if let Some(slug) = &custom_slug { body["custom_slug"] = serde_json::Value::String(slug.clone()); }
+    if let Some(max) = max_downloads { body["max_downloads"] = serde_json::Value::from(max); }
+    if single_use { body["single_use"] = serde_json::Value::Bool(true); }
 
     let url = format!("{}{}", api_config.api_base_url, api_config.create_public_link);
-    let resp = client.post(&url).headers(headers).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let resp = client.post(&url).headers(link_headers(credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
     let status = resp.status();
     let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
     if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
 
     let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
     let link_hash = json.get("link_hash").and_then(|v| v.as_str()).ok_or("No link_hash in response")?.to_string();
+    // the server may normalize the slug or ignore it; only a slug it echoes back is known to resolve
+    let requested_slug = custom_slug;
+    let custom_slug = ["custom_slug", "slug"].iter().find_map(|k| json.get(*k)?.as_str().map(str::to_string));
+    if let (Some(requested), None) = (&requested_slug, &custom_slug) {
+        println!("[LINK] Server didn't confirm slug '{}' for {}", requested, remote_path);
+    }
 
-    let entry = PublicLinkEntry {
-        remote_path: remote_path.clone(),
-        link_hash: link_hash.clone(),
+    Ok(PublicLinkEntry {
+        remote_path: remote_path.to_string(),
+        link_hash,
         created_at: Utc::now().to_rfc3339(),
         custom_title,
         custom_description,
-    };
+        encrypted: false,
+        custom_slug,
+        preview_image: None,
+        max_downloads,
+        remaining_uses: max_downloads,
+        exhausted: false,
+        workspace_id: credentials.workspace_id.clone(),
+    })
+}
 
-    let mut links = read_public_links(&user_id, &app_handle).unwrap_or_default();
-    links.push(entry.clone());
-    let _ = write_public_links(&user_id, &links, &app_handle);
+async fn request_link_deletion(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    link_hash: &str,
+) -> Result<(), String> {
+    let body = serde_json::json!({ "link_hash": link_hash });
+    let url = format!("{}{}", api_config.api_base_url, api_config.delete_public_link);
 
-    Ok(entry)
+    let resp = client.post(&url).headers(link_headers(credentials)?).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn delete_public_link(
+#[allow(clippy::too_many_arguments)]
+pub async fn create_public_link(
     user_id: String,
-    link_hash: String,
+    remote_path: String,
+    custom_title: Option<String>,
+    custom_description: Option<String>,
+    custom_slug: Option<String>,
+    max_downloads: Option<u32>,
+    single_use: Option<bool>,
     app_handle: AppHandle,
-) -> Result<String, String> {
-    use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+) -> Result<PublicLinkEntry, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    if max_downloads == Some(0) {
+        return Err("Download limit must be at least 1".to_string());
+    }
+    let custom_slug = custom_slug.filter(|s| !s.trim().is_empty()).map(|s| validate_link_slug(&s)).transpose()?;
+    if let Some(slug) = &custom_slug {
+        if read_public_links(&user_id, &app_handle)?.iter().any(|l| l.custom_slug.as_ref() == Some(slug)) {
+            return Err(format!("Slug '{}' is already used by another link", slug));
+        }
+    }
 
     let mut credentials = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?
         .ok_or("No saved credentials found")?;
-    let api_config = ApiConfig::default();
-    let client = reqwest::Client::new();
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
     ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
 
-    let tokens = credentials.auth_tokens.as_ref().ok_or("No valid auth tokens")?;
+    let options = LinkOptions {
+        custom_title,
+        custom_description,
+        custom_slug,
+        max_downloads,
+        single_use: single_use.unwrap_or(false),
+    };
+    let entry = request_public_link(&client, &api_config, &credentials, &remote_path, options).await?;
 
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap());
-    if let Some(csrf) = &tokens.csrf_token { headers.insert("X-Csrf-Token", HeaderValue::from_str(csrf).unwrap()); }
+    update_public_links(&user_id, &app_handle, |links| links.push(entry.clone()))?;
+    Ok(entry)
+}
 
-    let body = serde_json::json!({ "link_hash": link_hash });
-    let url = format!("{}{}", api_config.api_base_url, api_config.delete_public_link);
+#[tauri::command]
+pub async fn delete_public_link(
+    user_id: String,
+    link_hash: String,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut credentials = load_credentials(app_handle.clone()).await.map_err(|e| format!("No credentials found: {}", e))?
+        .ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
 
-    let resp = client.post(&url).headers(headers).json(&body).send().await.map_err(|e| format!("HTTP error: {}", e))?;
-    let status = resp.status();
-    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
-    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    let deleted = request_link_deletion(&client, &api_config, &credentials, &link_hash).await;
+    audit::record(&app_handle, "link_delete", &link_hash, Some(&user_id), None, &deleted);
+    deleted?;
 
-    let mut links = read_public_links(&user_id, &app_handle)?;
-    let before = links.len();
-    links.retain(|l| l.link_hash != link_hash);
-    write_public_links(&user_id, &links, &app_handle)?;
-    Ok(format!("Deleted {} ({} -> {})", link_hash, before, links.len()))
+    let (before, after) = update_public_links(&user_id, &app_handle, |links| {
+        let before = links.len();
+        links.retain(|l| l.link_hash != link_hash);
+        (before, links.len())
+    })?;
+    Ok(format!("Deleted {} ({} -> {})", link_hash, before, after))
 }
 
 #[tauri::command]
@@ -1276,5 +2676,40 @@ pub async fn list_public_links(
     user_id: String,
     app_handle: AppHandle,
 ) -> Result<Vec<PublicLinkEntry>, String> {
-    read_public_links(&user_id, &app_handle)
+    let credentials = account_scope::authorize(&user_id, &app_handle).await?;
+    let mut links = read_public_links(&user_id, &app_handle)?;
+    link_limits::merge_server_state(&user_id, &mut links, &app_handle).await;
+    // links of the active workspace only
+    links.retain(|l| l.workspace_id == credentials.workspace_id);
+    Ok(links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_slugs_are_normalized() {
+        assert_eq!(validate_link_slug("  Team-Photos_2024 ").unwrap(), "team-photos_2024");
+        assert_eq!(validate_link_slug("abc").unwrap(), "abc");
+        assert_eq!(validate_link_slug(&"a".repeat(SLUG_MAX_LEN)).unwrap().len(), SLUG_MAX_LEN);
+    }
+
+    #[test]
+    fn link_slugs_that_dont_fit_a_url_are_rejected() {
+        for slug in ["ab", "", "my photos", "a/b/c", "café", "abc?x=1", "-abc", "abc_", &"a".repeat(SLUG_MAX_LEN + 1)] {
+            assert!(validate_link_slug(slug).is_err(), "accepted {:?}", slug);
+        }
+        assert_eq!(validate_link_slug("my.link").unwrap_err(), "Slug can't contain '.'; use letters, digits, '-' and '_'");
+    }
+
+    #[test]
+    fn error_responses_are_not_saved_as_downloads() {
+        use reqwest::StatusCode;
+        for status in [StatusCode::NOT_FOUND, StatusCode::UNAUTHORIZED, StatusCode::INTERNAL_SERVER_ERROR, StatusCode::FOUND] {
+            assert_eq!(check_download_status(status), Err(format!("Download failed - Status: {}", status)));
+        }
+        assert!(check_download_status(StatusCode::OK).is_ok());
+        assert!(check_download_status(StatusCode::PARTIAL_CONTENT).is_ok());
+    }
 }
@@ -0,0 +1,238 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::settings::{current_settings, update_settings};
+
+// =============================================================================================================
+// ================================================== NETWORK ==================================================
+// =============================================================================================================
+//
+// API requests share one HTTP client built from the network settings: the local address outgoing connections
+// bind to, which picks the interface on multi-homed machines, the IP family tried first when a host has both,
+// per-host DNS overrides that pin a host to an edge node or bypass a broken resolver, and connection tuning
+// (HTTP/2 window, pool idle timeout, TCP keepalive and nodelay). The client is rebuilt on the next request
+// after those settings change; `benchmark_transfer` measures the effect.
+
+const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IpPreference {
+    /// Addresses in the order the system resolver returns them
+    #[default]
+    System,
+    Ipv4,
+    Ipv6,
+}
+
+/// Put the preferred family first, keeping the resolver's order otherwise
+fn sort_by_preference(addrs: &mut [SocketAddr], preference: IpPreference) {
+    match preference {
+        IpPreference::System => {}
+        IpPreference::Ipv4 => addrs.sort_by_key(|a| !a.is_ipv4()),
+        IpPreference::Ipv6 => addrs.sort_by_key(|a| !a.is_ipv6()),
+    }
+}
+
+/// System resolver with the results reordered; the connector tries the first family and falls back to the other
+struct PreferFamily(IpPreference);
+
+impl Resolve for PreferFamily {
+    fn resolve(&self, name: Name) -> Resolving {
+        let preference = self.0;
+        Box::pin(async move {
+            let mut addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            sort_by_preference(&mut addrs, preference);
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Lowercased host name of a DNS override, rejecting URLs and host:port
+fn normalize_host(host: &str) -> Result<String, String> {
+    let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+    let valid = !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'));
+    if !valid {
+        return Err(format!("Invalid host name: {}", host));
+    }
+    Ok(host)
+}
+
+pub type HttpClientState = Mutex<Option<reqwest::Client>>;
+pub fn new_http_client_state() -> HttpClientState { Mutex::new(None) }
+
+/// Client builder with the network settings applied, for requests that need their own timeouts
+pub fn builder(app_handle: &AppHandle) -> reqwest::ClientBuilder {
+    let settings = current_settings(app_handle);
+    let mut builder = reqwest::Client::builder()
+        .http2_adaptive_window(settings.http2_adaptive_window)
+        .pool_idle_timeout(Duration::from_secs(settings.pool_idle_timeout_secs.unwrap_or(DEFAULT_POOL_IDLE_TIMEOUT_SECS)))
+        .tcp_keepalive(settings.tcp_keepalive_secs.map(Duration::from_secs))
+        .tcp_nodelay(settings.tcp_nodelay.unwrap_or(true));
+    if let Some(addr) = settings.bind_address.as_deref().and_then(|a| a.parse::<IpAddr>().ok()) {
+        builder = builder.local_address(addr);
+    }
+    if settings.ip_preference != IpPreference::System {
+        builder = builder.dns_resolver(Arc::new(PreferFamily(settings.ip_preference)));
+    }
+    for (host, ip) in settings.dns_overrides.iter().flatten() {
+        match ip.parse::<IpAddr>() {
+            // the port is taken from the URL
+            Ok(ip) => builder = builder.resolve(host, SocketAddr::new(ip, 0)),
+            Err(_) => println!("[NETWORK] Ignoring DNS override {} -> {}: not an IP address", host, ip),
+        }
+    }
+    builder
+}
+
+/// The shared client for API requests
+pub fn client(app_handle: &AppHandle) -> reqwest::Client {
+    let state = app_handle.state::<HttpClientState>();
+    let mut shared = state.lock().unwrap();
+    if let Some(client) = shared.as_ref() {
+        return client.clone();
+    }
+    let client = builder(app_handle).build().unwrap_or_else(|e| {
+        println!("[NETWORK] Failed to build HTTP client, using defaults: {}", e);
+        reqwest::Client::new()
+    });
+    *shared = Some(client.clone());
+    client
+}
+
+/// Drop the shared client so the next request builds one from the current settings
+pub fn reset_client(app_handle: &AppHandle) {
+    *app_handle.state::<HttpClientState>().lock().unwrap() = None;
+}
+
+/// Bind outgoing connections to `bind_address` (unset: let the system choose) and try `ip_preference` first
+#[tauri::command]
+pub async fn set_network_binding(
+    bind_address: Option<String>,
+    ip_preference: IpPreference,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let bind_address = match bind_address.as_deref().map(str::trim).filter(|a| !a.is_empty()) {
+        Some(addr) => {
+            let ip: IpAddr = addr.parse().map_err(|_| format!("Invalid IP address: {}", addr))?;
+            // binding fails unless the address belongs to one of this machine's interfaces
+            std::net::UdpSocket::bind((ip, 0)).map_err(|e| format!("Failed to bind to {}: {}", ip, e))?;
+            Some(ip.to_string())
+        }
+        None => None,
+    };
+    update_settings(&app_handle, |s| {
+        s.bind_address = bind_address.clone();
+        s.ip_preference = ip_preference;
+    })?;
+    reset_client(&app_handle);
+    println!("🌐 Outgoing connections: bind {}, prefer {:?}", bind_address.as_deref().unwrap_or("any"), ip_preference);
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientTuning {
+    pub http2_adaptive_window: bool,
+    pub pool_idle_timeout_secs: u64,
+    /// Off when unset
+    pub tcp_keepalive_secs: Option<u64>,
+    pub tcp_nodelay: bool,
+}
+
+/// Connection tuning the shared client is built with
+pub fn client_tuning(app_handle: &AppHandle) -> ClientTuning {
+    let settings = current_settings(app_handle);
+    ClientTuning {
+        http2_adaptive_window: settings.http2_adaptive_window,
+        pool_idle_timeout_secs: settings.pool_idle_timeout_secs.unwrap_or(DEFAULT_POOL_IDLE_TIMEOUT_SECS),
+        tcp_keepalive_secs: settings.tcp_keepalive_secs,
+        tcp_nodelay: settings.tcp_nodelay.unwrap_or(true),
+    }
+}
+
+/// Change connection tuning; `None` restores the defaults. Applies from the next request.
+#[tauri::command]
+pub async fn set_client_tuning(tuning: Option<ClientTuning>, app_handle: AppHandle) -> Result<ClientTuning, String> {
+    if tuning.as_ref().is_some_and(|t| t.tcp_keepalive_secs == Some(0)) {
+        return Err("TCP keepalive must be at least 1 second".to_string());
+    }
+    update_settings(&app_handle, |s| {
+        s.http2_adaptive_window = tuning.as_ref().is_some_and(|t| t.http2_adaptive_window);
+        s.pool_idle_timeout_secs = tuning.as_ref().map(|t| t.pool_idle_timeout_secs);
+        s.tcp_keepalive_secs = tuning.as_ref().and_then(|t| t.tcp_keepalive_secs);
+        s.tcp_nodelay = tuning.as_ref().map(|t| t.tcp_nodelay);
+    })?;
+    reset_client(&app_handle);
+    let tuning = client_tuning(&app_handle);
+    println!("🌐 Client tuning: {:?}", tuning);
+    Ok(tuning)
+}
+
+/// Resolve `host` to `ip` from now on; no `ip` removes the override
+#[tauri::command]
+pub async fn set_dns_override(host: String, ip: Option<String>, app_handle: AppHandle) -> Result<(), String> {
+    let host = normalize_host(&host)?;
+    let ip = match ip.as_deref().map(str::trim).filter(|ip| !ip.is_empty()) {
+        Some(ip) => Some(ip.parse::<IpAddr>().map_err(|_| format!("Invalid IP address: {}", ip))?),
+        None => None,
+    };
+    update_settings(&app_handle, |s| {
+        let overrides = s.dns_overrides.get_or_insert_with(Default::default);
+        match ip {
+            Some(ip) => overrides.insert(host.clone(), ip.to_string()),
+            None => overrides.remove(&host),
+        };
+        if overrides.is_empty() {
+            s.dns_overrides = None;
+        }
+    })?;
+    reset_client(&app_handle);
+    match ip {
+        Some(ip) => println!("🌐 DNS override: {} -> {}", host, ip),
+        None => println!("🌐 DNS override for {} removed", host),
+    }
+    Ok(())
+}
+
+/// Remove every DNS override and go back to the system resolver. Returns how many were removed.
+#[tauri::command]
+pub async fn flush_dns_overrides(app_handle: AppHandle) -> Result<usize, String> {
+    let mut removed = 0;
+    update_settings(&app_handle, |s| removed = s.dns_overrides.take().map_or(0, |o| o.len()))?;
+    reset_client(&app_handle);
+    println!("🌐 Flushed {} DNS override(s)", removed);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preferred_family_comes_first() {
+        let v4: SocketAddr = "192.0.2.1:0".parse().unwrap();
+        let v6a: SocketAddr = "[2001:db8::1]:0".parse().unwrap();
+        let v6b: SocketAddr = "[2001:db8::2]:0".parse().unwrap();
+
+        let mut addrs = [v6a, v4, v6b];
+        sort_by_preference(&mut addrs, IpPreference::Ipv4);
+        assert_eq!(addrs, [v4, v6a, v6b]);
+        sort_by_preference(&mut addrs, IpPreference::Ipv6);
+        assert_eq!(addrs, [v6a, v6b, v4]);
+        sort_by_preference(&mut addrs, IpPreference::System);
+        assert_eq!(addrs, [v6a, v6b, v4]);
+    }
+
+    #[test]
+    fn override_hosts_are_bare_names() {
+        assert_eq!(normalize_host(" API.Example.com. ").unwrap(), "api.example.com");
+        for bad in ["", "https://api.example.com", "api.example.com:443", "api example.com"] {
+            assert!(normalize_host(bad).is_err(), "{:?} accepted", bad);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_opener::OpenerExt;
+
+use super::is_plain_path;
+
+// =============================================================================================================
+// ============================================ OPEN / REVEAL FILES ============================================
+// =============================================================================================================
+//
+// "Open" and "Show in folder" for finished downloads, done through the opener plugin on the Rust side so the
+// frontend doesn't need per-platform logic or extra capabilities.
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DownloadCompleted {
+    pub file_name: String,
+    pub output_path: String,
+    pub can_open: bool,
+    pub can_reveal: bool,
+}
+
+fn can_reveal(path: &str) -> bool {
+    cfg!(desktop) && is_plain_path(path)
+}
+
+/// Emitted once a download has landed, with what the UI can offer for it
+pub fn emit_download_completed(app_handle: &AppHandle, file_name: &str, output_path: &str) {
+    let payload = DownloadCompleted {
+        file_name: file_name.to_string(),
+        output_path: output_path.to_string(),
+        can_open: true,
+        can_reveal: can_reveal(output_path),
+    };
+    app_handle.emit("download_completed", payload).ok();
+}
+
+fn check_exists(path: &str) -> Result<(), String> {
+    if is_plain_path(path) && !std::path::Path::new(path).exists() {
+        return Err(format!("File not found: {}", path));
+    }
+    Ok(())
+}
+
+/// Open a downloaded file with the system default application
+#[tauri::command]
+pub async fn open_downloaded_file(path: String, app_handle: AppHandle) -> Result<(), String> {
+    check_exists(&path)?;
+    app_handle
+        .opener()
+        .open_path(path.as_str(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path, e))
+}
+
+/// Show a downloaded file selected in Finder / Explorer / the Linux file manager
+#[tauri::command]
+pub async fn reveal_in_file_manager(path: String, app_handle: AppHandle) -> Result<(), String> {
+    check_exists(&path)?;
+    if !can_reveal(&path) {
+        return Err("Revealing files isn't supported on this platform".to_string());
+    }
+    #[cfg(desktop)]
+    {
+        app_handle
+            .opener()
+            .reveal_item_in_dir(&path)
+            .map_err(|e| format!("Failed to reveal {}: {}", path, e))
+    }
+    #[cfg(not(desktop))]
+    {
+        let _ = app_handle;
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use super::settings::{current_settings, update_settings};
+use super::{ipc_guard, is_plain_path, local_paths};
+
+// =============================================================================================================
+// ================================================ OUTPUT PATHS ===============================================
+// =============================================================================================================
+//
+// Downloads only write where the user meant them to: inside the default download folder, or exactly a path
+// picked in a save dialog opened from Rust (`pick_download_path`) this session. A picked file doesn't open up
+// its folder, and folders earlier downloads went to aren't trusted either. The path is resolved (`..` segments,
+// then symlinks of the part that exists) before it is compared, so a webview script can't escape with `../..`
+// or a planted link. `allow_any_download_path` turns the check off.
+// Picker URIs on mobile are grants from the OS and always pass.
+
+pub type PickedPathsState = Mutex<HashSet<PathBuf>>;
+pub fn new_picked_paths_state() -> PickedPathsState { Mutex::new(HashSet::new()) }
+
+/// Folder downloads go to when the frontend names none
+pub fn default_download_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    #[cfg(mobile)]
+    {
+        super::mobile::default_download_dir(app_handle)
+    }
+    #[cfg(not(mobile))]
+    {
+        app_handle
+            .path()
+            .download_dir()
+            .or_else(|_| app_handle.path().document_dir())
+            .map_err(|e| format!("Failed to get download directory: {}", e))
+    }
+}
+
+/// `path` made absolute against the download folder, with `.`/`..` applied and existing symlinks resolved
+fn resolve(path: &Path, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let absolute = if path.is_absolute() { path.to_path_buf() } else { default_download_dir(app_handle)?.join(path) };
+    let mut lexical = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::ParentDir => {
+                lexical.pop();
+            }
+            Component::CurDir => {}
+            other => lexical.push(other),
+        }
+    }
+    // canonicalize the longest existing prefix; the rest doesn't exist yet, so it can't be a link
+    let mut existing = lexical.as_path();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        let Some(parent) = existing.parent() else { break };
+        rest.push(existing.file_name().map(|n| n.to_os_string()).unwrap_or_default());
+        existing = parent;
+    }
+    let mut resolved = std::fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+    resolved.extend(rest.iter().rev());
+    Ok(resolved)
+}
+
+fn canonical(dir: &Path) -> PathBuf {
+    std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+/// The download target for `path` if it is allowed, as a path string
+pub fn check_output_path(path: &str, app_handle: &AppHandle) -> Result<String, String> {
+    if !is_plain_path(path) || current_settings(app_handle).allow_any_download_path {
+        return Ok(path.to_string());
+    }
+    let resolved = resolve(&local_paths::to_fs_path(path), app_handle)?;
+    let picked = app_handle.state::<PickedPathsState>().lock().unwrap().clone();
+    let in_download_dir = default_download_dir(app_handle).map(|d| canonical(&d)).is_ok_and(|root| resolved.starts_with(root));
+    let allowed = picked.contains(&resolved) || in_download_dir;
+    if !allowed {
+        return Err(format!(
+            "Download path '{}' is outside the download folder; choose it in the save dialog or allow any download path",
+            path
+        ));
+    }
+    Ok(local_paths::to_wire(&resolved))
+}
+
+/// Save dialog for a download; the picked path is allowed as a target for the rest of the session
+#[tauri::command]
+pub async fn pick_download_path(default_name: Option<String>, app_handle: AppHandle) -> Result<Option<String>, String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let mut dialog = app_handle.dialog().file();
+    if let Some(name) = default_name.filter(|n| !n.trim().is_empty()) {
+        dialog = dialog.set_file_name(name);
+    }
+    if let Ok(dir) = default_download_dir(&app_handle) {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog.save_file(move |picked| {
+        let _ = tx.send(picked);
+    });
+    let Some(picked) = rx.await.map_err(|_| "Save dialog closed unexpectedly".to_string())? else {
+        return Ok(None);
+    };
+    match picked.clone().into_path() {
+        Ok(path) => {
+            let resolved = resolve(&path, &app_handle)?;
+            app_handle.state::<PickedPathsState>().lock().unwrap().insert(resolved);
+            Ok(Some(local_paths::to_wire(&path)))
+        }
+        // a content URI on mobile
+        Err(_) => Ok(Some(picked.to_string())),
+    }
+}
+
+/// Let downloads write anywhere the app can (off by default). Turns the path check off, so only the app's own
+/// windows may call it.
+#[tauri::command]
+pub async fn set_allow_any_download_path(
+    enabled: bool,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    ipc_guard::check_sensitive("set_allow_any_download_path", &webview, &ipc_nonce, &app_handle)?;
+    update_settings(&app_handle, |s| s.allow_any_download_path = enabled)?;
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::redaction::println_redacted;
+use super::{check_wallet, get_tier_pricing, get_token_usage, load_credentials};
+
+// =============================================================================================================
+// ============================================= DASHBOARD POLLING =============================================
+// =============================================================================================================
+//
+// One scheduler owns all dashboard polling: each endpoint has its own interval (plus jitter), concurrent
+// refreshes are coalesced into the fetch already in flight, and nothing is fetched while the main window is
+// hidden or minimized. Results are pushed as `dashboard_snapshot` events.
+
+/// No endpoint is hit more often than this, whatever the frontend asks for
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// Longest the scheduler sleeps before re-checking window visibility
+const MAX_IDLE_SLEEP: Duration = Duration::from_secs(5);
+/// Random spread added to every interval, as a fraction of it
+const JITTER_FRACTION: f64 = 0.1;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PollSubscription {
+    /// "token_usage" | "wallet" | "tier_pricing"
+    pub endpoint: String,
+    pub interval_secs: u64,
+    /// Usage period for "token_usage" (defaults to "month")
+    pub period: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DashboardSnapshot {
+    pub endpoint: String,
+    pub data: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub fetched_at: String,
+}
+
+struct PollTarget {
+    subscription: PollSubscription,
+    next_due: Instant,
+    last_fetch: Option<Instant>,
+    in_flight: bool,
+}
+
+#[derive(Default)]
+pub struct DashboardPoller {
+    targets: HashMap<String, PollTarget>,
+    snapshots: HashMap<String, DashboardSnapshot>,
+    paused: bool,
+    /// Bumped on every (re)start so an old scheduler loop knows to exit
+    generation: u64,
+}
+
+pub type DashboardPollerState = Mutex<DashboardPoller>;
+pub fn new_dashboard_poller_state() -> DashboardPollerState { Mutex::new(DashboardPoller::default()) }
+
+fn jittered(interval: Duration) -> Duration {
+    let spread = interval.as_secs_f64() * JITTER_FRACTION;
+    let offset = rand::thread_rng().gen_range(-spread..=spread);
+    Duration::from_secs_f64((interval.as_secs_f64() + offset).max(MIN_POLL_INTERVAL.as_secs_f64()))
+}
+
+fn effective_interval(subscription: &PollSubscription) -> Duration {
+    Duration::from_secs(subscription.interval_secs).max(MIN_POLL_INTERVAL)
+}
+
+fn window_visible(app_handle: &AppHandle) -> bool {
+    match app_handle.get_webview_window("main") {
+        Some(window) => window.is_visible().unwrap_or(true) && !window.is_minimized().unwrap_or(false),
+        None => true,
+    }
+}
+
+async fn fetch_endpoint(subscription: &PollSubscription, app_handle: &AppHandle) -> Result<serde_json::Value, String> {
+    match subscription.endpoint.as_str() {
+        "token_usage" => {
+            let credentials = load_credentials(app_handle.clone()).await?;
+            let period = subscription.period.clone().unwrap_or_else(|| "month".to_string());
+            get_token_usage(period, credentials, app_handle.clone()).await
+        }
+        "wallet" => check_wallet(app_handle.clone()).await,
+        "tier_pricing" => get_tier_pricing(app_handle.clone()).await,
+        other => Err(format!("Unknown dashboard endpoint: {}", other)),
+    }
+}
+
+/// Fetch one endpoint unless a fetch for it is already in flight, then publish the snapshot
+async fn refresh_target(endpoint: String, app_handle: AppHandle) {
+    let subscription = {
+        let state = app_handle.state::<DashboardPollerState>();
+        let mut poller = state.lock().unwrap();
+        match poller.targets.get_mut(&endpoint) {
+            Some(target) if !target.in_flight => {
+                target.in_flight = true;
+                target.subscription.clone()
+            }
+            _ => return,
+        }
+    };
+
+    let started = Instant::now();
+    let result = fetch_endpoint(&subscription, &app_handle).await;
+    super::metrics::record_api_latency(&app_handle, &endpoint, started.elapsed());
+    let snapshot = DashboardSnapshot {
+        endpoint: endpoint.clone(),
+        data: result.as_ref().ok().cloned(),
+        error: result.err(),
+        fetched_at: Utc::now().to_rfc3339(),
+    };
+
+    {
+        let state = app_handle.state::<DashboardPollerState>();
+        let mut poller = state.lock().unwrap();
+        if let Some(target) = poller.targets.get_mut(&endpoint) {
+            let now = Instant::now();
+            target.in_flight = false;
+            target.last_fetch = Some(now);
+            target.next_due = now + jittered(effective_interval(&target.subscription));
+        }
+        poller.snapshots.insert(endpoint.clone(), snapshot.clone());
+    }
+    if let Some(e) = &snapshot.error {
+        println_redacted!("[POLL] {} failed: {}", endpoint, e);
+    }
+    app_handle.emit("dashboard_snapshot", snapshot).ok();
+}
+
+async fn run_scheduler(app_handle: AppHandle, generation: u64) {
+    loop {
+        let (due, sleep_for) = {
+            let state = app_handle.state::<DashboardPollerState>();
+            let poller = state.lock().unwrap();
+            if poller.generation != generation || poller.targets.is_empty() {
+                break;
+            }
+            let now = Instant::now();
+            let due: Vec<String> = if poller.paused {
+                Vec::new()
+            } else {
+                poller
+                    .targets
+                    .iter()
+                    .filter(|(_, t)| !t.in_flight && t.next_due <= now)
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            };
+            let next = poller
+                .targets
+                .values()
+                .map(|t| t.next_due.saturating_duration_since(now))
+                .min()
+                .unwrap_or(MAX_IDLE_SLEEP);
+            (due, next.clamp(Duration::from_millis(250), MAX_IDLE_SLEEP))
+        };
+
+        if !due.is_empty() && window_visible(&app_handle) {
+            for endpoint in due {
+                tauri::async_runtime::spawn(refresh_target(endpoint, app_handle.clone()));
+            }
+        }
+        tokio::time::sleep(sleep_for).await;
+    }
+}
+
+/// Replace the polled endpoint set and (re)start the scheduler
+#[tauri::command]
+pub async fn start_dashboard_polling(subscriptions: Vec<PollSubscription>, app_handle: AppHandle) -> Result<(), String> {
+    let generation = {
+        let state = app_handle.state::<DashboardPollerState>();
+        let mut poller = state.lock().unwrap();
+        let now = Instant::now();
+        poller.targets = subscriptions
+            .into_iter()
+            .map(|s| {
+                let target = PollTarget { subscription: s.clone(), next_due: now, last_fetch: None, in_flight: false };
+                (s.endpoint, target)
+            })
+            .collect();
+        poller.generation += 1;
+        poller.generation
+    };
+    println!("📊 Dashboard polling started");
+    tauri::async_runtime::spawn(run_scheduler(app_handle, generation));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_dashboard_polling(app_handle: AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<DashboardPollerState>();
+    let mut poller = state.lock().unwrap();
+    poller.targets.clear();
+    poller.generation += 1;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_dashboard_polling_paused(paused: bool, app_handle: AppHandle) -> Result<(), String> {
+    app_handle.state::<DashboardPollerState>().lock().unwrap().paused = paused;
+    Ok(())
+}
+
+/// Manual refresh. Rate limited: within the minimum interval the last snapshot is returned instead.
+#[tauri::command]
+pub async fn refresh_dashboard_endpoint(endpoint: String, app_handle: AppHandle) -> Result<Option<DashboardSnapshot>, String> {
+    let fetch = {
+        let state = app_handle.state::<DashboardPollerState>();
+        let poller = state.lock().unwrap();
+        let target = poller.targets.get(&endpoint).ok_or(format!("Endpoint '{}' is not being polled", endpoint))?;
+        !target.in_flight && target.last_fetch.map(|t| t.elapsed() >= MIN_POLL_INTERVAL).unwrap_or(true)
+    };
+    if fetch {
+        refresh_target(endpoint.clone(), app_handle.clone()).await;
+    }
+    Ok(app_handle.state::<DashboardPollerState>().lock().unwrap().snapshots.get(&endpoint).cloned())
+}
+
+#[tauri::command]
+pub async fn get_dashboard_snapshots(app_handle: AppHandle) -> Result<Vec<DashboardSnapshot>, String> {
+    Ok(app_handle.state::<DashboardPollerState>().lock().unwrap().snapshots.values().cloned().collect())
+}
@@ -0,0 +1,243 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use chrono::Utc;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::{account_scope, get_user_data_dir, UploadLogEntry};
+
+// =============================================================================================================
+// ============================================== UPLOAD RECEIPTS ==============================================
+// =============================================================================================================
+//
+// Every successful upload gets a small JSON receipt in `receipts/<entry id>.json`: remote name, size, blake3,
+// upload time, the server's request id and a blake3 of its response. The receipt is signed with Ed25519 under a
+// per-account keypair kept next to the history (and carried by the app state backup), so
+// `verify_upload_receipt` can later show it was issued by this account's app and hasn't been edited since.
+// `get_receipt_public_key` hands out the public half so others can check receipts without the secret key.
+// Receipts from before the switch (signed `hmac-sha256=`) no longer verify.
+
+const RECEIPT_VERSION: u32 = 1;
+const SIGNATURE_PREFIX: &str = "ed25519=";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReceiptBody {
+    pub version: u32,
+    pub entry_id: String,
+    pub user_id: String,
+    pub file_name: String,
+    pub file_size: u64,
+    pub blake3_hash: String,
+    pub uploaded_at: String,
+    /// Request id the server sent back, when it sends one
+    pub request_id: Option<String>,
+    /// blake3 of the server's response body
+    pub response_blake3: String,
+    pub issued_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UploadReceipt {
+    #[serde(flatten)]
+    pub body: ReceiptBody,
+    /// Fingerprint of the public key that verifies it
+    pub key_id: String,
+    /// `ed25519=<hex>` over the JSON of the other fields
+    pub signature: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReceiptPublicKey {
+    /// Hex of the 32-byte Ed25519 public key
+    pub public_key: String,
+    pub key_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReceiptKey {
+    /// Hex of the 32-byte Ed25519 secret key; `key` in files written before the switch from HMAC
+    #[serde(alias = "key")]
+    secret_key: String,
+    created_at: String,
+}
+
+/// Held while the receipt key is read or created, so two first uploads don't each write a key
+pub type ReceiptKeyLock = Mutex<()>;
+pub fn new_receipt_key_lock() -> ReceiptKeyLock { Mutex::new(()) }
+
+fn receipts_dir(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join("receipts"))
+}
+
+fn receipt_key_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join(format!("receipt-key-{}.json", user_id)))
+}
+
+fn read_receipt_key(path: &std::path::Path) -> Result<SigningKey, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read receipt key: {}", e))?;
+    let saved: ReceiptKey = serde_json::from_str(&content).map_err(|e| format!("Failed to parse receipt key: {}", e))?;
+    let bytes = hex::decode(saved.secret_key).map_err(|e| format!("Invalid receipt key: {}", e))?;
+    let secret: [u8; 32] = bytes.try_into().map_err(|_| "Invalid receipt key: expected 32 bytes".to_string())?;
+    Ok(SigningKey::from_bytes(&secret))
+}
+
+/// The account's receipt keypair, created on first use. The file is created with `create_new`, so a key
+/// written by another process in the meantime is read back rather than overwritten.
+fn receipt_key(user_id: &str, app_handle: &AppHandle) -> Result<SigningKey, String> {
+    let lock = app_handle.state::<ReceiptKeyLock>();
+    let _guard = lock.lock().unwrap();
+    let path = receipt_key_path(user_id, app_handle)?;
+    if path.exists() {
+        return read_receipt_key(&path);
+    }
+    let key = SigningKey::generate(&mut rand::rngs::OsRng);
+    let saved = ReceiptKey { secret_key: hex::encode(key.to_bytes()), created_at: Utc::now().to_rfc3339() };
+    let json = serde_json::to_string_pretty(&saved).map_err(|e| format!("Failed to serialize receipt key: {}", e))?;
+    let mut file = match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return read_receipt_key(&path),
+        Err(e) => return Err(format!("Failed to create receipt key: {}", e)),
+    };
+    if let Err(e) = file.write_all(json.as_bytes()).and_then(|_| file.sync_all()) {
+        drop(file);
+        let _ = std::fs::remove_file(&path);
+        return Err(format!("Failed to write receipt key: {}", e));
+    }
+    println!("[RECEIPT] Created receipt key for {}", user_id);
+    Ok(key)
+}
+
+fn key_id(public_key: &VerifyingKey) -> String {
+    blake3::hash(public_key.as_bytes()).to_hex()[..16].to_string()
+}
+
+fn body_json(body: &ReceiptBody) -> Result<String, String> {
+    serde_json::to_string(body).map_err(|e| format!("Failed to serialize receipt: {}", e))
+}
+
+fn sign(key: &SigningKey, body: ReceiptBody) -> Result<UploadReceipt, String> {
+    let signature = key.sign(body_json(&body)?.as_bytes());
+    Ok(UploadReceipt {
+        signature: format!("{}{}", SIGNATURE_PREFIX, hex::encode(signature.to_bytes())),
+        key_id: key_id(&key.verifying_key()),
+        body,
+    })
+}
+
+fn verify(public_key: &VerifyingKey, receipt: &UploadReceipt) -> Result<bool, String> {
+    if receipt.key_id != key_id(public_key) {
+        return Ok(false);
+    }
+    let Some(signature) = receipt.signature.strip_prefix(SIGNATURE_PREFIX) else {
+        return Ok(false);
+    };
+    let Ok(bytes) = hex::decode(signature) else {
+        return Ok(false);
+    };
+    let Ok(signature) = Signature::from_slice(&bytes) else {
+        return Ok(false);
+    };
+    Ok(public_key.verify(body_json(&receipt.body)?.as_bytes(), &signature).is_ok())
+}
+
+/// Upload ids are uuids; anything else can't name a receipt file
+fn check_entry_id(entry_id: &str) -> Result<(), String> {
+    if entry_id.is_empty() || !entry_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("No receipt for history entry {}", entry_id));
+    }
+    Ok(())
+}
+
+/// Write the receipt for a successful upload. Called right after its history entry is logged.
+pub fn issue(user_id: &str, entry: &UploadLogEntry, response_text: &str, app_handle: &AppHandle) -> Result<UploadReceipt, String> {
+    let entry_id = entry.upload_id.as_deref().ok_or("Upload has no id to issue a receipt for")?;
+    check_entry_id(entry_id)?;
+    let body = ReceiptBody {
+        version: RECEIPT_VERSION,
+        entry_id: entry_id.to_string(),
+        user_id: user_id.to_string(),
+        file_name: entry.remote_path.clone(),
+        file_size: entry.file_size,
+        blake3_hash: entry.blake3_hash.clone(),
+        uploaded_at: entry.timestamp.clone(),
+        request_id: entry.details.as_ref().and_then(|d| d.request_id.clone()),
+        response_blake3: blake3::hash(response_text.as_bytes()).to_hex().to_string(),
+        issued_at: Utc::now().to_rfc3339(),
+    };
+    let dir = receipts_dir(user_id, app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create receipts dir: {}", e))?;
+    let receipt = sign(&receipt_key(user_id, app_handle)?, body)?;
+    let json = serde_json::to_string_pretty(&receipt).map_err(|e| format!("Failed to serialize receipt: {}", e))?;
+    std::fs::write(dir.join(format!("{}.json", entry_id)), json).map_err(|e| format!("Failed to write receipt: {}", e))?;
+    Ok(receipt)
+}
+
+/// Receipt of the upload with history entry id `entry_id`
+#[tauri::command]
+pub async fn get_upload_receipt(user_id: String, entry_id: String, app_handle: AppHandle) -> Result<UploadReceipt, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    check_entry_id(&entry_id)?;
+    let path = receipts_dir(&user_id, &app_handle)?.join(format!("{}.json", entry_id));
+    if !path.exists() {
+        return Err(format!("No receipt for history entry {}", entry_id));
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read receipt: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse receipt: {}", e))
+}
+
+/// Whether the receipt file at `path` was signed with this account's receipt key and is unchanged
+#[tauri::command]
+pub async fn verify_upload_receipt(user_id: String, path: String, app_handle: AppHandle) -> Result<bool, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read receipt: {}", e))?;
+    let receipt: UploadReceipt = serde_json::from_str(&content).map_err(|e| format!("Not an upload receipt: {}", e))?;
+    if receipt.body.user_id != user_id {
+        return Ok(false);
+    }
+    verify(&receipt_key(&user_id, &app_handle)?.verifying_key(), &receipt)
+}
+
+/// Public half of the account's receipt key, for checking its receipts elsewhere
+#[tauri::command]
+pub async fn get_receipt_public_key(user_id: String, app_handle: AppHandle) -> Result<ReceiptPublicKey, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let public_key = receipt_key(&user_id, &app_handle)?.verifying_key();
+    Ok(ReceiptPublicKey { public_key: hex::encode(public_key.as_bytes()), key_id: key_id(&public_key) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edited_receipts_fail_verification() {
+        let body = ReceiptBody {
+            version: RECEIPT_VERSION,
+            entry_id: "0b6c3f2e-0000-4000-8000-000000000000".to_string(),
+            user_id: "user-1".to_string(),
+            file_name: "report.pdf".to_string(),
+            file_size: 42,
+            blake3_hash: "abc".to_string(),
+            uploaded_at: "2024-01-01T00:00:00Z".to_string(),
+            request_id: Some("req-1".to_string()),
+            response_blake3: "def".to_string(),
+            issued_at: "2024-01-01T00:00:01Z".to_string(),
+        };
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = key.verifying_key();
+        let receipt = sign(&key, body).unwrap();
+        let parsed: UploadReceipt = serde_json::from_str(&serde_json::to_string_pretty(&receipt).unwrap()).unwrap();
+        assert!(verify(&public_key, &parsed).unwrap());
+
+        let mut edited = parsed.clone();
+        edited.body.file_size = 43;
+        assert!(!verify(&public_key, &edited).unwrap());
+        assert!(!verify(&SigningKey::from_bytes(&[8u8; 32]).verifying_key(), &parsed).unwrap());
+
+        let mut legacy = parsed.clone();
+        legacy.signature = parsed.signature.replacen(SIGNATURE_PREFIX, "hmac-sha256=", 1);
+        assert!(!verify(&public_key, &legacy).unwrap());
+    }
+}
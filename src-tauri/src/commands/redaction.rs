@@ -0,0 +1,168 @@
+// =============================================================================================================
+// ================================================= REDACTION =================================================
+// =============================================================================================================
+//
+// One place that masks secrets before text leaves the process: console lines (`println_redacted!`), the API
+// trace, errors returned to the frontend, history and audit entries, and crash reports. Values of known secret
+// fields (`access_token=…`, `"password": "…"`, `Bearer …`) are masked, as is any long run of key-like
+// characters with both letters and digits, which catches bare tokens, JWTs and app keys in URLs.
+
+const REDACTED: &str = "[redacted]";
+/// Unbroken runs of key/token characters at least this long are masked
+const SECRET_MIN_LEN: usize = 24;
+/// Field names whose values are secret, in JSON bodies, query strings and headers (lowercase)
+const SECRET_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "csrf_token",
+    "id_token",
+    "token",
+    "password",
+    "new_password",
+    "old_password",
+    "passphrase",
+    "app_key",
+    "user_app_key",
+    "api_key",
+    "secret",
+    "client_secret",
+];
+
+fn is_word(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+fn skip_spaces(bytes: &[u8], mut i: usize) -> usize {
+    while bytes.get(i) == Some(&b' ') {
+        i += 1;
+    }
+    i
+}
+
+/// Byte range of the secret value when a secret field name or `Bearer` starts at `at`
+fn secret_value_at(lower: &[u8], at: usize) -> Option<(usize, usize)> {
+    if at > 0 && is_word(lower[at - 1]) {
+        return None;
+    }
+    let mut i = if lower[at..].starts_with(b"bearer ") {
+        skip_spaces(lower, at + "bearer ".len())
+    } else {
+        let field = SECRET_FIELDS
+            .iter()
+            .find(|f| lower[at..].starts_with(f.as_bytes()) && !lower.get(at + f.len()).is_some_and(|b| is_word(*b)))?;
+        let mut i = at + field.len();
+        if lower.get(i) == Some(&b'"') {
+            i += 1;
+        }
+        i = skip_spaces(lower, i);
+        if !matches!(lower.get(i), Some(b':') | Some(b'=')) {
+            return None;
+        }
+        skip_spaces(lower, i + 1)
+    };
+    let quoted = lower.get(i) == Some(&b'"');
+    if quoted {
+        i += 1;
+    }
+    let start = i;
+    while let Some(&b) = lower.get(i) {
+        if quoted && b == b'\\' {
+            i += 2;
+            continue;
+        }
+        let end = if quoted { b == b'"' } else { b.is_ascii_whitespace() || matches!(b, b'&' | b',' | b';' | b'"' | b'}' | b')') };
+        if end {
+            break;
+        }
+        i += 1;
+    }
+    let i = i.min(lower.len());
+    (i > start).then_some((start, i))
+}
+
+fn mask_fields(text: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let lower = lower.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let (mut copied, mut i) = (0, 0);
+    while i < lower.len() {
+        match secret_value_at(lower, i) {
+            Some((start, end)) => {
+                out.push_str(&text[copied..start]);
+                out.push_str(REDACTED);
+                copied = end;
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+fn mask_long_runs(text: &str) -> String {
+    let is_secret_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+' | '/' | '=' | '.');
+    let mut out = String::with_capacity(text.len());
+    let mut run = String::new();
+    let flush = |run: &mut String, out: &mut String| {
+        // long runs with both letters and digits; plain words and paths are left alone
+        let looks_secret = run.len() >= SECRET_MIN_LEN
+            && run.chars().any(|c| c.is_ascii_digit())
+            && run.chars().any(|c| c.is_ascii_alphabetic())
+            && !run.contains('/');
+        out.push_str(if looks_secret { REDACTED } else { run.as_str() });
+        run.clear();
+    };
+    for c in text.chars() {
+        if is_secret_char(c) {
+            run.push(c);
+        } else {
+            flush(&mut run, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut run, &mut out);
+    out
+}
+
+/// `text` with anything that looks like a password, key, token or signature masked
+pub fn redact(text: &str) -> String {
+    mask_long_runs(&mask_fields(text))
+}
+
+/// `println!` for lines that can carry server responses, URLs or error text
+macro_rules! println_redacted {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::commands::redaction::redact(&format!($($arg)*)))
+    };
+}
+pub(crate) use println_redacted;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_fields_are_masked() {
+        assert_eq!(
+            redact(r#"{"username":"ann","password": "hunter2","csrf_token":"abc"}"#),
+            r#"{"username":"ann","password": "[redacted]","csrf_token":"[redacted]"}"#
+        );
+        assert_eq!(redact("GET /download?file=a.txt&user_app_key=k1&x=1"), "GET /download?file=a.txt&user_app_key=[redacted]&x=1");
+        assert_eq!(redact("Authorization: Bearer abc.def"), "Authorization: Bearer [redacted]");
+        assert_eq!(redact(r#"{"token":"a\"b","n":1}"#), r#"{"token":"[redacted]","n":1}"#);
+    }
+
+    #[test]
+    fn ordinary_text_is_kept() {
+        assert_eq!(redact("Token refresh failed: HTTP 401"), "Token refresh failed: HTTP 401");
+        assert_eq!(redact("Failed to open /home/ann/report-2024-final-version.pdf"), "Failed to open /home/ann/report-2024-final-version.pdf");
+        assert_eq!(redact("my_token_count=3"), "my_token_count=3");
+        assert_eq!(redact("naïve password=sécret ok"), "naïve password=[redacted] ok");
+    }
+
+    #[test]
+    fn bare_tokens_are_masked() {
+        assert_eq!(redact("error for url (https://x.io/f?k=a1b2c3d4e5f6g7h8i9j0k1l2m3)"), "error for url (https://x.io/f?[redacted])");
+    }
+}
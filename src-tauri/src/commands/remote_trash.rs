@@ -0,0 +1,191 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::{
+    account_scope, audit, bearer_headers, csrf, current_api_config, ensure_valid_token, history_log, link_batch, network,
+    workspaces, ApiConfig, SavedCredentials,
+};
+
+// =============================================================================================================
+// ================================================ REMOTE TRASH ===============================================
+// =============================================================================================================
+//
+// Backends with soft delete answer a delete with `"restorable": true` and keep the file in a trash until it's
+// purged (by `purge_remote_trash`, after confirmation, or by the server's own expiry). History entries of such
+// files are marked `trashed` rather than `deleted`, go back to `success` when restored and become `deleted` once
+// purged. Needs the optional `list_trash`, `restore_file` and `purge_trash` endpoints. Public links of a trashed
+// file are kept until it is purged, so a restored file is still shared.
+
+pub const TRASHED: &str = "trashed";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RemoteTrashEntry {
+    pub name: String,
+    pub size: Option<u64>,
+    pub deleted_at: Option<String>,
+    /// When the server purges it on its own
+    pub purge_at: Option<String>,
+}
+
+/// Whether a delete response says the file went to the trash
+pub(super) fn is_restorable(response_text: &str) -> bool {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(response_text) else { return false };
+    ["restorable", "trashed"].iter().any(|k| json.get(*k).and_then(|v| v.as_bool()) == Some(true))
+}
+
+/// Trash items in a list response: a bare array or `{ "files": [...] }` of names or objects
+fn parse_trash(json: &serde_json::Value) -> Vec<RemoteTrashEntry> {
+    let items = json.as_array().or_else(|| json.get("files").and_then(|f| f.as_array()));
+    let text = |item: &serde_json::Value, keys: &[&str]| keys.iter().find_map(|k| item.get(*k)?.as_str().map(str::to_string));
+    items
+        .into_iter()
+        .flatten()
+        .filter_map(|item| match item {
+            serde_json::Value::String(name) => Some(RemoteTrashEntry { name: name.clone(), size: None, deleted_at: None, purge_at: None }),
+            _ => Some(RemoteTrashEntry {
+                name: text(item, &["name", "file_name"])?,
+                size: ["size", "file_size"].iter().find_map(|k| item.get(*k)?.as_u64()),
+                deleted_at: text(item, &["deleted_at"]),
+                purge_at: text(item, &["purge_at", "expires_at"]),
+            }),
+        })
+        .collect()
+}
+
+/// Credentials of `user_id` with a fresh token
+async fn signed_in(user_id: &str, app_handle: &AppHandle) -> Result<(reqwest::Client, ApiConfig, SavedCredentials), String> {
+    let mut credentials = account_scope::authorize(user_id, app_handle).await?;
+    let api_config = current_api_config(app_handle);
+    let client = network::client(app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, app_handle).await?;
+    Ok((client, api_config, credentials))
+}
+
+/// Bearer POST in the active workspace, renewing the CSRF token once if the server rejects it
+async fn post(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    url: &str,
+    credentials: &mut SavedCredentials,
+    body: serde_json::Value,
+    app_handle: &AppHandle,
+) -> Result<String, String> {
+    let mut renewed = false;
+    loop {
+        let response = workspaces::scope(client.post(url), credentials)
+            .headers(bearer_headers(credentials)?)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("HTTP error: {}", e))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+        if !renewed && csrf::is_rejection(status, &text) {
+            csrf::renew(client, api_config, credentials, app_handle).await?;
+            renewed = true;
+            continue;
+        }
+        if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+        return Ok(text);
+    }
+}
+
+async fn fetch_trash(client: &reqwest::Client, api_config: &ApiConfig, credentials: &SavedCredentials) -> Result<Vec<RemoteTrashEntry>, String> {
+    let url = api_config.optional_url(&api_config.list_trash, "Trash listing")?;
+    let response = workspaces::scope(client.get(&url), credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .send()
+        .await
+        .map_err(|e| format!("HTTP error: {}", e))?;
+    let status = response.status();
+    let text = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    Ok(parse_trash(&json))
+}
+
+/// Move history entries of `names` from status `from` to `to`
+async fn set_history_status(user_id: &str, names: Vec<String>, from: &'static str, to: &'static str, app_handle: &AppHandle) -> Result<usize, String> {
+    history_log::update_entries(
+        user_id,
+        move |entry| {
+            if entry.status != from || !names.contains(&entry.remote_path) {
+                return false;
+            }
+            entry.status = to.to_string();
+            true
+        },
+        app_handle,
+    )
+    .await
+}
+
+/// Files in the remote trash, as the server lists them
+#[tauri::command]
+pub async fn list_remote_trash(user_id: String, app_handle: AppHandle) -> Result<Vec<RemoteTrashEntry>, String> {
+    let (client, api_config, credentials) = signed_in(&user_id, &app_handle).await?;
+    fetch_trash(&client, &api_config, &credentials).await
+}
+
+/// Take `remote_path` out of the remote trash; its history entries count as uploaded again
+#[tauri::command]
+pub async fn restore_remote_file(user_id: String, remote_path: String, app_handle: AppHandle) -> Result<(), String> {
+    let (client, api_config, mut credentials) = signed_in(&user_id, &app_handle).await?;
+    let url = api_config.optional_url(&api_config.restore_file, "Restore file")?;
+    let body = serde_json::json!({ "file_name": remote_path });
+    let restored = post(&client, &api_config, &url, &mut credentials, body, &app_handle).await;
+    audit::record(&app_handle, "remote_restore", &remote_path, Some(&user_id), None, &restored);
+    restored?;
+    set_history_status(&user_id, vec![remote_path.clone()], TRASHED, "success", &app_handle).await?;
+    println!("♻️ Restored {} from the remote trash", remote_path);
+    Ok(())
+}
+
+/// Permanently delete `names` from the remote trash, or everything in it. Run by `confirm_action` once the user
+/// confirms `purge_remote_trash`; returns the names purged.
+pub(super) async fn purge(user_id: &str, names: Option<Vec<String>>, app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let (client, api_config, mut credentials) = signed_in(user_id, app_handle).await?;
+    let url = api_config.optional_url(&api_config.purge_trash, "Purge trash")?;
+    let names = match names {
+        Some(names) => names,
+        None => fetch_trash(&client, &api_config, &credentials).await?.into_iter().map(|e| e.name).collect(),
+    };
+    if names.is_empty() {
+        return Ok(names);
+    }
+    let body = serde_json::json!({ "file_names": names });
+    let purged = post(&client, &api_config, &url, &mut credentials, body, app_handle).await;
+    for name in &names {
+        audit::record(app_handle, "remote_purge", name, Some(user_id), None, &purged);
+    }
+    purged?;
+    set_history_status(user_id, names.clone(), TRASHED, "deleted", app_handle).await?;
+    for name in &names {
+        link_batch::revoke_links_to(&client, &api_config, &credentials, name, app_handle).await;
+    }
+    println!("🗑️ Purged {} file(s) from the remote trash", names.len());
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trash_listings_and_delete_responses() {
+        let json = serde_json::json!({ "files": [
+            "a.txt",
+            { "file_name": "b.txt", "size": 3, "deleted_at": "2024-01-01T00:00:00Z", "expires_at": "2024-01-31T00:00:00Z" },
+            { "id": 1 },
+        ] });
+        let trash = parse_trash(&json);
+        assert_eq!(trash.len(), 2);
+        assert_eq!(trash[0].name, "a.txt");
+        assert_eq!((trash[1].size, trash[1].purge_at.as_deref()), (Some(3), Some("2024-01-31T00:00:00Z")));
+
+        assert!(is_restorable(r#"{ "deleted": true, "restorable": true }"#));
+        assert!(!is_restorable(r#"{ "deleted": true }"#));
+        assert!(!is_restorable("OK"));
+    }
+}
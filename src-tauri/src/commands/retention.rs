@@ -0,0 +1,271 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::settings::{current_settings, update_settings};
+use super::{account_scope, app_data_root, crash_reports, history_log, hooks};
+
+// =============================================================================================================
+// ============================================== DATA RETENTION ===============================================
+// =============================================================================================================
+//
+// Optional limits on how much local history and logging is kept: history entries older than N days or beyond
+// the newest N per account (archives included), and a size cap per log (hook runs, crash reports) that drops
+// the oldest records first. A maintenance pass applies them every few hours and on `run_maintenance_now`.
+// The audit log is never pruned; it exists to outlive what it records.
+
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// Give the app time to settle before the first pass
+const MAINTENANCE_START_DELAY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Drop history entries older than this many days
+    pub history_max_age_days: Option<u32>,
+    /// Keep only this many of the newest history entries per account
+    pub history_max_entries: Option<usize>,
+    /// Size cap per log in MB
+    pub log_max_mb: Option<u64>,
+}
+
+impl RetentionPolicy {
+    fn is_empty(&self) -> bool {
+        self == &RetentionPolicy::default()
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountPrune {
+    pub user_id: String,
+    pub entries_removed: usize,
+    pub archives_removed: usize,
+    pub bytes_freed: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LogPrune {
+    /// "hook_runs" | "crash_reports"
+    pub log: String,
+    /// Lines or reports removed
+    pub removed: usize,
+    pub bytes_freed: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MaintenanceReport {
+    pub ran_at: String,
+    pub policy: RetentionPolicy,
+    /// Accounts that had something pruned
+    pub history: Vec<AccountPrune>,
+    pub logs: Vec<LogPrune>,
+    pub bytes_freed: u64,
+    /// Accounts or logs that couldn't be pruned
+    pub errors: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct MaintenanceRunner {
+    running: bool,
+    last_report: Option<MaintenanceReport>,
+}
+
+pub type MaintenanceState = Mutex<MaintenanceRunner>;
+pub fn new_maintenance_state() -> MaintenanceState { Mutex::new(MaintenanceRunner::default()) }
+
+/// The newest lines of `content` that fit in `max_bytes`, and how many older lines were dropped
+fn newest_lines_within(content: &str, max_bytes: u64) -> (String, usize) {
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut size = 0;
+    let keep_from = lines
+        .iter()
+        .rposition(|line| {
+            size += line.len() as u64 + 1;
+            size > max_bytes
+        })
+        .map_or(0, |i| i + 1);
+    let mut kept = String::with_capacity(size.min(content.len() as u64) as usize);
+    for line in &lines[keep_from..] {
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    (kept, keep_from)
+}
+
+fn trim_hook_log(max_bytes: u64, app_handle: &AppHandle) -> Result<Option<LogPrune>, String> {
+    let path = hooks::hook_log_path(app_handle)?;
+    if std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0) <= max_bytes {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read hook log: {}", e))?;
+    let (kept, removed) = newest_lines_within(&content, max_bytes);
+    let tmp = path.with_extension("jsonl.tmp");
+    std::fs::write(&tmp, &kept).map_err(|e| format!("Failed to write hook log: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace hook log: {}", e))?;
+    Ok(Some(LogPrune { log: "hook_runs".to_string(), removed, bytes_freed: (content.len() - kept.len()) as u64 }))
+}
+
+/// Delete the oldest crash reports until the rest fit in `max_bytes`. Report ids start with their timestamp.
+fn trim_crash_reports(max_bytes: u64, app_handle: &AppHandle) -> Result<Option<LogPrune>, String> {
+    let dir = crash_reports::crash_dir(app_handle)?;
+    let Ok(entries) = std::fs::read_dir(&dir) else { return Ok(None) };
+    let mut reports: Vec<(String, u64)> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| Some((e.file_name().to_str()?.to_string(), e.metadata().ok()?.len())))
+        .collect();
+    reports.sort();
+    let mut total: u64 = reports.iter().map(|(_, bytes)| bytes).sum();
+    let mut pruned = LogPrune { log: "crash_reports".to_string(), removed: 0, bytes_freed: 0 };
+    for (name, bytes) in reports {
+        if total <= max_bytes {
+            break;
+        }
+        std::fs::remove_file(dir.join(&name)).map_err(|e| format!("Failed to delete crash report {}: {}", name, e))?;
+        total -= bytes;
+        pruned.removed += 1;
+        pruned.bytes_freed += bytes;
+    }
+    Ok((pruned.removed > 0).then_some(pruned))
+}
+
+/// Accounts with history in the data dir
+fn history_accounts(root: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(root) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| e.file_name().to_str().map(str::to_string))
+        .filter(|user_id| account_scope::check_user_id(user_id).is_ok())
+        .filter(|user_id| {
+            let dir = root.join(user_id);
+            dir.join(format!("list-upload-{}.json", user_id)).exists() || dir.join("history-archive").is_dir()
+        })
+        .collect()
+}
+
+/// One maintenance pass. Skipped (`None`) while another pass is running.
+async fn run_maintenance(app_handle: &AppHandle) -> Result<Option<MaintenanceReport>, String> {
+    {
+        let state = app_handle.state::<MaintenanceState>();
+        let mut runner = state.lock().unwrap();
+        if runner.running {
+            return Ok(None);
+        }
+        runner.running = true;
+    }
+    let result = async {
+        let policy = current_settings(app_handle).retention;
+        let mut report = MaintenanceReport {
+            ran_at: Utc::now().to_rfc3339(),
+            policy: policy.clone(),
+            history: Vec::new(),
+            logs: Vec::new(),
+            bytes_freed: 0,
+            errors: Vec::new(),
+        };
+
+        if policy.history_max_age_days.is_some() || policy.history_max_entries.is_some() {
+            let cutoff = policy.history_max_age_days.map(|days| Utc::now() - chrono::Duration::days(days as i64));
+            for user_id in history_accounts(&app_data_root(app_handle)?) {
+                match history_log::prune(&user_id, cutoff, policy.history_max_entries, app_handle).await {
+                    Ok(pruned) if pruned.entries_removed > 0 || pruned.archives_removed > 0 => report.history.push(AccountPrune {
+                        user_id,
+                        entries_removed: pruned.entries_removed,
+                        archives_removed: pruned.archives_removed,
+                        bytes_freed: pruned.bytes_freed,
+                    }),
+                    Ok(_) => {}
+                    Err(e) => report.errors.push(format!("{}: {}", user_id, e)),
+                }
+            }
+        }
+
+        if let Some(max_bytes) = policy.log_max_mb.map(|mb| mb * 1024 * 1024) {
+            for trimmed in [trim_hook_log(max_bytes, app_handle), trim_crash_reports(max_bytes, app_handle)] {
+                match trimmed {
+                    Ok(Some(pruned)) => report.logs.push(pruned),
+                    Ok(None) => {}
+                    Err(e) => report.errors.push(e),
+                }
+            }
+        }
+
+        report.bytes_freed = report.history.iter().map(|h| h.bytes_freed).chain(report.logs.iter().map(|l| l.bytes_freed)).sum();
+        Ok(report)
+    }
+    .await;
+
+    let state = app_handle.state::<MaintenanceState>();
+    let mut runner = state.lock().unwrap();
+    runner.running = false;
+    if let Ok(report) = &result {
+        runner.last_report = Some(report.clone());
+    }
+    result.map(Some)
+}
+
+/// Background task applying the retention policy every few hours. Called once in setup.
+pub fn start_maintenance_scheduler(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(MAINTENANCE_START_DELAY).await;
+        loop {
+            if !current_settings(&app_handle).retention.is_empty() {
+                match run_maintenance(&app_handle).await {
+                    Ok(Some(report)) => {
+                        if report.bytes_freed > 0 || !report.errors.is_empty() {
+                            println!("🧹 Maintenance pass freed {} bytes", report.bytes_freed);
+                            app_handle.emit("maintenance_report", &report).ok();
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => println!("[MAINTENANCE] {}", e),
+                }
+            }
+            tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+        }
+    });
+}
+
+/// Replace the retention policy; unset limits keep everything. Applies from the next maintenance pass.
+#[tauri::command]
+pub async fn set_retention_policy(policy: RetentionPolicy, app_handle: AppHandle) -> Result<RetentionPolicy, String> {
+    if policy.history_max_age_days == Some(0) || policy.history_max_entries == Some(0) || policy.log_max_mb == Some(0) {
+        return Err("Retention limits must be at least 1".to_string());
+    }
+    update_settings(&app_handle, |s| s.retention = policy.clone())?;
+    println!("🧹 Retention policy: {:?}", policy);
+    Ok(policy)
+}
+
+/// Apply the retention policy now and report what was pruned
+#[tauri::command]
+pub async fn run_maintenance_now(app_handle: AppHandle) -> Result<MaintenanceReport, String> {
+    run_maintenance(&app_handle)
+        .await?
+        .ok_or_else(|| "A maintenance pass is already running".to_string())
+}
+
+/// Report of the last scheduled or manual pass
+#[tauri::command]
+pub async fn get_last_maintenance_report(app_handle: AppHandle) -> Result<Option<MaintenanceReport>, String> {
+    Ok(app_handle.state::<MaintenanceState>().lock().unwrap().last_report.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_keep_their_newest_lines() {
+        let content = "aaaa\nbbbb\ncccc\n";
+        assert_eq!(newest_lines_within(content, 100), (content.to_string(), 0));
+        assert_eq!(newest_lines_within(content, 10), ("bbbb\ncccc\n".to_string(), 1));
+        assert_eq!(newest_lines_within(content, 9), ("cccc\n".to_string(), 2));
+        assert_eq!(newest_lines_within(content, 3), (String::new(), 3));
+    }
+}
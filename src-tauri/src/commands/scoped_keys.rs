@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use super::account::account_request;
+use super::{account_scope, audit, current_api_config, get_user_data_dir};
+
+// =============================================================================================================
+// ================================================ SCOPED KEYS ================================================
+// =============================================================================================================
+//
+// Limited API keys for scripts and automation, minted by the server so the main app key never has to leave the
+// app. The secret is returned once at creation; only metadata (permissions, expiry, a short prefix to recognise
+// the key by) is kept locally.
+
+pub const SCOPED_KEY_PERMISSIONS: &[&str] = &["upload", "download", "list", "links", "delete"];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScopedKeyInfo {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub permissions: Vec<String>,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    /// First characters of the secret
+    pub prefix: String,
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CreatedScopedKey {
+    pub key: ScopedKeyInfo,
+    /// Shown once; not stored anywhere
+    pub secret: String,
+}
+
+fn scoped_keys_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join(format!("scoped-keys-{}.json", user_id)))
+}
+
+fn read_scoped_keys(user_id: &str, app_handle: &AppHandle) -> Result<Vec<ScopedKeyInfo>, String> {
+    let path = scoped_keys_path(user_id, app_handle)?;
+    if !path.exists() { return Ok(vec![]); }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read scoped keys: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse scoped keys: {}", e))
+}
+
+fn write_scoped_keys(user_id: &str, keys: &[ScopedKeyInfo], app_handle: &AppHandle) -> Result<(), String> {
+    let path = scoped_keys_path(user_id, app_handle)?;
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create user dir: {}", e))?; }
+    let json = serde_json::to_string_pretty(keys).map_err(|e| format!("Failed to serialize scoped keys: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write scoped keys: {}", e))
+}
+
+fn is_expired(key: &ScopedKeyInfo) -> bool {
+    key.expires_at
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc) <= Utc::now())
+        .unwrap_or(false)
+}
+
+/// Mint a key limited to `permissions`. `expiry` is an RFC 3339 timestamp; `None` means the key never expires.
+#[tauri::command]
+pub async fn create_scoped_key(
+    user_id: String,
+    permissions: Vec<String>,
+    expiry: Option<String>,
+    label: Option<String>,
+    app_handle: AppHandle,
+) -> Result<CreatedScopedKey, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut permissions: Vec<String> = permissions.iter().map(|p| p.trim().to_lowercase()).collect();
+    permissions.sort();
+    permissions.dedup();
+    if permissions.is_empty() {
+        return Err("A scoped key needs at least one permission".to_string());
+    }
+    if let Some(unknown) = permissions.iter().find(|p| !SCOPED_KEY_PERMISSIONS.contains(&p.as_str())) {
+        return Err(format!("Unknown permission '{}' (expected one of: {})", unknown, SCOPED_KEY_PERMISSIONS.join(", ")));
+    }
+    let expires_at = match expiry.as_deref().map(str::trim).filter(|e| !e.is_empty()) {
+        Some(expiry) => {
+            let at = DateTime::parse_from_rfc3339(expiry).map_err(|e| format!("Invalid expiry '{}': {}", expiry, e))?;
+            if at.with_timezone(&Utc) <= Utc::now() {
+                return Err("Expiry must be in the future".to_string());
+            }
+            Some(at.with_timezone(&Utc).to_rfc3339())
+        }
+        None => None,
+    };
+    let label = label.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+
+    let endpoint = current_api_config(&app_handle).scoped_key_create;
+    let body = serde_json::json!({ "permissions": permissions, "expires_at": expires_at, "label": label });
+    let (_, text) = account_request(&endpoint, "Scoped key", body, &app_handle).await?;
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let field = |keys: &[&str]| keys.iter().find_map(|k| json.get(*k)?.as_str().map(str::to_string));
+    let id = field(&["key_id", "id"]).ok_or("No key id in response")?;
+    let secret = field(&["secret", "key", "api_key"]).ok_or("No key in response")?;
+
+    let key = ScopedKeyInfo {
+        id,
+        label,
+        permissions,
+        created_at: Utc::now().to_rfc3339(),
+        expires_at,
+        prefix: secret.chars().take(8).collect(),
+        revoked: false,
+    };
+    let mut keys = read_scoped_keys(&user_id, &app_handle)?;
+    keys.push(key.clone());
+    write_scoped_keys(&user_id, &keys, &app_handle)?;
+
+    println!("🔑 Created scoped key {} [{}]", key.id, key.permissions.join(", "));
+    Ok(CreatedScopedKey { key, secret })
+}
+
+/// Keys minted from this app, newest first; expired and revoked ones only with `include_inactive`
+#[tauri::command]
+pub async fn list_scoped_keys(user_id: String, include_inactive: Option<bool>, app_handle: AppHandle) -> Result<Vec<ScopedKeyInfo>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut keys = read_scoped_keys(&user_id, &app_handle)?;
+    if !include_inactive.unwrap_or(false) {
+        keys.retain(|k| !k.revoked && !is_expired(k));
+    }
+    keys.reverse();
+    Ok(keys)
+}
+
+#[tauri::command]
+pub async fn revoke_scoped_key(user_id: String, key_id: String, app_handle: AppHandle) -> Result<ScopedKeyInfo, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut keys = read_scoped_keys(&user_id, &app_handle)?;
+    let index = keys.iter().position(|k| k.id == key_id).ok_or_else(|| format!("Scoped key {} not found", key_id))?;
+
+    let endpoint = current_api_config(&app_handle).scoped_key_revoke;
+    let revoked = account_request(&endpoint, "Revoke scoped key", serde_json::json!({ "key_id": key_id }), &app_handle).await;
+    audit::record(&app_handle, "scoped_key_revoke", &key_id, Some(&user_id), None, &revoked);
+    revoked?;
+
+    keys[index].revoked = true;
+    write_scoped_keys(&user_id, &keys, &app_handle)?;
+    println!("🔑 Revoked scoped key {}", key_id);
+    Ok(keys[index].clone())
+}
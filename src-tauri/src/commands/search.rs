@@ -0,0 +1,203 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::redaction::println_redacted;
+use super::{
+    current_api_config, ensure_valid_token, load_credentials, network, read_public_links, read_upload_history, workspaces,
+    PublicLinkEntry, SavedCredentials, UploadLogEntry,
+};
+
+// =============================================================================================================
+// ================================================== SEARCH ===================================================
+// =============================================================================================================
+//
+// One query over the remote listing, the local upload history (names, tags and notes) and the saved public
+// links of the signed-in account, run concurrently. Every word of the query must appear in a field
+// (case-insensitive); whole-name and word-start matches rank above matches inside a word. Backends without the
+// `list_files` endpoint are searched through history and links only.
+
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchHit {
+    Remote { name: String, size: Option<u64> },
+    History { entry: Box<UploadLogEntry> },
+    Link { link: PublicLinkEntry },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchResult {
+    pub score: u32,
+    #[serde(flatten)]
+    pub hit: SearchHit,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchResults {
+    pub query: String,
+    /// Best match first
+    pub results: Vec<SearchResult>,
+    /// Sources that couldn't be searched, with the reason
+    pub unavailable: Vec<String>,
+}
+
+fn terms(query: &str) -> Vec<String> {
+    query.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// How well `text` matches all `terms`; `None` when one is missing
+fn score(terms: &[String], text: &str) -> Option<u32> {
+    let text = text.to_lowercase();
+    let mut total = 0;
+    for term in terms {
+        let pos = text.find(term.as_str())?;
+        total += if text == *term {
+            100
+        } else if pos == 0 {
+            60
+        } else if !text[..pos].ends_with(char::is_alphanumeric) {
+            40
+        } else {
+            20
+        };
+    }
+    Some(total)
+}
+
+/// Best score over `fields`; the file name part of a path counts as a field of its own
+fn best_score<'a>(terms: &[String], fields: impl IntoIterator<Item = &'a str>) -> Option<u32> {
+    fields
+        .into_iter()
+        .flat_map(|field| [field, field.rsplit(['/', '\\']).next().unwrap_or(field)])
+        .filter_map(|field| score(terms, field))
+        .max()
+}
+
+/// Remote objects in a list response: a bare array or `{ "files": [...] }` of names or objects
+fn parse_listing(json: &serde_json::Value) -> Vec<(String, Option<u64>)> {
+    let items = json.as_array().or_else(|| json.get("files").and_then(|f| f.as_array()));
+    items
+        .into_iter()
+        .flatten()
+        .filter_map(|item| match item {
+            serde_json::Value::String(name) => Some((name.clone(), None)),
+            _ => {
+                let name = ["name", "file_name", "remote_path"].iter().find_map(|k| item.get(*k)?.as_str())?;
+                let size = ["size", "file_size"].iter().find_map(|k| item.get(*k)?.as_u64());
+                Some((name.to_string(), size))
+            }
+        })
+        .collect()
+}
+
+async fn search_remote(terms: &[String], url: &str, credentials: &SavedCredentials, app_handle: &AppHandle) -> Result<Vec<SearchResult>, String> {
+    let response = workspaces::scope(network::client(app_handle).get(url), credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .send()
+        .await
+        .map_err(|e| format!("HTTP error: {}", e))?;
+    let status = response.status();
+    let text = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    Ok(parse_listing(&json)
+        .into_iter()
+        .filter_map(|(name, size)| Some(SearchResult { score: best_score(terms, [name.as_str()])?, hit: SearchHit::Remote { name, size } }))
+        .collect())
+}
+
+async fn search_history(terms: &[String], user_id: &str, app_handle: &AppHandle) -> Result<Vec<SearchResult>, String> {
+    let entries = read_upload_history(user_id.to_string(), app_handle.clone()).await?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let fields = [entry.remote_path.as_str(), entry.local_path.as_str(), entry.blake3_hash.as_str()]
+                .into_iter()
+                .chain(entry.tags.iter().map(String::as_str))
+                .chain(entry.note.as_deref());
+            let score = best_score(terms, fields)?;
+            Some(SearchResult { score, hit: SearchHit::History { entry: Box::new(entry) } })
+        })
+        .collect())
+}
+
+fn search_links(terms: &[String], user_id: &str, app_handle: &AppHandle) -> Result<Vec<SearchResult>, String> {
+    Ok(read_public_links(user_id, app_handle)?
+        .into_iter()
+        .filter_map(|link| {
+            let fields = [Some(&link.remote_path), link.custom_title.as_ref(), link.custom_description.as_ref(), link.custom_slug.as_ref()];
+            let score = best_score(terms, fields.into_iter().flatten().map(String::as_str))?;
+            Some(SearchResult { score, hit: SearchHit::Link { link } })
+        })
+        .collect())
+}
+
+/// Newest first among equal scores
+fn timestamp(hit: &SearchHit) -> &str {
+    match hit {
+        SearchHit::Remote { .. } => "",
+        SearchHit::History { entry } => &entry.timestamp,
+        SearchHit::Link { link } => &link.created_at,
+    }
+}
+
+/// Search remote files, upload history and public links of the signed-in account
+#[tauri::command]
+pub async fn search(query: String, limit: Option<usize>, app_handle: AppHandle) -> Result<SearchResults, String> {
+    let terms = terms(&query);
+    if terms.is_empty() {
+        return Ok(SearchResults { query, results: Vec::new(), unavailable: Vec::new() });
+    }
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let user_id = credentials.user_id.clone();
+
+    let remote = async {
+        let url = api_config.optional_url(&api_config.list_files, "File listing")?;
+        ensure_valid_token(&network::client(&app_handle), &api_config, &mut credentials, &app_handle).await?;
+        search_remote(&terms, &url, &credentials, &app_handle).await
+    };
+    let history = search_history(&terms, &user_id, &app_handle);
+    let links = async { search_links(&terms, &user_id, &app_handle) };
+    let (remote, history, links) = tokio::join!(remote, history, links);
+
+    let mut results = Vec::new();
+    let mut unavailable = Vec::new();
+    for (source, found) in [("remote", remote), ("history", history), ("links", links)] {
+        match found {
+            Ok(found) => results.extend(found),
+            Err(e) => {
+                println_redacted!("[SEARCH] {} not searched: {}", source, e);
+                unavailable.push(format!("{}: {}", source, e));
+            }
+        }
+    }
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| timestamp(&b.hit).cmp(timestamp(&a.hit))));
+    results.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(SearchResults { query, results, unavailable })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_and_word_start_matches_rank_first() {
+        let q = terms("Report");
+        assert_eq!(score(&q, "report"), Some(100));
+        assert_eq!(score(&q, "report-2024.pdf"), Some(60));
+        assert_eq!(score(&q, "q3 report.pdf"), Some(40));
+        assert_eq!(score(&q, "myreport.pdf"), Some(20));
+        assert_eq!(score(&terms("q3 pdf"), "Q3 Report.PDF"), Some(100));
+        assert_eq!(score(&terms("q3 xls"), "q3 report.pdf"), None);
+        assert_eq!(best_score(&q, ["backups/report"]), Some(100));
+    }
+
+    #[test]
+    fn listings_take_names_or_objects() {
+        let json = serde_json::json!({ "files": ["a.txt", { "file_name": "b.txt", "size": 3 }, { "id": 1 }] });
+        assert_eq!(parse_listing(&json), [("a.txt".to_string(), None), ("b.txt".to_string(), Some(3))]);
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::SeekFrom;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use futures_util::StreamExt;
+use tauri::AppHandle;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use super::redaction::println_redacted;
+use super::{bandwidth, disk_space, local_paths, workspaces, SavedCredentials};
+
+// =============================================================================================================
+// ============================================ SEGMENTED DOWNLOADS ============================================
+// =============================================================================================================
+//
+// Large downloads from servers that accept Range requests are split into segments fetched in parallel, each
+// writing at its own offset of the preallocated target. A failed segment resumes from where it stopped.
+
+/// Smaller files aren't worth the extra requests
+pub const MIN_SEGMENTED_BYTES: u64 = 32 * 1024 * 1024;
+const SEGMENT_RETRIES: u32 = 3;
+const PROGRESS_TICK: Duration = Duration::from_millis(100);
+
+/// Fetch `[*pos, end)` into `file`, advancing `pos` by what was written
+#[allow(clippy::too_many_arguments)]
+async fn fetch_range(
+    client: &reqwest::Client,
+    url: &str,
+    credentials: &SavedCredentials,
+    file: &mut tokio::fs::File,
+    pos: &mut u64,
+    end: u64,
+    done: &AtomicU64,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    file.seek(SeekFrom::Start(*pos)).await.map_err(|e| format!("Failed to seek: {}", e))?;
+    let response = workspaces::scope(client.get(url), credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .header(reqwest::header::RANGE, format!("bytes={}-{}", *pos, end - 1))
+        .send()
+        .await
+        .map_err(|e| format!("Segment request failed: {}", e))?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(format!("Range request returned {}", response.status()));
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut writer = tokio::io::BufWriter::new(&mut *file);
+    let streamed: Result<(), String> = async {
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("Download chunk error: {}", e))?;
+            let take = chunk.len().min((end - *pos) as usize);
+            bandwidth::pace(app_handle, take).await;
+            writer.write_all(&chunk[..take]).await.map_err(|e| disk_space::write_error("Failed to write chunk", e))?;
+            *pos += take as u64;
+            done.fetch_add(take as u64, Ordering::Relaxed);
+            if *pos >= end {
+                break;
+            }
+        }
+        Ok(())
+    }
+    .await;
+    // keep what arrived so a retry resumes at `pos`
+    writer.flush().await.map_err(|e| disk_space::write_error("Failed to write file", e))?;
+    streamed?;
+    if *pos < end {
+        return Err(format!("Segment ended at {} of {}", *pos, end));
+    }
+    Ok(())
+}
+
+async fn download_segment(
+    client: &reqwest::Client,
+    url: &str,
+    credentials: &SavedCredentials,
+    path: &str,
+    (start, end): (u64, u64),
+    done: Arc<AtomicU64>,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(local_paths::to_fs_path(path))
+        .await
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut pos = start;
+    let mut attempt = 0;
+    loop {
+        match fetch_range(client, url, credentials, &mut file, &mut pos, end, &done, app_handle).await {
+            Ok(()) => return Ok(()),
+            // retrying can't make room
+            Err(e) if attempt < SEGMENT_RETRIES && !disk_space::is_disk_full(&e) => {
+                attempt += 1;
+                println_redacted!("[DOWNLOAD] Segment {}-{} failed at {}: {} (retry {}/{})", start, end, pos, e, attempt, SEGMENT_RETRIES);
+                tokio::time::sleep(Duration::from_millis(500 * 2u64.pow(attempt))).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Download `total` bytes in `segments` parallel ranges into the existing file at `path`.
+/// `on_progress` gets the combined byte count on a fixed tick and once at the end.
+#[allow(clippy::too_many_arguments)]
+pub async fn download(
+    client: &reqwest::Client,
+    url: &str,
+    credentials: &SavedCredentials,
+    path: &str,
+    total: u64,
+    segments: usize,
+    on_progress: &mut impl FnMut(u64),
+    app_handle: &AppHandle,
+) -> Result<u64, String> {
+    let done = Arc::new(AtomicU64::new(0));
+    let segment_len = total.div_ceil(segments as u64);
+    let ranges = (0..segments as u64)
+        .map(|i| (i * segment_len, ((i + 1) * segment_len).min(total)))
+        .filter(|(start, end)| start < end);
+    let all = futures_util::future::try_join_all(
+        ranges.map(|range| download_segment(client, url, credentials, path, range, done.clone(), app_handle)),
+    );
+    tokio::pin!(all);
+
+    println!("📥 Downloading {} bytes in {} segments", total, segments);
+    let mut ticker = tokio::time::interval(PROGRESS_TICK);
+    let result = loop {
+        tokio::select! {
+            result = &mut all => break result,
+            _ = ticker.tick() => on_progress(done.load(Ordering::Relaxed)),
+        }
+    };
+    let downloaded = done.load(Ordering::Relaxed);
+    on_progress(downloaded);
+    result.map(|_| downloaded)
+}
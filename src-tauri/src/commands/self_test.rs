@@ -0,0 +1,241 @@
+use chrono::Utc;
+use percent_encoding::utf8_percent_encode;
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use super::redaction::println_redacted;
+use super::{
+    app_data_root, clock_skew, current_api_config, ensure_valid_token, lifecycle, load_credentials, network, redaction,
+    upload_route, ApiConfig, SavedCredentials, QUERY_ENCODE_SET,
+};
+
+// =============================================================================================================
+// ================================================= SELF TEST =================================================
+// =============================================================================================================
+//
+// One-click diagnostics for support: server health, sign-in, local write access, clock skew against the
+// server's `Date` header, and a round trip of a tiny test object (upload, download, compare, delete). Checks
+// that depend on an earlier one are skipped rather than failed when it didn't pass, so the first failure
+// points at the actual problem.
+
+/// Skew beyond this fails the clock check; token expiry and signed links start misbehaving around here
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+const ROUND_TRIP_ATTEMPTS: u32 = 3;
+const ROUND_TRIP_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SelfTestCheck {
+    pub name: String,
+    /// "pass" | "fail" | "skip"
+    pub status: String,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub ran_at: String,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+#[derive(Default)]
+struct SelfTest {
+    checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTest {
+    async fn run<T, F: Future<Output = Result<(T, String), String>>>(&mut self, name: &str, check: F) -> Option<T> {
+        let started = Instant::now();
+        let result = check.await;
+        let (status, detail, value) = match result {
+            Ok((value, detail)) => ("pass", detail, Some(value)),
+            Err(e) => ("fail", redaction::redact(&e), None),
+        };
+        println_redacted!("[SELFTEST] {} {}: {}", name, status, detail);
+        self.checks.push(SelfTestCheck {
+            name: name.to_string(),
+            status: status.to_string(),
+            detail,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+        value
+    }
+
+    fn skip(&mut self, name: &str, reason: &str) {
+        self.checks.push(SelfTestCheck {
+            name: name.to_string(),
+            status: "skip".to_string(),
+            detail: reason.to_string(),
+            duration_ms: 0,
+        });
+    }
+}
+
+/// Health endpoint answers; returns server minus local time in seconds if the server sent a `Date` header
+async fn check_api_health(client: &reqwest::Client, api_config: &ApiConfig, app_handle: &AppHandle) -> Result<(Option<i64>, String), String> {
+    let url = format!("{}/health", api_config.api_base_url.trim_end_matches('/'));
+    let sent = Utc::now();
+    let response = client.get(&url).send().await.map_err(|e| format!("Request failed: {}", e))?;
+    let received = Utc::now();
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Server responded with status {}", status));
+    }
+    clock_skew::observe(app_handle, response.headers(), sent, received);
+    Ok((clock_skew::measure(response.headers(), sent, received), format!("Status {}", status)))
+}
+
+async fn check_auth(client: &reqwest::Client, api_config: &ApiConfig, app_handle: &AppHandle) -> Result<(SavedCredentials, String), String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("Not signed in")?;
+    ensure_valid_token(client, api_config, &mut credentials, app_handle).await?;
+    let detail = format!("Signed in as {}", credentials.username.as_deref().unwrap_or(&credentials.user_id));
+    Ok((credentials, detail))
+}
+
+async fn check_data_dir(app_handle: &AppHandle) -> Result<((), String), String> {
+    let dir = app_data_root(app_handle)?;
+    tokio::fs::create_dir_all(&dir).await.map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let probe = dir.join(format!(".selftest-{}", uuid::Uuid::new_v4().simple()));
+    let written = async {
+        tokio::fs::write(&probe, b"firestarter").await.map_err(|e| format!("Failed to write: {}", e))?;
+        let read = tokio::fs::read(&probe).await.map_err(|e| format!("Failed to read back: {}", e))?;
+        if read != b"firestarter" {
+            return Err("Read back different content".to_string());
+        }
+        Ok(())
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&probe).await;
+    written.map(|_| ((), format!("{} is writable", dir.display())))
+}
+
+fn check_clock_skew(offset_secs: i64) -> Result<((), String), String> {
+    let detail = format!("Local clock is {}s {} the server", offset_secs.abs(), if offset_secs <= 0 { "ahead of" } else { "behind" });
+    if offset_secs.abs() > MAX_CLOCK_SKEW_SECS {
+        return Err(detail);
+    }
+    Ok(((), detail))
+}
+
+async fn upload_test_object(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: &str,
+    body: &[u8],
+) -> Result<((), String), String> {
+    let (_, upload_url) = upload_route(api_config, false);
+    let url = format!("{}?file_name={}", upload_url, utf8_percent_encode(name, QUERY_ENCODE_SET));
+    let response = client
+        .post(&url)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .body(body.to_vec())
+        .send()
+        .await
+        .map_err(|e| format!("Upload request failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Upload failed - Status: {}, Response: {}", status, text));
+    }
+    Ok(((), format!("Uploaded {} ({} bytes)", name, body.len())))
+}
+
+/// Download the test object and compare it; the server may need a moment before it's readable
+async fn verify_test_object(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    name: &str,
+    expected: &[u8],
+) -> Result<((), String), String> {
+    let url = format!(
+        "{}{}?file_name={}",
+        api_config.api_base_url,
+        api_config.download,
+        utf8_percent_encode(name, QUERY_ENCODE_SET)
+    );
+    let mut last_error = String::new();
+    for attempt in 1..=ROUND_TRIP_ATTEMPTS {
+        if attempt > 1 {
+            tokio::time::sleep(ROUND_TRIP_RETRY_DELAY).await;
+        }
+        let response = match client
+            .get(&url)
+            .header("X-User-Id", &credentials.user_id)
+            .header("X-User-App-Key", credentials.user_app_key.expose())
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                last_error = format!("Download request failed: {}", e);
+                continue;
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            last_error = format!("Download failed - Status: {}", status);
+            continue;
+        }
+        let bytes = response.bytes().await.map_err(|e| format!("Failed to read download: {}", e))?;
+        if bytes.as_ref() != expected {
+            return Err(format!(
+                "Content mismatch: expected {} ({} bytes), got {} ({} bytes)",
+                blake3::hash(expected).to_hex(),
+                expected.len(),
+                blake3::hash(&bytes).to_hex(),
+                bytes.len()
+            ));
+        }
+        return Ok(((), format!("Downloaded and matched blake3 {}", &blake3::hash(expected).to_hex()[..16])));
+    }
+    Err(last_error)
+}
+
+/// Run every check and return the matrix. Doesn't stop at the first failure.
+#[tauri::command]
+pub async fn run_self_test(app_handle: AppHandle) -> Result<SelfTestReport, String> {
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    let mut test = SelfTest::default();
+
+    let skew = test.run("api_health", check_api_health(&client, &api_config, &app_handle)).await;
+    let credentials = test.run("auth", check_auth(&client, &api_config, &app_handle)).await;
+    test.run("data_dir_write", check_data_dir(&app_handle)).await;
+
+    match skew {
+        Some(Some(skew)) => {
+            test.run("clock_skew", async { check_clock_skew(skew) }).await;
+        }
+        Some(None) => test.skip("clock_skew", "Server didn't send a usable Date header"),
+        None => test.skip("clock_skew", "Server unreachable"),
+    }
+
+    match credentials {
+        Some(credentials) => {
+            let name = format!(".firestarter-selftest-{}.txt", uuid::Uuid::new_v4().simple());
+            let body = format!("Firestarter self test {}\n", Utc::now().to_rfc3339()).into_bytes();
+            let uploaded = test.run("upload", upload_test_object(&client, &api_config, &credentials, &name, &body)).await;
+            if uploaded.is_some() {
+                test.run("download_verify", verify_test_object(&client, &api_config, &credentials, &name, &body)).await;
+                if let Err(e) = lifecycle::delete_remote(&client, &api_config, &credentials, &name, &app_handle).await {
+                    println_redacted!("[SELFTEST] Test object {} left behind: {}", name, e);
+                }
+            } else {
+                test.skip("download_verify", "Upload failed");
+            }
+        }
+        None => {
+            test.skip("upload", "Not signed in");
+            test.skip("download_verify", "Not signed in");
+        }
+    }
+
+    let passed = test.checks.iter().all(|c| c.status == "pass");
+    Ok(SelfTestReport { passed, ran_at: Utc::now().to_rfc3339(), checks: test.checks })
+}
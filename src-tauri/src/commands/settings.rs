@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::app_data_root;
+use super::bandwidth::BandwidthSchedule;
+use super::hooks::HookCommand;
+use super::local_walk::SymlinkPolicy;
+use super::network::IpPreference;
+use super::retention::RetentionPolicy;
+
+// =============================================================================================================
+// ================================================ APP SETTINGS ===============================================
+// =============================================================================================================
+//
+// Device-wide preferences owned by the Rust side, persisted to `settings.json`. Missing fields fall back to
+// their defaults so older files keep loading as settings are added.
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Serve Prometheus / JSON metrics on localhost
+    pub metrics_endpoint_enabled: bool,
+    pub metrics_endpoint_port: Option<u16>,
+    /// POST target for job completion events
+    pub webhook_url: Option<String>,
+    /// HMAC-SHA256 key for the `X-Firestarter-Signature` header
+    pub webhook_secret: Option<String>,
+    /// Transfers smaller than this don't trigger the webhook
+    pub webhook_min_transfer_bytes: Option<u64>,
+    pub pre_upload_hook: Option<HookCommand>,
+    pub post_download_hook: Option<HookCommand>,
+    /// Remote folder used when an upload doesn't name one
+    pub default_remote_prefix: Option<String>,
+    /// Pre-hash uploads and send the digest as `X-Expected-Blake3`
+    pub send_expected_hash: bool,
+    /// Fixed upload read buffer; automatic when unset
+    pub upload_buffer_bytes: Option<u64>,
+    /// Minimum time between progress events (default 100 ms)
+    pub progress_interval_ms: Option<u64>,
+    /// Percentage change that triggers a progress event before the interval is up (default 1%)
+    pub progress_min_percent: Option<f64>,
+    /// Download write buffer; 1 MB when unset
+    pub download_write_buffer_bytes: Option<u64>,
+    /// fsync downloads once they complete
+    pub download_fsync: bool,
+    /// Parallel range requests for large downloads (default 4, 1 disables)
+    pub download_segments: Option<u8>,
+    /// Keep finished downloads in a local cache keyed by blake3
+    pub download_cache_enabled: bool,
+    /// Download cache size limit; 1 GB when unset
+    pub download_cache_max_bytes: Option<u64>,
+    /// Most recent first
+    pub recent_download_dirs: Vec<String>,
+    pub recent_remote_dirs: Vec<String>,
+    /// Template for default upload names, e.g. `{date}-{basename}`
+    pub upload_name_template: Option<String>,
+    /// Move local files to the OS trash once the server confirms their hash
+    pub trash_after_upload: bool,
+    /// Global shortcut per action; defaults when unset, an empty accelerator disables the action
+    pub global_shortcuts: Option<std::collections::BTreeMap<String, String>>,
+    /// What folder walks do with symbolic links
+    pub symlink_policy: SymlinkPolicy,
+    /// Seconds a file's size must stay unchanged before an automatic upload; off when unset
+    pub upload_stable_secs: Option<u64>,
+    /// Read files other apps hold open exclusively from a Volume Shadow Copy (Windows)
+    pub shadow_copy_locked_files: bool,
+    /// Keep raw server responses in upload history entries
+    pub debug_history_responses: bool,
+    /// Let downloads write outside the download folders and picked paths
+    pub allow_any_download_path: bool,
+    /// Time windows with their own transfer speed limit
+    pub bandwidth_schedule: Option<BandwidthSchedule>,
+    /// Local IP address outgoing connections bind to; the system picks when unset
+    pub bind_address: Option<String>,
+    /// Address family tried first when a host has both
+    pub ip_preference: IpPreference,
+    /// Host name -> IP address, used instead of DNS for those hosts
+    pub dns_overrides: Option<std::collections::BTreeMap<String, String>>,
+    /// Grow the HTTP/2 flow-control window to the measured bandwidth-delay product
+    pub http2_adaptive_window: bool,
+    /// Seconds an idle pooled connection is kept; 90 when unset
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// TCP keepalive interval in seconds; off when unset
+    pub tcp_keepalive_secs: Option<u64>,
+    /// Send small TCP segments without delay (Nagle off); on when unset
+    pub tcp_nodelay: Option<bool>,
+    /// How much local history and logging is kept; everything when no limit is set
+    pub retention: RetentionPolicy,
+    /// Revoke a file's public links when it's deleted remotely (or purged from the remote trash); on when unset
+    pub revoke_links_on_delete: Option<bool>,
+}
+
+pub type AppSettingsState = Mutex<AppSettings>;
+
+fn settings_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join("settings.json"))
+}
+
+/// Read settings from disk, falling back to defaults. Called once at startup.
+pub fn load_app_settings(app_handle: &AppHandle) -> AppSettingsState {
+    let settings = settings_file_path(app_handle)
+        .ok()
+        .filter(|p| p.exists())
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|content| match serde_json::from_str(&content) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                println!("[SETTINGS] Failed to parse settings file: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default();
+    Mutex::new(settings)
+}
+
+/// Current settings snapshot
+pub fn current_settings(app_handle: &AppHandle) -> AppSettings {
+    app_handle.state::<AppSettingsState>().lock().unwrap().clone()
+}
+
+/// Apply `change` to the managed settings and persist the result. The file is written under the settings lock,
+/// so concurrent updates reach the disk in the order they were applied, and the change is only kept in memory
+/// once it has been saved.
+pub fn update_settings<F: FnOnce(&mut AppSettings)>(app_handle: &AppHandle, change: F) -> Result<AppSettings, String> {
+    let state = app_handle.state::<AppSettingsState>();
+    let mut settings = state.lock().unwrap();
+    let mut updated = settings.clone();
+    change(&mut updated);
+    let path = settings_file_path(app_handle)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&updated).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    // write-then-rename so a crash mid-write never truncates the existing file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    *settings = updated.clone();
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn get_app_settings(app_handle: AppHandle) -> Result<AppSettings, String> {
+    Ok(current_settings(&app_handle))
+}
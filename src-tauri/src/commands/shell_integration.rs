@@ -0,0 +1,346 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{data_dir, ipc_guard, local_paths};
+use super::transfers::{enqueue, NewUpload};
+use super::window_state::MAIN_WINDOW;
+
+// =============================================================================================================
+// ============================================= SHELL INTEGRATION =============================================
+// =============================================================================================================
+//
+// "Upload with Firestarter" in the system file manager: a per-user registry verb on Windows, a Nautilus script
+// and a Dolphin service menu on Linux, a Finder service on macOS. Each one runs this executable with
+// `--upload <paths>`; the single-instance plugin hands those arguments to the running instance (or the new
+// instance reads them at startup) and the files go into the transfer queue. Nothing needs admin rights.
+
+pub const UPLOAD_FLAG: &str = "--upload";
+const MENU_LABEL: &str = "Upload with Firestarter";
+
+/// Paths following `--upload`, resolved against the caller's working directory. Only regular files are kept.
+fn upload_paths(args: &[OsString], cwd: &Path) -> Vec<PathBuf> {
+    let Some(start) = args.iter().position(|a| a == UPLOAD_FLAG) else { return vec![] };
+    args[start + 1..]
+        .iter()
+        .map(|arg| {
+            let path = PathBuf::from(arg);
+            if path.is_absolute() { path } else { cwd.join(path) }
+        })
+        .filter(|path| {
+            let is_file = path.is_file();
+            if !is_file {
+                println!("[SHELL] Skipping {} (not a file)", path.display());
+            }
+            is_file
+        })
+        .collect()
+}
+
+/// Queue the files passed on a command line. Called at startup with our own arguments and from the
+/// single-instance callback with a second launch's arguments.
+pub fn handle_launch_args(app_handle: &AppHandle, args: Vec<OsString>, cwd: &Path) {
+    let paths = upload_paths(&args, cwd);
+    if paths.is_empty() {
+        return;
+    }
+    if let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    for path in &paths {
+        let file_path = local_paths::to_wire(path);
+        enqueue(app_handle, NewUpload { file_path, notify: true, ..Default::default() });
+    }
+    println!("📂 Queued {} file(s) from the file manager", paths.len());
+    app_handle.emit("shell_upload_received", serde_json::json!({ "count": paths.len() })).ok();
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn current_exe() -> Result<String, String> {
+    std::env::current_exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to locate the app executable: {}", e))
+}
+
+// ===== Windows =====
+
+#[cfg(target_os = "windows")]
+const REGISTRY_KEY: &str = r"HKCU\Software\Classes\*\shell\FirestarterUpload";
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run reg.exe: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("reg.exe failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn install(_app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let exe = current_exe()?;
+    let command = format!("\"{}\" {} \"%1\"", exe, UPLOAD_FLAG);
+    let command_key = format!(r"{}\command", REGISTRY_KEY);
+    reg(&["add", REGISTRY_KEY, "/ve", "/d", MENU_LABEL, "/f"])?;
+    reg(&["add", REGISTRY_KEY, "/v", "Icon", "/d", &exe, "/f"])?;
+    reg(&["add", &command_key, "/ve", "/d", &command, "/f"])?;
+    Ok(vec![REGISTRY_KEY.to_string()])
+}
+
+#[cfg(target_os = "windows")]
+fn remove(_app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    match reg(&["query", REGISTRY_KEY]) {
+        Ok(()) => reg(&["delete", REGISTRY_KEY, "/f"]).map(|_| vec![REGISTRY_KEY.to_string()]),
+        Err(_) => Ok(vec![]),
+    }
+}
+
+// ===== Linux =====
+
+#[cfg(target_os = "linux")]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quoting for an `Exec=` argument in a desktop entry
+#[cfg(target_os = "linux")]
+fn exec_quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(target_os = "linux")]
+fn linux_targets(app_handle: &AppHandle) -> Result<[PathBuf; 2], String> {
+    let data_home = match std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => app_handle
+            .path()
+            .home_dir()
+            .map_err(|e| format!("Failed to get home directory: {}", e))?
+            .join(".local/share"),
+    };
+    Ok([
+        data_home.join("nautilus/scripts").join(MENU_LABEL),
+        data_home.join("kio/servicemenus/firestarter-upload.desktop"),
+    ])
+}
+
+#[cfg(target_os = "linux")]
+fn write_executable(path: &Path, content: &str) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?; }
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("Failed to make {} executable: {}", path.display(), e))
+}
+
+#[cfg(target_os = "linux")]
+fn install(app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let exe = current_exe()?;
+    let [nautilus, dolphin] = linux_targets(app_handle)?;
+    // Nautilus runs scripts from the current folder with the selected names as arguments
+    let script = format!("#!/bin/sh\nexec {} {} \"$@\"\n", shell_quote(&exe), UPLOAD_FLAG);
+    write_executable(&nautilus, &script)?;
+    let service_menu = format!(
+        "[Desktop Entry]\nType=Service\nMimeType=application/octet-stream;\nX-KDE-ServiceTypes=KonqPopupMenu/Plugin\nActions=upload\n\n\
+         [Desktop Action upload]\nName={}\nIcon=firestarter\nExec={} {} %F\n",
+        MENU_LABEL,
+        exec_quote(&exe),
+        UPLOAD_FLAG
+    );
+    // Dolphin only honours service menus that are marked executable
+    write_executable(&dolphin, &service_menu)?;
+    Ok(vec![nautilus.display().to_string(), dolphin.display().to_string()])
+}
+
+#[cfg(target_os = "linux")]
+fn remove(app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let mut removed = Vec::new();
+    for path in linux_targets(app_handle)? {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            removed.push(path.display().to_string());
+        }
+    }
+    Ok(removed)
+}
+
+// ===== macOS =====
+
+#[cfg(target_os = "macos")]
+const SERVICE_INFO_PLIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict><key>default</key><string>__LABEL__</string></dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict><key>NSApplicationIdentifier</key><string>com.apple.finder</string></dict>
+			<key>NSSendFileTypes</key>
+			<array><string>public.item</string></array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#;
+
+/// A one-action Automator workflow: "Run Shell Script" with the selected files as arguments
+#[cfg(target_os = "macos")]
+const SERVICE_WORKFLOW: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>521</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key><string>List</string>
+					<key>Optional</key><true/>
+					<key>Types</key><array><string>com.apple.cocoa.path</string></array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key><string>List</string>
+					<key>Types</key><array><string>com.apple.cocoa.string</string></array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key><string>__COMMAND__</string>
+					<key>CheckedForUserDefaultShell</key><true/>
+					<key>inputMethod</key><integer>1</integer>
+					<key>shell</key><string>/bin/sh</string>
+					<key>source</key><string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+			</dict>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>serviceInputTypeIdentifier</key><string>com.apple.Automator.fileSystemObject</string>
+		<key>serviceOutputTypeIdentifier</key><string>com.apple.Automator.nothing</string>
+		<key>workflowTypeIdentifier</key><string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#;
+
+#[cfg(target_os = "macos")]
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(target_os = "macos")]
+fn service_bundle(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_handle
+        .path()
+        .home_dir()
+        .map_err(|e| format!("Failed to get home directory: {}", e))?
+        .join("Library/Services")
+        .join(format!("{}.workflow", MENU_LABEL)))
+}
+
+/// Make Finder pick up added or removed services without logging out
+#[cfg(target_os = "macos")]
+fn refresh_services() {
+    let _ = std::process::Command::new("/System/Library/CoreServices/pbs").arg("-flush").output();
+}
+
+#[cfg(target_os = "macos")]
+fn install(app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let exe = current_exe()?;
+    let bundle = service_bundle(app_handle)?;
+    let contents = bundle.join("Contents");
+    std::fs::create_dir_all(&contents).map_err(|e| format!("Failed to create {}: {}", contents.display(), e))?;
+    let command = format!("exec '{}' {} \"$@\"", exe.replace('\'', r"'\''"), UPLOAD_FLAG);
+    let info = SERVICE_INFO_PLIST.replace("__LABEL__", MENU_LABEL);
+    let workflow = SERVICE_WORKFLOW.replace("__COMMAND__", &xml_escape(&command));
+    std::fs::write(contents.join("Info.plist"), info).map_err(|e| format!("Failed to write service: {}", e))?;
+    std::fs::write(contents.join("document.wflow"), workflow).map_err(|e| format!("Failed to write service: {}", e))?;
+    refresh_services();
+    Ok(vec![bundle.display().to_string()])
+}
+
+#[cfg(target_os = "macos")]
+fn remove(app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let bundle = service_bundle(app_handle)?;
+    if !bundle.exists() {
+        return Ok(vec![]);
+    }
+    std::fs::remove_dir_all(&bundle).map_err(|e| format!("Failed to remove {}: {}", bundle.display(), e))?;
+    refresh_services();
+    Ok(vec![bundle.display().to_string()])
+}
+
+// ===== Other platforms =====
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn install(_app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    Err("Shell integration isn't available on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn remove(_app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    Ok(vec![])
+}
+
+/// Add "Upload with Firestarter" to the file manager's context menu. Returns what was written.
+#[tauri::command]
+pub async fn install_shell_integration(ipc_nonce: String, webview: tauri::Webview, app_handle: AppHandle) -> Result<Vec<String>, String> {
+    ipc_guard::check_sensitive("install_shell_integration", &webview, &ipc_nonce, &app_handle)?;
+    // a portable copy leaves nothing behind on the machine it runs on
+    if data_dir::is_portable() {
+        return Err("Shell integration is not available in portable mode".to_string());
+    }
+    let installed = install(&app_handle)?;
+    println!("📂 Shell integration installed: {}", installed.join(", "));
+    Ok(installed)
+}
+
+/// Undo `install_shell_integration`. Returns what was removed; nothing installed is not an error.
+#[tauri::command]
+pub async fn remove_shell_integration(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    let removed = remove(&app_handle)?;
+    println!("📂 Shell integration removed ({} item(s))", removed.len());
+    Ok(removed)
+}
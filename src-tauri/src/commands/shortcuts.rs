@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use super::settings::{current_settings, AppSettings};
+
+// =============================================================================================================
+// ============================================= GLOBAL SHORTCUTS ==============================================
+// =============================================================================================================
+//
+// System-wide shortcuts that work while the app is in the background: one opens a file picker and queues the
+// picked files, the other uploads the clipboard (file paths are uploaded as files, anything else as a text
+// file). Bindings live in settings as action -> accelerator; an empty accelerator turns an action off.
+// Desktop only.
+
+/// Actions with their default accelerators
+pub const SHORTCUT_ACTIONS: &[(&str, &str)] = &[
+    ("upload_file", "CmdOrCtrl+Shift+U"),
+    ("upload_clipboard", "CmdOrCtrl+Shift+V"),
+];
+
+/// Configured bindings merged over the defaults, disabled actions left out
+fn effective_shortcuts(settings: &AppSettings) -> BTreeMap<String, String> {
+    SHORTCUT_ACTIONS
+        .iter()
+        .filter_map(|(action, default)| {
+            let accelerator = settings
+                .global_shortcuts
+                .as_ref()
+                .and_then(|s| s.get(*action))
+                .map(|a| a.trim().to_string())
+                .unwrap_or_else(|| default.to_string());
+            Some((action.to_string(), accelerator)).filter(|(_, a)| !a.is_empty())
+        })
+        .collect()
+}
+
+#[cfg(desktop)]
+mod desktop {
+    use std::collections::BTreeMap;
+    use tauri::{AppHandle, Emitter};
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    use tauri_plugin_dialog::DialogExt;
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+    use super::effective_shortcuts;
+    use crate::commands::data_dir;
+    use crate::commands::local_paths;
+    use crate::commands::settings::current_settings;
+    use crate::commands::transfers::{enqueue, NewUpload};
+
+    pub fn parse(accelerator: &str) -> Result<Shortcut, String> {
+        accelerator.parse::<Shortcut>().map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))
+    }
+
+    fn queue(app_handle: &AppHandle, file_path: String, staged: bool) {
+        let job = enqueue(app_handle, NewUpload { file_path, notify: true, staged, ..Default::default() });
+        println!("⌨️ Queued {} from shortcut", job.file_path);
+    }
+
+    fn upload_picked_files(app_handle: &AppHandle) {
+        let app = app_handle.clone();
+        app_handle.dialog().file().set_title("Upload with Firestarter").pick_files(move |paths| {
+            for path in paths.unwrap_or_default() {
+                match path.into_path() {
+                    Ok(path) => queue(&app, local_paths::to_wire(&path), false),
+                    Err(e) => println!("[SHORTCUT] Unusable path: {}", e),
+                }
+            }
+        });
+    }
+
+    fn upload_clipboard(app_handle: &AppHandle) -> Result<(), String> {
+        let text = app_handle.clipboard().read_text().map_err(|e| format!("Clipboard has no text: {}", e))?;
+        let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        if lines.is_empty() {
+            return Err("Clipboard is empty".to_string());
+        }
+        // copied files arrive as one path per line
+        if lines.iter().all(|l| std::path::Path::new(l).is_file()) {
+            for line in lines {
+                queue(app_handle, line.to_string(), false);
+            }
+            return Ok(());
+        }
+        let dir = data_dir::app_cache_dir(app_handle)?.join("clipboard");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create clipboard dir: {}", e))?;
+        let path = dir.join(format!("clipboard-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write clipboard file: {}", e))?;
+        queue(app_handle, path.to_string_lossy().to_string(), true);
+        Ok(())
+    }
+
+    fn run_action(app_handle: &AppHandle, action: &str) {
+        app_handle.emit("global_shortcut_triggered", serde_json::json!({ "action": action })).ok();
+        match action {
+            "upload_file" => upload_picked_files(app_handle),
+            "upload_clipboard" => {
+                if let Err(e) = upload_clipboard(app_handle) {
+                    println!("[SHORTCUT] {}", e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Install the plugin and register the saved bindings. Called once in setup.
+    pub fn init(app_handle: &AppHandle) -> Result<(), String> {
+        let plugin = tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app, shortcut, event| {
+                if event.state() != ShortcutState::Pressed {
+                    return;
+                }
+                let bindings = effective_shortcuts(&current_settings(app));
+                if let Some((action, _)) = bindings.iter().find(|(_, a)| parse(a).ok().as_ref() == Some(shortcut)) {
+                    run_action(app, action);
+                }
+            })
+            .build();
+        app_handle.plugin(plugin).map_err(|e| format!("Failed to load global shortcuts: {}", e))?;
+        app_handle
+            .plugin(tauri_plugin_clipboard_manager::init())
+            .map_err(|e| format!("Failed to load clipboard access: {}", e))?;
+        for (action, accelerator) in effective_shortcuts(&current_settings(app_handle)) {
+            if let Err(e) = parse(&accelerator).and_then(|s| register(app_handle, s)) {
+                println!("[SHORTCUT] {} ({}): {}", action, accelerator, e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn register(app_handle: &AppHandle, shortcut: Shortcut) -> Result<(), String> {
+        app_handle
+            .global_shortcut()
+            .register(shortcut)
+            .map_err(|e| format!("Shortcut is taken by another application: {}", e))
+    }
+
+    /// Swap `old` bindings for `new`; on any failure the old set is put back
+    pub fn rebind(app_handle: &AppHandle, old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> Result<(), String> {
+        let shortcuts = app_handle.global_shortcut();
+        for accelerator in old.values() {
+            if let Ok(shortcut) = parse(accelerator) {
+                let _ = shortcuts.unregister(shortcut);
+            }
+        }
+        let mut registered = Vec::new();
+        for (action, accelerator) in new {
+            match parse(accelerator).and_then(|s| register(app_handle, s).map(|_| s)) {
+                Ok(shortcut) => registered.push(shortcut),
+                Err(e) => {
+                    for shortcut in registered {
+                        let _ = shortcuts.unregister(shortcut);
+                    }
+                    for accelerator in old.values() {
+                        let _ = parse(accelerator).and_then(|s| register(app_handle, s));
+                    }
+                    return Err(format!("{} ({}): {}", action, accelerator, e));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Register the global shortcuts. Called once in setup, after settings are loaded.
+pub fn init_global_shortcuts(app_handle: &AppHandle) {
+    #[cfg(desktop)]
+    if let Err(e) = desktop::init(app_handle) {
+        println!("[SHORTCUT] {}", e);
+    }
+    #[cfg(not(desktop))]
+    let _ = app_handle;
+}
+
+#[tauri::command]
+pub async fn get_shortcuts(app_handle: AppHandle) -> Result<BTreeMap<String, String>, String> {
+    Ok(effective_shortcuts(&current_settings(&app_handle)))
+}
+
+/// Rebind shortcuts by action. Actions not listed keep their binding; an empty accelerator disables one.
+/// Rejects unknown actions, unparsable accelerators, two actions on one key and keys held by other apps.
+#[tauri::command]
+pub async fn set_shortcuts(shortcuts: BTreeMap<String, String>, app_handle: AppHandle) -> Result<BTreeMap<String, String>, String> {
+    if let Some(unknown) = shortcuts.keys().find(|a| !SHORTCUT_ACTIONS.iter().any(|(known, _)| known == a)) {
+        return Err(format!("Unknown shortcut action '{}'", unknown));
+    }
+    #[cfg(not(desktop))]
+    {
+        let _ = app_handle;
+        Err("Global shortcuts aren't available on this platform".to_string())
+    }
+    #[cfg(desktop)]
+    {
+        let old = effective_shortcuts(&current_settings(&app_handle));
+        let mut proposed = current_settings(&app_handle);
+        let mut bindings = proposed.global_shortcuts.take().unwrap_or_default();
+        for (action, accelerator) in shortcuts {
+            bindings.insert(action, accelerator.trim().to_string());
+        }
+        proposed.global_shortcuts = Some(bindings.clone());
+        let new = effective_shortcuts(&proposed);
+
+        let mut seen = Vec::new();
+        for (action, accelerator) in &new {
+            let shortcut = desktop::parse(accelerator)?;
+            if let Some((_, other)) = seen.iter().find(|(s, _)| *s == shortcut) {
+                return Err(format!("'{}' is bound to both {} and {}", accelerator, other, action));
+            }
+            seen.push((shortcut, action));
+        }
+
+        desktop::rebind(&app_handle, &old, &new)?;
+        super::settings::update_settings(&app_handle, |s| s.global_shortcuts = Some(bindings))?;
+        println!("⌨️ Global shortcuts updated");
+        Ok(new)
+    }
+}
@@ -0,0 +1,105 @@
+use std::fs::Metadata;
+use serde::Serialize;
+
+use super::{is_plain_path, local_paths};
+
+// =============================================================================================================
+// =============================================== SPARSE FILES ================================================
+// =============================================================================================================
+//
+// A sparse file (VM disk images, database preallocations) takes far less disk than its size says, but the upload
+// sends, and the backend bills, every byte including the holes. Uploads of such files emit
+// `upload_sparse_warning` and record both sizes in the history; `inspect_sparse_file` lets the UI warn first.
+// Filesystem compression looks the same from here and is reported the same way.
+
+/// Gap between apparent and allocated size below which a file isn't reported
+const MIN_HOLE_BYTES: u64 = 1024 * 1024;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct SparseSizes {
+    /// Size the upload sends and is billed for
+    pub apparent: u64,
+    /// Bytes actually allocated on disk
+    pub allocated: u64,
+}
+
+/// Both sizes, when `meta` (of the file at `path`) describes a sparse file
+pub fn sparse_sizes(path: &str, meta: &Metadata) -> Option<SparseSizes> {
+    let apparent = meta.len();
+    let allocated = allocated_size(path, meta)?;
+    // at least 1 MB and 10% of the file are holes
+    let hole = apparent.saturating_sub(allocated);
+    (hole >= MIN_HOLE_BYTES && hole >= apparent / 10).then_some(SparseSizes { apparent, allocated })
+}
+
+/// `sparse_sizes` for a path alone; content URIs are never reported
+pub fn sparse_sizes_at(path: &str) -> Option<SparseSizes> {
+    if !is_plain_path(path) {
+        return None;
+    }
+    let meta = std::fs::metadata(local_paths::to_fs_path(path)).ok()?;
+    sparse_sizes(path, &meta)
+}
+
+#[cfg(unix)]
+fn allocated_size(_path: &str, meta: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    // st_blocks is always in 512-byte units
+    Some(meta.blocks() * 512)
+}
+
+#[cfg(windows)]
+fn allocated_size(path: &str, _meta: &Metadata) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCompressedFileSizeW(file_name: *const u16, file_size_high: *mut u32) -> u32;
+    }
+    let wide: Vec<u16> = local_paths::to_fs_path(path).as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high = 0u32;
+    // SAFETY: `wide` is NUL-terminated and outlives the call; `high` is a valid out pointer
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    // INVALID_FILE_SIZE is also a valid low word, so the error code decides
+    if low == u32::MAX && std::io::Error::last_os_error().raw_os_error() != Some(0) {
+        return None;
+    }
+    Some(((high as u64) << 32) | low as u64)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn allocated_size(_path: &str, _meta: &Metadata) -> Option<u64> {
+    None
+}
+
+/// Apparent and allocated size of a local file, or `None` when it isn't sparse
+#[tauri::command]
+pub async fn inspect_sparse_file(path: String) -> Result<Option<SparseSizes>, String> {
+    Ok(sparse_sizes_at(&path))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn files_mostly_made_of_holes_are_reported() {
+        let dir = std::env::temp_dir().join(format!("firestarter-sparse-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sparse = dir.join("disk.img");
+        std::fs::File::create(&sparse).unwrap().set_len(64 * MIN_HOLE_BYTES).unwrap();
+        let dense = dir.join("dense.bin");
+        std::fs::write(&dense, vec![7u8; 2 * MIN_HOLE_BYTES as usize]).unwrap();
+
+        let sizes = sparse_sizes_at(&sparse.to_string_lossy());
+        // some filesystems allocate every byte and have no holes to find
+        if std::fs::metadata(&sparse).unwrap().blocks() * 512 < MIN_HOLE_BYTES {
+            let sizes = sizes.expect("sparse file not reported");
+            assert_eq!(sizes.apparent, 64 * MIN_HOLE_BYTES);
+            assert!(sizes.allocated < MIN_HOLE_BYTES);
+        }
+        assert!(sparse_sizes_at(&dense.to_string_lossy()).is_none());
+        assert!(sparse_sizes_at(&dir.join("missing").to_string_lossy()).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
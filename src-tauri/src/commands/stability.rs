@@ -0,0 +1,144 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use futures_util::{Stream, StreamExt};
+use tauri::AppHandle;
+
+use super::settings::{current_settings, update_settings};
+use super::{is_plain_path, local_paths};
+
+// =============================================================================================================
+// ============================================= FILE STABILITY ================================================
+// =============================================================================================================
+//
+// Files still being written (exports, recordings, downloads by another app) would be uploaded truncated. With
+// `upload_stable_secs` set, automatic uploads (lifecycle rules, the transfer queue) first wait until the file's
+// size and modification time stop changing for that long. Every upload also checks that it reads exactly the
+// size it started with and aborts with a clear error if the file grows or shrinks mid-stream.
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Give up on a file that never settles
+const MAX_STABILITY_WAIT: Duration = Duration::from_secs(10 * 60);
+const MAX_STABLE_SECS: u64 = 600;
+
+fn snapshot(path: &str) -> Option<(u64, Option<SystemTime>)> {
+    let meta = std::fs::metadata(local_paths::to_fs_path(path)).ok()?;
+    Some((meta.len(), meta.modified().ok()))
+}
+
+/// Wait until `path` has stopped changing for the configured time; returns at once when the check is off
+pub async fn wait_until_stable(path: &str, app_handle: &AppHandle) -> Result<(), String> {
+    let Some(secs) = current_settings(app_handle).upload_stable_secs.filter(|s| *s > 0) else {
+        return Ok(());
+    };
+    if !is_plain_path(path) {
+        return Ok(());
+    }
+    let needed = Duration::from_secs(secs);
+    let started = Instant::now();
+    let mut last = snapshot(path);
+    let mut unchanged_since = Instant::now();
+    loop {
+        if unchanged_since.elapsed() >= needed {
+            return Ok(());
+        }
+        if started.elapsed() >= MAX_STABILITY_WAIT {
+            return Err(format!("File is still being written after {} s: {}", MAX_STABILITY_WAIT.as_secs(), path));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let current = snapshot(path);
+        if current != last {
+            last = current;
+            unchanged_since = Instant::now();
+        }
+    }
+}
+
+/// Why a size-guarded stream stopped early, once it has
+pub type SizeGuardError = Arc<Mutex<Option<String>>>;
+
+/// Pass `stream` through, failing it as soon as it yields more than `expected` bytes or ends short of it
+/// (no check when the size is unknown). The reason is also left in the returned slot, since the HTTP client's
+/// error hides it.
+pub fn guard_size<S, B>(stream: S, expected: Option<u64>, name: &str) -> (impl Stream<Item = std::io::Result<B>>, SizeGuardError)
+where
+    S: Stream<Item = std::io::Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    let slot: SizeGuardError = Arc::new(Mutex::new(None));
+    let name = name.to_string();
+    let reason = slot.clone();
+    let guarded = futures_util::stream::unfold((stream, 0u64, false), move |(mut stream, mut read, done)| {
+        let name = name.clone();
+        let reason = reason.clone();
+        async move {
+            if done {
+                return None;
+            }
+            let changed = match stream.next().await {
+                Some(Ok(chunk)) => {
+                    read += chunk.as_ref().len() as u64;
+                    let Some(expected) = expected.filter(|e| read > *e) else {
+                        return Some((Ok(chunk), (stream, read, false)));
+                    };
+                    format!("'{}' grew during upload (more than {} bytes); it is probably still being written", name, expected)
+                }
+                Some(Err(e)) => return Some((Err(e), (stream, read, true))),
+                None => match expected.filter(|e| read < *e) {
+                    Some(expected) => {
+                        format!("'{}' shrank during upload ({} of {} bytes); it is probably still being written", name, read, expected)
+                    }
+                    None => return None,
+                },
+            };
+            *reason.lock().unwrap() = Some(changed.clone());
+            Some((Err(std::io::Error::other(changed)), (stream, read, true)))
+        }
+    });
+    (guarded, slot)
+}
+
+/// Seconds a file must stay unchanged before an automatic upload starts; `None` or 0 turns the wait off
+#[tauri::command]
+pub async fn set_upload_stability(seconds: Option<u64>, app_handle: AppHandle) -> Result<(), String> {
+    update_settings(&app_handle, |s| s.upload_stable_secs = seconds.filter(|s| *s > 0).map(|s| s.min(MAX_STABLE_SECS)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(sizes: &[usize]) -> impl Stream<Item = std::io::Result<Vec<u8>>> + Unpin {
+        futures_util::stream::iter(sizes.iter().map(|n| Ok(vec![0u8; *n])).collect::<Vec<_>>())
+    }
+
+    async fn run(sizes: &[usize], expected: Option<u64>) -> (usize, bool, Option<String>) {
+        let (guarded, reason) = guard_size(chunks(sizes), expected, "clip.mov");
+        let items: Vec<_> = guarded.collect().await;
+        let ok = items.iter().filter(|i| i.is_ok()).count();
+        let failed = items.last().is_some_and(|i| i.is_err());
+        let reason = reason.lock().unwrap().clone();
+        (ok, failed, reason)
+    }
+
+    #[tokio::test]
+    async fn unchanged_files_pass_through() {
+        assert_eq!(run(&[4, 4, 2], Some(10)).await, (3, false, None));
+        assert_eq!(run(&[4, 4, 2], None).await, (3, false, None));
+        assert_eq!(run(&[], Some(0)).await, (0, false, None));
+    }
+
+    #[tokio::test]
+    async fn growing_files_fail_at_the_first_extra_byte() {
+        let (ok, failed, reason) = run(&[4, 4, 4, 4], Some(10)).await;
+        assert_eq!((ok, failed), (2, true));
+        assert_eq!(reason.unwrap(), "'clip.mov' grew during upload (more than 10 bytes); it is probably still being written");
+    }
+
+    #[tokio::test]
+    async fn shrinking_files_fail_at_the_end() {
+        let (ok, failed, reason) = run(&[4, 4], Some(10)).await;
+        assert_eq!((ok, failed), (2, true));
+        assert_eq!(reason.unwrap(), "'clip.mov' shrank during upload (8 of 10 bytes); it is probably still being written");
+    }
+}
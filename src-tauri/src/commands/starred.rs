@@ -0,0 +1,162 @@
+use std::path::PathBuf;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{account_scope, get_user_data_dir, read_upload_history, UploadLogEntry};
+
+// =============================================================================================================
+// =============================================== STARRED FILES ===============================================
+// =============================================================================================================
+//
+// Remote files the user wants at hand, kept in `starred-<user>.json`. Each star remembers the file's blake3
+// hash, so when the name disappears from history but the same content shows up under another name (renamed or
+// re-uploaded elsewhere), the star follows it.
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StarredFile {
+    pub remote_path: String,
+    /// Content hash from the upload history; `None` for files this device never uploaded
+    #[serde(default)]
+    pub blake3_hash: Option<String>,
+    #[serde(default)]
+    pub file_size: Option<u64>,
+    pub starred_at: String,
+    /// Previous name, when the star followed a rename
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+}
+
+fn starred_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join(format!("starred-{}.json", user_id)))
+}
+
+fn read_starred(user_id: &str, app_handle: &AppHandle) -> Result<Vec<StarredFile>, String> {
+    let path = starred_path(user_id, app_handle)?;
+    if !path.exists() { return Ok(vec![]); }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read starred files: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse starred files: {}", e))
+}
+
+fn write_starred(user_id: &str, starred: &[StarredFile], app_handle: &AppHandle) -> Result<(), String> {
+    let path = starred_path(user_id, app_handle)?;
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create user dir: {}", e))?; }
+    let json = serde_json::to_string_pretty(starred).map_err(|e| format!("Failed to serialize starred files: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write starred files: {}", e))
+}
+
+/// Newest successful upload under `remote_path`
+fn latest_upload<'a>(history: &'a [UploadLogEntry], remote_path: &str) -> Option<&'a UploadLogEntry> {
+    history.iter().rev().find(|e| e.status == "success" && e.remote_path == remote_path)
+}
+
+/// Point stars whose name is gone from history at the newest upload with the same hash. Returns whether any
+/// star changed.
+fn follow_renames(starred: &mut [StarredFile], history: &[UploadLogEntry]) -> bool {
+    let mut changed = false;
+    for i in 0..starred.len() {
+        if latest_upload(history, &starred[i].remote_path).is_some() {
+            continue;
+        }
+        let Some(hash) = starred[i].blake3_hash.clone().filter(|h| !h.is_empty()) else { continue };
+        let moved = history.iter().rev().find(|e| {
+            e.status == "success" && e.blake3_hash == hash && !starred.iter().any(|s| s.remote_path == e.remote_path)
+        });
+        if let Some(entry) = moved {
+            let star = &mut starred[i];
+            println!("⭐ Star moved from {} to {}", star.remote_path, entry.remote_path);
+            star.renamed_from = Some(std::mem::replace(&mut star.remote_path, entry.remote_path.clone()));
+            star.file_size = Some(entry.file_size);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Star a remote file for quick access
+#[tauri::command]
+pub async fn star_file(user_id: String, remote_path: String, app_handle: AppHandle) -> Result<StarredFile, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let remote_path = remote_path.trim().to_string();
+    if remote_path.is_empty() {
+        return Err("Remote path is required".to_string());
+    }
+    let mut starred = read_starred(&user_id, &app_handle)?;
+    if let Some(existing) = starred.iter().find(|s| s.remote_path == remote_path) {
+        return Ok(existing.clone());
+    }
+    let history = read_upload_history(user_id.clone(), app_handle.clone()).await?;
+    let upload = latest_upload(&history, &remote_path);
+    let star = StarredFile {
+        blake3_hash: upload.map(|e| e.blake3_hash.clone()).filter(|h| !h.is_empty()),
+        file_size: upload.map(|e| e.file_size),
+        starred_at: Utc::now().to_rfc3339(),
+        renamed_from: None,
+        remote_path,
+    };
+    starred.push(star.clone());
+    write_starred(&user_id, &starred, &app_handle)?;
+    Ok(star)
+}
+
+#[tauri::command]
+pub async fn unstar_file(user_id: String, remote_path: String, app_handle: AppHandle) -> Result<bool, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut starred = read_starred(&user_id, &app_handle)?;
+    let before = starred.len();
+    starred.retain(|s| s.remote_path != remote_path);
+    if starred.len() == before {
+        return Ok(false);
+    }
+    write_starred(&user_id, &starred, &app_handle)?;
+    Ok(true)
+}
+
+/// Starred files, most recently starred first, following renames found in the upload history
+#[tauri::command]
+pub async fn list_starred(user_id: String, app_handle: AppHandle) -> Result<Vec<StarredFile>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut starred = read_starred(&user_id, &app_handle)?;
+    let history = read_upload_history(user_id.clone(), app_handle.clone()).await?;
+    if follow_renames(&mut starred, &history) {
+        write_starred(&user_id, &starred, &app_handle)?;
+    }
+    starred.reverse();
+    Ok(starred)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(remote_path: &str, hash: &str) -> UploadLogEntry {
+        serde_json::from_value(serde_json::json!({
+            "local_path": "/tmp/a", "remote_path": remote_path, "status": "success", "message": "",
+            "blake3_hash": hash, "file_size": 7, "timestamp": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn star(remote_path: &str, hash: Option<&str>) -> StarredFile {
+        StarredFile {
+            remote_path: remote_path.to_string(),
+            blake3_hash: hash.map(str::to_string),
+            file_size: None,
+            starred_at: String::new(),
+            renamed_from: None,
+        }
+    }
+
+    #[test]
+    fn stars_follow_content_to_its_new_name() {
+        let mut history = vec![upload("report.pdf", "aaa"), upload("notes.txt", "bbb")];
+        let mut starred = vec![star("report.pdf", Some("aaa")), star("notes.txt", Some("bbb")), star("remote-only", None)];
+        assert!(!follow_renames(&mut starred, &history));
+
+        history[0].remote_path = "reports/2024.pdf".to_string();
+        assert!(follow_renames(&mut starred, &history));
+        assert_eq!(starred[0].remote_path, "reports/2024.pdf");
+        assert_eq!(starred[0].renamed_from.as_deref(), Some("report.pdf"));
+        assert_eq!(starred[2], star("remote-only", None));
+    }
+}
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::transfers::queue_depth;
+use super::{account_scope, app_data_root, data_dir, get_user_data_dir};
+
+// =============================================================================================================
+// ============================================ LOCAL STORAGE USAGE ============================================
+// =============================================================================================================
+
+/// Categories `clear_local_cache` may delete. Credentials, history and keys are never cleared here.
+const CLEARABLE: [&str; 6] = ["assets", "downloads", "shared", "hook_outputs", "hook_log", "quarantine"];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StorageCategory {
+    pub category: String,
+    pub path: String,
+    pub bytes: u64,
+    pub files: u64,
+    pub clearable: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LocalStorageUsage {
+    pub total_bytes: u64,
+    pub categories: Vec<StorageCategory>,
+}
+
+/// (bytes, files) under `path`, which may be a single file or a directory
+fn disk_usage(path: &Path) -> (u64, u64) {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return (0, 0);
+    };
+    if !meta.is_dir() {
+        return (meta.len(), 1);
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries.flatten().fold((0, 0), |(bytes, files), entry| {
+                let (b, f) = disk_usage(&entry.path());
+                (bytes + b, files + f)
+            })
+        })
+        .unwrap_or((0, 0))
+}
+
+fn cache_root(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    data_dir::app_cache_dir(app_handle)
+}
+
+fn category_path(category: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(match category {
+        "assets" => cache_root(app_handle)?.join("assets"),
+        "downloads" => cache_root(app_handle)?.join("downloads"),
+        "shared" => cache_root(app_handle)?.join("shared"),
+        "hook_outputs" => cache_root(app_handle)?.join("hooks"),
+        "hook_log" => app_data_root(app_handle)?.join("hook-runs.jsonl"),
+        "quarantine" => app_data_root(app_handle)?.join("corrupt"),
+        other => return Err(format!("Unknown cache category: {}", other)),
+    })
+}
+
+#[tauri::command]
+pub async fn get_local_storage_usage(user_id: String, app_handle: AppHandle) -> Result<LocalStorageUsage, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let user_dir = get_user_data_dir(&user_id, &app_handle)?;
+    let root = app_data_root(&app_handle)?;
+    let mut paths: Vec<(&str, PathBuf)> = CLEARABLE
+        .iter()
+        .map(|c| category_path(c, &app_handle).map(|p| (*c, p)))
+        .collect::<Result<_, _>>()?;
+    paths.extend([
+        ("history", user_dir.join(format!("list-upload-{}.json", user_id))),
+        ("history_archive", user_dir.join("history-archive")),
+        ("links", user_dir.join(format!("link-{}.json", user_id))),
+        ("vault", user_dir.join(format!("keyvault-{}.json", user_id))),
+        ("credentials", user_dir.join(format!("{}.json", user_id))),
+        ("transfer_journal", root.join("transfer-queue.json")),
+        ("metrics", root.join("transfer-metrics.json")),
+    ]);
+
+    let categories: Vec<StorageCategory> = paths
+        .into_iter()
+        .map(|(category, path)| {
+            let (bytes, files) = disk_usage(&path);
+            StorageCategory {
+                category: category.to_string(),
+                path: path.to_string_lossy().to_string(),
+                bytes,
+                files,
+                clearable: CLEARABLE.contains(&category),
+            }
+        })
+        .collect();
+    Ok(LocalStorageUsage { total_bytes: categories.iter().map(|c| c.bytes).sum(), categories })
+}
+
+/// Delete the given cache categories and return the bytes freed
+#[tauri::command]
+pub async fn clear_local_cache(categories: Vec<String>, app_handle: AppHandle) -> Result<u64, String> {
+    let mut freed = 0;
+    for category in &categories {
+        if !CLEARABLE.contains(&category.as_str()) {
+            return Err(format!("Category '{}' cannot be cleared", category));
+        }
+        // staged share-sheet files are still needed by pending uploads
+        if category == "shared" && queue_depth(&app_handle) != (0, 0) {
+            return Err("Shared files are still queued for upload".to_string());
+        }
+    }
+    for category in &categories {
+        let path = category_path(category, &app_handle)?;
+        let (bytes, _) = disk_usage(&path);
+        let removed = if path.is_dir() { std::fs::remove_dir_all(&path) } else if path.exists() { std::fs::remove_file(&path) } else { Ok(()) };
+        removed.map_err(|e| format!("Failed to clear {}: {}", category, e))?;
+        freed += bytes;
+    }
+    println!("🧹 Cleared {:?} ({} bytes)", categories, freed);
+    Ok(freed)
+}
@@ -0,0 +1,222 @@
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use rand::RngCore;
+use tauri::{AppHandle, Manager};
+
+use super::redaction::println_redacted;
+use super::{current_api_config, encoding, ensure_valid_token, gateways, load_credentials, network, workspaces};
+
+// =============================================================================================================
+// ============================================ LOCAL STREAM BRIDGE ============================================
+// =============================================================================================================
+//
+// `<video>` / `<audio>` elements can't attach auth headers, so media is served through a loopback-only
+// HTTP server on a random port. Every URL carries a per-launch random token; requests are forwarded
+// (including Range) to the download endpoint with the user's credentials injected on the Rust side. Upstream
+// errors come back as a bare status with no detail, and no CORS header is sent: media elements don't need one,
+// and without it a page that learns the URL can't read the stream with `fetch()`.
+
+#[derive(Clone)]
+pub struct StreamServer {
+    port: u16,
+    token: Arc<String>,
+}
+
+pub type StreamServerState = Mutex<Option<StreamServer>>;
+pub fn new_stream_server_state() -> StreamServerState { Mutex::new(None) }
+
+/// Headers passed back from the download endpoint to the media element
+const FORWARDED_HEADERS: [&str; 6] = ["content-type", "content-length", "content-range", "accept-ranges", "etag", "last-modified"];
+
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Fallback MIME type when the backend answers with application/octet-stream
+fn media_type_for(file_name: &str) -> Option<&'static str> {
+    let ext = file_name.rsplit('.').next()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "mkv" => "video/x-matroska",
+        "ogv" => "video/ogg",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "ogg" | "oga" => "audio/ogg",
+        "opus" => "audio/opus",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        _ => return None,
+    })
+}
+
+/// Status passed to the media element for the download endpoint's `status`. Errors other than a missing file or
+/// an unsatisfiable range say nothing about the account, credentials or backend.
+fn client_status(status: StatusCode) -> StatusCode {
+    match status {
+        s if s.is_success() => s,
+        StatusCode::NOT_FOUND | StatusCode::RANGE_NOT_SATISFIABLE => status,
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message.to_string()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+async fn proxy(req: Request<Body>, app_handle: AppHandle, token: Arc<String>) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return Ok(error_response(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed"));
+    }
+
+    // /stream/<token>/<percent-encoded remote name>
+    let path = req.uri().path().to_string();
+    let mut parts = path.trim_start_matches('/').splitn(3, '/');
+    let (Some("stream"), Some(req_token), Some(encoded_name)) = (parts.next(), parts.next(), parts.next()) else {
+        return Ok(error_response(StatusCode::NOT_FOUND, "Not found"));
+    };
+    if !tokens_match(req_token, &token) {
+        return Ok(error_response(StatusCode::FORBIDDEN, "Forbidden"));
+    }
+    let Ok(file_name) = encoding::decode_remote_name(encoded_name) else {
+        return Ok(error_response(StatusCode::BAD_REQUEST, "Bad request"));
+    };
+
+    let mut credentials = match load_credentials(app_handle.clone()).await {
+        Ok(Some(c)) => c,
+        _ => return Ok(error_response(StatusCode::UNAUTHORIZED, "No saved credentials found")),
+    };
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    if let Err(e) = ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await {
+        println_redacted!("[STREAM] {}", e);
+        return Ok(error_response(StatusCode::BAD_GATEWAY, "Bad gateway"));
+    }
+
+    let transfer_config = gateways::transfer_config(&api_config, &credentials, &app_handle).await;
+    let url = format!(
+        "{}{}?file_name={}",
+        transfer_config.api_base_url,
+        transfer_config.download,
+        encoding::encode_remote_name(&file_name)
+    );
+    let mut upstream = workspaces::scope(client.get(&url), &credentials)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose());
+    if let Some(range) = req.headers().get(hyper::header::RANGE) {
+        upstream = upstream.header(hyper::header::RANGE, range.clone());
+    }
+
+    let resp = match upstream.send().await {
+        Ok(r) => r,
+        Err(e) => {
+            println_redacted!("[STREAM] Upstream error for {}: {}", file_name, e);
+            return Ok(error_response(StatusCode::BAD_GATEWAY, "Bad gateway"));
+        }
+    };
+    let status = client_status(resp.status());
+    if !status.is_success() {
+        println_redacted!("[STREAM] {} answered {} for {}", transfer_config.api_base_url, resp.status(), file_name);
+        let mut builder = Response::builder().status(status).header("Cache-Control", "no-store");
+        // tells the player how long the file is after a seek past the end
+        if let Some(range) = resp.headers().get(hyper::header::CONTENT_RANGE).filter(|_| status == StatusCode::RANGE_NOT_SATISFIABLE) {
+            builder = builder.header(hyper::header::CONTENT_RANGE, range.clone());
+        }
+        return Ok(builder.body(Body::empty()).unwrap_or_else(|_| Response::new(Body::empty())));
+    }
+
+    let mut builder = Response::builder().status(status).header("Cache-Control", "no-store");
+    for name in FORWARDED_HEADERS {
+        if let Some(value) = resp.headers().get(name) {
+            builder = builder.header(name, value.clone());
+        }
+    }
+    let generic_type = resp
+        .headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .map(|v| v.as_bytes() == b"application/octet-stream")
+        .unwrap_or(true);
+    if generic_type {
+        if let Some(mime) = media_type_for(&file_name) {
+            if let Some(headers) = builder.headers_mut() {
+                headers.insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static(mime));
+            }
+        }
+    }
+
+    let body = if req.method() == Method::HEAD { Body::empty() } else { Body::wrap_stream(resp.bytes_stream()) };
+    Ok(builder.body(body).unwrap_or_else(|_| error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response")))
+}
+
+fn start_server(app_handle: &AppHandle) -> Result<StreamServer, String> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(|e| format!("Failed to bind stream server: {}", e))?;
+    listener.set_nonblocking(true).map_err(|e| format!("Failed to configure stream server: {}", e))?;
+    let port = listener.local_addr().map_err(|e| format!("Failed to read stream server address: {}", e))?.port();
+
+    let mut raw = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut raw);
+    let token = Arc::new(hex::encode(raw));
+
+    let handle = app_handle.clone();
+    let svc_token = token.clone();
+    let make_svc = make_service_fn(move |_conn| {
+        let handle = handle.clone();
+        let token = svc_token.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| proxy(req, handle.clone(), token.clone()))) }
+    });
+    let server = Server::from_tcp(listener).map_err(|e| format!("Failed to start stream server: {}", e))?.serve(make_svc);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = server.await {
+            println!("❌ Stream server stopped: {}", e);
+        }
+    });
+
+    println!("🎬 Stream server listening on 127.0.0.1:{}", port);
+    Ok(StreamServer { port, token })
+}
+
+/// Loopback URL the webview's media elements can play directly (supports seeking via Range)
+#[tauri::command]
+pub async fn get_stream_url(file_name: String, app_handle: AppHandle) -> Result<String, String> {
+    let server = {
+        let state = app_handle.state::<StreamServerState>();
+        let mut guard = state.lock().unwrap();
+        match guard.as_ref() {
+            Some(server) => server.clone(),
+            None => {
+                let server = start_server(&app_handle)?;
+                *guard = Some(server.clone());
+                server
+            }
+        }
+    };
+    Ok(format!(
+        "http://127.0.0.1:{}/stream/{}/{}",
+        server.port,
+        server.token,
+        encoding::encode_remote_name(&file_name)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upstream_errors_are_not_passed_through() {
+        assert_eq!(client_status(StatusCode::OK), StatusCode::OK);
+        assert_eq!(client_status(StatusCode::PARTIAL_CONTENT), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(client_status(StatusCode::NOT_FOUND), StatusCode::NOT_FOUND);
+        assert_eq!(client_status(StatusCode::RANGE_NOT_SATISFIABLE), StatusCode::RANGE_NOT_SATISFIABLE);
+        for status in [StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN, StatusCode::INTERNAL_SERVER_ERROR, StatusCode::FOUND] {
+            assert_eq!(client_status(status), StatusCode::BAD_GATEWAY);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+use super::{account_scope, history_log, read_upload_history, UploadLogEntry};
+
+// =============================================================================================================
+// =============================================== TAGS AND NOTES ==============================================
+// =============================================================================================================
+//
+// Labels and a free-text note on upload history entries, e.g. which backup run a file belongs to. They are
+// stored on the entry itself, so search and the app state backup pick them up. Entries are addressed by
+// `entry_id` (the upload id, or the timestamp for older entries); archived history can't be edited.
+
+const MAX_TAG_LEN: usize = 64;
+const MAX_NOTE_LEN: usize = 4096;
+
+/// Trimmed, deduplicated (case-insensitively, first spelling wins) and sorted tags
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(format!("Tag '{}' is longer than {} characters", tag, MAX_TAG_LEN));
+        }
+        if !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized.sort_by_key(|t| t.to_lowercase());
+    Ok(normalized)
+}
+
+/// Apply `change` to the entry with `entry_id` in `user_id`'s history and return the result
+async fn edit_entry<F>(user_id: &str, entry_id: &str, change: F, app_handle: &AppHandle) -> Result<UploadLogEntry, String>
+where
+    F: Fn(&mut UploadLogEntry) + Send + 'static,
+{
+    let edited = Arc::new(Mutex::new(None));
+    let (target, found) = (entry_id.to_string(), edited.clone());
+    history_log::update_entries(
+        user_id,
+        move |entry| {
+            if entry.entry_id() != target {
+                return false;
+            }
+            change(entry);
+            *found.lock().unwrap() = Some(entry.clone());
+            true
+        },
+        app_handle,
+    )
+    .await?;
+    let edited = edited.lock().unwrap().take();
+    edited.ok_or_else(|| format!("History entry not found: {}", entry_id))
+}
+
+/// Replace the tags of a history entry; an empty list removes them
+#[tauri::command]
+pub async fn tag_upload(user_id: String, entry_id: String, tags: Vec<String>, app_handle: AppHandle) -> Result<UploadLogEntry, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let tags = normalize_tags(&tags)?;
+    let entry = edit_entry(&user_id, &entry_id, move |entry| entry.tags = tags.clone(), &app_handle).await?;
+    println!("🏷️ Tagged {} with {:?}", entry.remote_path, entry.tags);
+    Ok(entry)
+}
+
+/// Set the note of a history entry; `None` or blank removes it
+#[tauri::command]
+pub async fn set_upload_note(user_id: String, entry_id: String, note: Option<String>, app_handle: AppHandle) -> Result<UploadLogEntry, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note.as_ref().is_some_and(|n| n.chars().count() > MAX_NOTE_LEN) {
+        return Err(format!("Note is longer than {} characters", MAX_NOTE_LEN));
+    }
+    edit_entry(&user_id, &entry_id, move |entry| entry.note = note.clone(), &app_handle).await
+}
+
+/// History entries carrying `tag` (case-insensitive), newest first
+#[tauri::command]
+pub async fn list_by_tag(user_id: String, tag: String, app_handle: AppHandle) -> Result<Vec<UploadLogEntry>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let tag = tag.trim();
+    let mut entries: Vec<UploadLogEntry> = read_upload_history(user_id.clone(), app_handle.clone())
+        .await?
+        .into_iter()
+        .filter(|e| e.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_trimmed_and_deduplicated() {
+        let tags = ["  nightly ", "Backup-2024", "", "NIGHTLY", "archive"].map(String::from);
+        assert_eq!(normalize_tags(&tags).unwrap(), ["archive", "Backup-2024", "nightly"]);
+        assert!(normalize_tags(&["x".repeat(MAX_TAG_LEN + 1)]).is_err());
+    }
+}
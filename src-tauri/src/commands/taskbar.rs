@@ -0,0 +1,105 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use super::transfers::queue_depth;
+
+// =============================================================================================================
+// ============================================= TASKBAR PROGRESS ==============================================
+// =============================================================================================================
+//
+// Overall progress of the transfer queue on the taskbar button (Windows, Linux launchers that support it) and
+// the dock icon with a percentage badge (macOS). A batch runs from the first queued upload until the queue
+// drains: finished jobs count as whole, the running job by its bytes. The indicator turns red once a job in
+// the batch fails and is cleared when the queue is empty. Desktop only.
+
+#[derive(Default)]
+pub struct TaskbarProgress {
+    /// Jobs finished since the queue last drained
+    finished: usize,
+    failed: bool,
+    /// Queue job being uploaded and how far along it is (0..1)
+    current: Option<(String, f64)>,
+    /// Last percentage shown, to skip redundant native calls
+    shown: Option<u64>,
+}
+
+pub type TaskbarProgressState = Mutex<TaskbarProgress>;
+pub fn new_taskbar_progress_state() -> TaskbarProgressState { Mutex::new(TaskbarProgress::default()) }
+
+#[cfg(desktop)]
+fn show(app_handle: &AppHandle, percent: Option<u64>, failed: bool) {
+    use super::window_state::MAIN_WINDOW;
+    use tauri::window::{ProgressBarState, ProgressBarStatus};
+    let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) else { return };
+    let status = match (percent, failed) {
+        (None, _) => ProgressBarStatus::None,
+        (Some(_), true) => ProgressBarStatus::Error,
+        (Some(_), false) => ProgressBarStatus::Normal,
+    };
+    let _ = window.set_progress_bar(ProgressBarState { status: Some(status), progress: percent });
+    #[cfg(target_os = "macos")]
+    let _ = window.set_badge_label(percent.map(|p| format!("{}%", p)));
+}
+
+#[cfg(not(desktop))]
+fn show(_app_handle: &AppHandle, _percent: Option<u64>, _failed: bool) {}
+
+/// Recompute the batch percentage from the queue and push it to the taskbar if it changed
+fn refresh(app_handle: &AppHandle) {
+    let (queued, active) = queue_depth(app_handle);
+    let state = app_handle.state::<TaskbarProgressState>();
+    let mut progress = state.lock().unwrap();
+    let total = progress.finished + queued + active;
+    let percent = if queued + active == 0 {
+        None
+    } else {
+        let done = progress.finished as f64 + progress.current.as_ref().map(|(_, f)| *f).unwrap_or(0.0);
+        Some(((done / total as f64) * 100.0).min(100.0) as u64)
+    };
+    if percent == progress.shown {
+        return;
+    }
+    progress.shown = percent;
+    let failed = progress.failed;
+    drop(progress);
+    show(app_handle, percent, failed);
+}
+
+/// The queue worker picked up `job_id`
+pub fn job_started(app_handle: &AppHandle, job_id: &str) {
+    app_handle.state::<TaskbarProgressState>().lock().unwrap().current = Some((job_id.to_string(), 0.0));
+    refresh(app_handle);
+}
+
+/// Bytes sent for an upload; ignored unless it's the queue job being tracked
+pub fn job_progress(app_handle: &AppHandle, job_id: &str, uploaded: u64, total: u64) {
+    {
+        let state = app_handle.state::<TaskbarProgressState>();
+        let mut progress = state.lock().unwrap();
+        match &mut progress.current {
+            Some((id, fraction)) if id == job_id => {
+                *fraction = if total > 0 { (uploaded as f64 / total as f64).min(1.0) } else { 0.0 };
+            }
+            _ => return,
+        }
+    }
+    refresh(app_handle);
+}
+
+/// The tracked job is done; call after its status has left "uploading"
+pub fn job_finished(app_handle: &AppHandle, success: bool) {
+    {
+        let state = app_handle.state::<TaskbarProgressState>();
+        let mut progress = state.lock().unwrap();
+        progress.current = None;
+        progress.finished += 1;
+        progress.failed |= !success;
+    }
+    refresh(app_handle);
+}
+
+/// The queue drained: remove the indicator and start the next batch from zero
+pub fn clear(app_handle: &AppHandle) {
+    *app_handle.state::<TaskbarProgressState>().lock().unwrap() = TaskbarProgress::default();
+    show(app_handle, None, false);
+}
@@ -0,0 +1,246 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::redaction::println_redacted;
+use super::{
+    conflicts, current_api_config, data_dir, download_file, ensure_valid_token, get_tier_pricing, history_log,
+    load_credentials, network, read_upload_history, tier_pricing_url, upload_file, ApiConfig, ApiConfigState, SavedCredentials,
+    UploadOptions,
+};
+
+// =============================================================================================================
+// =============================================== TIER CHANGES ================================================
+// =============================================================================================================
+//
+// When the backend exposes `change_tier` the object is moved server-side. Otherwise it is downloaded and
+// uploaded again at the new tier, which is billed as a full upload and so needs explicit confirmation.
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TierChangePlan {
+    pub file_name: String,
+    pub file_size: u64,
+    /// Last known tier from upload history, if it was recorded
+    pub current_tier: Option<String>,
+    pub new_tier: String,
+    /// "api" | "reupload"
+    pub method: String,
+    pub current_price_per_gb: Option<f64>,
+    pub new_price_per_gb: Option<f64>,
+    /// Price difference between the two tiers for this object's size (PIPE)
+    pub cost_delta: Option<f64>,
+    /// What the change is expected to be charged: the delta via the API, the full new-tier price on re-upload
+    pub estimated_cost: Option<f64>,
+    pub requires_confirmation: bool,
+}
+
+/// How long fetched prices are reused for upload cost estimates; the `get_tier_pricing` command always fetches
+const PRICING_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Last `getTierPricing` response: (url, fetched at, response)
+pub type TierPricingCache = Mutex<Option<(String, Instant, serde_json::Value)>>;
+pub fn new_tier_pricing_cache() -> TierPricingCache { Mutex::new(None) }
+
+/// Keep a fresh `getTierPricing` response from `url` for `cached_pricing`
+pub(super) fn remember_pricing(app_handle: &AppHandle, url: &str, pricing: &serde_json::Value) {
+    *app_handle.state::<TierPricingCache>().lock().unwrap() = Some((url.to_string(), Instant::now(), pricing.clone()));
+}
+
+/// Tier prices, fetched at most once per `PRICING_TTL` for the endpoint in effect
+pub(super) async fn cached_pricing(app_handle: &AppHandle) -> Result<serde_json::Value, String> {
+    let url = tier_pricing_url(&current_api_config(app_handle))?;
+    let cached = app_handle.state::<TierPricingCache>().lock().unwrap().clone();
+    match cached {
+        Some((cached_url, fetched, pricing)) if cached_url == url && fetched.elapsed() < PRICING_TTL => Ok(pricing),
+        _ => get_tier_pricing(app_handle.clone()).await,
+    }
+}
+
+/// `current_price` of `tier` in the `getTierPricing` response (number or numeric string)
+pub(super) fn tier_price(pricing: &serde_json::Value, tier: &str) -> Option<f64> {
+    let info = pricing.as_array()?.iter().find(|t| {
+        t.get("name").and_then(|n| n.as_str()).is_some_and(|n| n.eq_ignore_ascii_case(tier))
+    })?;
+    match info.get("current_price")? {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+async fn plan_tier_change(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    file_name: &str,
+    new_tier: &str,
+    app_handle: &AppHandle,
+) -> Result<TierChangePlan, String> {
+    let new_tier = new_tier.trim().to_lowercase();
+    if new_tier.is_empty() {
+        return Err("No target tier given".to_string());
+    }
+
+    let last_upload = read_upload_history(credentials.user_id.clone(), app_handle.clone())
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .find(|e| e.status == "success" && e.remote_path == file_name);
+    let (file_size, current_tier) = match last_upload {
+        Some(entry) => (entry.file_size, entry.tier),
+        None => match conflicts::probe_remote(client, api_config, credentials, file_name).await? {
+            Some(size) => (size.unwrap_or(0), None),
+            None => return Err(format!("File '{}' not found", file_name)),
+        },
+    };
+    if current_tier.as_deref() == Some(new_tier.as_str()) {
+        return Err(format!("'{}' is already in tier '{}'", file_name, new_tier));
+    }
+
+    let pricing = get_tier_pricing(app_handle.clone()).await.ok();
+    let new_price = pricing.as_ref().and_then(|p| tier_price(p, &new_tier));
+    if pricing.as_ref().is_some_and(|p| p.as_array().is_some()) && new_price.is_none() {
+        return Err(format!("Unknown tier: {}", new_tier));
+    }
+    let current_price = match (&pricing, &current_tier) {
+        (Some(p), Some(t)) => tier_price(p, t),
+        _ => None,
+    };
+
+    let gb = file_size as f64 / (1024.0 * 1024.0 * 1024.0);
+    let cost_delta = current_price.zip(new_price).map(|(old, new)| (new - old) * gb);
+    let via_api = api_config.optional_url(&api_config.change_tier, "Tier change").is_ok();
+    Ok(TierChangePlan {
+        file_name: file_name.to_string(),
+        file_size,
+        current_tier,
+        new_tier,
+        method: if via_api { "api" } else { "reupload" }.to_string(),
+        current_price_per_gb: current_price,
+        new_price_per_gb: new_price,
+        cost_delta,
+        estimated_cost: if via_api { cost_delta } else { new_price.map(|p| p * gb) },
+        requires_confirmation: !via_api,
+    })
+}
+
+async fn change_tier_via_api(
+    client: &reqwest::Client,
+    api_config: &ApiConfig,
+    credentials: &SavedCredentials,
+    plan: &TierChangePlan,
+) -> Result<(), String> {
+    let url = api_config.optional_url(&api_config.change_tier, "Tier change")?;
+    let response = client
+        .post(&url)
+        .header("X-User-Id", &credentials.user_id)
+        .header("X-User-App-Key", credentials.user_app_key.expose())
+        .json(&serde_json::json!({ "file_name": plan.file_name, "tier": plan.new_tier }))
+        .send()
+        .await
+        .map_err(|e| format!("Tier change request failed: {}", e))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        let text = response.text().await.unwrap_or_default();
+        Err(format!("Tier change failed - Status: {}, Response: {}", status, text))
+    }
+}
+
+/// Download into the cache and upload again under the same name at the new tier
+async fn change_tier_via_reupload(plan: &TierChangePlan, app_handle: &AppHandle) -> Result<(), String> {
+    let dir = data_dir::app_cache_dir(app_handle)?.join("tier-change").join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create tier change dir: {}", e))?;
+    let local_name = plan.file_name.rsplit('/').next().unwrap_or(&plan.file_name).to_string();
+    let local_path = dir.join(local_name).to_string_lossy().to_string();
+
+    let result = async {
+        download_file(plan.file_name.clone(), local_path.clone(), app_handle.state::<ApiConfigState>(), app_handle.clone()).await?;
+        let options = UploadOptions {
+            tier: Some(plan.new_tier.clone()),
+            remote_file_name: Some(plan.file_name.clone()),
+            // the name is already final, don't apply the default folder again
+            remote_dir: Some(String::new()),
+            on_conflict: Some("overwrite".to_string()),
+            ..Default::default()
+        };
+        upload_file(local_path.clone(), options, app_handle.clone()).await.map(|_| ())
+    }
+    .await;
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+/// Pre-flight for `change_file_tier`: sizes, prices, cost delta and which method will be used
+#[tauri::command]
+pub async fn preview_tier_change(file_name: String, new_tier: String, app_handle: AppHandle) -> Result<TierChangePlan, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+    plan_tier_change(&client, &api_config, &credentials, &file_name, &new_tier, &app_handle).await
+}
+
+/// Move `file_name` to `new_tier`. The re-upload fallback only runs with `confirm_reupload`.
+#[tauri::command]
+pub async fn change_file_tier(
+    file_name: String,
+    new_tier: String,
+    confirm_reupload: Option<bool>,
+    app_handle: AppHandle,
+) -> Result<TierChangePlan, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let api_config = current_api_config(&app_handle);
+    let client = network::client(&app_handle);
+    ensure_valid_token(&client, &api_config, &mut credentials, &app_handle).await?;
+
+    let plan = plan_tier_change(&client, &api_config, &credentials, &file_name, &new_tier, &app_handle).await?;
+    if plan.requires_confirmation && confirm_reupload != Some(true) {
+        return Err(format!(
+            "Changing the tier of '{}' needs a download and re-upload (estimated cost: {}). Confirm to continue.",
+            plan.file_name,
+            plan.estimated_cost.map(|c| format!("{:.4} PIPE", c)).unwrap_or_else(|| "unknown".to_string())
+        ));
+    }
+
+    println!("🔀 Moving '{}' to tier '{}' via {}", plan.file_name, plan.new_tier, plan.method);
+    if plan.method == "api" {
+        change_tier_via_api(&client, &api_config, &credentials, &plan).await?;
+    } else {
+        change_tier_via_reupload(&plan, &app_handle).await?;
+    }
+
+    let (name, tier) = (plan.file_name.clone(), plan.new_tier.clone());
+    let updated = history_log::update_entries(
+        &credentials.user_id,
+        move |entry| {
+            if entry.status != "success" || entry.remote_path != name || entry.tier.as_deref() == Some(tier.as_str()) {
+                return false;
+            }
+            entry.tier = Some(tier.clone());
+            true
+        },
+        &app_handle,
+    )
+    .await;
+    if let Err(e) = updated {
+        println_redacted!("[TIER] Failed to update history for '{}': {}", plan.file_name, e);
+    }
+
+    app_handle
+        .emit(
+            "file_tier_changed",
+            serde_json::json!({
+                "user_id": credentials.user_id,
+                "file_name": plan.file_name,
+                "old_tier": plan.current_tier,
+                "new_tier": plan.new_tier,
+                "method": plan.method,
+            }),
+        )
+        .ok();
+    Ok(plan)
+}
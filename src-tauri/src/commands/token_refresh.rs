@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::redaction::println_redacted;
+use super::{
+    clock_skew, current_api_config, ensure_valid_token, is_token_expired, load_credentials, network,
+    TOKEN_REFRESH_BUFFER_SECS,
+};
+
+// =============================================================================================================
+// =============================================== TOKEN REFRESH ===============================================
+// =============================================================================================================
+//
+// Background refresh for the active account's access token, so it's renewed as it enters the refresh window
+// instead of by whichever request happens to notice first — a long sync shouldn't stall on a refresh round
+// trip or a 401. Refreshes go through `ensure_valid_token`, which serializes them on `TokenRefreshLock`. A failed
+// refresh (the server busy or down) is retried with a doubling delay instead of every check.
+
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_REFRESH_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+/// Held while a refresh is in flight
+pub type TokenRefreshLock = tokio::sync::Mutex<()>;
+pub fn new_token_refresh_lock() -> TokenRefreshLock { tokio::sync::Mutex::new(()) }
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TokenStatus {
+    pub user_id: String,
+    pub expires_at: Option<String>,
+    /// Seconds until the token expires, on the server's clock
+    pub expires_in_secs: Option<i64>,
+    /// Seconds until the scheduler refreshes it; 0 when due
+    pub refresh_in_secs: Option<i64>,
+}
+
+/// Refresh the active account's token if it's due. Err when a refresh was tried and failed.
+async fn refresh_if_due(app_handle: &AppHandle) -> Result<(), String> {
+    let Ok(Some(mut credentials)) = load_credentials(app_handle.clone()).await else { return Ok(()) };
+    let due = credentials
+        .auth_tokens
+        .as_ref()
+        .is_some_and(|t| is_token_expired(t, clock_skew::server_now(app_handle)));
+    if !due {
+        return Ok(());
+    }
+    let client = network::client(app_handle);
+    if let Err(e) = ensure_valid_token(&client, &current_api_config(app_handle), &mut credentials, app_handle).await {
+        println_redacted!("[TOKEN] Scheduled refresh failed: {}", e);
+        app_handle
+            .emit("token_refresh_failed", serde_json::json!({ "user_id": credentials.user_id, "error": e }))
+            .ok();
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Check the active account's token every `REFRESH_CHECK_INTERVAL`. Called once in setup.
+pub fn start_token_refresh_scheduler(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut delay = REFRESH_CHECK_INTERVAL;
+        loop {
+            tokio::time::sleep(delay).await;
+            delay = match refresh_if_due(&app_handle).await {
+                Ok(()) => REFRESH_CHECK_INTERVAL,
+                // a rejected refresh token clears the tokens, so only transient failures come back here
+                Err(_) => (delay * 2).min(MAX_REFRESH_BACKOFF),
+            };
+        }
+    });
+}
+
+/// Expiry countdown for the active account; `None` when signed out
+#[tauri::command]
+pub async fn get_token_status(app_handle: AppHandle) -> Result<Option<TokenStatus>, String> {
+    let Some(credentials) = load_credentials(app_handle.clone()).await? else { return Ok(None) };
+    let expires_at = credentials.auth_tokens.as_ref().and_then(|t| t.expires_at.clone());
+    let expires_in_secs = expires_at
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| (t.with_timezone(&Utc) - clock_skew::server_now(&app_handle)).num_seconds());
+    Ok(Some(TokenStatus {
+        user_id: credentials.user_id,
+        expires_at,
+        expires_in_secs,
+        refresh_in_secs: expires_in_secs.map(|s| (s - TOKEN_REFRESH_BUFFER_SECS).max(0)),
+    }))
+}
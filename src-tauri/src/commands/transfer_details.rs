@@ -0,0 +1,133 @@
+use std::time::Duration;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::read_upload_history;
+use super::redaction;
+use super::settings::{current_settings, update_settings};
+
+// =============================================================================================================
+// ============================================= TRANSFER DETAILS ==============================================
+// =============================================================================================================
+//
+// Structured facts about one upload attempt, kept with its history entry for troubleshooting and statistics.
+// The entry's `message` is a short summary; the raw response body is only kept with `debug_history_responses`.
+
+/// Raw bodies kept in debug mode are cut to this many bytes
+const MAX_DEBUG_BODY: usize = 4096;
+/// Failure summaries longer than this are cut
+const MAX_SUMMARY: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TransferDetails {
+    pub duration_ms: u64,
+    pub average_bytes_per_sec: u64,
+    /// Earlier failed attempts with the same idempotency key
+    pub retry_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Raw response, only with `debug_history_responses`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<String>,
+}
+
+impl TransferDetails {
+    pub fn new(bytes: u64, elapsed: Duration, retry_count: u32) -> Self {
+        let secs = elapsed.as_secs_f64();
+        TransferDetails {
+            duration_ms: elapsed.as_millis() as u64,
+            average_bytes_per_sec: if secs > 0.0 { (bytes as f64 / secs) as u64 } else { 0 },
+            retry_count,
+            ..Default::default()
+        }
+    }
+
+    /// Status, request id and (in debug mode) body of the server's answer
+    pub fn with_response(mut self, status: StatusCode, request_id: Option<String>, body: &str, app_handle: &AppHandle) -> Self {
+        self.http_status = Some(status.as_u16());
+        self.request_id = request_id;
+        if current_settings(app_handle).debug_history_responses {
+            self.response_body = Some(redaction::redact(truncate(body, MAX_DEBUG_BODY)));
+        }
+        self
+    }
+}
+
+fn truncate(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// One-line description of a failed response: the server's error message when the body is JSON carrying one
+pub fn failure_summary(status: StatusCode, body: &str) -> String {
+    let reported = serde_json::from_str::<serde_json::Value>(body).ok().and_then(|json| {
+        ["error", "message", "detail"].iter().find_map(|k| json.get(*k)?.as_str().map(str::to_string))
+    });
+    let text = reported.unwrap_or_else(|| body.trim().to_string());
+    if text.is_empty() {
+        format!("HTTP {}", status)
+    } else {
+        format!("HTTP {}: {}", status, redaction::redact(truncate(&text, MAX_SUMMARY)))
+    }
+}
+
+/// Failed attempts already logged under `upload_id`
+pub async fn previous_attempts(user_id: &str, upload_id: &str, app_handle: &AppHandle) -> u32 {
+    read_upload_history(user_id.to_string(), app_handle.clone())
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter(|e| e.status != "success" && e.upload_id.as_deref() == Some(upload_id))
+        .count() as u32
+}
+
+/// Keep raw server responses in upload history entries (for troubleshooting)
+#[tauri::command]
+pub async fn set_debug_history_responses(enabled: bool, app_handle: AppHandle) -> Result<(), String> {
+    update_settings(&app_handle, |s| s.debug_history_responses = enabled)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_prefer_the_servers_error_message() {
+        let status = StatusCode::INSUFFICIENT_STORAGE;
+        assert_eq!(
+            failure_summary(status, r#"{"detail": "ignored", "error": "Quota exceeded"}"#),
+            "HTTP 507 Insufficient Storage: Quota exceeded"
+        );
+        assert_eq!(failure_summary(status, "  upstream timed out\n"), "HTTP 507 Insufficient Storage: upstream timed out");
+        assert_eq!(failure_summary(status, r#"{"error": 42}"#), r#"HTTP 507 Insufficient Storage: {"error": 42}"#);
+        assert_eq!(failure_summary(status, " "), "HTTP 507 Insufficient Storage");
+    }
+
+    #[test]
+    fn long_text_is_cut_on_a_character_boundary() {
+        let text = "é".repeat(MAX_SUMMARY);
+        assert_eq!(truncate(&text, MAX_SUMMARY + 1), "é".repeat(MAX_SUMMARY / 2));
+        assert_eq!(truncate("short", MAX_SUMMARY), "short");
+        let summary = failure_summary(StatusCode::BAD_GATEWAY, &text);
+        assert_eq!(summary, format!("HTTP 502 Bad Gateway: {}", "é".repeat(MAX_SUMMARY / 2)));
+    }
+
+    #[test]
+    fn details_record_duration_and_average_speed() {
+        let details = TransferDetails::new(10_000_000, Duration::from_millis(2_500), 2);
+        assert_eq!((details.duration_ms, details.average_bytes_per_sec, details.retry_count), (2_500, 4_000_000, 2));
+        assert_eq!(TransferDetails::new(10, Duration::ZERO, 0).average_bytes_per_sec, 0);
+        let json = serde_json::to_value(&details).unwrap();
+        assert!(json.get("http_status").is_none() && json.get("response_body").is_none());
+    }
+}
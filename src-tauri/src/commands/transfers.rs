@@ -0,0 +1,433 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use super::folders::resolve_remote_dir;
+use super::{destinations, groups, stability, taskbar};
+use super::{app_data_root, create_local_file, data_dir, local_file_name, open_local_file, upload_file, UploadOptions};
+
+// =============================================================================================================
+// ============================================== TRANSFER QUEUE ===============================================
+// =============================================================================================================
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueuedUpload {
+    pub id: String,
+    /// Idempotency key reused if this job is retried or restored
+    #[serde(default)]
+    pub upload_id: String,
+    pub file_path: String,
+    pub remote_file_name: Option<String>,
+    /// Folder fixed when the job was queued ("" for the root); `None` takes the default prefix at upload time
+    #[serde(default)]
+    pub remote_dir: Option<String>,
+    pub tier: Option<String>,
+    pub epochs: Option<u32>,
+    /// "overwrite" | "rename" | "abort" when the remote name is taken
+    #[serde(default)]
+    pub on_conflict: Option<String>,
+    /// Use the priority upload route when the backend has one
+    #[serde(default)]
+    pub priority: bool,
+    /// Transfer group this upload reports to
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// "queued" | "uploading" | "success" | "failed"
+    pub status: String,
+    pub message: Option<String>,
+    /// Show a system notification when this upload finishes
+    pub notify: bool,
+    /// File is a staged copy owned by the app. It is removed once the upload succeeds or the job is cleared, so
+    /// a failed upload doesn't lose the only copy of a shared file.
+    pub staged: bool,
+    pub enqueued_at: String,
+}
+
+#[derive(Default)]
+pub struct TransferQueue {
+    jobs: Vec<QueuedUpload>,
+    running: bool,
+    next_id: u64,
+    /// Woken whenever the worker runs out of jobs
+    drained: Arc<tokio::sync::Notify>,
+}
+
+pub type TransferQueueState = Mutex<TransferQueue>;
+pub fn new_transfer_queue_state() -> TransferQueueState { Mutex::new(TransferQueue::default()) }
+
+impl TransferQueue {
+    fn depth(&self) -> (usize, usize) {
+        let queued = self.jobs.iter().filter(|j| j.status == "queued").count();
+        let active = self.jobs.iter().filter(|j| j.status == "uploading").count();
+        (queued, active)
+    }
+}
+
+/// (queued, uploading) job counts
+pub fn queue_depth(app_handle: &AppHandle) -> (usize, usize) {
+    app_handle.state::<TransferQueueState>().lock().unwrap().depth()
+}
+
+/// `queue_depth` that gives up instead of waiting on the lock, for use from the panic hook
+pub fn try_queue_depth(app_handle: &AppHandle) -> Option<(usize, usize)> {
+    let state = app_handle.try_state::<TransferQueueState>()?;
+    let depth = state.try_lock().ok()?.depth();
+    Some(depth)
+}
+
+/// Pending jobs are persisted so an OS background task (or the next launch) can finish them
+fn queue_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join("transfer-queue.json"))
+}
+
+/// Whether a job is still kept across restarts: unfinished ones, and failed ones whose staged copy would
+/// otherwise be left behind
+fn is_kept(job: &QueuedUpload) -> bool {
+    job.status == "queued" || job.status == "uploading" || (job.staged && job.status == "failed")
+}
+
+fn persist_pending(jobs: &[QueuedUpload], app_handle: &AppHandle) -> Result<(), String> {
+    let pending: Vec<&QueuedUpload> = jobs.iter().filter(|j| is_kept(j)).collect();
+    let path = queue_file_path(app_handle)?;
+    if pending.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| format!("Failed to remove queue file: {}", e))?;
+        }
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&pending).map_err(|e| format!("Failed to serialize queue: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write queue file: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace queue file: {}", e))
+}
+
+fn emit_queue_updated(app_handle: &AppHandle) {
+    let state = app_handle.state::<TransferQueueState>();
+    let jobs = {
+        // written under the lock so two updates can't land on disk in the wrong order
+        let queue = state.lock().unwrap();
+        if let Err(e) = persist_pending(&queue.jobs, app_handle) {
+            println!("[QUEUE] {}", e);
+        }
+        queue.jobs.clone()
+    };
+    app_handle.emit("transfer_queue_updated", jobs).ok();
+}
+
+fn start_worker(app_handle: &AppHandle) {
+    // Ask the native side to keep us alive (WorkManager on Android, BGProcessingTask on iOS)
+    #[cfg(mobile)]
+    app_handle.emit("background_transfers_requested", ()).ok();
+
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move { run_queue(handle).await });
+}
+
+/// Parameters for a new queue entry
+#[derive(Default)]
+pub struct NewUpload {
+    pub file_path: String,
+    pub remote_file_name: Option<String>,
+    pub remote_dir: Option<String>,
+    pub tier: Option<String>,
+    pub epochs: Option<u32>,
+    pub on_conflict: Option<String>,
+    pub priority: bool,
+    pub group_id: Option<String>,
+    pub notify: bool,
+    pub staged: bool,
+}
+
+/// Add an upload to the queue and make sure a worker is draining it
+pub fn enqueue(app_handle: &AppHandle, upload: NewUpload) -> QueuedUpload {
+    let (job, spawn_worker) = {
+        let state = app_handle.state::<TransferQueueState>();
+        let mut queue = state.lock().unwrap();
+        queue.next_id += 1;
+        let job = QueuedUpload {
+            id: format!("queue-{}-{}", Utc::now().timestamp_millis(), queue.next_id),
+            upload_id: uuid::Uuid::new_v4().to_string(),
+            file_path: upload.file_path,
+            remote_file_name: upload.remote_file_name,
+            remote_dir: upload.remote_dir,
+            tier: upload.tier,
+            epochs: upload.epochs,
+            on_conflict: upload.on_conflict,
+            priority: upload.priority,
+            group_id: upload.group_id.filter(|g| !g.trim().is_empty()),
+            status: "queued".to_string(),
+            message: None,
+            notify: upload.notify,
+            staged: upload.staged,
+            enqueued_at: Utc::now().to_rfc3339(),
+        };
+        queue.jobs.push(job.clone());
+        let spawn_worker = !queue.running;
+        queue.running = true;
+        (job, spawn_worker)
+    };
+
+    if let Some(group_id) = &job.group_id {
+        groups::add_member(app_handle, group_id, &job.upload_id, &display_name(&job));
+    }
+    emit_queue_updated(app_handle);
+    if spawn_worker {
+        start_worker(app_handle);
+    }
+    job
+}
+
+/// Re-queue uploads left over from a previous run. Called once at startup.
+pub fn restore_transfer_queue(app_handle: &AppHandle) {
+    let restored: Vec<QueuedUpload> = match queue_file_path(app_handle)
+        .ok()
+        .filter(|p| p.exists())
+        .and_then(|p| std::fs::read_to_string(p).ok())
+    {
+        Some(content) => match serde_json::from_str(&content) {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                println!("[QUEUE] Failed to parse queue file: {}", e);
+                return;
+            }
+        },
+        None => return,
+    };
+    if restored.is_empty() {
+        return;
+    }
+
+    {
+        let state = app_handle.state::<TransferQueueState>();
+        let mut queue = state.lock().unwrap();
+        for mut job in restored {
+            // an upload interrupted mid-stream starts over; failed staged jobs stay failed until cleared
+            if job.status != "failed" {
+                job.status = "queued".to_string();
+            }
+            if let Some(group_id) = &job.group_id {
+                groups::add_member(app_handle, group_id, &job.upload_id, &display_name(&job));
+            }
+            queue.jobs.push(job);
+        }
+        queue.running = true;
+    }
+    println!("🔄 Restored pending uploads from previous session");
+    emit_queue_updated(app_handle);
+    start_worker(app_handle);
+}
+
+fn display_name(job: &QueuedUpload) -> String {
+    job.remote_file_name
+        .clone()
+        .or_else(|| local_file_name(&job.file_path))
+        .unwrap_or_else(|| job.file_path.clone())
+}
+
+/// Upload queued jobs one at a time until nothing is left
+async fn run_queue(app_handle: AppHandle) {
+    loop {
+        let job = {
+            let state = app_handle.state::<TransferQueueState>();
+            let mut queue = state.lock().unwrap();
+            match queue.jobs.iter_mut().find(|j| j.status == "queued") {
+                Some(job) => {
+                    job.status = "uploading".to_string();
+                    job.clone()
+                }
+                None => {
+                    queue.running = false;
+                    queue.drained.notify_waiters();
+                    break;
+                }
+            }
+        };
+        emit_queue_updated(&app_handle);
+        taskbar::job_started(&app_handle, &job.id);
+
+        // staged copies are complete; anything else may still be written to
+        let settled = if job.staged { Ok(()) } else { stability::wait_until_stable(&job.file_path, &app_handle).await };
+        let result = match settled {
+            Err(e) => Err(e),
+            Ok(()) => {
+                let options = UploadOptions {
+                    tier: job.tier.clone(),
+                    epochs: job.epochs,
+                    remote_file_name: job.remote_file_name.clone(),
+                    remote_dir: job.remote_dir.clone(),
+                    on_conflict: job.on_conflict.clone(),
+                    priority: job.priority,
+                    group_id: job.group_id.clone(),
+                    id: Some(job.id.clone()),
+                    upload_id: Some(job.upload_id.clone()),
+                    ..Default::default()
+                };
+                upload_file(job.file_path.clone(), options, app_handle.clone()).await
+            }
+        };
+
+        if job.staged && result.is_ok() {
+            let _ = tokio::fs::remove_file(&job.file_path).await;
+        }
+
+        if let Some(group_id) = &job.group_id {
+            // covers failures before upload_file started tracking the member
+            groups::finish_member(&app_handle, group_id, &job.upload_id, &result);
+        }
+
+        let display_name = display_name(&job);
+        if job.notify {
+            let (title, body) = match &result {
+                Ok(_) => ("Upload complete", format!("'{}' was uploaded to Firestarter", display_name)),
+                Err(e) => ("Upload failed", format!("'{}': {}", display_name, e)),
+            };
+            let _ = app_handle.notification().builder().title(title).body(body).show();
+        }
+
+        let succeeded = result.is_ok();
+        {
+            let state = app_handle.state::<TransferQueueState>();
+            let mut queue = state.lock().unwrap();
+            if let Some(entry) = queue.jobs.iter_mut().find(|j| j.id == job.id) {
+                match result {
+                    Ok(msg) => {
+                        entry.status = "success".to_string();
+                        entry.message = Some(msg);
+                    }
+                    Err(e) => {
+                        entry.status = "failed".to_string();
+                        entry.message = Some(e);
+                    }
+                }
+            }
+        }
+        emit_queue_updated(&app_handle);
+        taskbar::job_finished(&app_handle, succeeded);
+    }
+    emit_queue_updated(&app_handle);
+    taskbar::clear(&app_handle);
+    #[cfg(mobile)]
+    app_handle.emit("background_transfers_finished", ()).ok();
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn enqueue_upload(
+    file_path: String,
+    tier: Option<String>,
+    epochs: Option<u32>,
+    remote_file_name: Option<String>,
+    remote_dir: Option<String>,
+    on_conflict: Option<String>,
+    priority: Option<bool>,
+    group_id: Option<String>,
+    app_handle: AppHandle,
+) -> Result<QueuedUpload, String> {
+    // resolve the folder now so a later change of the default prefix doesn't move queued jobs
+    let name = match remote_file_name.filter(|n| !n.trim().is_empty()) {
+        Some(name) => name,
+        None => destinations::default_remote_name(&file_path, &app_handle)?,
+    };
+    let remote_dir = resolve_remote_dir(&name, remote_dir.as_deref(), &app_handle)?;
+    Ok(enqueue(
+        &app_handle,
+        NewUpload {
+            file_path,
+            remote_file_name: Some(name),
+            remote_dir: Some(remote_dir),
+            tier,
+            epochs,
+            on_conflict,
+            priority: priority.unwrap_or(false),
+            group_id,
+            ..Default::default()
+        },
+    ))
+}
+
+#[tauri::command]
+pub async fn get_transfer_queue(app_handle: AppHandle) -> Result<Vec<QueuedUpload>, String> {
+    Ok(app_handle.state::<TransferQueueState>().lock().unwrap().jobs.clone())
+}
+
+/// Entry point for platform background tasks: resolves once the queue is empty,
+/// so the WorkManager worker / BGTask can report completion to the OS.
+#[tauri::command]
+pub async fn run_background_transfers(app_handle: AppHandle) -> Result<Vec<QueuedUpload>, String> {
+    let drained = app_handle.state::<TransferQueueState>().lock().unwrap().drained.clone();
+    let notified = drained.notified();
+    let running = app_handle.state::<TransferQueueState>().lock().unwrap().running;
+    if running {
+        notified.await;
+    }
+    Ok(app_handle.state::<TransferQueueState>().lock().unwrap().jobs.clone())
+}
+
+#[tauri::command]
+pub async fn clear_finished_transfers(app_handle: AppHandle) -> Result<usize, String> {
+    let dropped: Vec<QueuedUpload> = {
+        let state = app_handle.state::<TransferQueueState>();
+        let mut queue = state.lock().unwrap();
+        let (dropped, kept) = std::mem::take(&mut queue.jobs)
+            .into_iter()
+            .partition(|j| j.status != "queued" && j.status != "uploading");
+        queue.jobs = kept;
+        dropped
+    };
+    // staged copies of failed jobs were kept until now; nothing refers to them any more
+    for job in dropped.iter().filter(|j| j.staged) {
+        let _ = tokio::fs::remove_file(&job.file_path).await;
+    }
+    emit_queue_updated(&app_handle);
+    Ok(dropped.len())
+}
+
+// =============================================================================================================
+// ============================================== SHARED CONTENT ===============================================
+// =============================================================================================================
+
+/// Entry point for the OS share sheet (Android ACTION_SEND / iOS share extension).
+/// The shared URI is only readable for a short time, so copy it into the app cache first,
+/// then queue the staged copy and notify when it is done.
+#[tauri::command]
+pub async fn receive_shared_file(uri: String, app_handle: AppHandle) -> Result<QueuedUpload, String> {
+    use tokio::io::AsyncWriteExt;
+
+    let name = local_file_name(&uri).ok_or("Shared item has no file name")?;
+    let staging_dir = data_dir::app_cache_dir(&app_handle)?.join("shared");
+    let staged_path = staging_dir
+        .join(format!("{}-{}", Utc::now().timestamp_millis(), name))
+        .to_string_lossy()
+        .to_string();
+
+    let mut source = open_local_file(&uri, &app_handle).await?;
+    let mut target = create_local_file(&staged_path, &app_handle).await?;
+    tokio::io::copy(&mut source, &mut target)
+        .await
+        .map_err(|e| format!("Failed to stage shared file: {}", e))?;
+    target.flush().await.map_err(|e| format!("Failed to stage shared file: {}", e))?;
+
+    println!("📤 Staged shared file {} -> {}", uri, staged_path);
+    app_handle
+        .emit("shared_file_received", serde_json::json!({ "uri": uri, "file_name": name, "staged_path": staged_path }))
+        .ok();
+
+    // shared files never replace an existing object
+    Ok(enqueue(
+        &app_handle,
+        NewUpload {
+            file_path: staged_path,
+            remote_file_name: Some(name),
+            on_conflict: Some("rename".to_string()),
+            notify: true,
+            staged: true,
+            ..Default::default()
+        },
+    ))
+}
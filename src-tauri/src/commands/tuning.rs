@@ -0,0 +1,155 @@
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use super::disk_space;
+use super::hashing::HashingStatsState;
+use super::settings::{current_settings, update_settings};
+
+// =============================================================================================================
+// ============================================== TRANSFER TUNING ==============================================
+// =============================================================================================================
+
+const MIN_BUFFER: usize = 16 * 1024;
+const MAX_BUFFER: usize = 16 * 1024 * 1024;
+/// Links faster than this get the largest automatic buffer
+const FAST_LINK_MB_PER_SEC: f64 = 50.0;
+const DEFAULT_PROGRESS_INTERVAL_MS: u64 = 100;
+const DEFAULT_PROGRESS_MIN_PERCENT: f64 = 1.0;
+const DEFAULT_DOWNLOAD_WRITE_BUFFER: usize = 1024 * 1024;
+const DEFAULT_DOWNLOAD_SEGMENTS: u8 = 4;
+const MAX_DOWNLOAD_SEGMENTS: u8 = 16;
+
+/// Read buffer for upload streams: the settings override, otherwise picked from file size and the last
+/// measured upload rate. Small chunks cap throughput and multiply progress events.
+pub fn upload_buffer_size(file_size: u64, app_handle: &AppHandle) -> usize {
+    if let Some(bytes) = current_settings(app_handle).upload_buffer_bytes {
+        return (bytes as usize).clamp(MIN_BUFFER, MAX_BUFFER);
+    }
+    let last_rate = app_handle
+        .state::<HashingStatsState>()
+        .lock()
+        .unwrap()
+        .last
+        .as_ref()
+        .and_then(|run| run.network_mb_per_sec);
+    let by_size = match file_size {
+        0..=1_048_575 => 64 * 1024,
+        1_048_576..=67_108_863 => 256 * 1024,
+        _ => 1024 * 1024,
+    };
+    match last_rate {
+        Some(rate) if rate >= FAST_LINK_MB_PER_SEC && file_size > 64 * 1024 * 1024 => 4 * 1024 * 1024,
+        _ => by_size,
+    }
+}
+
+/// Rate limit for progress events: one per `interval`, or sooner when the percentage moved by `min_percent`.
+/// The first and the final update always go out.
+pub struct ProgressThrottle {
+    interval: Duration,
+    min_percent: f64,
+    last_emit: Option<Instant>,
+    last_percent: f64,
+}
+
+impl ProgressThrottle {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        let settings = current_settings(app_handle);
+        Self {
+            interval: Duration::from_millis(settings.progress_interval_ms.unwrap_or(DEFAULT_PROGRESS_INTERVAL_MS)),
+            min_percent: settings.progress_min_percent.unwrap_or(DEFAULT_PROGRESS_MIN_PERCENT),
+            last_emit: None,
+            last_percent: 0.0,
+        }
+    }
+
+    pub fn should_emit(&mut self, percent: f64, done: bool) -> bool {
+        let due = match self.last_emit {
+            None => true,
+            Some(last) => last.elapsed() >= self.interval || (percent - self.last_percent).abs() >= self.min_percent,
+        };
+        if due || done {
+            self.last_emit = Some(Instant::now());
+            self.last_percent = percent;
+        }
+        due || done
+    }
+}
+
+/// Configure progress event throttling; `None` restores the default (100 ms / 1%)
+#[tauri::command]
+pub async fn set_progress_throttle(interval_ms: Option<u64>, min_percent: Option<f64>, app_handle: AppHandle) -> Result<(), String> {
+    if min_percent.is_some_and(|p| !(0.0..=100.0).contains(&p)) {
+        return Err("min_percent must be between 0 and 100".to_string());
+    }
+    update_settings(&app_handle, |s| {
+        s.progress_interval_ms = interval_ms;
+        s.progress_min_percent = min_percent;
+    })?;
+    Ok(())
+}
+
+/// Buffer size for download writes; the buffer is flushed whenever it fills
+pub fn download_write_buffer(app_handle: &AppHandle) -> usize {
+    current_settings(app_handle)
+        .download_write_buffer_bytes
+        .map(|b| (b as usize).clamp(MIN_BUFFER, MAX_BUFFER))
+        .unwrap_or(DEFAULT_DOWNLOAD_WRITE_BUFFER)
+}
+
+/// Parallel connections for large ranged downloads; 1 disables segmenting
+pub fn download_segments(app_handle: &AppHandle) -> usize {
+    current_settings(app_handle)
+        .download_segments
+        .unwrap_or(DEFAULT_DOWNLOAD_SEGMENTS)
+        .clamp(1, MAX_DOWNLOAD_SEGMENTS) as usize
+}
+
+#[tauri::command]
+pub async fn set_download_segments(segments: Option<u8>, app_handle: AppHandle) -> Result<usize, String> {
+    update_settings(&app_handle, |s| s.download_segments = segments.map(|n| n.clamp(1, MAX_DOWNLOAD_SEGMENTS)))?;
+    Ok(download_segments(&app_handle))
+}
+
+/// Reserve `len` bytes for a download so the file isn't grown (and fragmented) chunk by chunk.
+/// Uses fallocate on Linux/Android; elsewhere `set_len`, which is SetFileInformationByHandle on Windows.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub async fn preallocate(file: &tokio::fs::File, len: u64) -> Result<(), String> {
+    use std::os::unix::io::AsRawFd;
+    // fallocate can take a while on filesystems that emulate it by writing zeros; keep it off the runtime
+    let handle = file.try_clone().await.map_err(|e| format!("Failed to preallocate file: {}", e))?.into_std().await;
+    let rc = tokio::task::spawn_blocking(move || {
+        // SAFETY: the fd is owned by `handle`, which lives until the end of this closure
+        unsafe { libc::posix_fallocate(handle.as_raw_fd(), 0, len as libc::off_t) }
+    })
+    .await
+    .map_err(|e| format!("Failed to preallocate file: {}", e))?;
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(disk_space::write_error("Failed to preallocate file", std::io::Error::from_raw_os_error(rc)))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub async fn preallocate(file: &tokio::fs::File, len: u64) -> Result<(), String> {
+    file.set_len(len).await.map_err(|e| disk_space::write_error("Failed to preallocate file", e))
+}
+
+/// Download write options: buffer size (`None` = 1 MB) and whether to fsync once the download completes
+#[tauri::command]
+pub async fn set_download_write_options(buffer_bytes: Option<u64>, fsync_on_complete: bool, app_handle: AppHandle) -> Result<(), String> {
+    update_settings(&app_handle, |s| {
+        s.download_write_buffer_bytes = buffer_bytes.map(|b| b.clamp(MIN_BUFFER as u64, MAX_BUFFER as u64));
+        s.download_fsync = fsync_on_complete;
+    })?;
+    Ok(())
+}
+
+/// Override the upload read buffer; `None` goes back to automatic sizing
+#[tauri::command]
+pub async fn set_upload_buffer_size(bytes: Option<u64>, app_handle: AppHandle) -> Result<Option<u64>, String> {
+    let bytes = bytes.map(|b| b.clamp(MIN_BUFFER as u64, MAX_BUFFER as u64));
+    update_settings(&app_handle, |s| s.upload_buffer_bytes = bytes)?;
+    Ok(bytes)
+}
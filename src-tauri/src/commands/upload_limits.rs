@@ -0,0 +1,82 @@
+use reqwest::header::HeaderMap;
+
+// =============================================================================================================
+// ============================================== UPLOAD SIZE LIMIT ============================================
+// =============================================================================================================
+//
+// A 413 from the upload endpoint means the object is over the server's size limit, which retrying won't fix.
+// The limit is read from the response when the server states it (JSON body field or header), and the upload
+// fails with a message saying how far over it is. `upload_too_large` is emitted so the UI can suggest a smaller
+// file; there is no split-upload route in the API to fall back to.
+
+/// JSON fields and headers servers use for the limit, in bytes
+const LIMIT_FIELDS: &[&str] = &["max_size", "max_bytes", "max_upload_size", "max_file_size", "limit"];
+const LIMIT_HEADERS: &[&str] = &["x-max-upload-size", "x-upload-limit", "x-max-content-length"];
+
+/// The server's upload size limit, when the 413 response states it
+pub fn server_limit(headers: &HeaderMap, body: &str) -> Option<u64> {
+    let from_header = LIMIT_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok()?.trim().parse::<u64>().ok());
+    from_header.or_else(|| {
+        let json = serde_json::from_str::<serde_json::Value>(body).ok()?;
+        LIMIT_FIELDS.iter().find_map(|k| {
+            let value = json.get(*k)?;
+            value.as_u64().or_else(|| value.as_str()?.trim().parse().ok())
+        })
+    })
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Error for an upload the server refused as too large
+pub fn too_large_message(file_name: &str, file_size: u64, limit: Option<u64>) -> String {
+    match limit {
+        Some(limit) => format!(
+            "'{}' is too large for the server: {} over the {} upload limit",
+            file_name,
+            megabytes(file_size.saturating_sub(limit)),
+            megabytes(limit)
+        ),
+        None => format!("'{}' ({}) is larger than the server accepts", file_name, megabytes(file_size)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn limit_is_read_from_headers_before_the_body() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-upload-limit", HeaderValue::from_static(" 1048576 "));
+        assert_eq!(server_limit(&headers, r#"{"max_size": 5}"#), Some(1_048_576));
+        assert_eq!(server_limit(&HeaderMap::new(), r#"{"max_size": 5}"#), Some(5));
+    }
+
+    #[test]
+    fn limit_fields_may_be_numbers_or_strings() {
+        let none = HeaderMap::new();
+        assert_eq!(server_limit(&none, r#"{"error": "too large", "max_upload_size": "2048"}"#), Some(2048));
+        assert_eq!(server_limit(&none, r#"{"limit": 4096}"#), Some(4096));
+        assert_eq!(server_limit(&none, r#"{"limit": "lots"}"#), None);
+        assert_eq!(server_limit(&none, r#"{"limit": -1}"#), None);
+        assert_eq!(server_limit(&none, "413 Request Entity Too Large"), None);
+        let mut bad_header = HeaderMap::new();
+        bad_header.insert("x-max-upload-size", HeaderValue::from_static("10MB"));
+        assert_eq!(server_limit(&bad_header, r#"{"max_bytes": 10}"#), Some(10));
+    }
+
+    #[test]
+    fn message_says_how_far_over_the_limit() {
+        let mb = 1024 * 1024;
+        assert_eq!(
+            too_large_message("big.iso", 150 * mb, Some(100 * mb)),
+            "'big.iso' is too large for the server: 50.0 MB over the 100.0 MB upload limit"
+        );
+        assert_eq!(too_large_message("big.iso", 3 * mb / 2, None), "'big.iso' (1.5 MB) is larger than the server accepts");
+    }
+}
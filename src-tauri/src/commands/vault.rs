@@ -0,0 +1,324 @@
+use std::path::PathBuf;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::Utc;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio::io::AsyncWriteExt;
+
+use super::{account_scope, audit, create_local_file, get_user_data_dir, ipc_guard, output_paths};
+
+// =============================================================================================================
+// ================================================ KEY VAULT ==================================================
+// =============================================================================================================
+//
+// Client-side encryption keys are never stored in the clear: each 256-bit key is wrapped with
+// ChaCha20-Poly1305 under a key derived from the user's passphrase with argon2id. The wrapped entry
+// (plus the record of which uploads used it) can be exported and imported on another machine.
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    pub salt: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VaultKeyEntry {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    /// Short blake3 fingerprint of the raw key, safe to display
+    pub fingerprint: String,
+    pub kdf: KdfParams,
+    pub nonce: String,
+    pub wrapped_key: String,
+}
+
+/// Key metadata returned to the frontend (no wrapped material)
+#[derive(Serialize, Debug, Clone)]
+pub struct VaultKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub fingerprint: String,
+    pub usage_count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyUsage {
+    pub key_id: String,
+    pub remote_path: String,
+    pub blake3_hash: String,
+    pub timestamp: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(super) struct VaultFile {
+    keys: Vec<VaultKeyEntry>,
+    usages: Vec<KeyUsage>,
+}
+
+/// Portable export format: one wrapped key and the uploads it was used for
+#[derive(Serialize, Deserialize, Debug)]
+struct VaultExport {
+    version: u32,
+    key: VaultKeyEntry,
+    usages: Vec<KeyUsage>,
+}
+
+fn vault_path(user_id: &str, app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_user_data_dir(user_id, app_handle)?.join(format!("keyvault-{}.json", user_id)))
+}
+
+fn read_vault(user_id: &str, app_handle: &AppHandle) -> Result<VaultFile, String> {
+    let path = vault_path(user_id, app_handle)?;
+    if !path.exists() { return Ok(VaultFile::default()); }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read key vault: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse key vault: {}", e))
+}
+
+fn write_vault(user_id: &str, vault: &VaultFile, app_handle: &AppHandle) -> Result<(), String> {
+    let path = vault_path(user_id, app_handle)?;
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create user dir: {}", e))?; }
+    let json = serde_json::to_string_pretty(vault).map_err(|e| format!("Failed to serialize key vault: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write key vault: {}", e))
+}
+
+fn info(entry: &VaultKeyEntry, vault: &VaultFile) -> VaultKeyInfo {
+    VaultKeyInfo {
+        id: entry.id.clone(),
+        name: entry.name.clone(),
+        created_at: entry.created_at.clone(),
+        fingerprint: entry.fingerprint.clone(),
+        usage_count: vault.usages.iter().filter(|u| u.key_id == entry.id).count(),
+    }
+}
+
+fn fingerprint(key: &[u8]) -> String {
+    blake3::hash(key).to_hex()[..16].to_string()
+}
+
+pub(super) fn derive_wrapping_key(passphrase: &str, kdf: &KdfParams) -> Result<[u8; 32], String> {
+    if kdf.algorithm != "argon2id" {
+        return Err(format!("Unsupported key derivation: {}", kdf.algorithm));
+    }
+    let salt = hex::decode(&kdf.salt).map_err(|e| format!("Invalid salt: {}", e))?;
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32)).map_err(|e| format!("Invalid KDF params: {}", e))?;
+    let mut out = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut out)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(out)
+}
+
+/// argon2id with the default cost and a fresh random salt
+pub(super) fn new_kdf_params() -> KdfParams {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let defaults = Params::default();
+    KdfParams {
+        algorithm: "argon2id".to_string(),
+        m_cost: defaults.m_cost(),
+        t_cost: defaults.t_cost(),
+        p_cost: defaults.p_cost(),
+        salt: hex::encode(salt),
+    }
+}
+
+fn wrap_key(id: String, name: String, key: &[u8], passphrase: &str) -> Result<VaultKeyEntry, String> {
+    let kdf = new_kdf_params();
+    let wrapping_key = derive_wrapping_key(passphrase, &kdf)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&wrapping_key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let wrapped = cipher.encrypt(&nonce, key).map_err(|e| format!("Failed to wrap key: {}", e))?;
+
+    Ok(VaultKeyEntry {
+        id,
+        name,
+        created_at: Utc::now().to_rfc3339(),
+        fingerprint: fingerprint(key),
+        kdf,
+        nonce: hex::encode(nonce),
+        wrapped_key: hex::encode(wrapped),
+    })
+}
+
+/// Decrypt a vault entry with the user's passphrase
+pub fn unwrap_key(entry: &VaultKeyEntry, passphrase: &str) -> Result<Vec<u8>, String> {
+    let wrapping_key = derive_wrapping_key(passphrase, &entry.kdf)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&wrapping_key));
+    let nonce = hex::decode(&entry.nonce).map_err(|e| format!("Invalid nonce: {}", e))?;
+    if nonce.len() != 12 { return Err("Invalid nonce length".to_string()); }
+    let wrapped = hex::decode(&entry.wrapped_key).map_err(|e| format!("Invalid wrapped key: {}", e))?;
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), wrapped.as_ref())
+        .map_err(|_| "Wrong passphrase or corrupted key".to_string())
+}
+
+/// Load and unlock a key by id, for the encryption paths
+pub fn unlock_key(user_id: &str, key_id: &str, passphrase: &str, app_handle: &AppHandle) -> Result<Vec<u8>, String> {
+    let vault = read_vault(user_id, app_handle)?;
+    let entry = vault.keys.iter().find(|k| k.id == key_id).ok_or_else(|| format!("Key not found: {}", key_id))?;
+    unwrap_key(entry, passphrase)
+}
+
+#[tauri::command]
+pub async fn create_vault_key(user_id: String, name: String, passphrase: String, app_handle: AppHandle) -> Result<VaultKeyInfo, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    if name.trim().is_empty() { return Err("Key name is required".to_string()); }
+    if passphrase.len() < 8 { return Err("Passphrase must be at least 8 characters".to_string()); }
+
+    let mut vault = read_vault(&user_id, &app_handle)?;
+    if vault.keys.iter().any(|k| k.name == name.trim()) {
+        return Err(format!("A key named '{}' already exists", name.trim()));
+    }
+
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    let id = format!("key-{}", &fingerprint(&key)[..12]);
+    let entry = wrap_key(id, name.trim().to_string(), &key, &passphrase)?;
+    vault.keys.push(entry.clone());
+    write_vault(&user_id, &vault, &app_handle)?;
+
+    println!("🔑 Created vault key {} ({})", entry.name, entry.fingerprint);
+    Ok(info(&entry, &vault))
+}
+
+#[tauri::command]
+pub async fn list_vault_keys(user_id: String, app_handle: AppHandle) -> Result<Vec<VaultKeyInfo>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let vault = read_vault(&user_id, &app_handle)?;
+    Ok(vault.keys.iter().map(|k| info(k, &vault)).collect())
+}
+
+/// Remember that an upload was encrypted with a key, so it can be decrypted elsewhere
+#[tauri::command]
+pub async fn record_key_usage(user_id: String, key_id: String, remote_path: String, blake3_hash: String, app_handle: AppHandle) -> Result<(), String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut vault = read_vault(&user_id, &app_handle)?;
+    if !vault.keys.iter().any(|k| k.id == key_id) {
+        return Err(format!("Key not found: {}", key_id));
+    }
+    vault.usages.push(KeyUsage {
+        key_id,
+        remote_path,
+        blake3_hash,
+        timestamp: Utc::now().to_rfc3339(),
+    });
+    write_vault(&user_id, &vault, &app_handle)
+}
+
+#[tauri::command]
+pub async fn list_key_usage(user_id: String, key_id: Option<String>, app_handle: AppHandle) -> Result<Vec<KeyUsage>, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let vault = read_vault(&user_id, &app_handle)?;
+    Ok(vault
+        .usages
+        .into_iter()
+        .filter(|u| key_id.as_ref().map(|id| &u.key_id == id).unwrap_or(true))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn verify_vault_passphrase(user_id: String, key_id: String, passphrase: String, app_handle: AppHandle) -> Result<bool, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    match unlock_key(&user_id, &key_id, &passphrase, &app_handle) {
+        Ok(_) => Ok(true),
+        Err(e) if e.starts_with("Wrong passphrase") => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write the wrapped key (still passphrase protected) and its usage records to a file
+#[tauri::command]
+pub async fn export_vault_key(
+    user_id: String,
+    key_id: String,
+    output_path: String,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    ipc_guard::check_sensitive("export_vault_key", &webview, &ipc_nonce, &app_handle)?;
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let vault = read_vault(&user_id, &app_handle)?;
+    let key = vault.keys.iter().find(|k| k.id == key_id).ok_or_else(|| format!("Key not found: {}", key_id))?;
+    let export = VaultExport {
+        version: 1,
+        key: key.clone(),
+        usages: vault.usages.iter().filter(|u| u.key_id == key_id).cloned().collect(),
+    };
+    let json = serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize key export: {}", e))?;
+    let output_path = output_paths::check_output_path(&output_path, &app_handle)?;
+    let mut file = create_local_file(&output_path, &app_handle).await?;
+    file.write_all(json.as_bytes()).await.map_err(|e| format!("Failed to write key export: {}", e))?;
+    file.flush().await.map_err(|e| format!("Failed to write key export: {}", e))?;
+    Ok(format!("Key '{}' exported to '{}'", key.name, output_path))
+}
+
+/// Import an exported key. The passphrase is checked before anything is stored.
+#[tauri::command]
+pub async fn import_vault_key(user_id: String, path: String, passphrase: String, app_handle: AppHandle) -> Result<VaultKeyInfo, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read key export: {}", e))?;
+    let export: VaultExport = serde_json::from_str(&content).map_err(|e| format!("Invalid key export: {}", e))?;
+    if export.version != 1 {
+        return Err(format!("Unsupported key export version: {}", export.version));
+    }
+    let raw = unwrap_key(&export.key, &passphrase)?;
+    if fingerprint(&raw) != export.key.fingerprint {
+        return Err("Key fingerprint mismatch".to_string());
+    }
+
+    let mut vault = read_vault(&user_id, &app_handle)?;
+    if vault.keys.iter().any(|k| k.id == export.key.id) {
+        return Err(format!("Key '{}' is already in the vault", export.key.name));
+    }
+    for usage in export.usages {
+        let known = vault.usages.iter().any(|u| u.key_id == usage.key_id && u.remote_path == usage.remote_path && u.blake3_hash == usage.blake3_hash);
+        if !known { vault.usages.push(usage); }
+    }
+    vault.keys.push(export.key.clone());
+    write_vault(&user_id, &vault, &app_handle)?;
+    Ok(info(&export.key, &vault))
+}
+
+/// Re-wrap a key under a new passphrase. The key itself doesn't change, so earlier uploads still decrypt.
+pub(super) fn rewrap_vault_key(
+    user_id: &str,
+    key_id: &str,
+    old_passphrase: &str,
+    new_passphrase: &str,
+    app_handle: &AppHandle,
+) -> Result<VaultKeyInfo, String> {
+    if new_passphrase.len() < 8 { return Err("Passphrase must be at least 8 characters".to_string()); }
+    let mut vault = read_vault(user_id, app_handle)?;
+    let index = vault.keys.iter().position(|k| k.id == key_id).ok_or_else(|| format!("Key not found: {}", key_id))?;
+    let raw = unwrap_key(&vault.keys[index], old_passphrase)?;
+    let entry = wrap_key(vault.keys[index].id.clone(), vault.keys[index].name.clone(), &raw, new_passphrase)?;
+    // keep the original creation time
+    vault.keys[index] = VaultKeyEntry { created_at: vault.keys[index].created_at.clone(), ..entry };
+    write_vault(user_id, &vault, app_handle)?;
+    println!("🔑 Rotated passphrase for vault key {}", key_id);
+    Ok(info(&vault.keys[index], &vault))
+}
+
+#[tauri::command]
+pub async fn delete_vault_key(user_id: String, key_id: String, app_handle: AppHandle) -> Result<String, String> {
+    account_scope::authorize(&user_id, &app_handle).await?;
+    let mut vault = read_vault(&user_id, &app_handle)?;
+    let before = vault.keys.len();
+    vault.keys.retain(|k| k.id != key_id);
+    if vault.keys.len() == before {
+        return Err(format!("Key not found: {}", key_id));
+    }
+    // usage records stay behind so the affected uploads remain identifiable
+    let written = write_vault(&user_id, &vault, &app_handle);
+    audit::record(&app_handle, "vault_key_delete", &key_id, Some(&user_id), None, &written);
+    written?;
+    Ok(format!("Deleted key {}", key_id))
+}
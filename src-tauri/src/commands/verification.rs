@@ -0,0 +1,95 @@
+use tauri::AppHandle;
+use tokio::io::AsyncReadExt;
+
+use super::open_local_file;
+use super::settings::update_settings;
+
+// =============================================================================================================
+// ============================================ UPLOAD VERIFICATION ============================================
+// =============================================================================================================
+//
+// The client hashes every upload with blake3. When the server reports its own hash (header or JSON body) the
+// two are compared: a match marks the history entry `verified`, a mismatch is recorded as `hash_mismatch`.
+// Optionally the file is hashed up front and sent as `X-Expected-Blake3`, so the server can reject bad bodies.
+
+pub const EXPECTED_HASH_HEADER: &str = "X-Expected-Blake3";
+const SERVER_HASH_HEADER: &str = "x-blake3-hash";
+
+/// blake3 hex digest and size of a local file (or picker URI on mobile)
+pub async fn hash_local_file(path: &str, app_handle: &AppHandle) -> Result<(String, u64), String> {
+    let mut file = open_local_file(path, app_handle).await?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf).await.map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((hasher.finalize().to_hex().to_string(), size))
+}
+
+pub(super) fn looks_like_blake3(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Hash the server computed for the upload, from the response header or a JSON body field
+pub fn server_reported_hash(headers: &reqwest::header::HeaderMap, body: &str) -> Option<String> {
+    let from_header = headers.get(SERVER_HASH_HEADER).and_then(|v| v.to_str().ok()).map(str::trim);
+    let from_body = || {
+        let json = serde_json::from_str::<serde_json::Value>(body).ok()?;
+        ["blake3_hash", "blake3", "hash"]
+            .iter()
+            .find_map(|k| json.get(*k)?.as_str().map(str::to_string))
+    };
+    from_header
+        .map(str::to_string)
+        .or_else(from_body)
+        .map(|h| h.to_lowercase())
+        .filter(|h| looks_like_blake3(h))
+}
+
+/// Hash the file before uploading and send it as `X-Expected-Blake3`
+#[tauri::command]
+pub async fn set_upload_verification(send_expected_hash: bool, app_handle: AppHandle) -> Result<bool, String> {
+    update_settings(&app_handle, |s| s.send_expected_hash = send_expected_hash)?;
+    Ok(send_expected_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    const HASH: &str = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
+    #[test]
+    fn header_hash_wins_over_the_body() {
+        let mut headers = HeaderMap::new();
+        headers.insert(SERVER_HASH_HEADER, HeaderValue::from_str(&format!(" {} ", HASH.to_uppercase())).unwrap());
+        let other = "0".repeat(64);
+        assert_eq!(server_reported_hash(&headers, &format!(r#"{{"blake3_hash": "{}"}}"#, other)).as_deref(), Some(HASH));
+    }
+
+    #[test]
+    fn body_fields_are_tried_in_order() {
+        let none = HeaderMap::new();
+        let body = format!(r#"{{"hash": "{}", "blake3": "{}"}}"#, "1".repeat(64), HASH);
+        assert_eq!(server_reported_hash(&none, &body).as_deref(), Some(HASH));
+        assert_eq!(server_reported_hash(&none, &format!(r#"{{"hash": "{}"}}"#, HASH)).as_deref(), Some(HASH));
+    }
+
+    #[test]
+    fn values_that_are_not_blake3_are_ignored() {
+        let none = HeaderMap::new();
+        assert_eq!(server_reported_hash(&none, r#"{"hash": "d41d8cd98f00b204e9800998ecf8427e"}"#), None);
+        assert_eq!(server_reported_hash(&none, &format!(r#"{{"hash": "{}"}}"#, "g".repeat(64))), None);
+        assert_eq!(server_reported_hash(&none, HASH), None);
+        let mut headers = HeaderMap::new();
+        headers.insert(SERVER_HASH_HEADER, HeaderValue::from_static("pending"));
+        assert_eq!(server_reported_hash(&headers, &format!(r#"{{"hash": "{}"}}"#, HASH)), None);
+    }
+}
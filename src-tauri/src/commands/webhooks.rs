@@ -0,0 +1,144 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use tauri::AppHandle;
+
+use super::ipc_guard;
+use super::redaction::println_redacted;
+use super::settings::{current_settings, update_settings};
+
+// =============================================================================================================
+// ================================================= WEBHOOKS ==================================================
+// =============================================================================================================
+//
+// When configured, job events are POSTed as JSON to the user's URL. Each request carries
+// `X-Firestarter-Event`, `X-Firestarter-Timestamp` and `X-Firestarter-Signature: sha256=<hex>`, an
+// HMAC-SHA256 of `<timestamp>.<body>` keyed with the webhook secret.
+
+/// Default threshold for "large" transfers
+pub const DEFAULT_WEBHOOK_MIN_TRANSFER_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct WebhookConfig {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub min_transfer_bytes: u64,
+}
+
+fn sign(secret: &str, timestamp: &str, body: &str) -> Result<String, String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|e| format!("Invalid webhook secret: {}", e))?;
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn deliver(url: &str, secret: &str, event: &str, data: serde_json::Value) -> Result<(), String> {
+    let timestamp = Utc::now().timestamp().to_string();
+    let body = serde_json::json!({ "event": event, "sent_at": Utc::now().to_rfc3339(), "data": data }).to_string();
+    let signature = sign(secret, &timestamp, &body)?;
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let resp = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("X-Firestarter-Event", event)
+        .header("X-Firestarter-Timestamp", &timestamp)
+        .header("X-Firestarter-Signature", format!("sha256={}", signature))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+    let status = resp.status();
+    if status.is_success() { Ok(()) } else { Err(format!("Webhook returned HTTP {}", status)) }
+}
+
+/// Fire-and-forget delivery of `event` to the configured webhook, if any
+pub fn notify_webhook(app_handle: &AppHandle, event: &str, data: serde_json::Value) {
+    let settings = current_settings(app_handle);
+    let (Some(url), Some(secret)) = (settings.webhook_url, settings.webhook_secret) else {
+        return;
+    };
+    let event = event.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = deliver(&url, &secret, &event, data).await {
+            println_redacted!("[WEBHOOK] {}: {}", event, e);
+        }
+    });
+}
+
+/// `transfer.completed` / `transfer.failed` for transfers over the configured size
+pub fn notify_transfer(app_handle: &AppHandle, direction: &str, file_name: &str, bytes: u64, result: &Result<String, String>) {
+    let threshold = current_settings(app_handle)
+        .webhook_min_transfer_bytes
+        .unwrap_or(DEFAULT_WEBHOOK_MIN_TRANSFER_BYTES);
+    if bytes < threshold {
+        return;
+    }
+    let (event, message) = match result {
+        Ok(msg) => ("transfer.completed", msg),
+        Err(e) => ("transfer.failed", e),
+    };
+    notify_webhook(
+        app_handle,
+        event,
+        serde_json::json!({ "direction": direction, "file_name": file_name, "bytes": bytes, "message": message }),
+    );
+}
+
+#[tauri::command]
+pub async fn get_webhook_config(app_handle: AppHandle) -> Result<WebhookConfig, String> {
+    let settings = current_settings(&app_handle);
+    Ok(WebhookConfig {
+        url: settings.webhook_url,
+        secret: settings.webhook_secret,
+        min_transfer_bytes: settings.webhook_min_transfer_bytes.unwrap_or(DEFAULT_WEBHOOK_MIN_TRANSFER_BYTES),
+    })
+}
+
+/// Set or clear (`url: None`) the webhook. A signing secret is generated the first time, or on `rotate_secret`.
+#[tauri::command]
+pub async fn set_webhook(
+    url: Option<String>,
+    min_transfer_bytes: Option<u64>,
+    rotate_secret: Option<bool>,
+    ipc_nonce: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<WebhookConfig, String> {
+    ipc_guard::check_sensitive("set_webhook", &webview, &ipc_nonce, &app_handle)?;
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    if let Some(u) = &url {
+        let parsed = reqwest::Url::parse(u).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        if parsed.scheme() != "https" && parsed.scheme() != "http" {
+            return Err("Webhook URL must be http(s)".to_string());
+        }
+    }
+    update_settings(&app_handle, |s| {
+        if url.is_some() && (s.webhook_secret.is_none() || rotate_secret.unwrap_or(false)) {
+            let mut raw = [0u8; 32];
+            rand::rngs::OsRng.fill_bytes(&mut raw);
+            s.webhook_secret = Some(hex::encode(raw));
+        }
+        s.webhook_url = url;
+        if min_transfer_bytes.is_some() {
+            s.webhook_min_transfer_bytes = min_transfer_bytes;
+        }
+    })?;
+    get_webhook_config(app_handle).await
+}
+
+/// Send a `webhook.test` event and wait for the response
+#[tauri::command]
+pub async fn test_webhook(app_handle: AppHandle) -> Result<String, String> {
+    let settings = current_settings(&app_handle);
+    let url = settings.webhook_url.ok_or("No webhook configured")?;
+    let secret = settings.webhook_secret.ok_or("No webhook secret configured")?;
+    deliver(&url, &secret, "webhook.test", serde_json::json!({ "message": "Firestarter webhook test" })).await?;
+    Ok(format!("Test event delivered to {}", url))
+}
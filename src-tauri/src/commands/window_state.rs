@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow};
+
+use super::app_data_root;
+
+// =============================================================================================================
+// =============================================== WINDOW STATE ================================================
+// =============================================================================================================
+//
+// Main window size, position and maximized state, saved on close and restored at startup. States are keyed by
+// the monitor layout (names, sizes and positions of all monitors), so a laptop used docked and undocked keeps
+// one placement per setup. A saved position that no longer lands on any monitor is ignored and the window is
+// centred instead. Desktop only.
+
+pub(super) const MAIN_WINDOW: &str = "main";
+const WINDOW_STATE_FILE: &str = "window-state.json";
+/// How much of the window's top edge must be on a monitor to count as visible
+const MIN_VISIBLE: (i32, i32) = (100, 40);
+const DEFAULT_SIZE: (f64, f64) = (800.0, 600.0);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+fn state_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app_handle)?.join(WINDOW_STATE_FILE))
+}
+
+fn read_states(app_handle: &AppHandle) -> HashMap<String, WindowState> {
+    state_path(app_handle)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_states(states: &HashMap<String, WindowState>, app_handle: &AppHandle) -> Result<(), String> {
+    let path = state_path(app_handle)?;
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {}", e))?; }
+    let json = serde_json::to_string_pretty(states).map_err(|e| format!("Failed to serialize window state: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write window state: {}", e))
+}
+
+/// One monitor, as far as placement cares
+#[derive(Debug, Clone, PartialEq)]
+struct Screen {
+    name: String,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+fn screens<R: Runtime>(window: &WebviewWindow<R>) -> Vec<Screen> {
+    window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| Screen {
+            name: m.name().cloned().unwrap_or_else(|| "?".to_string()),
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+        })
+        .collect()
+}
+
+/// Stable key for a set of monitors, whatever order they are listed in
+fn layout_key(screens: &[Screen]) -> Option<String> {
+    let mut monitors: Vec<String> =
+        screens.iter().map(|m| format!("{}:{}x{}@{},{}", m.name, m.width, m.height, m.x, m.y)).collect();
+    if monitors.is_empty() {
+        return None;
+    }
+    monitors.sort();
+    Some(blake3::hash(monitors.join("|").as_bytes()).to_hex()[..16].to_string())
+}
+
+fn is_visible(screens: &[Screen], state: &WindowState) -> bool {
+    screens.iter().any(|m| {
+        let right = m.x + m.width as i32;
+        let bottom = m.y + m.height as i32;
+        // top edge (where the title bar is) overlaps the monitor enough to grab
+        state.x + state.width as i32 - MIN_VISIBLE.0 > m.x
+            && state.x + MIN_VISIBLE.0 < right
+            && state.y >= m.y - MIN_VISIBLE.1
+            && state.y + MIN_VISIBLE.1 < bottom
+    })
+}
+
+/// Remember the main window's placement for the current monitor layout. Called when the window is closing.
+pub fn save_window_state(app_handle: &AppHandle) {
+    if !cfg!(desktop) {
+        return;
+    }
+    let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) else { return };
+    let Some(key) = layout_key(&screens(&window)) else { return };
+    let mut states = read_states(app_handle);
+    let maximized = window.is_maximized().unwrap_or(false);
+    let state = if maximized {
+        // keep the last normal geometry so un-maximizing after a restart goes back to it
+        match states.get(&key) {
+            Some(previous) => WindowState { maximized: true, ..*previous },
+            None => return,
+        }
+    } else {
+        let (Ok(pos), Ok(size)) = (window.outer_position(), window.inner_size()) else { return };
+        if window.is_minimized().unwrap_or(false) {
+            return;
+        }
+        WindowState { x: pos.x, y: pos.y, width: size.width, height: size.height, maximized: false }
+    };
+    states.insert(key, state);
+    if let Err(e) = write_states(&states, app_handle) {
+        println!("[WINDOW] {}", e);
+    }
+}
+
+/// Put the main window back where it was for this monitor layout. Called once in setup.
+pub fn restore_window_state(app_handle: &AppHandle) {
+    if !cfg!(desktop) {
+        return;
+    }
+    let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) else { return };
+    let screens = screens(&window);
+    let Some(state) = layout_key(&screens).and_then(|key| read_states(app_handle).remove(&key)) else { return };
+    if !is_visible(&screens, &state) {
+        println!("[WINDOW] Saved position is off-screen, centring");
+        let _ = window.center();
+        return;
+    }
+    let _ = window.set_size(PhysicalSize::new(state.width, state.height));
+    let _ = window.set_position(PhysicalPosition::new(state.x, state.y));
+    if state.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Forget all saved placements and put the window back at its default size, centred
+#[tauri::command]
+pub async fn reset_window_state(app_handle: AppHandle) -> Result<(), String> {
+    write_states(&HashMap::new(), &app_handle)?;
+    if cfg!(desktop) {
+        let window = app_handle.get_webview_window(MAIN_WINDOW).ok_or("Main window not found")?;
+        window.unmaximize().map_err(|e| format!("Failed to reset window: {}", e))?;
+        window
+            .set_size(tauri::LogicalSize::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1))
+            .map_err(|e| format!("Failed to reset window: {}", e))?;
+        window.center().map_err(|e| format!("Failed to reset window: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(name: &str, x: i32, y: i32, width: u32, height: u32) -> Screen {
+        Screen { name: name.to_string(), x, y, width, height }
+    }
+
+    fn at(x: i32, y: i32) -> WindowState {
+        WindowState { x, y, width: 800, height: 600, maximized: false }
+    }
+
+    #[test]
+    fn each_monitor_layout_gets_its_own_key() {
+        let laptop = screen("eDP-1", 0, 0, 1920, 1080);
+        let external = screen("DP-2", 1920, 0, 2560, 1440);
+        let docked = layout_key(&[laptop.clone(), external.clone()]).unwrap();
+        assert_eq!(layout_key(&[external.clone(), laptop.clone()]).unwrap(), docked);
+        assert_ne!(layout_key(std::slice::from_ref(&laptop)).unwrap(), docked);
+        // same monitors, arranged differently
+        assert_ne!(layout_key(&[laptop, screen("DP-2", -2560, 0, 2560, 1440)]).unwrap(), docked);
+        assert_eq!(layout_key(&[]), None);
+    }
+
+    #[test]
+    fn windows_left_off_screen_are_not_restored() {
+        let docked = [screen("eDP-1", 0, 0, 1920, 1080), screen("DP-2", 1920, 0, 2560, 1440)];
+        let undocked = &docked[..1];
+        assert!(is_visible(undocked, &at(100, 100)));
+        assert!(is_visible(&docked, &at(2500, 300)));
+        assert!(!is_visible(undocked, &at(2500, 300)));
+        // only a sliver of the title bar left to grab
+        assert!(!is_visible(undocked, &at(-750, 100)));
+        assert!(!is_visible(undocked, &at(100, 1050)));
+        assert!(!is_visible(undocked, &at(100, -100)));
+        assert!(!is_visible(&[], &at(0, 0)));
+    }
+}
@@ -0,0 +1,132 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use super::{
+    bearer_headers, current_api_config, ensure_valid_token, load_credentials, network, save_credentials,
+    SavedCredentials,
+};
+
+// =============================================================================================================
+// ================================================ WORKSPACES =================================================
+// =============================================================================================================
+//
+// On backends with organizations, an account can act in a team workspace instead of its personal space. The
+// chosen one is saved with the account's credentials (`workspace_id`) and sent as `X-Workspace-Id` on every
+// authenticated request: bearer and link headers, the API proxies, uploads and downloads. Links remember the
+// workspace they were made in and are listed per workspace. No workspace id means the personal space.
+
+pub const WORKSPACE_HEADER: &str = "X-Workspace-Id";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    /// The account's role in it, as the server names it
+    pub role: Option<String>,
+    pub active: bool,
+}
+
+/// Add the workspace header when the account has switched to a workspace
+pub fn attach(headers: &mut HeaderMap, credentials: &SavedCredentials) -> Result<(), String> {
+    if let Some(id) = &credentials.workspace_id {
+        headers.insert(WORKSPACE_HEADER, HeaderValue::from_str(id).map_err(|e| format!("Invalid workspace id: {}", e))?);
+    }
+    Ok(())
+}
+
+/// Same for requests built with the app key headers
+pub fn scope(request: reqwest::RequestBuilder, credentials: &SavedCredentials) -> reqwest::RequestBuilder {
+    match &credentials.workspace_id {
+        Some(id) => request.header(WORKSPACE_HEADER, id),
+        None => request,
+    }
+}
+
+/// Workspaces in a list response: a bare array or `{ "workspaces": [...] }`
+fn parse_workspaces(json: &serde_json::Value, active: Option<&str>) -> Vec<Workspace> {
+    let items = json.as_array().or_else(|| json.get("workspaces").and_then(|w| w.as_array()));
+    let text = |item: &serde_json::Value, keys: &[&str]| keys.iter().find_map(|k| item.get(*k).and_then(|v| v.as_str()).map(str::to_string));
+    items
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let id = text(item, &["id", "workspace_id"])?;
+            Some(Workspace {
+                name: text(item, &["name", "display_name"]).unwrap_or_else(|| id.clone()),
+                role: text(item, &["role"]),
+                active: active == Some(id.as_str()),
+                id,
+            })
+        })
+        .collect()
+}
+
+async fn fetch_workspaces(credentials: &mut SavedCredentials, app_handle: &AppHandle) -> Result<Vec<Workspace>, String> {
+    let api_config = current_api_config(app_handle);
+    let url = api_config.optional_url(&api_config.workspaces, "Workspaces")?;
+    let client = network::client(app_handle);
+    ensure_valid_token(&client, &api_config, credentials, app_handle).await?;
+
+    let resp = client.get(&url).headers(bearer_headers(credentials)?).send().await.map_err(|e| format!("HTTP error: {}", e))?;
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() { return Err(format!("HTTP {}: {}", status, text)); }
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    Ok(parse_workspaces(&json, credentials.workspace_id.as_deref()))
+}
+
+/// Workspaces the signed-in account belongs to
+#[tauri::command]
+pub async fn list_workspaces(app_handle: AppHandle) -> Result<Vec<Workspace>, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    fetch_workspaces(&mut credentials, &app_handle).await
+}
+
+/// Act in `workspace_id` from now on; `None` goes back to the personal space
+#[tauri::command]
+pub async fn switch_workspace(workspace_id: Option<String>, app_handle: AppHandle) -> Result<Option<Workspace>, String> {
+    let mut credentials = load_credentials(app_handle.clone()).await?.ok_or("No saved credentials found")?;
+    let workspace = match workspace_id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => {
+            let workspace = fetch_workspaces(&mut credentials, &app_handle)
+                .await?
+                .into_iter()
+                .find(|w| w.id == id)
+                .ok_or_else(|| format!("Not a member of workspace {}", id))?;
+            Some(Workspace { active: true, ..workspace })
+        }
+        None => None,
+    };
+
+    credentials.workspace_id = workspace.as_ref().map(|w| w.id.clone());
+    save_credentials(credentials.clone(), app_handle.clone()).await?;
+    match &workspace {
+        Some(w) => println!("👥 Switched {} to workspace {} ({})", credentials.user_id, w.name, w.id),
+        None => println!("👤 Switched {} to the personal workspace", credentials.user_id),
+    }
+    let _ = app_handle.emit(
+        "workspace_changed",
+        serde_json::json!({ "user_id": credentials.user_id, "workspace_id": credentials.workspace_id }),
+    );
+    Ok(workspace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_lists_are_read_from_either_shape() {
+        let bare = serde_json::json!([{ "id": "w1", "name": "Design", "role": "admin" }, { "name": "no id" }]);
+        assert_eq!(
+            parse_workspaces(&bare, Some("w1")),
+            vec![Workspace { id: "w1".into(), name: "Design".into(), role: Some("admin".into()), active: true }]
+        );
+        let wrapped = serde_json::json!({ "workspaces": [{ "workspace_id": "w2" }] });
+        assert_eq!(
+            parse_workspaces(&wrapped, None),
+            vec![Workspace { id: "w2".into(), name: "w2".into(), role: None, active: false }]
+        );
+    }
+}
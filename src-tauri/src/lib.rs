@@ -3,20 +3,123 @@ use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let stdin_upload = match commands::cli::stdin_upload(&std::env::args_os().collect::<Vec<_>>()) {
+        Some(Ok(upload)) => Some(upload),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+        None => None,
+    };
+    let builder = tauri::Builder::default();
+    // must be the first plugin: a second launch hands its arguments over here and exits. A stdin upload runs on
+    // its own next to the open app
+    #[cfg(desktop)]
+    let builder = if stdin_upload.is_some() {
+        builder
+    } else {
+        builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            commands::shell_integration::handle_launch_args(app, args.into_iter().map(Into::into).collect(), std::path::Path::new(&cwd));
+        }))
+    };
+    builder
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_opener::init())
+        .register_asynchronous_uri_scheme_protocol(commands::assets::ASSET_PROTOCOL, |ctx, request, responder| {
+            let app_handle = ctx.app_handle().clone();
+            tauri::async_runtime::spawn(async move {
+                responder.respond(commands::assets::serve_asset(app_handle, request).await);
+            });
+        })
         .invoke_handler(tauri::generate_handler![
             commands::get_api_config,
+            commands::is_demo_mode,
             commands::test_api_connection,
             commands::get_config_path,
             commands::proxy_api_get,
             commands::proxy_api_post,
+            commands::extensions::list_named_endpoints,
+            commands::extensions::call_named_endpoint,
             commands::get_token_usage,
             commands::register_user,
             commands::login_user,
+            commands::submit_2fa_code,
+            commands::enable_2fa,
+            commands::disable_2fa,
             commands::upload_file,
             commands::download_file,
+            commands::get_file_preview,
+            commands::conflicts::check_remote_exists,
+            commands::tiers::preview_tier_change,
+            commands::tiers::change_file_tier,
+            commands::groups::get_group_status,
+            commands::verification::set_upload_verification,
+            commands::hashing::get_hashing_stats,
+            commands::hashing::benchmark_hashing,
+            commands::tuning::set_upload_buffer_size,
+            commands::tuning::set_progress_throttle,
+            commands::tuning::set_download_write_options,
+            commands::tuning::set_download_segments,
+            commands::download_cache::get_cached_path,
+            commands::download_cache::set_download_cache,
+            commands::opener::open_downloaded_file,
+            commands::opener::reveal_in_file_manager,
+            commands::destinations::get_recent_destinations,
+            commands::destinations::set_upload_name_template,
+            commands::local_trash::set_trash_after_upload,
+            commands::local_trash::restore_from_trash,
+            commands::lifecycle::create_lifecycle_rule,
+            commands::lifecycle::list_lifecycle_rules,
+            commands::lifecycle::delete_lifecycle_rule,
+            commands::lifecycle::evaluate_lifecycle_rules,
+            commands::lifecycle::get_last_lifecycle_report,
+            commands::lifecycle::resume_lifecycle_rule,
+            commands::e2e_links::create_encrypted_public_link,
+            commands::e2e_links::download_public_encrypted,
+            commands::link_batch::create_public_links,
+            commands::link_batch::delete_public_links,
+            commands::link_previews::update_public_link,
+            commands::link_limits::get_link_stats,
+            commands::account::get_account_profile,
+            commands::account::update_account_profile,
+            commands::account::change_username,
+            commands::account::request_email_verification,
+            commands::account::confirm_email,
+            commands::scoped_keys::create_scoped_key,
+            commands::scoped_keys::list_scoped_keys,
+            commands::scoped_keys::revoke_scoped_key,
+            commands::audit::get_audit_log,
+            commands::audit::export_audit_log,
+            commands::confirmations::request_withdrawal,
+            commands::confirmations::request_account_deletion,
+            commands::confirmations::request_remote_delete,
+            commands::confirmations::request_key_rotation,
+            commands::confirmations::confirm_action,
+            commands::confirmations::cancel_action,
+            commands::window_state::reset_window_state,
+            commands::shortcuts::get_shortcuts,
+            commands::shortcuts::set_shortcuts,
+            commands::shell_integration::install_shell_integration,
+            commands::shell_integration::remove_shell_integration,
+            commands::crash_reports::list_crash_reports,
+            commands::crash_reports::submit_crash_report,
+            commands::self_test::run_self_test,
+            commands::clock_skew::get_clock_skew,
+            commands::token_refresh::get_token_status,
+            commands::auth_upgrade::upgrade_to_password_auth,
+            commands::encoding::validate_remote_name,
+            commands::local_walk::set_symlink_policy,
+            commands::sparse::inspect_sparse_file,
+            commands::stability::set_upload_stability,
+            commands::locked_files::set_shadow_copy_locked_files,
+            commands::transfer_details::set_debug_history_responses,
+            commands::api_trace::get_api_trace,
+            commands::api_trace::clear_api_trace,
+            commands::output_paths::pick_download_path,
+            commands::output_paths::set_allow_any_download_path,
             commands::user_login,
             commands::set_user_password,
             commands::save_credentials,
@@ -24,19 +127,154 @@ pub fn run() {
             commands::clear_credentials,
             commands::list_saved_users,
             commands::refresh_token,
+            commands::list_active_sessions,
+            commands::revoke_session,
             commands::get_upload_history,
+            commands::folders::get_upload_history_by_folder,
+            commands::folders::set_default_remote_prefix,
             commands::create_public_link,
             commands::delete_public_link,
             commands::list_public_links,
             commands::get_tier_pricing,
-            commands::get_file_size
+            commands::get_file_size,
+            commands::assets::clear_asset_cache,
+            commands::metrics::get_transfer_metrics,
+            commands::exporter::set_metrics_endpoint,
+            commands::exporter::get_metrics_endpoint_url,
+            commands::settings::get_app_settings,
+            commands::health::local_data_health,
+            commands::compaction::compact_local_data,
+            commands::compaction::get_history_archives,
+            commands::compaction::get_archived_history,
+            commands::storage::get_local_storage_usage,
+            commands::storage::clear_local_cache,
+            commands::hooks::set_transfer_hooks,
+            commands::hooks::get_hook_runs,
+            commands::webhooks::get_webhook_config,
+            commands::webhooks::set_webhook,
+            commands::webhooks::test_webhook,
+            commands::polling::start_dashboard_polling,
+            commands::polling::stop_dashboard_polling,
+            commands::polling::set_dashboard_polling_paused,
+            commands::polling::refresh_dashboard_endpoint,
+            commands::polling::get_dashboard_snapshots,
+            commands::streaming::get_stream_url,
+            commands::transfers::enqueue_upload,
+            commands::transfers::get_transfer_queue,
+            commands::transfers::clear_finished_transfers,
+            commands::transfers::run_background_transfers,
+            commands::transfers::receive_shared_file,
+            commands::vault::create_vault_key,
+            commands::vault::list_vault_keys,
+            commands::vault::record_key_usage,
+            commands::vault::list_key_usage,
+            commands::vault::verify_vault_passphrase,
+            commands::vault::export_vault_key,
+            commands::vault::import_vault_key,
+            commands::vault::delete_vault_key,
+            commands::workspaces::list_workspaces,
+            commands::workspaces::switch_workspace,
+            commands::bandwidth::set_bandwidth_schedule,
+            commands::network::set_network_binding,
+            commands::network::set_dns_override,
+            commands::network::flush_dns_overrides,
+            commands::network::set_client_tuning,
+            commands::benchmark::benchmark_transfer,
+            commands::gateways::list_gateways,
+            commands::gateways::pin_gateway,
+            commands::data_dir::get_data_dir_status,
+            commands::data_dir::set_data_dir_override,
+            commands::backup::export_app_state,
+            commands::backup::import_app_state,
+            commands::retention::set_retention_policy,
+            commands::retention::run_maintenance_now,
+            commands::retention::get_last_maintenance_report,
+            commands::search::search,
+            commands::tags::tag_upload,
+            commands::tags::set_upload_note,
+            commands::tags::list_by_tag,
+            commands::starred::star_file,
+            commands::starred::unstar_file,
+            commands::starred::list_starred,
+            commands::receipts::get_upload_receipt,
+            commands::receipts::verify_upload_receipt,
+            commands::link_sheet::export_link_sheet,
+            commands::link_email::share_link_via_email,
+            commands::contacts::add_contact,
+            commands::contacts::list_contacts,
+            commands::contacts::remove_contact,
+            commands::link_batch::set_revoke_links_on_delete,
+            commands::remote_trash::list_remote_trash,
+            commands::remote_trash::restore_remote_file,
+            commands::confirmations::purge_remote_trash,
+            commands::receipts::get_receipt_public_key,
+            commands::dry_run::preview_upload
         ])
-        .setup(|app| {
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                commands::window_state::save_window_state(window.app_handle());
+            }
+        })
+        .setup(move |app| {
+            app.manage(commands::data_dir::init_data_dir(app.handle()));
+            commands::crash_reports::install_panic_hook(app.handle());
+            app.manage(commands::health::check_local_data(app.handle()));
+            app.manage(commands::history_log::start_history_writer(app.handle()));
 
-            let saved_config = commands::ApiConfig::default();
+            #[cfg(feature = "demo")]
+            let saved_config = commands::demo::start_demo_backend(app.handle())?;
+            #[cfg(not(feature = "demo"))]
+            let saved_config = commands::config_reload::load_api_config(app.handle());
             app.manage(commands::new_api_config_state(saved_config));
+            app.manage(commands::clock_skew::new_clock_skew_state());
+            app.manage(commands::api_trace::new_api_trace_state());
+            app.manage(commands::output_paths::new_picked_paths_state());
+            app.manage(commands::token_refresh::new_token_refresh_lock());
+            app.manage(commands::transfers::new_transfer_queue_state());
+            app.manage(commands::taskbar::new_taskbar_progress_state());
+            app.manage(commands::groups::new_transfer_groups_state());
+            app.manage(commands::new_in_flight_uploads_state());
+            app.manage(commands::new_link_file_lock());
+            app.manage(commands::receipts::new_receipt_key_lock());
+            app.manage(commands::tiers::new_tier_pricing_cache());
+            app.manage(commands::streaming::new_stream_server_state());
+            app.manage(commands::polling::new_dashboard_poller_state());
+            app.manage(commands::metrics::new_transfer_metrics_state());
+            app.manage(commands::hashing::new_hashing_stats_state());
+            app.manage(commands::settings::load_app_settings(app.handle()));
+            app.manage(commands::exporter::new_metrics_exporter_state());
+            app.manage(commands::lifecycle::new_lifecycle_state());
+            app.manage(commands::account::new_account_profile_state());
+            app.manage(commands::confirmations::new_confirmations_state());
+            app.manage(commands::ipc_guard::new_ipc_nonce_state());
+            app.manage(commands::bandwidth::new_bandwidth_state());
+            app.manage(commands::network::new_http_client_state());
+            app.manage(commands::gateways::new_gateway_state());
+            app.manage(commands::retention::new_maintenance_state());
+            if let Some(upload) = stdin_upload {
+                commands::cli::run_stdin_upload(app.handle(), upload);
+                return Ok(());
+            }
+            commands::ipc_guard::create_main_window(app)?;
+            commands::data_dir::announce_fallback(app.handle());
+            commands::transfers::restore_transfer_queue(app.handle());
+            commands::exporter::restore_metrics_endpoint(app.handle());
+            commands::lifecycle::start_lifecycle_scheduler(app.handle());
+            commands::retention::start_maintenance_scheduler(app.handle());
+            commands::token_refresh::start_token_refresh_scheduler(app.handle());
+            #[cfg(not(feature = "demo"))]
+            commands::config_reload::start_config_watcher(app.handle());
+            commands::window_state::restore_window_state(app.handle());
+            commands::shortcuts::init_global_shortcuts(app.handle());
+            let cwd = std::env::current_dir().unwrap_or_default();
+            commands::shell_integration::handle_launch_args(app.handle(), std::env::args_os().collect(), &cwd);
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running application");
+        .build(tauri::generate_context!())
+        .expect("error while building application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(commands::history_log::flush(app_handle));
+            }
+        });
 }
@@ -9,6 +9,7 @@ import { Sidebar } from './components/Sidebar';
 import Links from './components/Links';
 import { DownloadSelectionProvider } from './contexts/DownloadSelectionContext';
 import GlobalUploadProgressBar from './components/GlobalUploadProgressBar';
+import StorageWarning from './components/StorageWarning';
 
 const HEADER_H = 80;      // right header height
 const SIDEBAR_W = 200;    // sidebar width (match Sidebar)
@@ -154,6 +155,7 @@ function App() {
       <WalletProvider>
         <UploadProvider>
           <GlobalUploadProgressBar />
+          <StorageWarning />
           <DownloadSelectionProvider>
             <MainContent />
           </DownloadSelectionProvider>
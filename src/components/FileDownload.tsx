@@ -1,7 +1,6 @@
 import { useEffect, useMemo, useState, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { save } from '@tauri-apps/plugin-dialog';
 import { useAuth } from '../contexts/AuthContext';
 import { useDownloadSelection } from '../contexts/DownloadSelectionContext';
 
@@ -17,7 +16,30 @@ function FileDownload() {
   const [downloadPercent, setDownloadPercent] = useState<number | null>(null);
   const [downloaded, setDownloaded] = useState<number>(0);
   const [total, setTotal] = useState<number>(0);
+  const [completed, setCompleted] = useState<{ outputPath: string; canReveal: boolean } | null>(null);
   const progressEventUnlisten = useRef<(() => void) | null>(null);
+
+  // Offer Open / Show in folder for the last finished download
+  useEffect(() => {
+    const unlistenPromise = listen('download_completed', (event) => {
+      const { output_path, can_reveal } = event.payload as any;
+      setCompleted({ outputPath: output_path, canReveal: !!can_reveal });
+    });
+    return () => {
+      unlistenPromise.then((fn) => fn());
+    };
+  }, []);
+
+  const openCompleted = async (command: 'open_downloaded_file' | 'reveal_in_file_manager') => {
+    if (!completed) return;
+    try {
+      await invoke(command, { path: completed.outputPath });
+    } catch (err: any) {
+      setMessage(err?.message || String(err));
+      setIsError(true);
+    }
+  };
+
   // Listen for download progress events
   useEffect(() => {
     if (!isDownloading) return;
@@ -58,7 +80,7 @@ function FileDownload() {
     }
 
     try {
-      const selected = await save({ defaultPath: fileName.trim() });
+      const selected = await invoke<string | null>('pick_download_path', { defaultName: fileName.trim() });
       if (selected) {
         setDownloadPath(String(selected));
         setMessage('');
@@ -84,6 +106,7 @@ function FileDownload() {
     }
 
     setIsDownloading(true);
+    setCompleted(null);
     setMessage('');
     setIsError(false);
     setDownloadPercent(0);
@@ -206,6 +229,19 @@ function FileDownload() {
         </div>
       )}
 
+      {completed && !isDownloading && (
+        <div style={{ display: 'flex', gap: '1rem', alignItems: 'center', marginBottom: '1rem' }}>
+          <button className="button" onClick={() => openCompleted('open_downloaded_file')} style={{ minWidth: 120 }}>
+            Open
+          </button>
+          {completed.canReveal && (
+            <button className="button" onClick={() => openCompleted('reveal_in_file_manager')} style={{ minWidth: 120 }}>
+              Show in Folder
+            </button>
+          )}
+        </div>
+      )}
+
       <button
         className="button"
         onClick={handleDownload}
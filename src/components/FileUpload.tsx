@@ -107,7 +107,7 @@ export default function FileUpload({ onUploadSuccess }: FileUploadProps) {
     return rIdx === -1 ? tasks[tasks.length - 1] : tasks[tasks.length - 1 - rIdx];
   }, [tasks]);
 
-  const isUploading = currentTask?.status === 'uploading';
+  const isUploading = currentTask?.status === 'uploading' || currentTask?.status === 'processing';
 
   // Progress 
   const pct = useMemo(() => {
@@ -441,7 +441,7 @@ export default function FileUpload({ onUploadSuccess }: FileUploadProps) {
         onClick={handleUpload}
         disabled={isUploading || !filePath || !!walletValidationError || walletLoading}
       >
-        {isUploading ? 'Uploading...' : 'Upload'}
+        {currentTask?.status === 'processing' ? 'Processing...' : isUploading ? 'Uploading...' : 'Upload'}
       </button>
 
       {currentTask && (
@@ -472,6 +472,10 @@ export default function FileUpload({ onUploadSuccess }: FileUploadProps) {
                 </span>
               )}
 
+            {currentTask.status === 'processing' && (
+              <span style={{ marginTop: 2, color: '#9aa0a6', fontSize: 13 }}>⏳ Waiting for the server to finish processing…</span>
+            )}
+
             {currentTask.status === 'success' && (
               <span style={{ color: '#4caf50' }}>✅ Upload successful!</span>
             )}
@@ -36,7 +36,7 @@ export default function GlobalUploadProgressBar() {
   const { tasks } = useUpload();
 
   const data = useMemo(() => {
-    const uploading = tasks.filter(t => t.status === 'uploading');
+    const uploading = tasks.filter(t => t.status === 'uploading' || t.status === 'processing');
     if (uploading.length === 0) {
       return {
         active: 0,
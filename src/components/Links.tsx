@@ -1,5 +1,6 @@
 import React, { useCallback, useEffect, useMemo, useState } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
 import { useAuth } from '../contexts/AuthContext';
 import { API_BASE } from '../shared/api/endpoints';
 import { List } from './List';
@@ -11,8 +12,17 @@ interface PublicLinkEntry {
   created_at: string; // ISO
   custom_title?: string;
   custom_description?: string;
+  encrypted?: boolean; // key only in the shared URL fragment
+  custom_slug?: string;
+  preview_image?: string; // remote name, served through the asset protocol
+  max_downloads?: number; // 1 for single-use links
+  remaining_uses?: number; // last known, from the server
+  exhausted?: boolean;
 }
 
+// Mirrors the Rust-side check in create_public_link
+const SLUG_PATTERN = /^[a-z0-9](?:[a-z0-9_-]{1,46}[a-z0-9])$/;
+
 // ===== Component =====
 const Links: React.FC = () => {
   const { credentials } = useAuth();
@@ -31,6 +41,9 @@ const Links: React.FC = () => {
   const [newFileName, setNewFileName] = useState('');
   const [newTitle, setNewTitle] = useState('');
   const [newDescription, setNewDescription] = useState('');
+  const [newSlug, setNewSlug] = useState('');
+  const [newMaxDownloads, setNewMaxDownloads] = useState('');
+  const [newSingleUse, setNewSingleUse] = useState(false);
 
 
   const [copiedMsg, setCopiedMsg] = useState<string | null>(null);
@@ -46,6 +59,7 @@ const Links: React.FC = () => {
       (l.remote_path || '').toLowerCase().includes(q) ||
       (l.custom_title || '').toLowerCase().includes(q) ||
       (l.custom_description || '').toLowerCase().includes(q) ||
+      (l.custom_slug || '').toLowerCase().includes(q) ||
       (l.link_hash || '').toLowerCase().includes(q)
     );
   }, [links, search]);
@@ -95,6 +109,11 @@ const Links: React.FC = () => {
     e.preventDefault();
     const rp = newFileName.trim();
     if (!rp || !userId) return;
+    const slug = newSlug.trim().toLowerCase();
+    if (slug && !SLUG_PATTERN.test(slug)) {
+      setError('Slug must be 3-48 characters: letters, digits, "-" or "_", not starting or ending with a separator');
+      return;
+    }
 
     setCreating(true);
     setError('');
@@ -105,11 +124,17 @@ const Links: React.FC = () => {
         remotePath: rp,
         customTitle: newTitle.trim(),
         customDescription: newDescription.trim(),
+        customSlug: slug || null,
+        maxDownloads: !newSingleUse && newMaxDownloads ? Number(newMaxDownloads) : null,
+        singleUse: newSingleUse,
       });
 
       setNewFileName('');
       setNewTitle('');
       setNewDescription('');
+      setNewSlug('');
+      setNewMaxDownloads('');
+      setNewSingleUse(false);
       await fetchLinks();
     } catch (e: any) {
       const msg = typeof e === 'string' ? e : e?.message || JSON.stringify(e);
@@ -117,7 +142,7 @@ const Links: React.FC = () => {
     } finally {
       setCreating(false);
     }
-  }, [userId, newFileName, newTitle, newDescription, fetchLinks]);
+  }, [userId, newFileName, newTitle, newDescription, newSlug, newMaxDownloads, newSingleUse, fetchLinks]);
 
   const handleDelete = useCallback(async (linkHash: string) => {
     if (!userId) return;
@@ -133,6 +158,27 @@ const Links: React.FC = () => {
     }
   }, [userId, fetchLinks]);
 
+  const handleSetPreviewImage = useCallback(async (linkHash: string) => {
+    if (!userId) return;
+    const selected = await open({
+      multiple: false,
+      directory: false,
+      filters: [{ name: 'Images', extensions: ['png', 'jpg', 'jpeg', 'webp', 'gif'] }],
+    });
+    if (typeof selected !== 'string') return;
+    setLoading(true);
+    setError('');
+    try {
+      await invoke('update_public_link', { userId, linkHash, previewImagePath: selected });
+      await fetchLinks();
+    } catch (e: any) {
+      const msg = typeof e === 'string' ? e : e?.message || JSON.stringify(e);
+      setError(msg || 'Failed to set preview image');
+    } finally {
+      setLoading(false);
+    }
+  }, [userId, fetchLinks]);
+
   const handleCopy = useCallback(async (text: string, label: string) => {
     try {
       await navigator.clipboard.writeText(text);
@@ -176,6 +222,25 @@ const Links: React.FC = () => {
                 <label>Description (optional)</label>
                 <input value={newDescription} onChange={(e) => setNewDescription(e.target.value)} />
               </div>
+              <div className="form-group">
+                <label>Custom Slug (optional)</label>
+                <input value={newSlug} onChange={(e) => setNewSlug(e.target.value)} placeholder="my-file" />
+              </div>
+              <div className="form-group" style={{ display: 'flex', gap: 16, alignItems: 'center' }}>
+                <label style={{ margin: 0 }}>Max Downloads</label>
+                <input
+                  type="number"
+                  min={1}
+                  value={newMaxDownloads}
+                  onChange={(e) => setNewMaxDownloads(e.target.value)}
+                  disabled={newSingleUse}
+                  placeholder="unlimited"
+                  style={{ width: 110 }}
+                />
+                <label style={{ margin: 0 }}>
+                  <input type="checkbox" checked={newSingleUse} onChange={(e) => setNewSingleUse(e.target.checked)} /> Single use
+                </label>
+              </div>
               <button className="button" type="submit" disabled={creating}>
                 {creating ? 'Creating...' : 'Create Link'}
               </button>
@@ -255,7 +320,7 @@ const Links: React.FC = () => {
                       const directUrl = `${API_BASE}/publicDownload?hash=${encodeURIComponent(link.link_hash)}`;
 
                       return (
-                        <tr key={link.link_hash} style={{ borderBottom: '1px solid #23272f' }}>
+                        <tr key={link.link_hash} style={{ borderBottom: '1px solid #23272f', opacity: link.exhausted ? 0.5 : 1 }}>
                           {/* Created */}
                           <td style={{ padding: 10, whiteSpace: 'nowrap' }}>
                             {(() => {
@@ -281,11 +346,35 @@ const Links: React.FC = () => {
                             ) : (
                               link.remote_path
                             )}
+                            {link.encrypted && <span title="End-to-end encrypted" style={{ marginLeft: 6 }}>🔒</span>}
+                            {link.max_downloads != null && (
+                              <div style={{ color: link.exhausted ? '#c62828' : '#888', fontSize: 12 }}>
+                                {link.exhausted
+                                  ? 'Exhausted'
+                                  : `${link.remaining_uses ?? link.max_downloads} of ${link.max_downloads} downloads left`}
+                              </div>
+                            )}
                           </td>
 
                           {/* Title */}
                           <td style={{ padding: 10, maxWidth: 120, wordBreak: 'break-all' }}>
+                            {link.preview_image && (
+                              <img
+                                src={convertFileSrc(link.preview_image, 'firestarter-asset')}
+                                alt=""
+                                style={{ display: 'block', width: 96, maxHeight: 54, objectFit: 'cover', borderRadius: 4, marginBottom: 4 }}
+                              />
+                            )}
                             {link.custom_title || '-'}
+                            {link.custom_slug && (
+                              <div
+                                title="Copy slug"
+                                style={{ color: '#888', fontSize: 12, cursor: 'pointer' }}
+                                onClick={() => handleCopy(link.custom_slug!, 'Slug')}
+                              >
+                                /{link.custom_slug}
+                              </div>
+                            )}
                           </td>
 
                           {/* Hash */}
@@ -327,6 +416,15 @@ const Links: React.FC = () => {
                             >
                               Copy Direct
                             </button>
+                            <button
+                              className="button"
+                              style={{ background: '#6d4c41', minWidth: 32, padding: '6px 8px', fontSize: 13 }}
+                              title="Set Preview Image"
+                              type="button"
+                              onClick={() => handleSetPreviewImage(link.link_hash)}
+                            >
+                              Set Image
+                            </button>
                             <button
                               className="button"
                               style={{ background: '#c62828', minWidth: 60, padding: '6px 12px' }}
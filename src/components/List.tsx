@@ -12,6 +12,13 @@ interface UploadLogEntry {
   blake3_hash: string;
   file_size: number;
   timestamp: string; // ISO
+  hook_output?: string;
+  upload_id?: string;
+  tier?: string;
+  route?: string;
+  cost?: number;
+  verified?: boolean;
+  trashed?: boolean;
 }
 
 interface ListProps {
@@ -38,6 +45,8 @@ const statusColor = (status: string): string => {
       return '#facc15';
     case 'uploading':
       return '#38bdf8';
+    case 'hash_mismatch':
+      return '#fb923c';
     default:
       return '#fff';
   }
@@ -0,0 +1,54 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+type DataDirStatus = {
+  path: string;
+  source: string;
+  error?: string | null;
+  unavailable_path?: string | null;
+};
+
+// Shown when the data dir couldn't be written and this session runs on a temporary one.
+// The status is read on load too: `storage_unavailable` may fire before this listener exists.
+export default function StorageWarning() {
+  const [status, setStatus] = useState<DataDirStatus | null>(null);
+
+  useEffect(() => {
+    invoke<DataDirStatus>('get_data_dir_status')
+      .then((s) => { if (s.error) setStatus(s); })
+      .catch((e) => console.warn('[Storage] status unavailable:', e));
+    const unlisten = listen<DataDirStatus>('storage_unavailable', (event) => setStatus(event.payload));
+    return () => { unlisten.then((u) => u()); };
+  }, []);
+
+  if (!status) return null;
+
+  return (
+    <div
+      role="alert"
+      style={{
+        position: 'fixed',
+        left: 0,
+        right: 0,
+        bottom: 0,
+        zIndex: 300,
+        background: '#3a2a12',
+        color: '#ffd58a',
+        borderTop: '1px solid #6b4e1f',
+        padding: '8px 16px',
+        fontSize: 13,
+        display: 'flex',
+        alignItems: 'center',
+        justifyContent: 'space-between',
+        gap: 12,
+      }}
+    >
+      <span>
+        Can't write to {status.unavailable_path ?? 'the app data folder'} ({status.error}). This session uses a
+        temporary folder that is not kept; set another data location to keep your data.
+      </span>
+      <button onClick={() => setStatus(null)} style={{ flexShrink: 0 }}>Dismiss</button>
+    </div>
+  );
+}
@@ -3,6 +3,7 @@ import { useAuth } from '../contexts/AuthContext';
 import { useWallet } from '../contexts/WalletContext';
 import { invoke } from '@tauri-apps/api/core';
 import { ep } from '../shared/api/endpoints';
+import { ipcNonce } from '../shared/ipc';
 
 type TierDetail = {
   tier_name: string;
@@ -119,21 +120,20 @@ function Wallet() {
       if (!credentials?.auth_tokens?.access_token) throw new Error('No access token');
       const amountNum = parseFloat(withdrawSolAmount);
       if (!withdrawSolTo || !Number.isFinite(amountNum) || amountNum <= 0) throw new Error('Invalid address or amount');
-      const url = ep('withdraw_sol');
-      const headers: Record<string, string> = {
-        'Content-Type': 'application/json',
-        Accept: '*/*',
-        Authorization: `Bearer ${credentials.auth_tokens.access_token}`,
-      };
-      const data = await invoke('proxy_api_post', {
-        url,
-        headers,
-        body: { to_pubkey: withdrawSolTo, amount_sol: amountNum },
+      // two-phase: the backend registers the withdrawal and only sends it once confirmed
+      const pending = await invoke<{ token: string; summary: string }>('request_withdrawal', {
+        toAddress: withdrawSolTo,
+        amount: amountNum,
       });
+      if (!window.confirm(`${pending.summary}?`)) {
+        await invoke('cancel_action', { token: pending.token });
+        return;
+      }
+      const data = await invoke('confirm_action', { token: pending.token, ipcNonce: ipcNonce() });
       setWithdrawSolResult(data);
       await refreshWallet();
     } catch (e: any) {
-      setWithdrawSolResult({ error: e?.message || 'Withdraw failed' });
+      setWithdrawSolResult({ error: (typeof e === 'string' ? e : e?.message) || 'Withdraw failed' });
     } finally {
       setIsWithdrawingSol(false);
     }
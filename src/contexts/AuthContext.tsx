@@ -1,5 +1,7 @@
 import React, { createContext, useCallback, useContext, useEffect, useMemo, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ipcNonce } from '../shared/ipc';
 
 interface AuthTokens {
   access_token: string;
@@ -104,7 +106,7 @@ export const AuthProvider: React.FC<AuthProviderProps> = ({ children }) => {
 
   const deleteAccountData = useCallback(async (userId: string) => {
     try {
-      await invoke('clear_credentials', { user_id: userId });
+      await invoke('clear_credentials', { user_id: userId, ipcNonce: ipcNonce() });
       if (credentials?.user_id === userId) setCredentials(null);
       console.log('✅ Account data deleted for:', userId);
     } catch (err) {
@@ -140,22 +142,21 @@ export const AuthProvider: React.FC<AuthProviderProps> = ({ children }) => {
   }, []);
 
   useEffect(() => {
-    // check every 30s refresh if < 2 minutes remaining
-    const interval = setInterval(async () => {
-      try {
-        if (!credentials?.auth_tokens?.access_token) return;
-        const msLeft = msUntilExpiry(credentials);
-        if (msLeft <= 2 * 60 * 1000) {
-          await invoke('refresh_token');
-          await loadCredentials();
-          console.log('[Auth] token refreshed via background loop');
-        }
-      } catch (err) {
-        console.warn('[Auth] background refresh failed:', err);
-      }
-    }, 30_000);
-    return () => clearInterval(interval);
-  }, [credentials?.auth_tokens?.access_token, msUntilExpiry, loadCredentials]);
+    // the Rust scheduler refreshes ahead of expiry; pick up what it saved
+    const unlisteners = [
+      listen('token_refreshed', () => {
+        loadCredentials();
+        console.log('[Auth] token refreshed in background');
+      }),
+      listen<{ error: string }>('token_refresh_failed', (event) => {
+        console.warn('[Auth] background refresh failed:', event.payload.error);
+        loadCredentials();
+      }),
+    ];
+    return () => {
+      unlisteners.forEach((p) => p.then((unlisten) => unlisten()));
+    };
+  }, [loadCredentials]);
 
   // --- get a valid (fresh) access token ----------------------------
   const getValidAccessToken = useCallback(async (): Promise<string | null> => {
@@ -14,7 +14,7 @@ interface UploadTask {
   id: string;
   filePath: string;
   remoteFileName: string;
-  status: 'idle' | 'uploading' | 'success' | 'error' | 'cancelled';
+  status: 'idle' | 'uploading' | 'processing' | 'success' | 'error' | 'cancelled';
   progress: number;      // 0-100
   uploadedSize: number;  // cumulative bytes
   totalSize: number;     // total file bytes
@@ -52,6 +52,8 @@ type UploadProgressPayload =
       uploaded?: number;      // bytes
       total?: number;         // total file size
       completed?: boolean;    // true on final event
+      phase?: 'uploading' | 'processing' | 'done';
+      server_status?: string; // backend processing status while phase is 'processing'
       status?: string;
       message?: string;
       error?: string;
@@ -135,7 +137,7 @@ export const UploadProvider: React.FC<{ children: React.ReactNode }> = ({ childr
 
           if (!payload || typeof payload !== 'object') return prev;
 
-          const { id, percent, uploaded, total, completed, status, message, error } = payload;
+          const { id, percent, uploaded, total, completed, phase, status, message, error } = payload;
 
           // target task
           const pos = id
@@ -223,8 +225,9 @@ export const UploadProvider: React.FC<{ children: React.ReactNode }> = ({ childr
           }
 
           const viewPercent = normalizePercent(percent, cumUploaded, totalBytes);
-          const isCompletedFlag = completed === true || status === 'completed';
-          const doneByBytes = totalBytes > 0 && cumUploaded >= totalBytes;
+          // all bytes sent is not done: the server still has to acknowledge the object
+          const isCompletedFlag = completed === true || status === 'completed' || phase === 'done';
+          const isProcessing = phase === 'processing' || (totalBytes > 0 && cumUploaded >= totalBytes);
 
           const nextTask: UploadTask = {
             ...cur,
@@ -235,7 +238,7 @@ export const UploadProvider: React.FC<{ children: React.ReactNode }> = ({ childr
             etaSec: typeof etaSec === 'number' && isFinite(etaSec) ? etaSec : cur.etaSec,
             message: message ?? cur.message,
             error: error ?? cur.error,
-            status: error ? 'error' : (isCompletedFlag || doneByBytes) ? 'success' : cur.status,
+            status: error ? 'error' : isCompletedFlag ? 'success' : isProcessing ? 'processing' : cur.status,
           };
 
           if (nextTask.status === 'success' && cur.status !== 'success') {
@@ -297,11 +300,13 @@ export const UploadProvider: React.FC<{ children: React.ReactNode }> = ({ childr
         }
 
         await invoke('upload_file', {
-          id,
           filePath,
-          remoteFileName,
-          tier,
-          epochs: epochs ? Number(epochs) : undefined,
+          options: {
+            id,
+            remote_file_name: remoteFileName,
+            tier,
+            epochs: epochs ? Number(epochs) : undefined,
+          },
         });
 
       } catch (err: any) {
@@ -0,0 +1,10 @@
+declare global {
+  interface Window {
+    __FIRESTARTER_IPC__?: { nonce: string };
+  }
+}
+
+// Set by the backend in the main window only; sensitive commands (clear_credentials, confirm_action) require it.
+export function ipcNonce(): string {
+  return window.__FIRESTARTER_IPC__?.nonce ?? '';
+}